@@ -0,0 +1,14 @@
+//! С возможностью `lalrpop` собирает парсеры из грамматик `.lalrpop` в `src`:
+//! оператор - из `src/grammar/statement.lalrpop` (модуль `grammar::lalrpop`).
+
+fn main() {
+    println!("cargo::rerun-if-changed=build.rs");
+    #[cfg(feature = "lalrpop")]
+    if let Err(e) = lalrpop::Configuration::new()
+        .use_cargo_dir_conventions()
+        .emit_rerun_directives(true)
+        .process()
+    {
+        println!("cargo::error=Не удалось собрать грамматику LALRPOP: {}", e);
+    }
+}
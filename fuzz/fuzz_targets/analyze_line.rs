@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use taafl::analyzer::{analyze_line, analyze_lossy};
+use taafl::config::{AnalyzerConfig, Profile};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        let _ = analyze_line(input);
+    }
+    for profile in Profile::ALL {
+        let report = analyze_lossy(data, &AnalyzerConfig::from_profile(profile));
+        assert_eq!(report.accepted, report.errors.is_empty());
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use taafl::analyzer::tokenize;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        if let Ok(tokens) = tokenize(input) {
+            // Отрезки лексем не пусты, не пересекаются и лежат внутри строки
+            assert!(tokens.windows(2).all(|pair| pair[0].0.end <= pair[1].0.start));
+            assert!(tokens
+                .iter()
+                .all(|(span, _)| span.start < span.end && span.end <= input.len()));
+        }
+    }
+});
@@ -1,556 +1,4264 @@
-use std::collections::HashSet;
-use std::iter::Peekable;
+//! Данный код реализует синтаксический анализатор части оператора присваивания
+//! языка, сходного с фрагментом Modula-2.
+//! Формат оператора:
+//! <левая часть> := <правая часть>;
+//!
+//! <левая часть> ::= <имя> | <имя>[<список индексов>]
+//! <имя> ::= <идентификатор> | <имя>.<идентификатор>
+//! <список индексов> ::= <индекс> | <список индексов>,<индекс>
+//! <индекс> ::= [+ | -]<слагаемое индекса> | <индекс><операция><слагаемое индекса>
+//! <слагаемое индекса> ::= <имя> | <константа> | NOT <слагаемое индекса>
+//!
+//! <правая часть> ::= [+ | -]<слагаемое> | <правая часть><операция><слагаемое>
+//! <слагаемое> ::= <имя> | <имя>[<список индексов>] | <имя>(<аргументы>) | <константа>
+//!               | <литерал> | NOT <слагаемое>
+//! <аргументы> ::= <пусто> | <правая часть> | <аргументы>,<правая часть>
+//! <операция> ::= + | - | / | * | > | < | = | # | <= | >= | <> | DIV | MOD | AND | OR
+//!
+//! Идентификатор:
+//!   - начинается с буквы
+//!   - может содержать буквы и цифры
+//!   - длина не более 8 символов
+//!
+//! Имя `REC.F` выбирает поле записи: длина и зарезервированные слова проверяются
+//! у каждой компоненты, в списках символов оно - одно составное имя. Если
+//! оператор заканчивается точкой, полей записей нет.
+//!
+//! Вызов функции `CHR(65)`, `ORD(C)` (только в правой части): аргументы - выражения
+//! правой части, символы в них получают те же роли, что и вне вызова. Если индексы
+//! записываются в круглых скобках, вызовов нет: `F(1)` - элемент массива.
+//!
+//! Константа:
+//!   - целое число в допустимом диапазоне: по умолчанию [1..32767], настройкой
+//!     [`AnalyzerConfig::constants`] - `INTEGER`, `CARDINAL` или свой
+//!     (см. [`AnalyzerConfig::constant_range`]); отчёт записывает действующий
+//!     диапазон. Знак перед первым слагаемым правой части или индекса входит
+//!     в значение: `X := -1` - константа -1 вне диапазона по умолчанию
+//!   - десятичная запись или, как в Modula-2, с суффиксом: `0FFH` - шестнадцатеричная,
+//!     `377B` и `377C` - восьмеричная
+//!
+//! Литерал (только в правой части):
+//!   - текст в одинарных или двойных кавычках в пределах строки: `'A'`, `"text"`
+//!   - из одного символа - символьная константа, иначе - строковая
+//!
+//! Требуется:
+//! 1. Провести синтаксический анализ.
+//! 2. Собрать списки идентификаторов и констант с указанием их ролей:
+//!    - идентификатор-индекс
+//!    - идентификатор-массив
+//!    - идентификатор-массив (правая часть): элемент массива `B[I]` справа
+//!    - идентификатор-функция: имя вызываемой функции `CHR(65)`
+//!    - идентификатор-выражение
+//!    - константа-индекс
+//!    - константа-выражение
+//! 3. В случае ошибок отобразить их с указанием места ошибки (курсор) и описания.
+//!
+//! Дополнительно:
+//! - В правой части не допускается использование идентификатора массива в качестве имени,
+//!   совпадающего с самим массивом слева (т.е. нельзя присвоить массив самому себе):
+//!   ни `A`, ни `A[J]` справа от `A[I] :=`. Элементы других массивов допустимы
+//! - По умолчанию анализ останавливается при первой ошибке. В режиме
+//!   восстановления ([`AnalyzerConfig::recovery`]) лексер пропускает недопустимые
+//!   символы, а разбор после синтаксической ошибки продолжается с ближайшей точки
+//!   синхронизации, так что сообщается каждая ошибка строки. [`diagnose`]
+//!   возвращает их все вместе с предупреждениями; вывод [`analyze_line_with`]
+//!   ограничен [`AnalyzerConfig::max_errors`].
+//! - Регистр не учитывается.
+//! - Пробелы между конструкциями могут быть произвольными или отсутствовать.
+//!
+//! Анализ не должен паниковать ни на какой строке: элементы и срезы строк берутся
+//! через `get`, смещения складываются с насыщением, вместо `unwrap` - значения по
+//! умолчанию, а таблица грамматики при ошибке сборки не строится. Это проверяют
+//! запреты clippy ниже (кроме тестов) и прогон на случайных искажённых
+//! операторах. Если дефект анализатора всё же вызовет панику, [`try_analyze`] и
+//! [`catch_internal`] вернут вместо неё [`InternalError`].
+
+#![deny(
+    clippy::indexing_slicing,
+    clippy::string_slice,
+    clippy::arithmetic_side_effects,
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic
+)]
 
-/// Данный код реализует синтаксический анализатор части оператора присваивания
-/// языка, сходного с фрагментом Modula-2.
-/// Формат оператора:
-/// <левая часть> := <правая часть>;
-///
-/// <левая часть> ::= <идентификатор> | <идентификатор>[<список индексов>]
-/// <список индексов> ::= <индекс> | <список индексов>,<индекс>
-/// <индекс> ::= <идентификатор> | <константа>
-///
-/// <правая часть> ::= <идентификатор> | <константа> | <правая часть><операция><правая часть>
-/// <операция> ::= + | - | / | * | > | < | = | #
-///
-/// Идентификатор:
-///   - начинается с буквы
-///   - может содержать буквы и цифры
-///   - длина не более 8 символов
-///
-/// Константа:
-///   - положительное целое число в диапазоне [1..32767]
-///
-/// Требуется:
-/// 1. Провести синтаксический анализ.
-/// 2. Собрать списки идентификаторов и констант с указанием их ролей:
-///    - идентификатор-индекс
-///    - идентификатор-массив
-///    - идентификатор-выражение
-///    - константа-индекс
-///    - константа-выражение
-/// 3. В случае ошибок отобразить их с указанием места ошибки (курсор) и описания.
-///
-/// Дополнительно:
-/// - В правой части не допускается использование идентификатора массива в качестве имени,
-///   совпадающего с самим массивом слева (т.е. нельзя присвоить массив самому себе)
-/// - Анализ остановится при первой ошибке.
-/// - Регистр не учитывается.
-/// - Пробелы между конструкциями могут быть произвольными или отсутствовать.
+pub mod dfa;
+pub mod events;
+mod invariants;
+mod limits;
+pub mod sequence;
+#[cfg(test)]
+mod tests;
+mod whitespace;
+
+use crate::ast;
+use crate::cst;
+use crate::config::{
+    Allow, AnalyzerConfig, AssignOperator, Brackets, OperationSign, ParserKind, Rule, ScannerKind,
+    Severity, SymbolOrder, Terminator,
+};
+use crate::diagnostic::{self, Diagnostic, SourceMap};
+use crate::grammar::{self, earley, Grammar, Ll1Table, GRAMMAR};
+use crate::model;
+use crate::operators::{Associativity, Fixity, Operator, Registry};
+use crate::variant::{Variant, Variant20};
+use dfa::Dfa;
+use events::{Event, EventSink, Events, Trace};
+use indexmap::IndexMap;
+use std::collections::{BTreeSet, HashMap};
+use std::hash::Hash;
+use std::io::{self, BufRead, BufReader, Read};
+use std::iter::Peekable;
+use std::str::CharIndices;
+use std::ops::{Range, RangeInclusive};
+use std::slice::SliceIndex;
+use std::sync::OnceLock;
+use tracing::{debug, debug_span};
 
+/// Лексема. Идентификаторы приводятся к верхнему регистру.
 #[derive(Debug, Clone, PartialEq)]
-enum Token {
+#[non_exhaustive]
+pub enum Token {
     Identifier(String),
-    Constant(i32),
+    /// Значение константы и система счисления её записи
+    Constant(i32, Radix),
+    /// Вещественная константа, если она разрешена настройками
+    Real(ast::Real),
+    /// Символьная или строковая константа: текст без кавычек
+    Literal(String),
     LSquare,
     RSquare,
+    /// Круглые скобки: индексов, если они записываются в них ([`Brackets::Round`]),
+    /// иначе - аргументов вызова функции
+    LParen,
+    RParen,
     Comma,
     Assign,
-    Operation(char),
+    /// Знак бинарной операции: `+`, двухсимвольные `<=`, `>=` и `<>`, ключевые слова
+    /// `DIV`, `MOD`, `AND` и `OR`
+    Operation(&'static str),
+    /// Унарное логическое отрицание `NOT`
+    Not,
     Semicolon,
+    /// Точка, если оператор заканчивается ею ([`Terminator::Dot`])
+    Dot,
+    /// Точка выбора поля записи `REC.F`, если оператор заканчивается не ею
+    Period,
     End,
 }
 
-#[derive(Debug)]
-enum Error {
-    LexicalError(usize, String),
-    SyntaxError(usize, String),
-    SemanticError(usize, String),
+impl Token {
+    /// Терминал грамматики [`crate::grammar::GRAMMAR`], которому соответствует лексема.
+    pub fn terminal(&self) -> &'static str {
+        match self {
+            Token::Identifier(_) => "IDENT",
+            // Вещественная константа допустима там же, где целая: индекс
+            // отвергает семантическое правило
+            Token::Constant(..) | Token::Real(_) => "CONST",
+            Token::Literal(_) => "LITERAL",
+            Token::LSquare => "[",
+            Token::RSquare => "]",
+            Token::LParen => "(",
+            Token::RParen => ")",
+            Token::Comma => ",",
+            Token::Assign => ":=",
+            // Знак - ещё и унарная операция перед первым слагаемым
+            Token::Operation(symbol) if ast::SIGNS.contains(symbol) => "SIGN",
+            Token::Operation(_) => "OP",
+            Token::Not => "NOT",
+            Token::Semicolon => ";",
+            Token::Dot | Token::Period => ".",
+            Token::End => crate::grammar::END,
+        }
+    }
 }
 
-struct Lexer<'a> {
-    input: &'a [u8],
-    pos: usize,
-    length: usize,
+/// Отрезок строки в байтах.
+pub type Span = Range<usize>;
+
+/// Система счисления константы. Как в Modula-2, она задаётся суффиксом: `0FFH`,
+/// `377B`; `377C` в Modula-2 - символ с этим восьмеричным кодом, здесь - число.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Radix {
+    #[default]
+    Decimal,
+    /// Суффикс `H`; запись начинается с цифры
+    Hexadecimal,
+    /// Суффикс `B` или `C`
+    Octal,
 }
 
-impl<'a> Lexer<'a> {
-    fn new(input: &'a str) -> Self {
-        let bytes = input.as_bytes();
-        Self {
-            input: bytes,
-            pos: 0,
-            length: bytes.len(),
+impl Radix {
+    pub fn base(&self) -> u32 {
+        match self {
+            Radix::Decimal => 10,
+            Radix::Hexadecimal => 16,
+            Radix::Octal => 8,
         }
     }
 
-    fn peek_char(&self) -> Option<char> {
-        if self.pos < self.length {
-            Some(self.input[self.pos] as char)
-        } else {
-            None
+    pub fn title(&self) -> &'static str {
+        match self {
+            Radix::Decimal => "десятичная",
+            Radix::Hexadecimal => "шестнадцатеричная",
+            Radix::Octal => "восьмеричная",
         }
     }
 
-    fn next_char(&mut self) -> Option<char> {
-        if self.pos < self.length {
-            let c = self.input[self.pos] as char;
-            self.pos += 1;
-            Some(c)
-        } else {
-            None
+    /// Система счисления записи, начинающейся с цифры, и её цифры без суффикса.
+    /// `None`, если запись - не константа ни в одной системе.
+    fn of(lexeme: &str) -> Option<(Radix, &str)> {
+        let upper = lexeme.to_ascii_uppercase();
+        let body = lexeme.get(..lexeme.len().saturating_sub(1)).unwrap_or_default();
+        let (radix, digits) = match upper.as_bytes().last()? {
+            b'H' => (Radix::Hexadecimal, body),
+            b'B' | b'C' => (Radix::Octal, body),
+            _ => (Radix::Decimal, lexeme),
+        };
+        let valid = match radix {
+            Radix::Hexadecimal => digits.bytes().all(|c| c.is_ascii_hexdigit()),
+            // Цифры 8 и 9 проверяются при переводе: о них отдельное сообщение
+            _ => digits.bytes().all(|c| c.is_ascii_digit()),
+        };
+        (valid && !digits.is_empty()).then_some((radix, digits))
+    }
+}
+
+/// Вид участка строки для подсветки синтаксиса.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenClass {
+    Identifier,
+    Constant,
+    /// Символьная или строковая константа вместе с кавычками
+    Literal,
+    /// Знак операции `+ - * / > < = # <= >= <>` или `DIV`, `MOD`, `AND`, `OR`, `NOT`
+    Operator,
+    /// Скобка индексов: `[`, `]`, `(` или `)`
+    Bracket,
+    /// `:=`
+    Assign,
+    /// `,`, `;` или `.`
+    Separator,
+    /// Участок, на котором лексический анализ нашёл ошибку
+    Invalid,
+}
+
+impl Token {
+    /// Вид лексемы для таблицы лексем.
+    fn kind(&self) -> &'static str {
+        match self {
+            Token::Identifier(_) => "идентификатор",
+            Token::Constant(..) => "константа",
+            Token::Real(_) => "вещественная константа",
+            Token::Literal(_) => "литерал",
+            Token::LSquare | Token::LParen => "открывающая скобка",
+            Token::RSquare | Token::RParen => "закрывающая скобка",
+            Token::Comma => "запятая",
+            Token::Assign => "присваивание",
+            Token::Operation(_) | Token::Not => "операция",
+            Token::Period => "выбор поля",
+            Token::Semicolon | Token::Dot | Token::End => "конец оператора",
         }
     }
 
-    fn skip_spaces(&mut self) {
-        while let Some(c) = self.peek_char() {
-            if c.is_whitespace() {
-                self.pos += 1;
-            } else {
-                break;
-            }
+    /// Значение лексемы, у которой оно есть: имя в верхнем регистре, десятичное
+    /// значение константы, текст литерала без кавычек.
+    fn value(&self) -> Option<String> {
+        match self {
+            Token::Identifier(name) => Some(name.clone()),
+            Token::Constant(value, _) => Some(value.to_string()),
+            Token::Real(value) => Some(value.to_string()),
+            Token::Literal(text) => Some(text.clone()),
+            _ => None,
         }
     }
 
-    fn lex_number(&mut self) -> Result<(usize, Token), Error> {
-        let start_pos = self.pos;
-        let mut num_str = String::new();
-        while let Some(c) = self.peek_char() {
-            if c.is_ascii_digit() {
-                num_str.push(c);
-                self.pos += 1;
-            } else {
-                break;
+    fn class(&self) -> TokenClass {
+        match self {
+            Token::Identifier(_) => TokenClass::Identifier,
+            Token::Constant(..) | Token::Real(_) => TokenClass::Constant,
+            Token::Literal(_) => TokenClass::Literal,
+            Token::LSquare | Token::RSquare | Token::LParen | Token::RParen => TokenClass::Bracket,
+            Token::Comma | Token::Semicolon | Token::Dot | Token::Period | Token::End => {
+                TokenClass::Separator
             }
+            Token::Assign => TokenClass::Assign,
+            Token::Operation(_) | Token::Not => TokenClass::Operator,
         }
-        if let Ok(n) = num_str.parse::<i32>() {
-            if n < 1 || n > 32767 {
-                return Err(Error::SemanticError(
-                    start_pos,
-                    format!("Константа вне диапазона [1..32767]: {}", n),
-                ));
-            }
-            Ok((start_pos, Token::Constant(n)))
-        } else {
-            Err(Error::LexicalError(
-                start_pos,
-                format!("Невозможно преобразовать в число: {}", num_str),
-            ))
+    }
+}
+
+/// Таблица LL(1) встроенной грамматики с настроенными лексемами: концом оператора
+/// и скобками индексов. Строится при первом обращении; `None`, если грамматика
+/// не разобралась или не LL(1) - тогда анализ сообщает об ошибке, а не падает.
+pub fn ll1_table(config: &AnalyzerConfig) -> Option<&'static Ll1Table> {
+    const COUNT: usize = Terminator::ALL.len() * Brackets::ALL.len();
+    static TABLES: [OnceLock<Option<Ll1Table>>; COUNT] = [const { OnceLock::new() }; COUNT];
+    let terminator = Terminator::ALL.iter().position(|t| *t == config.terminator);
+    let brackets = Brackets::ALL.iter().position(|b| *b == config.brackets);
+    let i = terminator.unwrap_or(0).checked_mul(Brackets::ALL.len())?;
+    let i = i.checked_add(brackets.unwrap_or(0))?;
+    TABLES
+        .get(i)?
+        .get_or_init(|| {
+            let text = language_grammar(GRAMMAR, config);
+            Grammar::parse(&text).ok().and_then(|grammar| Ll1Table::build(grammar).ok())
+        })
+        .as_ref()
+}
+
+/// Текст грамматики с лексемами языка из настроек.
+fn language_grammar(text: &str, config: &AnalyzerConfig) -> String {
+    let text = grammar::with_terminator(text, config.terminator.symbol());
+    grammar::with_brackets(&text, config.brackets.pair())
+}
+
+/// Лексемы открывающей и закрывающей скобок индексов.
+fn bracket_tokens(brackets: Brackets) -> (Token, Token) {
+    match brackets {
+        Brackets::Square => (Token::LSquare, Token::RSquare),
+        Brackets::Round => (Token::LParen, Token::RParen),
+    }
+}
+
+/// Лексема, которой заканчивается оператор.
+fn terminator_token(terminator: Terminator) -> Option<Token> {
+    match terminator {
+        Terminator::Semicolon => Some(Token::Semicolon),
+        Terminator::Dot => Some(Token::Dot),
+        Terminator::None => None,
+    }
+}
+
+/// Ошибка с отрезком ошибочной конструкции: лексемы, идентификатора, константы.
+/// Пустой отрезок указывает на место между символами, например на конец строки.
+#[derive(Debug)]
+enum Error {
+    Lexical(Span, String),
+    Syntax(Span, String),
+    /// Синтаксическая ошибка там, где ожидалась одна из лексем: сообщение написано
+    /// для случая без грамматики, а с таблицей LL(1) его заменяет выведенное из
+    /// допустимых терминалов ([`Error::with_expected`])
+    Expected(Span, String),
+    /// Синтаксическая ошибка с терминалами грамматики, допустимыми на её месте
+    Unexpected(Span, String, Vec<String>),
+    /// Нарушение семантического правила
+    Semantic(Rule, Span, String),
+    /// Ввод длиннее [`AnalyzerConfig::max_input`]: анализ не выполнялся
+    InputSize(Span, String),
+}
+
+/// Начало подсказки, которую [`Error::with_hint`] дописывает к сообщению.
+const HINT_PREFIX: &str = "\nПодсказка: ";
+
+impl Error {
+    fn position(&self) -> usize {
+        self.span().start
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            Error::Lexical(span, _)
+            | Error::Syntax(span, _)
+            | Error::Expected(span, _)
+            | Error::Unexpected(span, ..)
+            | Error::Semantic(_, span, _)
+            | Error::InputSize(span, _) => span.clone(),
         }
     }
 
-    fn lex_identifier(&mut self, first_char: char) -> Result<(usize, Token), Error> {
-        let start_pos = self.pos - 1;
-        let mut ident = String::new();
-        ident.push(first_char);
-        while let Some(c) = self.peek_char() {
-            if c.is_ascii_alphanumeric() {
-                ident.push(c);
-                self.pos += 1;
-            } else {
-                break;
-            }
+    fn message(&self) -> &str {
+        match self {
+            Error::Lexical(_, msg)
+            | Error::Syntax(_, msg)
+            | Error::Expected(_, msg)
+            | Error::Unexpected(_, msg, _)
+            | Error::Semantic(_, _, msg)
+            | Error::InputSize(_, msg) => msg,
         }
-        let ident = ident.to_uppercase();
-        if ident.len() > 8 {
-            return Err(Error::SemanticError(
-                start_pos,
-                format!("Идентификатор слишком длинный: {}", ident),
-            ));
+    }
+
+    /// Дописывает к сообщению подсказку.
+    fn with_hint(self, hint: &str) -> Self {
+        let hinted = |msg: String| format!("{}{}{}", msg, HINT_PREFIX, hint);
+        match self {
+            Error::Lexical(span, msg) => Error::Lexical(span, hinted(msg)),
+            Error::Syntax(span, msg) => Error::Syntax(span, hinted(msg)),
+            Error::Expected(span, msg) => Error::Expected(span, hinted(msg)),
+            Error::Unexpected(span, msg, expected) => {
+                Error::Unexpected(span, hinted(msg), expected)
+            }
+            Error::Semantic(rule, span, msg) => Error::Semantic(rule, span, hinted(msg)),
+            Error::InputSize(span, msg) => Error::InputSize(span, hinted(msg)),
         }
-        Ok((start_pos, Token::Identifier(ident)))
     }
 
-    fn next_token(&mut self) -> Result<(usize, Token), Error> {
-        self.skip_spaces();
-        let start_pos = self.pos;
-        match self.next_char() {
-            Some(c) => {
-                if c.is_ascii_alphabetic() {
-                    self.lex_identifier(c)
-                } else if c.is_ascii_digit() {
-                    self.pos -= 1; // вернуть символ для lex_number
-                    let number = self.lex_number();
-
-                    if let Some(after) = self.peek_char() {
-                        if after.is_ascii_alphabetic() {
-                            Err(Error::SyntaxError(
-                                start_pos,
-                                "Идентификатор не может начинаться с цифры".to_string(),
-                            ))
-                        } else {
-                            number
-                        }
-                    } else {
-                        number
-                    }
-                } else {
-                    match c {
-                        '[' => Ok((start_pos, Token::LSquare)),
-                        ']' => Ok((start_pos, Token::RSquare)),
-                        ',' => Ok((start_pos, Token::Comma)),
-                        ':' => {
-                            if let Some('=') = self.peek_char() {
-                                self.pos += 1;
-                                Ok((start_pos, Token::Assign))
-                            } else {
-                                Err(Error::SyntaxError(
-                                    start_pos,
-                                    "Ожидался '=' после ':'".to_string(),
-                                ))
-                            }
-                        }
-                        ';' => Ok((start_pos, Token::Semicolon)),
-                        '+' | '-' | '*' | '/' | '>' | '<' | '=' | '#' => {
-                            Ok((start_pos, Token::Operation(c)))
-                        }
-                        _ => {
-                            // Прочие символы - ошибка
-                            Err(Error::SyntaxError(
-                                start_pos,
-                                format!("Недопустимый символ: '{}'", c),
-                            ))
-                        }
-                    }
-                }
+    /// Синтаксическая ошибка с терминалами, которые таблица LL(1) допускает на месте
+    /// ошибки; сообщение [`Error::Expected`] строится из них, как при разборе по
+    /// таблице. Ошибки других видов не меняются. После ошибки, с которой разбор
+    /// восстановился, лексемы перед следующей таблица не разбирает, и у той
+    /// списка нет.
+    fn with_expected(
+        self,
+        table: &Ll1Table,
+        terminals: &[(usize, &str)],
+        end: usize,
+        config: &AnalyzerConfig,
+    ) -> Self {
+        let (span, msg, derive) = match self {
+            Error::Syntax(span, msg) => (span, msg, false),
+            Error::Expected(span, msg) => (span, msg, true),
+            other => return other,
+        };
+        match expected_at(table, terminals, end, &span) {
+            Some((expected, derived)) if derive => {
+                // Подсказка остаётся после выведенного сообщения
+                let hint = msg
+                    .find(HINT_PREFIX)
+                    .and_then(|i| msg.get(i..))
+                    .unwrap_or_default();
+                let derived = table_message(&derived, config);
+                Error::Unexpected(span, format!("{}{}", derived, hint), expected)
             }
-            None => Ok((start_pos, Token::End)),
+            Some((expected, _)) => Error::Unexpected(span, msg, expected),
+            None => Error::Syntax(span, msg),
         }
     }
 
-    fn tokenize(mut self) -> Result<Vec<(usize, Token)>, Error> {
-        let mut tokens = Vec::new();
-        loop {
-            let (pos, token) = self.next_token()?;
-            if token == Token::End {
-                break;
+    /// Терминалы, допустимые на месте синтаксической ошибки.
+    fn expected(&self) -> &[String] {
+        match self {
+            Error::Unexpected(_, _, expected) => expected,
+            _ => &[],
+        }
+    }
+
+    fn to_entry(&self) -> model::ErrorEntry {
+        let kind = match self {
+            Error::Lexical(..) | Error::InputSize(..) => model::ErrorKind::Lexical,
+            Error::Syntax(..) | Error::Expected(..) | Error::Unexpected(..) => {
+                model::ErrorKind::Syntax
             }
-            tokens.push((pos, token));
+            Error::Semantic(..) => model::ErrorKind::Semantic,
+        };
+        let span = self.span();
+        model::ErrorEntry {
+            kind,
+            position: span.start,
+            end: span.end,
+            message: self.message().to_string(),
+            expected: self.expected().to_vec(),
         }
+    }
 
-        println!("{:?}", tokens);
-        Ok(tokens)
+    /// Диагностика, в которой подсказки вынесены из сообщения в пояснения.
+    /// Пустой отрезок расширяется до символа `input` в позиции ошибки.
+    fn to_diagnostic(&self, severity: diagnostic::Severity, input: &str) -> Diagnostic {
+        let code = match self {
+            Error::Lexical(..) => "lexical",
+            Error::Syntax(..) | Error::Expected(..) | Error::Unexpected(..) => "syntax",
+            Error::Semantic(rule, ..) => rule.id(),
+            Error::InputSize(..) => "input-size",
+        };
+        let mut parts = self.message().split(HINT_PREFIX);
+        let message = parts.next().unwrap_or_default().to_string();
+        let mut notes: Vec<String> = parts.map(|hint| format!("Подсказка: {}", hint)).collect();
+        if !self.expected().is_empty() {
+            let expected: Vec<String> =
+                self.expected().iter().map(|t| grammar::describe_terminal(t)).collect();
+            notes.push(format!("Ожидалось одно из: {}", expected.join(", ")));
+        }
+        let mut span = self.span();
+        if span.is_empty() {
+            let width = input
+                .get(span.start..)
+                .and_then(|rest| rest.chars().next())
+                .map_or(1, char::len_utf8);
+            span.end = span.start.saturating_add(width);
+        }
+        let map = SourceMap::new(input);
+        let location = map.location(span.start);
+        Diagnostic {
+            severity,
+            code,
+            line: map.is_multiline().then_some(location.line),
+            column: location.column,
+            span,
+            message,
+            notes,
+        }
     }
 }
 
-struct Parser {
-    tokens: Peekable<std::vec::IntoIter<(usize, Token)>>,
-    current_pos: usize,
-    input_str: String,
-    /// Для семантического анализа:
-    /// Списки идентификаторов и констант, разбитые по ролям
-    ids_array: HashSet<String>,
-    ids_index: HashSet<String>,
-    ids_expr: HashSet<String>,
-    const_index: HashSet<i32>,
-    const_expr: HashSet<i32>,
+/// Позиции всех вхождений каждого символа, в порядке первого появления.
+type Occurrences<T> = IndexMap<T, Vec<usize>>;
 
-    /// Имя массива в левой части
-    left_array_name: Option<String>,
+fn record<T: Hash + Eq>(occurrences: &mut Occurrences<T>, symbol: T, pos: usize) {
+    occurrences.entry(symbol).or_default().push(pos);
 }
 
-impl Parser {
-    fn new(tokens: Vec<(usize, Token)>, input_str: String) -> Self {
-        Parser {
-            tokens: tokens.into_iter().peekable(),
-            current_pos: 0,
-            input_str,
-            ids_array: HashSet::new(),
-            ids_index: HashSet::new(),
-            ids_expr: HashSet::new(),
-            const_index: HashSet::new(),
-            const_expr: HashSet::new(),
-            left_array_name: None,
+/// Идентификаторы, целые, вещественные и символьные или строковые константы для отчёта.
+type Symbols = (
+    Vec<model::Identifier>,
+    Vec<model::Constant>,
+    Vec<model::RealConstant>,
+    Vec<model::LiteralConstant>,
+);
+
+/// Символы с позициями вхождений в заданном порядке.
+fn ordered<T: Ord + Clone>(
+    occurrences: &Occurrences<T>,
+    order: SymbolOrder,
+) -> Vec<(T, Vec<usize>)> {
+    let mut items: Vec<(T, Vec<usize>)> = occurrences
+        .iter()
+        .map(|(symbol, positions)| (symbol.clone(), positions.clone()))
+        .collect();
+    if order == SymbolOrder::Sorted {
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    items
+}
+
+/// Различные записи символа в порядке появления.
+fn distinct(lexemes: impl Iterator<Item = String>) -> Vec<String> {
+    let mut distinct: Vec<String> = Vec::new();
+    for lexeme in lexemes {
+        if !distinct.contains(&lexeme) {
+            distinct.push(lexeme);
         }
     }
+    distinct
+}
 
-    fn peek(&mut self) -> Option<&(usize, Token)> {
-        self.tokens.peek()
+/// Столбцы вхождений (с единицы) для текстового вывода, в многострочном тексте -
+/// вместе со строками.
+fn columns(positions: &[usize], map: &SourceMap) -> String {
+    let n = positions.len();
+    if let [position] = positions {
+        map.describe(*position)
+    } else if map.is_multiline() {
+        let places: Vec<String> = positions.iter().map(|&pos| map.describe(pos)).collect();
+        format!("{} {}: {}", n, plural(n, "раз", "раза", "раз"), places.join("; "))
+    } else {
+        let columns: Vec<String> = positions
+            .iter()
+            .map(|&pos| map.location(pos).column.to_string())
+            .collect();
+        format!(
+            "{} {}, столбцы {}",
+            n,
+            plural(n, "раз", "раза", "раз"),
+            columns.join(", ")
+        )
     }
+}
 
-    fn next_token(&mut self) -> Option<(usize, Token)> {
-        let pair = self.tokens.next();
-        if let Some((pos, _t)) = pair.clone() {
-            self.current_pos = pos
-        } else {
-            self.current_pos = self.input_str.len() - 1;
-        };
-        pair
+/// Отчёт об ошибках анализа.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorReport {
+    /// Текст отчёта: введённая строка и ошибки с курсорами.
+    pub text: String,
+    /// Сколько ошибок не вошло в отчёт из-за ограничения `max_errors`.
+    pub hidden: usize,
+}
+
+/// Собирает ошибки и предупреждения, не прерывающие анализ.
+struct Reporter<'a> {
+    config: &'a AnalyzerConfig,
+    /// Допустимые значения констант по настройкам и варианту
+    constants: RangeInclusive<i32>,
+    /// Ошибки, накопленные в режиме восстановления
+    errors: Vec<Error>,
+    /// Нарушения правил со строгостью [`Severity::Warning`]
+    warnings: Vec<Error>,
+}
+
+impl<'a> Reporter<'a> {
+    fn new(config: &'a AnalyzerConfig, constants: RangeInclusive<i32>) -> Self {
+        Self {
+            config,
+            constants,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        }
     }
 
-    fn expect(
-        &mut self,
-        expected: &[Token],
-        error_message_some: String,
-        error_message_none: String,
-    ) -> Result<Token, Error> {
-        if let Some((_, t)) = self.next_token() {
-            if expected.contains(&t) {
-                Ok(t)
-            } else {
-                let pos = self.get_current_position();
-                Err(Error::SyntaxError(pos, error_message_some))
-            }
+    /// В режиме восстановления запоминает ошибку и позволяет продолжить,
+    /// иначе прерывает анализ.
+    fn report(&mut self, err: Error) -> Result<(), Error> {
+        if self.config.recovery {
+            self.errors.push(err);
+            Ok(())
         } else {
-            self.next_token();
-            let pos = self.get_current_position();
-            Err(Error::SyntaxError(pos, error_message_none))
+            Err(err)
         }
     }
 
-    fn get_current_position(&self) -> usize {
-        self.current_pos
+    /// Сообщает о нарушении правила с учётом его настроенной строгости.
+    fn violation(&mut self, rule: Rule, span: Span, message: String) -> Result<(), Error> {
+        let _span = debug_span!("semantic", rule = rule.id()).entered();
+        debug!(pos = span.start, severity = ?self.config.severity(rule), "нарушение правила");
+
+        let err = Error::Semantic(rule, span, message);
+        let err = if self.config.hints {
+            err.with_hint(&rule.description(&self.constants))
+        } else {
+            err
+        };
+        match self.config.severity(rule) {
+            Severity::Error => self.report(err),
+            Severity::Warning => {
+                self.warnings.push(err);
+                Ok(())
+            }
+            Severity::Off => Ok(()),
+        }
     }
+}
 
-    fn parse(&mut self) -> Result<(), Error> {
-        // <левая часть> := <правая часть>;
-        self.parse_left_part()?;
+/// Лексический анализатор. Идёт по символам UTF-8, а позиции лексем и ошибок -
+/// смещения в байтах, поэтому указывают на начало символа и для не-ASCII ввода.
+///
+/// Как итератор выдаёт лексемы по одной, по мере того как их запрашивает
+/// синтаксический анализатор, и останавливается после первой лексической ошибки.
+/// В режиме восстановления недопустимый символ не останавливает лексер: ошибка
+/// о нём запоминается в `skipped`, а символ пропускается.
+///
+/// С настройкой [`ScannerKind::Dfa`] границу и вид лексемы определяет автомат
+/// [`Dfa`], а значение и ошибки строятся теми же функциями, что и при
+/// посимвольном разборе.
+struct Lexer<'a> {
+    input: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    terminator: Terminator,
+    brackets: Brackets,
+    /// Допустимые лексемы присваивания
+    assign: Vec<AssignOperator>,
+    /// Присваивание уже встретилось: следующий `=` - операция, а не присваивание
+    assigned: bool,
+    /// Знаки операций варианта
+    operations: BTreeSet<OperationSign>,
+    /// Разрешены вещественные константы
+    reals: bool,
+    /// Разрешено подчёркивание внутри идентификаторов
+    underscores: bool,
+    /// В идентификаторах допустимы буквы любых алфавитов
+    unicode: bool,
+    /// Пропускать недопустимые символы, а не останавливаться на них
+    recover: bool,
+    /// Автомат лексем, если лексемы распознаются по таблице переходов
+    dfa: Option<Dfa>,
+    /// Наибольшая длина ввода в символах; проверяется перед первой лексемой
+    limit: Option<usize>,
+    /// Последняя ошибка - недопустимый символ, который можно пропустить
+    invalid: bool,
+    /// Ошибки о пропущенных символах по порядку
+    skipped: Vec<Error>,
+    /// Получатель выданных лексем
+    events: Events<'a>,
+    /// Запоминать пропущенные пробелы и комментарии в `trivia`
+    keep_trivia: bool,
+    /// Пробелы и комментарии перед очередной лексемой
+    trivia: Vec<Trivia>,
+    /// Достигнут конец ввода или выдана ошибка
+    done: bool,
+}
 
-        self.expect(
-            &[Token::Assign],
-            "Ожидалось ':='".to_string(),
-            "Ожидалось ':=', но достигнут конец".to_string(),
-        )?;
-        self.parse_right_part()?;
-        self.expect(
-            &[Token::Semicolon, Token::Operation('+')],
-            "Ожидалось либо ';', либо операция".to_string(),
-            "Ожидалось ';', но достигнут конец".to_string(),
-        )?;
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str) -> Self {
+        Self::configured(input, &AnalyzerConfig::default())
+    }
 
-        if let Some(_) = self.next_token() {
-            Err(Error::SyntaxError(
-                self.get_current_position(),
-                "После ';' ничего не ожидается".to_string(),
-            ))
-        } else {
-            Ok(())
+    /// Лексемы языка - конец оператора, скобки индексов, присваивание,
+    /// вещественные константы, подчёркивание и алфавит идентификаторов - и режим
+    /// восстановления берутся из настроек.
+    fn configured(input: &'a str, config: &AnalyzerConfig) -> Self {
+        Self {
+            input,
+            chars: input.char_indices().peekable(),
+            terminator: config.terminator,
+            brackets: config.brackets,
+            assign: config.assign_operators(),
+            assigned: false,
+            operations: config.operations.clone(),
+            reals: config.reals,
+            underscores: config.underscores,
+            unicode: config.unicode_identifiers,
+            recover: config.recovery,
+            dfa: (config.scanner == ScannerKind::Dfa).then(|| Dfa::new(config)),
+            limit: (config.max_input > 0).then_some(config.max_input),
+            invalid: false,
+            skipped: Vec::new(),
+            events: Events::default(),
+            keep_trivia: false,
+            trivia: Vec::new(),
+            done: false,
         }
     }
 
-    fn parse_left_part(&mut self) -> Result<(), Error> {
-        // <левая часть> ::= <идентификатор> | <идентификатор>[<список индексов>]
-        let ident = self.parse_identifier()?;
-        // Считаем, что это потенциально имя массива
-        // Но если не будет индексов - это просто одиночный идентификатор
-        if let Some((_, Token::LSquare)) = self.peek() {
-            // Тогда это массив
-            self.next_token();
-            self.ids_array.insert(ident.clone());
-            self.left_array_name = Some(ident.clone());
+    /// Ошибка о недопустимом символе, прочитанном с `start_pos`.
+    fn invalid(&mut self, start_pos: usize, message: String) -> Result<(Span, Token), Error> {
+        self.invalid = true;
+        Err(Error::Syntax(start_pos..self.pos(), message))
+    }
 
-            // Список индексов
-            self.parse_index_list()?;
-            self.expect(
-                &[Token::RSquare],
-                "Ожидалось ']'".to_string(),
-                "Ожидалось ']', но достигнут конец".to_string(),
-            )?;
+    /// Присваивание, если лексема `op` допустима, иначе ошибка с перечнем допустимых.
+    fn assign(&mut self, start_pos: usize, op: AssignOperator) -> Result<(Span, Token), Error> {
+        if self.assign.contains(&op) {
+            self.assigned = true;
+            Ok((start_pos..self.pos(), Token::Assign))
         } else {
-            self.left_array_name = None;
-            self.ids_expr.insert(ident);
+            Err(Error::Syntax(
+                start_pos..self.pos(),
+                format!(
+                    "Присваивание записывается как {}, а не '{}'",
+                    AssignOperator::describe(&self.assign),
+                    op.lexeme()
+                ),
+            ))
         }
+    }
 
-        Ok(())
+    /// Смещение следующего символа; в конце ввода - длина строки.
+    fn pos(&mut self) -> usize {
+        self.chars.peek().map_or(self.input.len(), |&(i, _)| i)
     }
 
-    fn parse_index_list(&mut self) -> Result<(), Error> {
-        // <список индексов> ::= <индекс> | <список индексов>,<индекс>
-        self.parse_index()?;
-        while let Some((_, Token::Comma)) = self.peek() {
-            self.next_token();
-            self.parse_index()?;
-        }
-        Ok(())
+    /// Текст ввода на отрезке `range`; пустой, если отрезок не на границах символов.
+    fn text<R: SliceIndex<str, Output = str>>(&self, range: R) -> &'a str {
+        self.input.get(range).unwrap_or_default()
     }
 
-    fn parse_index(&mut self) -> Result<(), Error> {
-        // <индекс> ::= <идентификатор> | <константа>
-        if let Some(t) = self.peek() {
-            match t {
-                (_, Token::Identifier(_)) => {
-                    let ident = self.parse_identifier()?;
-                    self.ids_index.insert(ident);
-                }
-                (_, Token::Constant(_)) => {
-                    let c = self.parse_constant()?;
-                    self.const_index.insert(c);
-                }
-                _ => {
-                    self.next_token();
-                    let pos = self.get_current_position();
-                    return Err(Error::SyntaxError(
-                        pos,
-                        "Ожидался идентификатор или константа в индексе".to_string(),
-                    ));
-                }
-            }
-        } else {
-            let pos = self.get_current_position();
-            return Err(Error::SyntaxError(
-                pos,
-                "Ожидался индекс, но достигнут конец".to_string(),
-            ));
-        }
-        Ok(())
+    fn next_char(&mut self) -> Option<char> {
+        self.chars.next().map(|(_, c)| c)
     }
 
-    fn parse_right_part(&mut self) -> Result<(), Error> {
-        // <правая часть> ::= <идентификатор> | <константа> | <правая часть><операция><правая часть>
-        self.parse_term()?;
+    /// Берёт следующий символ, если он подходит.
+    fn next_if(&mut self, accept: impl Fn(char) -> bool) -> Option<char> {
+        self.chars.next_if(|&(_, c)| accept(c)).map(|(_, c)| c)
+    }
 
-        while let Some((_, Token::Operation(_))) = self.peek() {
-            self.next_token();
-            self.parse_term()?;
-        }
+    /// Пропускает символы, начинающиеся до смещения `end`.
+    fn skip_to(&mut self, end: usize) {
+        while self.chars.next_if(|&(i, _)| i < end).is_some() {}
+    }
 
-        Ok(())
+    fn skip_spaces(&mut self) {
+        let start = self.pos();
+        while self.next_if(char::is_whitespace).is_some() {}
+        let end = self.pos();
+        if self.keep_trivia && end > start {
+            self.trivia.push(Trivia::Whitespace(start..end));
+        }
     }
 
-    fn parse_term(&mut self) -> Result<(), Error> {
-        // <term> ::= <идентификатор> | <константа>
-        match self.peek() {
-            Some((_, Token::Identifier(_))) => {
-                let ident = self.parse_identifier()?;
-                let pos = self.get_current_position();
-                let left_array_name = &self.left_array_name.clone();
-
-                // Проверяем семантику: нельзя использовать идентификатор массива (т.е. такой же, как слева) в правой части
-                if let Some(arr) = left_array_name {
-                    if ident == *arr {
-                        self.next_token();
-                        return Err(Error::SemanticError(
-                            pos,
-                            "Нельзя использовать массив в правой части".to_string(),
-                        ));
+    /// Пропускает пробелы и комментарии `(* ... *)`, в том числе вложенные.
+    fn skip_trivia(&mut self) -> Result<(), Error> {
+        self.skip_spaces();
+        loop {
+            let start = self.pos();
+            if !self.text(start..).starts_with("(*") {
+                return Ok(());
+            }
+            let open = start..start.saturating_add(2);
+            self.skip_to(open.end);
+            let mut depth = 1usize;
+            while depth > 0 {
+                match self.next_char() {
+                    Some('(') if self.next_if(|c| c == '*').is_some() => {
+                        depth = depth.saturating_add(1)
+                    }
+                    Some('*') if self.next_if(|c| c == ')').is_some() => {
+                        depth = depth.saturating_sub(1)
+                    }
+                    Some(_) => {}
+                    None => {
+                        return Err(Error::Lexical(
+                            open,
+                            "Комментарий не закрыт: нет '*)' для этого '(*'".to_string(),
+                        ))
                     }
                 }
-                self.ids_expr.insert(ident);
             }
-            Some((_, Token::Constant(_))) => {
-                let c = self.parse_constant()?;
-                self.const_expr.insert(c);
+            let end = self.pos();
+            if self.keep_trivia {
+                self.trivia.push(Trivia::Comment(start..end));
             }
-            _ => {
-                self.next_token();
-                return Err(Error::SyntaxError(
-                    self.get_current_position(),
-                    "Ожидался идентификатор или константа в правой части".to_string(),
+            self.skip_spaces();
+        }
+    }
+
+    /// Число, первая цифра которого начинается на `start_pos` и уже прочитана:
+    /// десятичное или с суффиксом системы счисления ([`Radix`]).
+    fn lex_number(&mut self, start_pos: usize) -> Result<(Span, Token), Error> {
+        let underscores = self.underscores;
+        while self
+            .next_if(|c| c.is_ascii_alphanumeric() || (underscores && c == '_'))
+            .is_some()
+        {}
+        let span = start_pos..self.pos();
+        let num_str = self.text(span.clone());
+        let fraction = self.text(span.end..).strip_prefix('.');
+        if self.reals && fraction.is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+            && num_str.bytes().all(|c| c.is_ascii_digit())
+        {
+            return self.lex_real(start_pos);
+        }
+        self.number_token(span)
+    }
+
+    /// Целая константа `span` - цифры и буквы за ними.
+    fn number_token(&self, span: Span) -> Result<(Span, Token), Error> {
+        let num_str = self.text(span.clone());
+        let Some((radix, digits)) = Radix::of(num_str) else {
+            // Ошибочная конструкция - цифры вместе с буквами за ними
+            return Err(Error::Syntax(
+                span,
+                "Идентификатор не может начинаться с цифры".to_string(),
+            ));
+        };
+        match i32::from_str_radix(digits, radix.base()) {
+            Ok(n) => Ok((span, Token::Constant(n, radix))),
+            Err(e) if *e.kind() == std::num::IntErrorKind::InvalidDigit => Err(Error::Lexical(
+                span,
+                format!("Недопустимая цифра в восьмеричной константе: {}", num_str),
+            )),
+            Err(_) => Err(Error::Lexical(
+                span,
+                format!("Невозможно преобразовать в число: {} (переполнение)", num_str),
+            )),
+        }
+    }
+
+    /// Вещественное число `цифры.цифры[E[+|-]цифры]`, целая часть которого уже
+    /// прочитана, а за ней стоят точка и цифра.
+    fn lex_real(&mut self, start_pos: usize) -> Result<(Span, Token), Error> {
+        self.next_char();
+        while self.next_if(|c| c.is_ascii_digit()).is_some() {}
+        if self.next_if(|c| c == 'E' || c == 'e').is_some() {
+            self.next_if(|c| c == '+' || c == '-');
+            if self.next_if(|c| c.is_ascii_digit()).is_none() {
+                return Err(Error::Lexical(
+                    start_pos..self.pos(),
+                    "Ожидались цифры порядка после 'E'".to_string(),
                 ));
             }
+            while self.next_if(|c| c.is_ascii_digit()).is_some() {}
         }
-        Ok(())
+        if self.next_if(|c| c.is_ascii_alphanumeric()).is_some() {
+            while self.next_if(|c| c.is_ascii_alphanumeric()).is_some() {}
+            return Err(Error::Syntax(
+                start_pos..self.pos(),
+                "Идентификатор не может начинаться с цифры".to_string(),
+            ));
+        }
+        let end = self.pos();
+        self.real_token(start_pos..end)
     }
 
-    fn parse_identifier(&mut self) -> Result<String, Error> {
-        if let Some((_, Token::Identifier(s))) = self.next_token() {
-            Ok(s)
-        } else {
-            let pos = self.get_current_position();
-            Err(Error::SyntaxError(
-                pos,
-                "Ожидался идентификатор".to_string(),
-            ))
+    /// Вещественная константа `span`, записанная без ошибок.
+    fn real_token(&self, span: Span) -> Result<(Span, Token), Error> {
+        let text = self.text(span.clone());
+        match text.parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok((span, Token::Real(ast::Real(value)))),
+            _ => Err(Error::Lexical(
+                span,
+                format!("Невозможно преобразовать в число: {} (переполнение)", text),
+            )),
         }
     }
 
-    fn parse_constant(&mut self) -> Result<i32, Error> {
-        if let Some((_, Token::Constant(c))) = self.next_token() {
-            Ok(c)
-        } else {
-            let pos = self.get_current_position();
-            Err(Error::SyntaxError(pos, "Ожидалась константа".to_string()))
+    /// Литерал, открывающая кавычка `quote` которого стоит на `start_pos` и уже
+    /// прочитана. Литерал заканчивается такой же кавычкой в той же строке.
+    fn lex_literal(&mut self, start_pos: usize, quote: char) -> Result<(Span, Token), Error> {
+        let content = self.pos();
+        loop {
+            match self.next_char() {
+                Some(c) if c == quote => break,
+                Some(c) if c != '\n' => {}
+                _ => return Err(Self::unclosed(start_pos..content, quote)),
+            }
         }
+        // Закрывающая кавычка - один байт
+        let end = self.pos().saturating_sub(1);
+        let value = self.text(content..end).to_string();
+        Ok((start_pos..self.pos(), Token::Literal(value)))
     }
 
-    fn finish(self) -> (Option<String>, Option<String>) {
-        // Формируем строки вывода
-        // Идентификаторы: могут быть в индексах, массивах, выражениях
-        // Константы: индекс, выражение
+    /// Ошибка о литерале, открывающая кавычка `quote` которого занимает `span`.
+    fn unclosed(span: Span, quote: char) -> Error {
+        Error::Lexical(
+            span,
+            format!("Литерал не закрыт: нет парной кавычки {} в этой строке", quote),
+        )
+    }
 
-        if !self.ids_array.is_empty()
-            || !self.ids_index.is_empty()
-            || !self.ids_expr.is_empty()
-            || !self.const_index.is_empty()
-            || !self.const_expr.is_empty()
-        {
-            let mut ids = String::new();
-            let mut consts = String::new();
+    /// Идентификатор, первая буква которого начинается на `start_pos` и уже прочитана.
+    /// Перевод в верхний регистр - по правилам Unicode: `масс` становится `МАСС`.
+    fn lex_identifier(&mut self, start_pos: usize) -> Result<(Span, Token), Error> {
+        let (underscores, unicode) = (self.underscores, self.unicode);
+        while self
+            .next_if(|c| {
+                c.is_ascii_alphanumeric()
+                    || (underscores && c == '_')
+                    || (unicode && c.is_alphabetic())
+            })
+            .is_some()
+        {}
+        let end = self.pos();
+        self.identifier_token(start_pos..end)
+    }
 
-            if !self.ids_array.is_empty() {
-                for id in &self.ids_array {
-                    ids.push_str(&format!("{} - идентификатор-массив\n", id));
-                }
+    /// Идентификатор или ключевое слово операции `span`.
+    fn identifier_token(&self, span: Span) -> Result<(Span, Token), Error> {
+        let ident = self.text(span.clone()).to_uppercase();
+        // Ключевые слова операций - не идентификаторы, в любом регистре
+        let token = match ast::KEYWORD_OPERATIONS.iter().find(|keyword| **keyword == ident) {
+            Some(keyword) => Token::Operation(keyword),
+            None if ast::UNARY_OPERATIONS.contains(&ident.as_str()) => Token::Not,
+            None => Token::Identifier(ident),
+        };
+        Ok((span, token))
+    }
+
+    fn next_token(&mut self) -> Result<(Span, Token), Error> {
+        if let Some(limit) = self.limit.take() {
+            if let Some((offset, _)) = self.input.char_indices().nth(limit) {
+                let message = format!("Ввод длиннее {} символов: анализ не выполнялся", limit);
+                return Err(Error::InputSize(offset..offset, message)
+                    .with_hint("ограничение задаёт настройка max-input, 0 снимает его"));
             }
-            if !self.ids_index.is_empty() {
-                for id in &self.ids_index {
-                    ids.push_str(&format!("{} - идентификатор-индекс\n", id));
+        }
+        self.skip_trivia()?;
+        let start_pos = self.pos();
+        if let Some(dfa) = &self.dfa {
+            let (state, len) = dfa.run(self.text(start_pos..));
+            return self.dfa_token(start_pos, state, len);
+        }
+        match self.next_char() {
+            Some(c) => {
+                if c.is_ascii_alphabetic() || (self.unicode && c.is_alphabetic()) {
+                    self.lex_identifier(start_pos)
+                } else if c.is_ascii_digit() {
+                    self.lex_number(start_pos)
+                } else {
+                    self.symbol(start_pos, c)
                 }
             }
-            if !self.ids_expr.is_empty() {
-                for id in &self.ids_expr {
-                    ids.push_str(&format!("{} - идентификатор-выражение\n", id));
-                }
+            None => Ok((start_pos..start_pos, Token::End)),
+        }
+    }
+
+    /// Лексема длиной `len` байт на `start_pos`, на которой автомат остановился
+    /// в состоянии `state`.
+    fn dfa_token(
+        &mut self,
+        start_pos: usize,
+        state: dfa::State,
+        len: usize,
+    ) -> Result<(Span, Token), Error> {
+        use dfa::State;
+
+        let span = start_pos..start_pos.saturating_add(len);
+        let text = self.text(span.clone());
+        // Первый символ лексемы; в начальном состоянии автомат остаётся только
+        // в конце ввода
+        let first = text.chars().next().unwrap_or_default();
+        self.skip_to(span.end);
+        match state {
+            State::Start => Ok((span, Token::End)),
+            State::Identifier => self.identifier_token(span),
+            // Из точки после цифр автомат возвращается к целой части
+            State::Decimal | State::Number | State::DecimalDot => self.number_token(span),
+            State::Fraction | State::Exponent => self.real_token(span),
+            State::BadReal => Err(Error::Syntax(
+                span,
+                "Идентификатор не может начинаться с цифры".to_string(),
+            )),
+            State::ExponentMark | State::ExponentSign => Err(Error::Lexical(
+                span,
+                "Ожидались цифры порядка после 'E'".to_string(),
+            )),
+            State::Equals if !self.assigned && self.assign.contains(&AssignOperator::Equals) => {
+                self.assign(start_pos, AssignOperator::Equals)
             }
+            State::Operator | State::Less | State::Greater | State::Compound | State::Equals => {
+                let Some(sign) = OperationSign::from_lexeme(text) else {
+                    let message = format!("Недопустимый символ: '{}'", first);
+                    return self.invalid(start_pos, message);
+                };
+                self.operation(start_pos, sign)
+            }
+            State::Assign => self.assign(start_pos, AssignOperator::ColonEquals),
+            State::Arrow => self.assign(start_pos, AssignOperator::Arrow),
+            State::Colon => self.invalid(start_pos, "Ожидался '=' после ':'".to_string()),
+            State::Quoted | State::DoubleQuoted => {
+                let span = start_pos..start_pos.saturating_add(first.len_utf8());
+                Err(Self::unclosed(span, first))
+            }
+            State::Literal => {
+                let value = text.get(1..text.len().saturating_sub(1)).unwrap_or_default();
+                Ok((span, Token::Literal(value.to_string())))
+            }
+            State::Punctuation | State::Invalid => self.symbol(start_pos, first),
+        }
+    }
+
+    /// Знак операции `sign` на `start_pos`; знак не из набора варианта -
+    /// недопустимый символ.
+    fn operation(&mut self, start_pos: usize, sign: OperationSign) -> Result<(Span, Token), Error> {
+        self.skip_to(start_pos.saturating_add(sign.lexeme().len()));
+        if self.operations.contains(&sign) {
+            return Ok((start_pos..self.pos(), Token::Operation(sign.lexeme())));
+        }
+        let allowed = if self.operations.is_empty() {
+            "знаков операций в нём нет".to_string()
+        } else {
+            format!("допустимы {}", OperationSign::describe(&self.operations))
+        };
+        let message = format!(
+            "Операция '{}' не допускается в этом варианте: {}",
+            sign.lexeme(),
+            allowed
+        );
+        self.invalid(start_pos, message)
+    }
 
-            if !self.const_index.is_empty() {
-                for c in &self.const_index {
-                    consts.push_str(&format!("{} - константа-индекс\n", c));
+    /// Лексема, которая начинается не с буквы и не с цифры: первый её символ `c`
+    /// стоит на `start_pos` и уже прочитан.
+    fn symbol(&mut self, start_pos: usize, c: char) -> Result<(Span, Token), Error> {
+        match c {
+            '[' | ']' | '(' | ')' => {
+                let (open, close) = bracket_tokens(self.brackets);
+                match self.brackets.pair() {
+                    (symbol, _) if symbol == c => Ok((start_pos..self.pos(), open)),
+                    (_, symbol) if symbol == c => Ok((start_pos..self.pos(), close)),
+                    // Круглые скобки не заняты индексами: это скобки вызова
+                    _ if c == '(' => Ok((start_pos..self.pos(), Token::LParen)),
+                    _ if c == ')' => Ok((start_pos..self.pos(), Token::RParen)),
+                    _ => {
+                        let usage = self.brackets.usage();
+                        let message =
+                            format!("Недопустимый символ: '{}' ({})", c, usage);
+                        self.invalid(start_pos, message)
+                    }
                 }
             }
-            if !self.const_expr.is_empty() {
-                for c in &self.const_expr {
-                    consts.push_str(&format!("{} - константа-выражение\n", c));
+            ',' => Ok((start_pos..self.pos(), Token::Comma)),
+            '\'' | '"' => self.lex_literal(start_pos, c),
+            '_' if self.underscores => self.invalid(
+                start_pos,
+                "Идентификатор начинается с буквы, а не с '_'".to_string(),
+            ),
+            '_' => self.invalid(
+                start_pos,
+                "Недопустимый символ: '_' (подчёркивание в идентификаторах \
+                 выключено настройкой underscores)"
+                    .to_string(),
+            ),
+            ':' => {
+                if self.next_if(|c| c == '=').is_some() {
+                    self.assign(start_pos, AssignOperator::ColonEquals)
+                } else {
+                    self.invalid(start_pos, "Ожидался '=' после ':'".to_string())
                 }
             }
-
-            return (Some(ids), Some(consts));
+            ';' | '.' if self.terminator.symbol() == Some(c) => {
+                let token = terminator_token(self.terminator).unwrap_or(Token::End);
+                Ok((start_pos..self.pos(), token))
+            }
+            '.' => Ok((start_pos..self.pos(), Token::Period)),
+            ';' => {
+                let ending = self.terminator.ending();
+                let message = format!("Недопустимый символ: '{}' ({})", c, ending);
+                self.invalid(start_pos, message)
+            }
+            '←' => self.assign(start_pos, AssignOperator::Arrow),
+            '=' if !self.assigned && self.assign.contains(&AssignOperator::Equals) => {
+                self.assign(start_pos, AssignOperator::Equals)
+            }
+            _ if c.is_alphabetic() => self.invalid(
+                start_pos,
+                format!(
+                    "Недопустимый символ: '{}' (в идентификаторах - латинские \
+                     буквы, другие алфавиты разрешает настройка unicode)",
+                    c
+                ),
+            ),
+            _ => {
+                if let Some(sign) = OperationSign::prefix_of(self.text(start_pos..)) {
+                    return self.operation(start_pos, sign);
+                }
+                // Прочие символы - ошибка; о типографском знаке - чем его заменить
+                let mut message = format!("Недопустимый символ: '{}'", c);
+                if let Some(ascii) = crate::sanitize::ascii_lookalike(c) {
+                    message = format!(
+                        "{} - похоже на типографский знак{}вместо него - '{}', \
+                         заменять такие знаки позволяет настройка typography",
+                        message, HINT_PREFIX, ascii
+                    );
+                }
+                self.invalid(start_pos, message)
+            }
         }
+    }
 
-        (None, None)
+    fn tokenize(&mut self) -> Result<Vec<(Span, Token)>, Error> {
+        self.collect()
     }
 }
 
-/// Анализирует строку входного кода, возвращая результаты синтаксического/семантического анализа.
-///
-/// Возвращает:
-/// - Ok((Some(ids_str), Some(consts_str))): при успешном разборе, строки со списками идентификаторов и констант.
-/// - Ok((None, None)): если нет идентификаторов и констант (теоретически не должно быть в данном языке).
-/// - Err(err_str): при ошибке, строка с сообщением и указанием позиции.
-pub fn analyze_line(input: &str) -> Result<(Option<String>, Option<String>), String> {
-    let lexer = Lexer::new(input);
-    let tokens = match lexer.tokenize() {
-        Ok(t) => t,
-        Err(e) => return Err(format_error(e, input)),
-    };
+impl Iterator for Lexer<'_> {
+    type Item = Result<(Span, Token), Error>;
 
-    let mut parser = Parser::new(tokens, input.to_string());
-    match parser.parse() {
-        Ok(_) => {
-            // Успешно
-            let (ids, consts) = parser.finish();
-            Ok((ids, consts))
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        loop {
+            match self.next_token() {
+                Ok((_, Token::End)) => {
+                    self.done = true;
+                    return None;
+                }
+                Ok((span, token)) => {
+                    debug!(?span, ?token, "лексема");
+                    self.events.emit(|| Event::TokenProduced {
+                        span: span.clone(),
+                        token: token.clone(),
+                    });
+                    return Some(Ok((span, token)));
+                }
+                Err(e) if std::mem::take(&mut self.invalid) && self.recover => {
+                    debug!(span = ?e.span(), "символ пропущен");
+                    self.skipped.push(e);
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            }
         }
-        Err(e) => Err(format_error(e, input)),
     }
 }
 
-fn format_error(err: Error, input: &str) -> String {
-    match err {
-        Error::LexicalError(pos, msg) => {
-            format_error_with_cursor(input, pos, &format!("Лексическая ошибка: {}", msg))
+/// Место выражения в операторе: от него зависят правила грамматики и допустимые
+/// операнды.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Place {
+    /// Правая часть: `right` и `term`
+    Right,
+    /// Индекс элемента массива: `index` и `index_term`
+    Index,
+}
+
+impl Place {
+    /// Нетерминал слагаемого выражения.
+    fn term_rule(self) -> &'static str {
+        match self {
+            Place::Right => "term",
+            Place::Index => "index_term",
+        }
+    }
+}
+
+/// Синтаксический анализатор: строит дерево разбора. Берёт лексемы из итератора
+/// по одной: лексическая ошибка дальше места, где разбор уже не удался, не ищется.
+struct Parser<'a, I: Iterator<Item = Result<(Span, Token), Error>>> {
+    tokens: Peekable<I>,
+    /// Отрезок последней взятой лексемы, в конце ввода - последний символ
+    current: Span,
+    input_str: String,
+    terminator: Terminator,
+    brackets: Brackets,
+    /// Допустимые лексемы присваивания для сообщений: `':='`
+    assign: String,
+    /// Разбор дошёл до лексической ошибки; она остаётся первой в `tokens`
+    lexical_error: bool,
+    /// Проверяется ли [`Rule::OperandOrder`]
+    operand_order: bool,
+    /// Операции выражений варианта
+    operators: &'a Registry,
+    /// Подсказка к нарушению [`Rule::OperandOrder`], если подсказки включены
+    operand_hint: Option<String>,
+    /// Режим восстановления ([`AnalyzerConfig::recovery`]): синтаксическая ошибка
+    /// запоминается, а разбор продолжается с ближайшей точки синхронизации
+    recover: bool,
+    /// Синтаксические ошибки, после которых разбор восстановился
+    errors: Vec<Error>,
+    /// Точки синхронизации вложенных частей оператора, от внешней к текущей
+    sync: Vec<Token>,
+    /// Получатель шагов разбора
+    events: Events<'a>,
+}
+
+impl<'a, I: Iterator<Item = Result<(Span, Token), Error>>> Parser<'a, I> {
+    fn new(
+        tokens: I,
+        input_str: String,
+        config: &AnalyzerConfig,
+        variant: &'a dyn Variant,
+        events: Events<'a>,
+    ) -> Self {
+        let rule = Rule::OperandOrder;
+        Parser {
+            tokens: tokens.peekable(),
+            current: 0..0,
+            input_str,
+            terminator: config.terminator,
+            brackets: config.brackets,
+            assign: AssignOperator::describe(&config.assign_operators()),
+            lexical_error: false,
+            operand_order: variant.rules().contains(&rule)
+                && config.severity(rule) != Severity::Off,
+            operators: variant.operators(),
+            operand_hint: config
+                .hints
+                .then(|| rule.description(&config.constant_range(variant.constant_range()))),
+            recover: config.recovery,
+            errors: Vec::new(),
+            sync: terminator_token(config.terminator).into_iter().collect(),
+            events,
+        }
+    }
+
+    /// Разбирает нетерминал `rule` грамматики функцией `parse`. Шаги разбора
+    /// получают начало и конец нетерминала, даже если разбор не удался.
+    fn rule<T>(
+        &mut self,
+        rule: &'static str,
+        parse: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let position = self.current.end;
+        self.events.emit(|| Event::RuleEntered { rule, position });
+        let result = parse(self);
+        let position = self.current.end;
+        self.events.emit(|| Event::RuleExited { rule, position });
+        result
+    }
+
+    /// Следующая лексема. Лексическая ошибка выглядит для разбора как конец ввода.
+    fn peek(&mut self) -> Option<&(Span, Token)> {
+        match self.tokens.peek() {
+            Some(Ok(pair)) => Some(pair),
+            Some(Err(_)) => {
+                self.lexical_error = true;
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn next_token(&mut self) -> Option<(Span, Token)> {
+        let pair = match self.tokens.next_if(Result::is_ok) {
+            Some(Ok(pair)) => Some(pair),
+            _ => {
+                self.lexical_error |= self.tokens.peek().is_some();
+                None
+            }
+        };
+        if let Some((span, token)) = pair.clone() {
+            self.events.emit(|| Event::TokenConsumed {
+                span: span.clone(),
+                token,
+            });
+            self.current = span
+        } else {
+            self.current = match self.input_str.char_indices().next_back() {
+                Some((pos, c)) => pos..pos.saturating_add(c.len_utf8()),
+                None => 0..0,
+            };
+        };
+        pair
+    }
+
+    fn expect(
+        &mut self,
+        expected: &[Token],
+        error_message_some: String,
+        error_message_none: String,
+    ) -> Result<Token, Error> {
+        if let Some((_, t)) = self.peek().filter(|(_, t)| expected.contains(t)) {
+            let t = t.clone();
+            self.next_token();
+            return Ok(t);
+        }
+        match self.unexpected() {
+            Some(_) => Err(Error::Expected(self.current_span(), error_message_some)),
+            None => Err(Error::Expected(self.current_span(), error_message_none)),
+        }
+    }
+
+    /// Берёт ошибочную лексему. Точка синхронизации в режиме восстановления
+    /// остаётся во входе - с неё разбор продолжится, - но ошибка указывает на неё.
+    fn unexpected(&mut self) -> Option<(Span, Token)> {
+        let sync = self.sync.clone();
+        let pair = self.peek().filter(|(_, t)| sync.contains(t)).cloned();
+        match pair {
+            Some((span, token)) if self.recover => {
+                self.current = span.clone();
+                Some((span, token))
+            }
+            _ => self.next_token(),
+        }
+    }
+
+    /// Разбирает часть оператора `parse`, восстанавливаясь после синтаксической
+    /// ошибки в ней или нарушения [`Rule::OperandOrder`]: ошибка запоминается,
+    /// лексемы пропускаются до точки синхронизации. `Ok(None)` - разбор
+    /// продолжается с точки `own` этой части; на точке внешней части или в конце
+    /// ввода ошибка уходит к ней.
+    fn recovering<T>(
+        &mut self,
+        own: &[Token],
+        parse: impl FnOnce(&mut Self) -> Result<T, Error>,
+    ) -> Result<Option<T>, Error> {
+        let depth = self.sync.len();
+        self.sync.extend_from_slice(own);
+        let result = parse(self);
+        let e = match result {
+            Err(
+                e @ (Error::Syntax(..)
+                | Error::Expected(..)
+                | Error::Semantic(Rule::OperandOrder, ..)),
+            ) if self.recover && !self.lexical_error => e,
+            result => {
+                self.sync.truncate(depth);
+                return result.map(Some);
+            }
+        };
+        let sync = self.sync.clone();
+        while self.peek().is_some_and(|(_, t)| !sync.contains(t)) {
+            self.next_token();
+        }
+        self.sync.truncate(depth);
+        if !self.peek().is_some_and(|(_, t)| own.contains(t)) {
+            return Err(e);
+        }
+        self.record(e);
+        Ok(None)
+    }
+
+    /// Запоминает ошибку, после которой разбор восстановился. Ошибка на месте
+    /// предыдущей - её следствие, а не новая ошибка.
+    fn record(&mut self, e: Error) {
+        if self.errors.last().is_none_or(|last| last.position() < e.position()) {
+            self.errors.push(e);
+        }
+    }
+
+    fn get_current_position(&self) -> usize {
+        self.current.start
+    }
+
+    fn current_span(&self) -> Span {
+        self.current.clone()
+    }
+
+    /// Разбирает оператор. Если разбор дошёл до лексической ошибки, возвращается она:
+    /// синтаксическая ошибка в этом случае - лишь её следствие. В режиме
+    /// восстановления ошибок может быть несколько, в порядке позиций.
+    fn parse(&mut self) -> Result<ast::Assignment, Vec<Error>> {
+        let result = self.rule("statement", Self::parse_statement);
+        let mut errors = std::mem::take(&mut self.errors);
+        if self.lexical_error {
+            if let Some(Err(e)) = self.tokens.next() {
+                errors.push(e);
+                return Err(errors);
+            }
+        }
+        match result {
+            Ok(Some(ast)) if errors.is_empty() => Ok(ast),
+            Ok(_) => Err(errors),
+            Err(e) => {
+                if errors.last().is_none_or(|last| last.position() < e.position()) {
+                    errors.push(e);
+                }
+                Err(errors)
+            }
+        }
+    }
+
+    /// Оператор; `None`, если в нём были ошибки, после которых разбор восстановился.
+    fn parse_statement(&mut self) -> Result<Option<ast::Assignment>, Error> {
+        // <левая часть> := <правая часть>;
+        let target = self.recovering(&[Token::Assign], Self::parse_left_part)?;
+
+        let assign = self.recovering(&[Token::Assign], |parser| {
+            let assign = parser.assign.clone();
+            parser.expect(
+                &[Token::Assign],
+                format!("Ожидалось {}", assign),
+                format!("Ожидалось {}, но достигнут конец", assign),
+            )
+        })?;
+        if assign.is_none() {
+            // Пропуск остановился на присваивании
+            self.next_token();
+        }
+        let end: Vec<Token> = terminator_token(self.terminator).into_iter().collect();
+        let value = self.recovering(&end, Self::parse_right_part)?;
+        let terminator = self.terminator.describe();
+        if let Some(token) = terminator_token(self.terminator) {
+            self.expect(
+                &[token],
+                format!("Ожидалось либо {}, либо операция", terminator),
+                format!("Ожидалось {}, но достигнут конец", terminator),
+            )?;
+        }
+
+        if self.next_token().is_some() {
+            let message = match self.terminator {
+                Terminator::None => "Ожидалась операция или конец строки".to_string(),
+                _ => format!("После {} ничего не ожидается", terminator),
+            };
+            Err(Error::Syntax(self.current_span(), message))
+        } else {
+            Ok(target
+                .zip(value)
+                .map(|(target, value)| ast::Assignment { target, value }))
+        }
+    }
+
+    fn parse_left_part(&mut self) -> Result<ast::Target, Error> {
+        self.rule("left", |parser| {
+            // <левая часть> ::= <имя> | <имя>[<список индексов>] { <доступ> }
+            let (ident, pos, fields) = parser.parse_name()?;
+            // Считаем, что это потенциально имя массива
+            // Но если не будет индексов - это просто одиночный идентификатор
+            let indices = parser.parse_indices()?;
+            let chain = match indices {
+                Some(_) => parser.parse_chain()?,
+                None => Vec::new(),
+            };
+            Ok(ast::Target {
+                name: ident,
+                position: pos,
+                fields,
+                indices,
+                chain,
+            })
+        })
+    }
+
+    /// Доступы к элементу массива левой части:
+    /// `<доступ> ::= .<идентификатор> | [<список индексов>]`.
+    fn parse_chain(&mut self) -> Result<Vec<ast::Access>, Error> {
+        let mut chain = Vec::new();
+        loop {
+            if let Some((_, Token::Period)) = self.peek() {
+                let (name, position) = self.parse_field()?;
+                chain.push(ast::Access::Field { name, position });
+                continue;
+            }
+            match self.parse_indices()? {
+                Some(indices) => chain.push(ast::Access::Indices(indices)),
+                None => return Ok(chain),
+            }
+        }
+    }
+
+    /// Имя с полями записи: `<имя> ::= <идентификатор> { .<идентификатор> }`.
+    /// Возвращает составное имя через точку, его позицию и позиции имён полей.
+    fn parse_name(&mut self) -> Result<(String, usize, Vec<usize>), Error> {
+        let mut name = self.parse_identifier()?;
+        let pos = self.get_current_position();
+        let mut fields = Vec::new();
+        while let Some((_, Token::Period)) = self.peek() {
+            let (field, position) = self.parse_field()?;
+            name.push('.');
+            name.push_str(&field);
+            fields.push(position);
+        }
+        Ok((name, pos, fields))
+    }
+
+    /// Точка и имя поля за ней; возвращает имя и его позицию.
+    fn parse_field(&mut self) -> Result<(String, usize), Error> {
+        self.next_token();
+        let field = match self.peek() {
+            Some((_, Token::Identifier(field))) => field.clone(),
+            _ => {
+                let token = self.unexpected();
+                return Err(self.expected("Ожидалось имя поля после '.'", token));
+            }
+        };
+        self.next_token();
+        Ok((field, self.get_current_position()))
+    }
+
+    /// Список индексов в скобках после имени массива; `None`, если скобки нет.
+    fn parse_indices(&mut self) -> Result<Option<Vec<ast::Expression>>, Error> {
+        let (open, close) = bracket_tokens(self.brackets);
+        if !self.peek().is_some_and(|(_, t)| *t == open) {
+            return Ok(None);
+        }
+        self.next_token();
+        let opened = self.current_span();
+        if let Some((span, _)) = self.peek().filter(|(_, t)| *t == close) {
+            let span = opened.start..span.end;
+            return Err(Error::Syntax(span, "Пустой список индексов".to_string()));
+        }
+
+        let indices = self.parse_index_list()?;
+        let (_, symbol) = self.brackets.pair();
+        self.expect(
+            &[close],
+            format!("Ожидалось '{}'", symbol),
+            format!("Ожидалось '{}', но достигнут конец", symbol),
+        )?;
+        Ok(Some(indices))
+    }
+
+    /// Аргументы вызова функции в круглых скобках; `None`, если скобки нет или
+    /// круглые скобки заняты индексами ([`Brackets::Round`]).
+    fn parse_arguments(&mut self) -> Result<Option<Vec<ast::Expression>>, Error> {
+        let opened = self.peek().is_some_and(|(_, t)| *t == Token::LParen);
+        if self.brackets == Brackets::Round || !opened {
+            return Ok(None);
+        }
+        self.next_token();
+        let arguments = self.rule("arguments", Self::parse_argument_list)?;
+        self.expect(
+            &[Token::RParen],
+            "Ожидалось ')' после аргументов".to_string(),
+            "Ожидалось ')', но достигнут конец".to_string(),
+        )?;
+        Ok(Some(arguments))
+    }
+
+    fn parse_argument_list(&mut self) -> Result<Vec<ast::Expression>, Error> {
+        // <аргументы> ::= <пусто> | <правая часть> | <аргументы>,<правая часть>
+        let mut arguments = Vec::new();
+        if !self.peek().is_some_and(|(_, t)| *t == Token::RParen) {
+            let sync = [Token::Comma, Token::RParen];
+            loop {
+                arguments.extend(self.recovering(&sync, Self::parse_right_part)?);
+                if !matches!(self.peek(), Some((_, Token::Comma))) {
+                    break;
+                }
+                self.next_token();
+            }
+        }
+        Ok(arguments)
+    }
+
+    fn parse_index_list(&mut self) -> Result<Vec<ast::Expression>, Error> {
+        self.rule("index_list", |parser| {
+            // <список индексов> ::= <индекс> | <список индексов>,<индекс>
+            let (_, close) = bracket_tokens(parser.brackets);
+            let sync = [Token::Comma, close];
+            let mut indices = Vec::new();
+            loop {
+                indices.extend(parser.recovering(&sync, Self::parse_index)?);
+                if !matches!(parser.peek(), Some((_, Token::Comma))) {
+                    break;
+                }
+                parser.next_token();
+            }
+            Ok(indices)
+        })
+    }
+
+    fn parse_index(&mut self) -> Result<ast::Expression, Error> {
+        self.rule("index", |parser| {
+            // <индекс> ::= [<знак>] <слагаемое индекса> | <индекс><операция><слагаемое индекса>
+            if parser.peek().is_none() {
+                return Err(Error::Expected(
+                    parser.current_span(),
+                    "Ожидался индекс, но достигнут конец".to_string(),
+                ));
+            }
+            parser.parse_expression(Place::Index)
+        })
+    }
+
+    fn parse_right_part(&mut self) -> Result<ast::Expression, Error> {
+        self.rule("right", |parser| {
+            // <правая часть> ::= [<знак>] <слагаемое> | <правая часть><операция><слагаемое>
+            parser.parse_expression(Place::Right)
+        })
+    }
+
+    /// Слагаемые выражения `place`, соединённые операциями, разбором Пратта по
+    /// реестру операций варианта. Слагаемые записываются в порядке записи; их
+    /// группировку по приоритетам даёт [`Registry::group`].
+    fn parse_expression(&mut self, place: Place) -> Result<ast::Expression, Error> {
+        let first = self.parse_term(true, place)?;
+        self.check_alternation(self.term_span(&first), true)?;
+        let mut rest = Vec::new();
+        self.parse_infix(0, place, &mut rest)?;
+        Ok(ast::Expression { first, rest })
+    }
+
+    /// Бинарные операции с приоритетом не ниже `min` и их правые операнды:
+    /// операции сильнее текущей входят в её правый операнд.
+    fn parse_infix(
+        &mut self,
+        min: u8,
+        place: Place,
+        rest: &mut Vec<(ast::Operation, ast::Term)>,
+    ) -> Result<(), Error> {
+        while let Some(operator) = self.peek_infix().filter(|o| o.precedence >= min) {
+            let Some((span, Token::Operation(symbol))) = self.next_token() else {
+                break;
+            };
+            self.check_alternation(span.clone(), false)?;
+            let operation = ast::Operation {
+                symbol,
+                position: span.start,
+            };
+            let term = self.parse_term(false, place)?;
+            self.check_alternation(self.term_span(&term), true)?;
+            rest.push((operation, term));
+            let right = match operator.associativity {
+                Associativity::Left => operator.precedence.saturating_add(1),
+                Associativity::Right => operator.precedence,
+            };
+            self.parse_infix(right, place, rest)?;
+        }
+        Ok(())
+    }
+
+    /// Бинарная операция реестра впереди.
+    fn peek_infix(&mut self) -> Option<&'a Operator> {
+        let operators = self.operators;
+        match self.peek() {
+            Some((_, Token::Operation(symbol))) => operators.get(symbol, Fixity::Infix),
+            _ => None,
+        }
+    }
+
+    /// Унарная операция реестра впереди; перед первым слагаемым (`first`)
+    /// годится и знак [`Fixity::Leading`].
+    fn peek_unary(&mut self, first: bool) -> Option<(usize, &'static str)> {
+        let operators = self.operators;
+        let (span, symbol) = match self.peek()? {
+            (span, Token::Operation(symbol)) => (span, *symbol),
+            (span, Token::Not) => (span, "NOT"),
+            _ => return None,
+        };
+        operators.unary(symbol, first)?;
+        Some((span.start, symbol))
+    }
+
+    /// Отрезок операнда только что разобранного слагаемого `term`: у элемента
+    /// массива - вместе с индексами.
+    fn term_span(&self, term: &ast::Term) -> Span {
+        term.operand.position()..self.current.end
+    }
+
+    /// Правило [`Rule::OperandOrder`]: за операндом (`operand`) или операцией
+    /// на отрезке `previous` не следует лексема того же рода; унарная операция
+    /// реестра после бинарной - начало операнда. Ошибка охватывает обе лексемы.
+    fn check_alternation(&mut self, previous: Span, operand: bool) -> Result<(), Error> {
+        if !self.operand_order {
+            return Ok(());
+        }
+        let operators = self.operators;
+        let next = match self.peek() {
+            Some((
+                span,
+                Token::Identifier(_) | Token::Constant(..) | Token::Real(_) | Token::Literal(_),
+            )) if operand => span.clone(),
+            Some((span, Token::Operation(symbol)))
+                if !operand && operators.unary(symbol, false).is_none() =>
+            {
+                span.clone()
+            }
+            _ => return Ok(()),
+        };
+        let text = |span: &Span| self.input_str.get(span.clone()).unwrap_or_default();
+        let (first, second) = (text(&previous), text(&next));
+        let message = if operand {
+            format!(
+                "Два операнда подряд: '{}' и '{}', между ними пропущена операция",
+                first, second
+            )
+        } else {
+            format!(
+                "Две операции подряд: '{}' и '{}', между ними пропущен операнд",
+                first, second
+            )
+        };
+        let err = Error::Semantic(Rule::OperandOrder, previous.start..next.end, message);
+        Err(match &self.operand_hint {
+            Some(hint) => err.with_hint(hint),
+            None => err,
+        })
+    }
+
+    /// Слагаемое выражения `place`; знак перед ним допустим, если оно первое (`first`).
+    fn parse_term(&mut self, first: bool, place: Place) -> Result<ast::Term, Error> {
+        self.rule(place.term_rule(), |parser| {
+            // <term> ::= [<знак>] { NOT } <операнд>
+            let mut unary = Vec::new();
+            // Знак перед первым слагаемым - только один и только в начале
+            let mut leading = first;
+            while let Some((position, symbol)) = parser.peek_unary(leading) {
+                leading = false;
+                parser.next_token();
+                unary.push(ast::Operation { symbol, position });
+            }
+            let operand = parser.parse_operand(place)?;
+            Ok(ast::Term { unary, operand })
+        })
+    }
+
+    fn parse_operand(&mut self, place: Place) -> Result<ast::Operand, Error> {
+        // <операнд> ::= <имя> | <имя>[<список индексов>] | <имя>(<аргументы>)
+        //             | <константа> | <литерал>
+        // <операнд индекса> ::= <имя> | <константа>
+        match self.peek() {
+            Some((_, Token::Literal(_))) if place == Place::Right => self.parse_literal(),
+            Some((_, Token::Identifier(_))) => {
+                let (ident, pos, fields) = self.parse_name()?;
+                let indices = match place {
+                    Place::Right => self.parse_indices()?,
+                    Place::Index => None,
+                };
+                let arguments = match (place, &indices) {
+                    (Place::Right, None) => self.parse_arguments()?,
+                    _ => None,
+                };
+                Ok(match (indices, arguments) {
+                    (Some(indices), _) => ast::Operand::Element {
+                        name: ident,
+                        position: pos,
+                        fields,
+                        indices,
+                    },
+                    (None, Some(arguments)) => ast::Operand::Call {
+                        name: ident,
+                        position: pos,
+                        fields,
+                        arguments,
+                    },
+                    (None, None) => ast::Operand::Identifier {
+                        name: ident,
+                        position: pos,
+                        fields,
+                    },
+                })
+            }
+            Some((_, Token::Real(_))) => self.parse_real(),
+            Some((_, Token::Constant(..))) => {
+                let c = self.parse_constant()?;
+                let pos = self.get_current_position();
+                Ok(ast::Operand::Constant {
+                    value: c,
+                    position: pos,
+                })
+            }
+            _ => {
+                let token = self.unexpected();
+                let message = match place {
+                    Place::Right => "Ожидался идентификатор или константа в правой части",
+                    Place::Index => "Ожидался идентификатор или константа в индексе",
+                };
+                Err(self.expected(message, token))
+            }
+        }
+    }
+
+    fn parse_identifier(&mut self) -> Result<String, Error> {
+        let name = match self.peek() {
+            Some((_, Token::Identifier(name))) => name.clone(),
+            _ => {
+                let token = self.unexpected();
+                return Err(self.expected("Ожидался идентификатор", token));
+            }
+        };
+        self.next_token();
+        Ok(name)
+    }
+
+    /// Ошибка на месте взятой лексемы `token`. Ключевое слово операции там, где
+    /// ожидался операнд, поясняется: вероятно, его написали как имя. Литерал вне
+    /// правой части тоже поясняется.
+    fn expected(&self, message: &str, token: Option<(Span, Token)>) -> Error {
+        if let Some((_, Token::Literal(_))) = token {
+            return Error::Syntax(
+                self.current_span(),
+                format!("{}: литерал допустим только в правой части", message),
+            );
+        }
+        let keyword = match token {
+            Some((_, Token::Operation(keyword))) if ast::is_keyword(keyword) => Some(keyword),
+            Some((_, Token::Not)) => Some("NOT"),
+            _ => None,
+        };
+        match keyword {
+            Some(keyword) => Error::Syntax(
+                self.current_span(),
+                format!(
+                    "{}: {} - ключевое слово операции, а не имя",
+                    message, keyword
+                ),
+            ),
+            None => Error::Expected(self.current_span(), message.to_string()),
+        }
+    }
+
+    fn parse_real(&mut self) -> Result<ast::Operand, Error> {
+        if let Some((span, Token::Real(value))) = self.next_token() {
+            Ok(ast::Operand::Real {
+                value,
+                position: span.start,
+            })
+        } else {
+            Err(Error::Syntax(
+                self.current_span(),
+                "Ожидалась вещественная константа".to_string(),
+            ))
+        }
+    }
+
+    fn parse_literal(&mut self) -> Result<ast::Operand, Error> {
+        if let Some((span, Token::Literal(value))) = self.next_token() {
+            Ok(ast::Operand::Literal {
+                value,
+                position: span.start,
+            })
+        } else {
+            Err(Error::Syntax(
+                self.current_span(),
+                "Ожидалась символьная или строковая константа".to_string(),
+            ))
+        }
+    }
+
+    fn parse_constant(&mut self) -> Result<i32, Error> {
+        if let Some((_, Token::Constant(c, _))) = self.next_token() {
+            Ok(c)
+        } else {
+            Err(Error::Syntax(
+                self.current_span(),
+                "Ожидалась константа".to_string(),
+            ))
+        }
+    }
+}
+
+/// Семантический анализ дерева разбора: проверка правил и списки символов по ролям.
+struct Checker<'a> {
+    ids_array: Occurrences<String>,
+    ids_index: Occurrences<String>,
+    ids_expr: Occurrences<String>,
+    /// Массивы, элементы которых стоят в правой части
+    ids_expr_array: Occurrences<String>,
+    /// Функции, вызовы которых стоят в правой части
+    ids_function: Occurrences<String>,
+    /// Поля элементов массивов в цепочке левой части
+    ids_field: Occurrences<String>,
+    const_index: Occurrences<i32>,
+    const_expr: Occurrences<i32>,
+    real_index: Occurrences<ast::Real>,
+    real_expr: Occurrences<ast::Real>,
+    /// Литералы правой части; роль - по числу символов
+    literal_expr: Occurrences<String>,
+    /// Правило грамматики каждого вхождения: левая часть, первый терминал, функция разбора
+    origins: HashMap<usize, (&'static str, &'static str, &'static str)>,
+    /// Позиции имён полей составного имени по позиции его первой компоненты
+    fields: HashMap<usize, Vec<usize>>,
+    /// Конец записи вещественной константы по её началу - из промежутка лексемы
+    real_ends: HashMap<usize, usize>,
+
+    variant: &'a dyn Variant,
+    reporter: Reporter<'a>,
+    /// Анализируемая строка: из неё берётся запись констант
+    input: &'a str,
+}
+
+impl<'a> Checker<'a> {
+    fn new(
+        config: &'a AnalyzerConfig,
+        variant: &'a dyn Variant,
+        input: &'a str,
+        lexed: &[(Span, Token)],
+    ) -> Self {
+        let real_ends = lexed
+            .iter()
+            .filter(|(_, token)| matches!(token, Token::Real(_)))
+            .map(|(span, _)| (span.start, span.end))
+            .collect();
+        Checker {
+            ids_array: IndexMap::new(),
+            ids_index: IndexMap::new(),
+            ids_expr: IndexMap::new(),
+            ids_expr_array: IndexMap::new(),
+            ids_function: IndexMap::new(),
+            ids_field: IndexMap::new(),
+            const_index: IndexMap::new(),
+            const_expr: IndexMap::new(),
+            real_index: IndexMap::new(),
+            real_expr: IndexMap::new(),
+            literal_expr: IndexMap::new(),
+            origins: HashMap::new(),
+            fields: HashMap::new(),
+            real_ends,
+            variant,
+            reporter: Reporter::new(config, config.constant_range(variant.constant_range())),
+            input,
+        }
+    }
+
+    /// Сообщает о нарушении правила, если правило есть в варианте.
+    fn violation(&mut self, rule: Rule, span: Span, message: String) -> Result<(), Error> {
+        if self.variant.rules().contains(&rule) {
+            self.reporter.violation(rule, span, message)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Нарушение правила с настроенной строгостью "ошибка" исключает символ из списков.
+    fn is_error(&self, rule: Rule) -> bool {
+        self.variant.rules().contains(&rule)
+            && self.reporter.config.severity(rule) == Severity::Error
+    }
+
+    /// Обходит дерево в порядке записи оператора.
+    fn check(&mut self, assignment: &ast::Assignment) -> Result<(), Error> {
+        let target = &assignment.target;
+        self.check_name(&target.name, target.position, &target.fields)?;
+        match &target.indices {
+            Some(indices) => {
+                record(&mut self.ids_array, target.name.clone(), target.position);
+                self.origin(target.position, "left", "IDENT", "parse_left_part");
+                for index in indices {
+                    self.check_index(index)?;
+                }
+                self.check_chain(&target.chain)?;
+            }
+            None => {
+                record(&mut self.ids_expr, target.name.clone(), target.position);
+                self.origin(target.position, "left", "IDENT", "parse_left_part");
+            }
+        }
+
+        // Имя массива в левой части
+        let left_array_name = target.indices.as_ref().map(|_| target.name.as_str());
+        self.check_right(&assignment.value, left_array_name)
+    }
+
+    /// Доступы к элементу массива левой части: поля и следующие списки индексов.
+    fn check_chain(&mut self, chain: &[ast::Access]) -> Result<(), Error> {
+        for access in chain {
+            match access {
+                ast::Access::Field { name, position } => {
+                    self.check_identifier(name, *position)?;
+                    record(&mut self.ids_field, name.clone(), *position);
+                    self.origin(*position, "field", "IDENT", "parse_field");
+                }
+                ast::Access::Indices(indices) => {
+                    for index in indices {
+                        self.check_index(index)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Слагаемые выражения правой части, в том числе аргументов вызовов.
+    fn check_right(
+        &mut self,
+        value: &ast::Expression,
+        left_array_name: Option<&str>,
+    ) -> Result<(), Error> {
+        for term in value.terms() {
+            match &term.operand {
+                ast::Operand::Identifier {
+                    name,
+                    position,
+                    fields,
+                } => {
+                    self.check_name(name, *position, fields)?;
+
+                    // Нельзя использовать идентификатор массива (т.е. такой же, как слева) в правой части
+                    if !self.check_left_array(left_array_name, name, *position)? {
+                        continue;
+                    }
+                    record(&mut self.ids_expr, name.clone(), *position);
+                    self.origin(*position, "term", "IDENT", "parse_term");
+                }
+                ast::Operand::Element {
+                    name,
+                    position,
+                    fields,
+                    indices,
+                } => {
+                    self.check_name(name, *position, fields)?;
+                    // Элемент того же массива, что слева, запрещён так же, как имя
+                    if self.check_left_array(left_array_name, name, *position)? {
+                        record(&mut self.ids_expr_array, name.clone(), *position);
+                        self.origin(*position, "term", "IDENT", "parse_term");
+                    }
+                    for index in indices {
+                        self.check_index(index)?;
+                    }
+                }
+                ast::Operand::Call {
+                    name,
+                    position,
+                    fields,
+                    arguments,
+                } => {
+                    self.check_name(name, *position, fields)?;
+                    if self.check_left_array(left_array_name, name, *position)? {
+                        record(&mut self.ids_function, name.clone(), *position);
+                        self.origin(*position, "term", "IDENT", "parse_term");
+                    }
+                    for argument in arguments {
+                        self.check_right(argument, left_array_name)?;
+                    }
+                }
+                ast::Operand::Constant { value, position } => {
+                    // Знак перед константой входит в её значение и в отрезок ошибки
+                    let value = term.constant().unwrap_or(*value);
+                    let start = term.sign().map_or(*position, |sign| sign.position);
+                    let end = position.saturating_add(self.lexeme_len(*position));
+                    self.check_constant(value, start..end)?;
+                    record(&mut self.const_expr, value, *position);
+                    self.origin(*position, "term", "CONST", "parse_term");
+                }
+                ast::Operand::Real { value, position } => {
+                    record(&mut self.real_expr, *value, *position);
+                    self.origin(*position, "term", "CONST", "parse_term");
+                }
+                ast::Operand::Literal { value, position } => {
+                    record(&mut self.literal_expr, value.clone(), *position);
+                    self.origin(*position, "term", "LITERAL", "parse_term");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Слагаемые индекса `index`: идентификаторы и константы получают роли индекса.
+    fn check_index(&mut self, index: &ast::Expression) -> Result<(), Error> {
+        for term in index.terms() {
+            self.check_index_term(term)?;
+        }
+        Ok(())
+    }
+
+    fn check_index_term(&mut self, term: &ast::Term) -> Result<(), Error> {
+        match &term.operand {
+            ast::Operand::Identifier {
+                name,
+                position,
+                fields,
+            } => {
+                self.check_name(name, *position, fields)?;
+                record(&mut self.ids_index, name.clone(), *position);
+                self.origin(*position, "index_term", "IDENT", "parse_term");
+            }
+            ast::Operand::Constant { value, position } => {
+                let value = term.constant().unwrap_or(*value);
+                let start = term.sign().map_or(*position, |sign| sign.position);
+                let end = position.saturating_add(self.lexeme_len(*position));
+                self.check_constant(value, start..end)?;
+                record(&mut self.const_index, value, *position);
+                self.origin(*position, "index_term", "CONST", "parse_term");
+            }
+            ast::Operand::Real { value, position } => {
+                let rule = Rule::RealIndex;
+                self.violation(
+                    rule,
+                    *position..position.saturating_add(self.real_len(*position)),
+                    format!("Индекс не может быть вещественной константой: {}", value),
+                )?;
+                if !self.is_error(rule) {
+                    record(&mut self.real_index, *value, *position);
+                    self.origin(*position, "index_term", "CONST", "parse_term");
+                }
+            }
+            // Литерал, элемент массива и вызов в индексе отвергает уже синтаксический анализ
+            ast::Operand::Literal { .. }
+            | ast::Operand::Element { .. }
+            | ast::Operand::Call { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Правило [`Rule::ArrayInExpression`] для идентификатора `name` правой части:
+    /// он не должен совпадать с массивом левой части `left_array_name`. `false`,
+    /// если нарушение - ошибка и вхождение в списки не попадает.
+    fn check_left_array(
+        &mut self,
+        left_array_name: Option<&str>,
+        name: &str,
+        position: usize,
+    ) -> Result<bool, Error> {
+        if left_array_name != Some(name) {
+            return Ok(true);
+        }
+        let rule = Rule::ArrayInExpression;
+        self.violation(
+            rule,
+            self.name_span(position),
+            format!("Нельзя использовать массив {} из левой части в правой части", name),
+        )?;
+        Ok(!self.is_error(rule))
+    }
+
+    fn origin(&mut self, pos: usize, lhs: &'static str, first: &'static str, parser: &'static str) {
+        self.origins.insert(pos, (lhs, first, parser));
+    }
+
+    /// Продукции и функции разбора вхождений для таблицы символов.
+    fn origins_of(&self, positions: &[usize]) -> Vec<model::Origin> {
+        let Some(table) = ll1_table(self.reporter.config) else {
+            return Vec::new();
+        };
+        let grammar = table.grammar();
+        positions
+            .iter()
+            .filter_map(|&position| {
+                let &(lhs, first, parser) = self.origins.get(&position)?;
+                let production = grammar
+                    .productions_of(lhs)
+                    .map(|(_, production)| production)
+                    .find(|production| {
+                        production.rhs.first().and_then(|symbol| symbol.terminal()) == Some(first)
+                    })
+                    .map_or_else(|| lhs.to_string(), ToString::to_string);
+                Some(model::Origin {
+                    position,
+                    production,
+                    parser: parser.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Отрезок записи идентификатора в позиции `pos`. Имя в дереве переведено
+    /// в верхний регистр и может быть длиннее записи: `ß` становится `SS`.
+    fn identifier_span(&self, pos: usize) -> Span {
+        let len: usize = self
+            .input
+            .get(pos..)
+            .unwrap_or_default()
+            .chars()
+            .take_while(|&c| c.is_alphanumeric() || c == '_')
+            .map(char::len_utf8)
+            .sum();
+        pos..pos.saturating_add(len)
+    }
+
+    /// Отрезок записи имени в позиции `pos` вместе с полями записи.
+    fn name_span(&self, pos: usize) -> Span {
+        let last = self.fields.get(&pos).and_then(|fields| fields.last());
+        pos..self.identifier_span(last.copied().unwrap_or(pos)).end
+    }
+
+    /// Запись имени в позиции `pos`: компоненты через точку, без пробелов
+    /// и комментариев между ними.
+    fn name_lexeme(&self, pos: usize) -> String {
+        let fields = self.fields.get(&pos).map_or(&[][..], Vec::as_slice);
+        let components = std::iter::once(&pos).chain(fields);
+        let components: Vec<&str> = components
+            .filter_map(|&pos| self.input.get(self.identifier_span(pos)))
+            .collect();
+        components.join(".")
+    }
+
+    /// Составное имя `name` проверяется по компонентам: у каждой свои
+    /// ограничения идентификатора.
+    fn check_name(&mut self, name: &str, position: usize, fields: &[usize]) -> Result<(), Error> {
+        for (component, pos) in ast::components(name, position, fields) {
+            self.check_identifier(component, pos)?;
+        }
+        if !fields.is_empty() {
+            self.fields.insert(position, fields.to_vec());
+        }
+        Ok(())
+    }
+
+    /// Длина идентификатора - в символах записи, а не в байтах.
+    fn check_identifier(&mut self, name: &str, position: usize) -> Result<(), Error> {
+        let span = self.identifier_span(position);
+        if self.variant.reserved_words().contains(&name) {
+            self.violation(
+                Rule::ReservedWord,
+                span.clone(),
+                format!("Зарезервированное слово не может быть идентификатором: {}", name),
+            )?;
+        }
+        let text = self.input.get(span.clone()).unwrap_or_default();
+        if text.chars().count() > self.variant.max_identifier_length() {
+            self.violation(
+                Rule::IdentifierLength,
+                span,
+                format!("Идентификатор слишком длинный: {}", name),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Допустимые значения констант по настройкам и варианту.
+    fn constant_range(&self) -> RangeInclusive<i32> {
+        self.reporter.constants.clone()
+    }
+
+    /// Правило [`Rule::ConstantRange`] для константы `value`, записанной на отрезке `span`.
+    fn check_constant(&mut self, value: i32, span: Span) -> Result<(), Error> {
+        let range = self.constant_range();
+        if !range.contains(&value) {
+            self.violation(
+                Rule::ConstantRange,
+                span,
+                format!(
+                    "Константа вне диапазона [{}..{}]: {}",
+                    range.start(),
+                    range.end(),
+                    value
+                ),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Длина записи константы в позиции `pos`: цифры и суффикс системы счисления.
+    fn lexeme_len(&self, pos: usize) -> usize {
+        self.input
+            .bytes()
+            .skip(pos)
+            .take_while(u8::is_ascii_alphanumeric)
+            .count()
+    }
+
+    /// Длина записи вещественной константы в позиции `pos` - по промежутку её лексемы,
+    /// а не по тексту: точка-терминатор после константы в неё не входит.
+    fn real_len(&self, pos: usize) -> usize {
+        self.real_ends
+            .get(&pos)
+            .map_or(0, |end| end.saturating_sub(pos))
+    }
+
+    /// Длина записи литерала в позиции `pos` вместе с кавычками.
+    fn literal_len(&self, pos: usize) -> usize {
+        // Кавычка - один байт: литерал начинается с неё
+        let content = pos.saturating_add(1);
+        let quote = self.input.get(pos..content).unwrap_or_default();
+        let rest = self.input.get(content..).unwrap_or_default();
+        rest.find(quote).map_or(1, |end| end.saturating_add(2))
+    }
+
+    /// Роль литерала: символьная константа или строковая.
+    fn literal_role(value: &str) -> model::ConstantRole {
+        if ast::is_character(value) {
+            model::ConstantRole::CharacterExpression
+        } else {
+            model::ConstantRole::StringExpression
+        }
+    }
+
+    /// Различные записи константы в порядке появления.
+    fn lexemes(&self, positions: &[usize]) -> Vec<String> {
+        self.lexemes_of(positions, |pos| self.lexeme_len(pos))
+    }
+
+    fn lexemes_of(&self, positions: &[usize], len: impl Fn(usize) -> usize) -> Vec<String> {
+        distinct(positions.iter().map(|&pos| {
+            let end = pos.saturating_add(len(pos));
+            self.input.get(pos..end).unwrap_or_default().to_string()
+        }))
+    }
+
+    fn symbols(&self, order: SymbolOrder) -> Symbols {
+        let mut identifiers = Vec::new();
+        let roles = [
+            (&self.ids_array, model::IdentifierRole::Array),
+            (&self.ids_field, model::IdentifierRole::Field),
+            (&self.ids_expr_array, model::IdentifierRole::ExpressionArray),
+            (&self.ids_function, model::IdentifierRole::Function),
+            (&self.ids_index, model::IdentifierRole::Index),
+            (&self.ids_expr, model::IdentifierRole::Expression),
+        ];
+        for (ids, role) in roles {
+            for (name, positions) in ordered(ids, order) {
+                identifiers.push(model::Identifier {
+                    path: name.split('.').map(str::to_string).collect(),
+                    name,
+                    lexemes: distinct(positions.iter().map(|&pos| self.name_lexeme(pos))),
+                    role,
+                    occurrences: positions.len(),
+                    origins: self.origins_of(&positions),
+                    positions,
+                });
+            }
+        }
+
+        let mut constants = Vec::new();
+        let roles = [
+            (&self.const_index, model::ConstantRole::Index),
+            (&self.const_expr, model::ConstantRole::Expression),
+        ];
+        for (consts, role) in roles {
+            for (value, positions) in ordered(consts, order) {
+                constants.push(model::Constant {
+                    value,
+                    lexemes: self.lexemes(&positions),
+                    out_of_range: !self.constant_range().contains(&value),
+                    role,
+                    occurrences: positions.len(),
+                    origins: self.origins_of(&positions),
+                    positions,
+                });
+            }
+        }
+
+        let mut reals = Vec::new();
+        let roles = [
+            (&self.real_index, model::ConstantRole::RealIndex),
+            (&self.real_expr, model::ConstantRole::RealExpression),
+        ];
+        for (values, role) in roles {
+            for (value, positions) in ordered(values, order) {
+                reals.push(model::RealConstant {
+                    value: value.0,
+                    lexemes: self.lexemes_of(&positions, |pos| self.real_len(pos)),
+                    role,
+                    occurrences: positions.len(),
+                    origins: self.origins_of(&positions),
+                    positions,
+                });
+            }
+        }
+
+        let literals = ordered(&self.literal_expr, order)
+            .into_iter()
+            .map(|(value, positions)| model::LiteralConstant {
+                role: Self::literal_role(&value),
+                value,
+                lexemes: self.lexemes_of(&positions, |pos| self.literal_len(pos)),
+                occurrences: positions.len(),
+                origins: self.origins_of(&positions),
+                positions,
+            })
+            .collect();
+
+        (identifiers, constants, reals, literals)
+    }
+}
+
+/// Запись, основание и допустимость константы для списка констант. Основание
+/// указывается у каждой записи, если среди них есть не десятичные. Без диапазона
+/// (`range` - `None`) допустимость берётся из `out_of_range`.
+fn constant_details(
+    constant: &model::Constant,
+    range: Option<&RangeInclusive<i32>>,
+) -> String {
+    let radix = |lexeme: &str| Radix::of(lexeme).map_or(Radix::Decimal, |(radix, _)| radix);
+    let decimal = constant.lexemes.iter().all(|lexeme| radix(lexeme) == Radix::Decimal);
+    let lexemes: Vec<String> = constant
+        .lexemes
+        .iter()
+        .map(|lexeme| match radix(lexeme) {
+            Radix::Decimal if lexeme.len() > 1 && lexeme.starts_with('0') => {
+                format!("{} (ведущие нули)", lexeme)
+            }
+            _ if decimal => lexeme.clone(),
+            other => format!("{} ({})", lexeme, other.title()),
+        })
+        .collect();
+    let value = constant.value;
+    let validity = match range {
+        Some(range) if value > *range.end() => {
+            format!("вне диапазона [{}..{}]: больше максимума", range.start(), range.end())
+        }
+        Some(range) if value < *range.start() => {
+            format!("вне диапазона [{}..{}]: меньше минимума", range.start(), range.end())
+        }
+        Some(range) => format!("в диапазоне [{}..{}]", range.start(), range.end()),
+        None if constant.out_of_range => "вне диапазона".to_string(),
+        None => "в диапазоне".to_string(),
+    };
+    if decimal {
+        format!("запись {}, десятичная, {}", lexemes.join(", "), validity)
+    } else {
+        format!("запись {}, {}", lexemes.join(", "), validity)
+    }
+}
+
+/// Списки идентификаторов и констант с ролями для текстового вывода; `None`,
+/// если символов нет. Символы перечисляются в порядке `symbols`; с
+/// `preserve_case` имя выводится так, как записано во вводе.
+fn symbol_lists(
+    input: &str,
+    symbols: &Symbols,
+    variant: &dyn Variant,
+    range: Option<&RangeInclusive<i32>>,
+    preserve_case: bool,
+) -> (Option<String>, Option<String>) {
+    let (identifiers, constants, reals, literals) = symbols;
+    if identifiers.is_empty() && constants.is_empty() && reals.is_empty() && literals.is_empty() {
+        return (None, None);
+    }
+    let map = SourceMap::new(input);
+    let mut ids = String::new();
+    for id in identifiers {
+        let name = match preserve_case && !id.lexemes.is_empty() {
+            true => id.lexemes.join(", "),
+            false => id.name.clone(),
+        };
+        ids.push_str(&format!(
+            "{} - {} ({})\n",
+            name,
+            variant.identifier_role(id.role),
+            columns(&id.positions, &map)
+        ));
+    }
+    let mut consts = String::new();
+    for c in constants {
+        consts.push_str(&format!(
+            "{} - {} ({}): {}\n",
+            c.value,
+            variant.constant_role(c.role),
+            columns(&c.positions, &map),
+            constant_details(c, range)
+        ));
+    }
+    for r in reals {
+        consts.push_str(&format!(
+            "{} - {} ({}): запись {}\n",
+            ast::Real(r.value),
+            variant.constant_role(r.role),
+            columns(&r.positions, &map),
+            r.lexemes.join(", ")
+        ));
+    }
+    for l in literals {
+        consts.push_str(&format!(
+            "{} - {} ({}): запись {}\n",
+            ast::quoted(&l.value),
+            variant.constant_role(l.role),
+            columns(&l.positions, &map),
+            l.lexemes.join(", ")
+        ));
+    }
+    (Some(ids), Some(consts))
+}
+
+/// Анализирует строку входного кода, возвращая результаты синтаксического/семантического анализа.
+///
+/// Возвращает:
+/// - Ok((Some(ids_str), Some(consts_str))): при успешном разборе, строки со списками идентификаторов и констант.
+/// - Ok((None, None)): если нет идентификаторов и констант (теоретически не должно быть в данном языке).
+/// - Err(err_str): при ошибке, строка с сообщением и указанием позиции.
+pub fn analyze_line(input: &str) -> Result<(Option<String>, Option<String>), String> {
+    analyze_line_with(input, &AnalyzerConfig::default())
+        .map(|success| (success.ids, success.consts))
+        .map_err(|report| report.text)
+}
+
+/// Успешный результат анализа в текстовом виде.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Success {
+    /// Список идентификаторов с ролями
+    pub ids: Option<String>,
+    /// Список констант с ролями
+    pub consts: Option<String>,
+    /// Нарушения правил со строгостью "предупреждение", если они были
+    pub warnings: Option<String>,
+    /// Идентификаторы в порядке строк `ids`, с происхождением вхождений
+    pub identifiers: Vec<model::Identifier>,
+    /// Константы в порядке строк `consts`
+    pub constants: Vec<model::Constant>,
+    /// Вещественные константы в порядке строк `consts` после целых
+    pub reals: Vec<model::RealConstant>,
+    /// Символьные и строковые константы в порядке строк `consts` после вещественных
+    pub literals: Vec<model::LiteralConstant>,
+}
+
+/// То же, что и [`analyze_line`], но с заданными настройками.
+///
+/// В режиме восстановления отчёт может содержать несколько ошибок, упорядоченных по позиции;
+/// выводится не более `config.max_errors` из них. Предупреждения выводятся только для
+/// строк, принадлежащих языку.
+///
+/// Ошибка в многострочном тексте указывается строкой и столбцом:
+///
+/// ```
+/// use taafl::analyzer::analyze_line_with;
+/// use taafl::config::AnalyzerConfig;
+///
+/// let report = analyze_line_with("A :=\n  B $ 2;", &AnalyzerConfig::default()).unwrap_err();
+/// let cursor = "строка 2, столбец 5:\n  B $ 2;\n    ^\n";
+/// assert!(report.text.contains(cursor));
+/// ```
+pub fn analyze_line_with(input: &str, config: &AnalyzerConfig) -> Result<Success, ErrorReport> {
+    let outcome = run(input, Setup::new(config));
+    match outcome.checker {
+        Some(checker) if outcome.errors.is_empty() => {
+            // Успешно
+            let symbols = checker.symbols(config.order);
+            let range = checker.constant_range();
+            let (ids, consts) =
+                symbol_lists(input, &symbols, checker.variant, Some(&range), config.preserve_case);
+            let (identifiers, constants, reals, literals) = symbols;
+            let warnings = format_warnings(outcome.warnings, input);
+            Ok(Success {
+                ids,
+                consts,
+                warnings,
+                identifiers,
+                constants,
+                reals,
+                literals,
+            })
+        }
+        _ => Err(format_errors(outcome.errors, input, config.max_errors)),
+    }
+}
+
+/// Анализирует строку и возвращает результат в виде структур [`model`](crate::model)
+/// для внешних программ.
+///
+/// Списки символов заполняются и при семантических ошибках, если строка разобрана
+/// синтаксически; ограничение `config.max_errors` к отчёту не применяется.
+pub fn analyze_report(input: &str, config: &AnalyzerConfig) -> model::AnalysisReport {
+    build_report(input, config, run(input, Setup::new(config)))
+}
+
+/// Внутренняя ошибка анализатора: паника, перехваченная [`catch_internal`].
+/// Строка языка её вызывать не должна; если вызвала - это дефект анализатора,
+/// а не ошибка в операторе.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternalError {
+    pub input: String,
+    /// Сообщение паники, если она передала текст
+    pub message: String,
+}
+
+impl std::fmt::Display for InternalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Внутренняя ошибка анализатора: {}", self.message)
+    }
+}
+
+/// Выполняет `analysis` над строкой `input`, превращая панику в [`InternalError`],
+/// чтобы она не дошла до оболочки.
+pub fn catch_internal<T>(input: &str, analysis: impl FnOnce() -> T) -> Result<T, InternalError> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(analysis)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|text| text.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "паника без сообщения".to_string());
+        InternalError { input: input.to_string(), message }
+    })
+}
+
+/// То же, что и [`analyze_report`], но без паники ни при каких входных данных.
+///
+/// ```
+/// use taafl::analyzer::try_analyze;
+/// use taafl::config::AnalyzerConfig;
+///
+/// let report = try_analyze("A[I := 1;", &AnalyzerConfig::default()).unwrap();
+/// assert!(!report.accepted);
+/// ```
+pub fn try_analyze(
+    input: &str,
+    config: &AnalyzerConfig,
+) -> Result<model::AnalysisReport, InternalError> {
+    catch_internal(input, || analyze_report(input, config))
+}
+
+/// Текстовый результат, как у [`analyze_line_with`], по готовому отчёту - например,
+/// прочитанному [`model::AnalysisReport::from_json`] с другой машины, - без
+/// повторного анализа. Из настроек `config` берутся только настройки вывода:
+/// число ошибок и регистр имён. Границы диапазона констант берутся из отчёта,
+/// а в отчёте без них - из `config`, как при анализе.
+///
+/// ```
+/// use taafl::analyzer::{analyze_report, render_report};
+/// use taafl::config::AnalyzerConfig;
+///
+/// let report = analyze_report("A[I] := 0;", &AnalyzerConfig::default());
+/// let text = render_report(&report, &AnalyzerConfig::default()).unwrap_err().text;
+/// assert!(text.starts_with("A[I] := 0;\n        ^\nСемантическая ошибка: "));
+///
+/// let report = analyze_report("A[I] := 07;", &AnalyzerConfig::default());
+/// let success = render_report(&report, &AnalyzerConfig::default()).unwrap();
+/// let ids = success.ids.unwrap();
+/// assert_eq!(ids.lines().next(), Some("A - идентификатор-массив (столбец 1)"));
+/// let consts = success.consts.unwrap();
+/// assert!(consts.ends_with("(ведущие нули), десятичная, в диапазоне [1..32767]\n"));
+/// ```
+pub fn render_report(
+    report: &model::AnalysisReport,
+    config: &AnalyzerConfig,
+) -> Result<Success, ErrorReport> {
+    let input = &report.input;
+    if !report.errors.is_empty() {
+        return Err(format_entries(&report.errors, input, config.max_errors));
+    }
+    let symbols = (
+        report.identifiers.clone(),
+        report.constants.clone(),
+        report.reals.clone(),
+        report.literals.clone(),
+    );
+    let range = match report.config.constant_range {
+        Some(range) => range.min..=range.max,
+        None => config.constant_range(Variant20.constant_range()),
+    };
+    let (ids, consts) = symbol_lists(
+        input,
+        &symbols,
+        &Variant20,
+        Some(&range),
+        config.preserve_case,
+    );
+    let (identifiers, constants, reals, literals) = symbols;
+    Ok(Success {
+        ids,
+        consts,
+        warnings: format_warning_entries(&report.warnings, input),
+        identifiers,
+        constants,
+        reals,
+        literals,
+    })
+}
+
+/// То же, что и [`analyze_report`], с событиями анализа для `sink`: лексемы
+/// и шаги разбора - по мере анализа, затем диагностики и завершение
+/// (см. [`events`]).
+///
+/// Рекурсивный спуск берёт лексемы по одной, и [`Event::TokenProduced`] чередуются
+/// с шагами разбора. С [`ParserKind::Table`] и [`ParserKind::Earley`] строка сначала
+/// разбивается на лексемы целиком: все `TokenProduced` приходят раньше первого
+/// [`Event::RuleEntered`].
+///
+/// ```
+/// use taafl::analyzer::analyze_with_events;
+/// use taafl::analyzer::events::Event;
+/// use taafl::config::{AnalyzerConfig, ParserKind};
+///
+/// let config = AnalyzerConfig {
+///     parser: ParserKind::Table,
+///     ..AnalyzerConfig::default()
+/// };
+/// let mut events = Vec::new();
+/// analyze_with_events("X := Y;", &config, &mut |event| events.push(event));
+/// let entered = events.iter().position(|e| matches!(e, Event::RuleEntered { .. }));
+/// let produced = events.iter().rposition(|e| matches!(e, Event::TokenProduced { .. }));
+/// assert!(produced < entered);
+/// ```
+pub fn analyze_with_events(
+    input: &str,
+    config: &AnalyzerConfig,
+    sink: &mut dyn EventSink,
+) -> model::AnalysisReport {
+    let sink = std::cell::RefCell::new(&mut *sink as &mut dyn EventSink);
+    let events = Events::new(&sink);
+    let outcome = run(input, Setup { events, ..Setup::new(config) });
+    for diagnostic in diagnostics(&outcome, input) {
+        events.emit(|| Event::DiagnosticEmitted(diagnostic));
+    }
+    let report = build_report(input, config, outcome);
+    events.emit(|| Event::Finished { accepted: report.accepted });
+    report
+}
+
+/// Отчёт и диагностики за один анализ, для [`crate::core::analyze`].
+pub(crate) fn analyze_full(
+    input: &str,
+    config: &AnalyzerConfig,
+) -> (model::AnalysisReport, Vec<Diagnostic>) {
+    let outcome = run(input, Setup::new(config));
+    let diagnostics = diagnostics(&outcome, input);
+    (build_report(input, config, outcome), diagnostics)
+}
+
+/// То же, что и [`analyze_report`], но для произвольных байтов: недопустимые
+/// последовательности UTF-8 заменяются символом U+FFFD и дают лексическую ошибку.
+///
+/// Функция не паникует ни на каком вводе, включая пустой; точки входа `fuzz/`
+/// проверяют это на случайных данных.
+///
+/// ```
+/// use taafl::analyzer::analyze_lossy;
+/// use taafl::config::AnalyzerConfig;
+///
+/// let config = AnalyzerConfig::default();
+/// assert!(!analyze_lossy(b"", &config).accepted);
+/// assert!(!analyze_lossy(b"X := \xff;", &config).accepted);
+/// assert!(analyze_lossy(b"X := 1;", &config).accepted);
+/// ```
+pub fn analyze_lossy(input: &[u8], config: &AnalyzerConfig) -> model::AnalysisReport {
+    let input = String::from_utf8_lossy(input);
+    analyze_report(&input, config)
+}
+
+fn build_report(
+    input: &str,
+    config: &AnalyzerConfig,
+    mut outcome: Outcome,
+) -> model::AnalysisReport {
+    outcome.errors.sort_by_key(Error::position);
+    outcome.warnings.sort_by_key(Error::position);
+
+    let (identifiers, constants, reals, literals) = match &outcome.checker {
+        Some(checker) => checker.symbols(config.order),
+        None => Default::default(),
+    };
+    let range = match &outcome.checker {
+        Some(checker) => checker.constant_range(),
+        None => config.constant_range(Variant20.constant_range()),
+    };
+
+    let report = model::AnalysisReport {
+        schema_version: model::SCHEMA_VERSION,
+        input: input.to_string(),
+        offset: 0,
+        source: None,
+        accepted: outcome.errors.is_empty(),
+        identifiers,
+        constants,
+        reals,
+        literals,
+        errors: outcome.errors.iter().map(Error::to_entry).collect(),
+        warnings: outcome.warnings.iter().map(Error::to_entry).collect(),
+        config: config_entry(config, range),
+        gaps: outcome.gaps,
+        derivation: outcome.derivation,
+    };
+    invariants::check_report(&report);
+    report
+}
+
+/// Все ошибки и предупреждения анализа строки в порядке их позиций.
+///
+/// В отличие от [`analyze_line_with`], список не ограничивается `config.max_errors`.
+/// Без режима восстановления в нём не больше одной ошибки: анализ останавливается на ней.
+pub fn diagnose(input: &str, config: &AnalyzerConfig) -> Vec<Diagnostic> {
+    diagnostics(&run(input, Setup::new(config)), input)
+}
+
+/// Лексический анализ строки: лексемы с байтовыми отрезками или первая лексическая ошибка.
+///
+/// ```
+/// use taafl::analyzer::{tokenize, Radix, Token};
+///
+/// let tokens = tokenize("xy := 10;").unwrap();
+/// assert_eq!(tokens[0], (0..2, Token::Identifier("XY".to_string())));
+/// assert_eq!(tokens[2], (6..8, Token::Constant(10, Radix::Decimal)));
+/// assert_eq!(tokenize("x := $;").unwrap_err().span.start, 5);
+///
+/// // Ошибочная лексема охватывается целиком
+/// assert_eq!(tokenize("x := 12AB;").unwrap_err().span, 5..9);
+///
+/// // Суффиксы систем счисления Modula-2
+/// assert_eq!(tokenize("x := 0FFH;").unwrap()[2].1, Token::Constant(255, Radix::Hexadecimal));
+/// assert_eq!(tokenize("x := 377b;").unwrap()[2].1, Token::Constant(255, Radix::Octal));
+/// assert_eq!(tokenize("x := 19B;").unwrap_err().span, 5..8);
+///
+/// // Двухсимвольные операции и ключевые слова операций - одна лексема
+/// assert_eq!(tokenize("x := a <> 1;").unwrap()[3], (7..9, Token::Operation("<>")));
+/// assert_eq!(tokenize("x := a mod 2;").unwrap()[3], (7..10, Token::Operation("MOD")));
+/// assert_eq!(tokenize("x := modulo;").unwrap()[2].1, Token::Identifier("MODULO".to_string()));
+///
+/// // Литералы - без кавычек и с учётом регистра; незакрытый указывает на кавычку
+/// assert_eq!(tokenize("x := 'a;b';").unwrap()[2], (5..10, Token::Literal("a;b".to_string())));
+/// assert_eq!(tokenize("x := \"ab;").unwrap_err().span, 5..6);
+///
+/// // Комментарии, в том числе вложенные, пропускаются; незакрытый указывает на своё начало
+/// assert_eq!(tokenize("x (* a (* b *) *) := 1;").unwrap()[1].0, 18..20);
+/// assert_eq!(tokenize("x := (* a (* b *) 1;").unwrap_err().span, 5..7);
+///
+/// // Позиции - в байтах, ошибочный символ охватывается целиком
+/// assert_eq!(tokenize("Б := 1;").unwrap_err().span, 0..2);
+/// assert_eq!(tokenize("ё := ?").unwrap_err().span, 0..2);
+/// assert_eq!(tokenize("x := 1 Б;").unwrap_err().span, 7..9);
+/// ```
+pub fn tokenize(input: &str) -> Result<Vec<(Span, Token)>, Diagnostic> {
+    Lexer::new(input)
+        .tokenize()
+        .map_err(|e| e.to_diagnostic(diagnostic::Severity::Error, input))
+}
+
+/// Пробелы или комментарий `(* ... *)` между лексемами.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trivia {
+    /// Пробелы, табуляции и переводы строк подряд
+    Whitespace(Span),
+    /// Комментарий вместе с `(*` и `*)`, в том числе с вложенными
+    Comment(Span),
+}
+
+impl Trivia {
+    pub fn span(&self) -> &Span {
+        match self {
+            Trivia::Whitespace(span) | Trivia::Comment(span) => span,
+        }
+    }
+}
+
+/// Лексема вместе с пробелами и комментариями перед ней.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lexeme {
+    pub leading: Vec<Trivia>,
+    pub span: Span,
+    pub token: Token,
+}
+
+/// Лексический анализ без потерь: каждая лексема несёт пробелы и комментарии
+/// перед ней, а последняя - [`Token::End`] с пустым отрезком - всё, что стоит
+/// после последней лексемы. Склеив по порядку пробелы, комментарии и записи
+/// лексем, можно получить исходную строку, например для форматирования.
+///
+/// Недопустимые символы здесь не пропускаются и в режиме восстановления.
+///
+/// ```
+/// use taafl::analyzer::{tokenize_full, Token, Trivia};
+/// use taafl::config::AnalyzerConfig;
+///
+/// let input = "X (* цель *) :=\n\t1; ";
+/// let lexemes = tokenize_full(input, &AnalyzerConfig::default()).unwrap();
+/// let comment = Trivia::Comment(2..16);
+/// assert_eq!(lexemes[1].leading, [Trivia::Whitespace(1..2), comment, Trivia::Whitespace(16..17)]);
+/// assert_eq!(lexemes.last().unwrap().token, Token::End);
+///
+/// let mut text = String::new();
+/// for lexeme in &lexemes {
+///     for trivia in &lexeme.leading {
+///         text.push_str(&input[trivia.span().clone()]);
+///     }
+///     text.push_str(&input[lexeme.span.clone()]);
+/// }
+/// assert_eq!(text, input);
+/// ```
+pub fn tokenize_full(input: &str, config: &AnalyzerConfig) -> Result<Vec<Lexeme>, Diagnostic> {
+    let mut lexer = Lexer::configured(input, config);
+    lexer.keep_trivia = true;
+    let mut lexemes = Vec::new();
+    loop {
+        let (span, token) = lexer
+            .next_token()
+            .map_err(|e| e.to_diagnostic(diagnostic::Severity::Error, input))?;
+        let end = token == Token::End;
+        let leading = std::mem::take(&mut lexer.trivia);
+        lexemes.push(Lexeme {
+            leading,
+            span,
+            token,
+        });
+        if end {
+            return Ok(lexemes);
+        }
+    }
+}
+
+/// Столбец таблицы лексем [`TokenDump::table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenColumn {
+    /// Порядковый номер лексемы
+    Number,
+    /// Столбец начала, в многострочном тексте - `строка:столбец`
+    Position,
+    /// Вид лексемы: идентификатор, константа, операция...
+    Kind,
+    /// Запись во входной строке
+    Lexeme,
+    /// Имя идентификатора, значение константы или текст литерала
+    Value,
+}
+
+impl TokenColumn {
+    pub const ALL: [TokenColumn; 5] = [
+        TokenColumn::Number,
+        TokenColumn::Position,
+        TokenColumn::Kind,
+        TokenColumn::Lexeme,
+        TokenColumn::Value,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            TokenColumn::Number => "number",
+            TokenColumn::Position => "position",
+            TokenColumn::Kind => "kind",
+            TokenColumn::Lexeme => "lexeme",
+            TokenColumn::Value => "value",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|column| column.id() == id)
+    }
+
+    /// Столбцы из списка через запятую в порядке записи: `number,lexeme`.
+    pub fn parse_list(list: &str) -> Option<Vec<Self>> {
+        list.split(',').map(|id| Self::from_id(id.trim())).collect()
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            TokenColumn::Number => "№",
+            TokenColumn::Position => "Позиция",
+            TokenColumn::Kind => "Тип",
+            TokenColumn::Lexeme => "Лексема",
+            TokenColumn::Value => "Значение",
+        }
+    }
+}
+
+/// Лексема в [`TokenDump`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenEntry {
+    pub span: Span,
+    /// Строка и столбец начала лексемы, с единицы
+    pub location: diagnostic::Location,
+    /// Текст лексемы во вводе
+    pub text: String,
+    /// Вид лексемы: "идентификатор", "константа", "операция"...
+    pub kind: &'static str,
+    /// Десятичное значение константы; у имени - оно в верхнем регистре,
+    /// у литерала - текст без кавычек
+    pub value: Option<String>,
+}
+
+/// Лексемы строки для просмотра: таблица в консоли и подсветка в интерфейсе.
+///
+/// ```
+/// use taafl::analyzer::{token_dump, TokenColumn};
+/// use taafl::config::AnalyzerConfig;
+///
+/// let dump = token_dump("X := 0FFH;", &AnalyzerConfig::default()).unwrap();
+/// let constant = &dump.entries[2];
+/// assert_eq!((constant.span.clone(), constant.text.as_str()), (5..9, "0FFH"));
+/// assert_eq!((constant.kind, constant.value.as_deref()), ("константа", Some("255")));
+///
+/// let table = dump.table(&TokenColumn::ALL);
+/// let lines: Vec<&str> = table.lines().collect();
+/// assert_eq!(
+///     lines,
+///     [
+///         "№  Позиция  Тип              Лексема  Значение",
+///         "-  -------  ---------------  -------  --------",
+///         "1        1  идентификатор    X        X",
+///         "2        3  присваивание     :=",
+///         "3        6  константа        0FFH     255",
+///         "4       10  конец оператора  ;",
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenDump {
+    pub entries: Vec<TokenEntry>,
+    /// Ввод многострочный: позиция в таблице - строка и столбец
+    pub multiline: bool,
+}
+
+impl TokenDump {
+    /// Лексемы `tokens` строки `input`, например [`Analysis::tokens`].
+    pub fn new(input: &str, tokens: &[(Span, Token)]) -> Self {
+        let map = SourceMap::new(input);
+        let entries = tokens
+            .iter()
+            .map(|(span, token)| TokenEntry {
+                span: span.clone(),
+                location: map.location(span.start),
+                text: input.get(span.clone()).unwrap_or_default().to_string(),
+                kind: token.kind(),
+                value: token.value(),
+            })
+            .collect();
+        TokenDump {
+            entries,
+            multiline: map.is_multiline(),
+        }
+    }
+
+    /// Таблица с выбранными столбцами `columns` в заданном порядке. Номера
+    /// и позиции прижаты вправо.
+    ///
+    /// ```
+    /// use taafl::analyzer::{token_dump, TokenColumn};
+    /// use taafl::config::AnalyzerConfig;
+    ///
+    /// let columns = TokenColumn::parse_list("lexeme,number").unwrap();
+    /// let dump = token_dump("X := 1;", &AnalyzerConfig::default()).unwrap();
+    /// assert_eq!(dump.table(&columns).lines().nth(2), Some("X        1"));
+    /// ```
+    pub fn table(&self, columns: &[TokenColumn]) -> String {
+        let rows: Vec<Vec<String>> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                columns
+                    .iter()
+                    .map(|column| match column {
+                        TokenColumn::Number => i.saturating_add(1).to_string(),
+                        TokenColumn::Position if self.multiline => {
+                            format!("{}:{}", entry.location.line, entry.location.column)
+                        }
+                        TokenColumn::Position => entry.location.column.to_string(),
+                        TokenColumn::Kind => entry.kind.to_string(),
+                        TokenColumn::Lexeme => entry.text.clone(),
+                        TokenColumn::Value => entry.value.clone().unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .collect();
+        let header: Vec<&str> = columns.iter().map(|column| column.title()).collect();
+        let right: Vec<bool> = columns
+            .iter()
+            .map(|column| matches!(column, TokenColumn::Number | TokenColumn::Position))
+            .collect();
+        diagnostic::table(&header, &rows, &right)
+    }
+}
+
+/// Лексемы строки с настройками `config`; при лексической ошибке - она.
+pub fn token_dump(input: &str, config: &AnalyzerConfig) -> Result<TokenDump, Diagnostic> {
+    let tokens = Lexer::configured(input, config)
+        .tokenize()
+        .map_err(|e| e.to_diagnostic(diagnostic::Severity::Error, input))?;
+    Ok(TokenDump::new(input, &tokens))
+}
+
+/// Лексемы строки таблицей [`TokenDump::table`].
+pub fn token_table(
+    input: &str,
+    config: &AnalyzerConfig,
+    columns: &[TokenColumn],
+) -> Result<String, Diagnostic> {
+    Ok(token_dump(input, config)?.table(columns))
+}
+
+/// Разбивает строку на участки для подсветки синтаксиса. В отличие от [`tokenize`],
+/// не останавливается на лексических ошибках: ошибочные участки отмечаются
+/// [`TokenClass::Invalid`], и разбор продолжается после них. Пробелы и комментарии
+/// не входят ни в один участок.
+///
+/// ```
+/// use taafl::analyzer::{classify, TokenClass};
+///
+/// let classes: Vec<TokenClass> = classify("A[1] := 1X $ + é:X;")
+///     .into_iter()
+///     .map(|(_, class)| class)
+///     .collect();
+/// assert_eq!(
+///     classes,
+///     [
+///         TokenClass::Identifier,
+///         TokenClass::Bracket,
+///         TokenClass::Constant,
+///         TokenClass::Bracket,
+///         TokenClass::Assign,
+///         TokenClass::Invalid,
+///         TokenClass::Invalid,
+///         TokenClass::Operator,
+///         TokenClass::Invalid,
+///         TokenClass::Identifier,
+///         TokenClass::Separator,
+///     ]
+/// );
+/// ```
+pub fn classify(input: &str) -> Vec<(Span, TokenClass)> {
+    let mut lexer = Lexer::new(input);
+    let mut regions: Vec<(Span, TokenClass)> = Vec::new();
+    loop {
+        let (start, class) = match lexer.next_token() {
+            Ok((_, Token::End)) => break,
+            Ok((span, token)) => (span.start, token.class()),
+            Err(e) => (e.position().min(lexer.pos()), TokenClass::Invalid),
+        };
+        // Ошибочный участок - хотя бы один символ целиком
+        let mut end = lexer.pos().max(start.saturating_add(1));
+        while end < input.len() && !input.is_char_boundary(end) {
+            end = end.saturating_add(1);
         }
-        Error::SyntaxError(pos, msg) => {
-            format_error_with_cursor(input, pos, &format!("Синтаксическая ошибка: {}", msg))
+        lexer.skip_to(end);
+
+        match regions.last_mut() {
+            Some((span, TokenClass::Invalid))
+                if class == TokenClass::Invalid && span.end == start =>
+            {
+                span.end = end
+            }
+            _ => regions.push((start..end, class)),
+        }
+    }
+    regions
+}
+
+/// Левый вывод оператора по грамматике языка: продукции в порядке применения
+/// таблицей LL(1). Семантические правила не проверяются; лексическая или
+/// синтаксическая ошибка прерывает вывод.
+///
+/// ```
+/// use taafl::analyzer::derivation;
+/// use taafl::config::AnalyzerConfig;
+///
+/// let config = AnalyzerConfig::default();
+/// let applied = derivation("A[I] := 1;", &config).unwrap();
+/// assert_eq!(applied[1], "left = IDENT field left_tail");
+/// assert_eq!(applied[2], "field = ε");
+/// assert_eq!(applied[3], r#"left_tail = "[" index_list "]" field left_tail"#);
+/// assert_eq!(derivation("A := ;", &config).unwrap_err().code, "syntax");
+/// ```
+pub fn derivation(input: &str, config: &AnalyzerConfig) -> Result<Vec<String>, Diagnostic> {
+    let (applied, grammar, _) = leftmost(input, config)?;
+    let productions = applied.0.iter().filter_map(|&i| grammar.productions.get(i));
+    Ok(productions.map(ToString::to_string).collect())
+}
+
+/// Тот же вывод, что и [`derivation`], таблицей
+/// [`Derivation::table`](grammar::ambiguity::Derivation::table): шаги, продукции
+/// и сентенциальные формы.
+///
+/// ```
+/// use taafl::analyzer::derivation_table;
+/// use taafl::config::AnalyzerConfig;
+///
+/// let table = derivation_table("A := 1;", &AnalyzerConfig::default()).unwrap();
+/// let last = table.lines().last().unwrap();
+/// assert!(last.ends_with(r#"IDENT ":=" CONST ";""#));
+/// ```
+pub fn derivation_table(input: &str, config: &AnalyzerConfig) -> Result<String, Diagnostic> {
+    let (applied, grammar, _) = leftmost(input, config)?;
+    Ok(applied.table(grammar))
+}
+
+/// Конкретное дерево разбора оператора по тому же выводу, что и [`derivation`]:
+/// листья - все лексемы строки, включая скобки, запятые и присваивание.
+///
+/// ```
+/// use taafl::analyzer::concrete_tree;
+///
+/// let cst = concrete_tree("F := G(1, 2);", &Default::default()).unwrap();
+/// let leaves = cst.leaves();
+/// let commas: Vec<_> = leaves.iter().filter(|l| l.name == ",").map(|l| l.span()).collect();
+/// assert_eq!(commas, [8..9]);
+/// assert!(concrete_tree("F := G(1;", &Default::default()).is_err());
+/// ```
+pub fn concrete_tree(input: &str, config: &AnalyzerConfig) -> Result<cst::Node, Diagnostic> {
+    let (applied, grammar, tokens) = leftmost(input, config)?;
+    let spans: Vec<Span> = tokens.into_iter().map(|(span, _)| span).collect();
+    cst::Node::build(grammar, &applied.0, &spans, input).ok_or_else(|| {
+        let message = "Вывод не порождает лексемы строки".to_string();
+        Error::Syntax(0..0, message).to_diagnostic(diagnostic::Severity::Error, input)
+    })
+}
+
+/// Конкретное дерево разбора лексем `tokens` строки `input` по таблице `table`.
+fn concrete(table: &Ll1Table, tokens: &[(Span, Token)], input: &str) -> Option<cst::Node> {
+    let end = end_position(input);
+    let applied = table.derivation(&terminals(tokens), end).ok()?;
+    let spans: Vec<Span> = tokens.iter().map(|(span, _)| span.clone()).collect();
+    cst::Node::build(table.grammar(), &applied, &spans, input)
+}
+
+/// Шаги левого вывода `applied` по грамматике `grammar` для отчёта.
+fn derivation_steps(grammar: &Grammar, applied: &[usize]) -> Vec<model::DerivationStep> {
+    let step = |&production: &usize| {
+        let rule = grammar.productions.get(production)?;
+        Some(model::DerivationStep {
+            production,
+            lhs: rule.lhs.clone(),
+            rhs: rule.rhs.iter().map(ToString::to_string).collect(),
+        })
+    };
+    applied.iter().filter_map(step).collect()
+}
+
+/// Левый вывод, грамматика, по которой он построен, и лексемы строки.
+type Leftmost = (
+    grammar::ambiguity::Derivation,
+    &'static Grammar,
+    Vec<(Span, Token)>,
+);
+
+fn leftmost(input: &str, config: &AnalyzerConfig) -> Result<Leftmost, Diagnostic> {
+    let fail = |e: Error| e.to_diagnostic(diagnostic::Severity::Error, input);
+    let mut lexer = Lexer::configured(input, config);
+    let tokens = lexer.tokenize().map_err(fail)?;
+    // Пропущенный символ не входит в грамматику: вывода у такой строки нет
+    if let Some(e) = lexer.skipped.into_iter().next() {
+        return Err(fail(e));
+    }
+    let Some(table) = ll1_table(config) else {
+        let message = format!("Грамматика варианта '{}' не является LL(1)", Variant20.name());
+        return Err(fail(Error::Syntax(0..0, message)));
+    };
+    let end = end_position(input);
+    let applied = table
+        .derivation(&terminals(&tokens), end)
+        .map_err(|(pos, message)| fail(table_error(&tokens, pos, &message, config)))?;
+    Ok((grammar::ambiguity::Derivation(applied), table.grammar(), tokens))
+}
+
+fn diagnostics(outcome: &Outcome, input: &str) -> Vec<Diagnostic> {
+    let errors = outcome
+        .errors
+        .iter()
+        .map(|e| e.to_diagnostic(diagnostic::Severity::Error, input));
+    let warnings = outcome
+        .warnings
+        .iter()
+        .map(|w| w.to_diagnostic(diagnostic::Severity::Warning, input));
+    let mut diagnostics: Vec<Diagnostic> = errors.chain(warnings).collect();
+    diagnostics.sort_by_key(|d| d.span.start);
+    invariants::check_diagnostics(input, &diagnostics);
+    diagnostics
+}
+
+/// Действующие настройки для отчёта: строгость перечисляется для всех правил,
+/// `range` - диапазон констант, по которому проверялся ввод.
+fn config_entry(config: &AnalyzerConfig, range: RangeInclusive<i32>) -> model::ConfigEntry {
+    let rules = Rule::ALL
+        .iter()
+        .map(|rule| {
+            let severity = match config.severity(*rule) {
+                Severity::Error => model::Severity::Error,
+                Severity::Warning => model::Severity::Warning,
+                Severity::Off => model::Severity::Off,
+            };
+            (rule.id().to_string(), severity)
+        })
+        .collect();
+
+    model::ConfigEntry {
+        profile: config.profile.map(|profile| profile.id().to_string()),
+        recovery: config.recovery,
+        hints: config.hints,
+        rules,
+        constant_range: Some(model::RangeEntry {
+            min: *range.start(),
+            max: *range.end(),
+        }),
+        settings: Some(model::SettingsEntry {
+            max_errors: config.max_errors,
+            max_input: config.max_input,
+            order: config.order.id().to_string(),
+            preserve_case: config.preserve_case,
+            parser: config.parser.id().to_string(),
+            scanner: config.scanner.id().to_string(),
+            terminator: config.terminator.id().to_string(),
+            brackets: config.brackets.id().to_string(),
+            assign: config.assign.iter().map(|op| op.id().to_string()).collect(),
+            operations: config
+                .operations
+                .iter()
+                .map(|sign| sign.lexeme().to_string())
+                .collect(),
+            reals: config.reals,
+            underscores: config.underscores,
+            unicode_identifiers: config.unicode_identifiers,
+            typography: config.typography,
+            compact_indices: config.whitespace.compact_indices,
+            spaced_assign: config.whitespace.spaced_assign,
+            max_terms: config.limits.max_terms,
+            max_indices: config.limits.max_indices,
+            max_nesting: config.limits.max_nesting,
+        }),
+    }
+}
+
+/// Анализатор с заданными настройками для многократного использования.
+///
+/// ```
+/// use taafl::analyzer::Analyzer;
+///
+/// let analyzer = Analyzer::builder().collect_ast(true).build();
+/// let analysis = analyzer.analyze("X[I] := I + 1;");
+/// assert!(analysis.report.accepted);
+/// assert!(analysis.ast.is_some());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Analyzer<V: Variant = Variant20> {
+    config: AnalyzerConfig,
+    variant: V,
+    /// Грамматика варианта; `None`, если её текст не разобрался
+    grammar: Option<Grammar>,
+    /// Таблица LL(1) по грамматике варианта; `None`, если грамматика не LL(1)
+    table: Option<Ll1Table>,
+    collect_ast: bool,
+    collect_cst: bool,
+    collect_tokens: bool,
+    collect_trace: bool,
+}
+
+/// Построитель [`Analyzer`].
+#[derive(Debug, Clone)]
+pub struct AnalyzerBuilder<V: Variant = Variant20> {
+    config: AnalyzerConfig,
+    variant: V,
+    collect_ast: bool,
+    collect_cst: bool,
+    collect_tokens: bool,
+    collect_trace: bool,
+    syntax_only: bool,
+}
+
+impl Default for AnalyzerBuilder {
+    fn default() -> Self {
+        Self {
+            config: AnalyzerConfig::default(),
+            variant: Variant20,
+            collect_ast: false,
+            collect_cst: false,
+            collect_tokens: false,
+            collect_trace: false,
+            syntax_only: false,
+        }
+    }
+}
+
+impl<V: Variant> AnalyzerBuilder<V> {
+    pub fn config(mut self, config: AnalyzerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Вариант задания, по которому выполняется анализ.
+    pub fn variant<W: Variant>(self, variant: W) -> AnalyzerBuilder<W> {
+        AnalyzerBuilder {
+            config: self.config,
+            variant,
+            collect_ast: self.collect_ast,
+            collect_cst: self.collect_cst,
+            collect_tokens: self.collect_tokens,
+            collect_trace: self.collect_trace,
+            syntax_only: self.syntax_only,
+        }
+    }
+
+    /// Сохранять дерево разбора в [`Analysis::ast`].
+    pub fn collect_ast(mut self, collect: bool) -> Self {
+        self.collect_ast = collect;
+        self
+    }
+
+    /// Строить конкретное дерево разбора в [`Analysis::cst`].
+    pub fn collect_cst(mut self, collect: bool) -> Self {
+        self.collect_cst = collect;
+        self
+    }
+
+    /// Сохранять лексемы в [`Analysis::tokens`].
+    pub fn collect_tokens(mut self, collect: bool) -> Self {
+        self.collect_tokens = collect;
+        self
+    }
+
+    /// Сохранять трассировку рекурсивного спуска в [`Analysis::trace`].
+    pub fn collect_trace(mut self, collect: bool) -> Self {
+        self.collect_trace = collect;
+        self
+    }
+
+    /// Только синтаксический анализ: все семантические правила отключаются,
+    /// списки символов по ролям при этом собираются.
+    pub fn syntax_only(mut self, syntax_only: bool) -> Self {
+        self.syntax_only = syntax_only;
+        self
+    }
+
+    pub fn build(mut self) -> Analyzer<V> {
+        if self.syntax_only {
+            for rule in Rule::ALL {
+                self.config.set_severity(rule, Severity::Off);
+            }
+            self.config.profile = None;
+        }
+        let text = language_grammar(self.variant.grammar(), &self.config);
+        let grammar = Grammar::parse(&text).ok();
+        let table = grammar.clone().and_then(|grammar| Ll1Table::build(grammar).ok());
+        Analyzer {
+            config: self.config,
+            variant: self.variant,
+            grammar,
+            table,
+            collect_ast: self.collect_ast,
+            collect_cst: self.collect_cst,
+            collect_tokens: self.collect_tokens,
+            collect_trace: self.collect_trace,
+        }
+    }
+}
+
+/// Результат [`Analyzer::analyze`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    pub report: model::AnalysisReport,
+    /// Ошибки и предупреждения, как в [`diagnose`]
+    pub diagnostics: Vec<Diagnostic>,
+    /// Лексемы с байтовыми отрезками, если лексический анализ дошёл до конца строки
+    pub tokens: Option<Vec<(Span, Token)>>,
+    /// Дерево разбора, если строка разобрана без синтаксических ошибок
+    pub ast: Option<ast::Assignment>,
+    /// Конкретное дерево разбора со всеми лексемами, при тех же условиях
+    pub cst: Option<cst::Node>,
+    /// Входы в нетерминалы, выходы из них и взятые лексемы, если их требовалось
+    /// сохранить
+    pub trace: Option<Trace>,
+}
+
+impl Analysis {
+    /// Лексемы для просмотра; `input` - проанализированная строка.
+    pub fn token_dump(&self, input: &str) -> Option<TokenDump> {
+        Some(TokenDump::new(input, self.tokens.as_ref()?))
+    }
+}
+
+impl Analyzer {
+    pub fn builder() -> AnalyzerBuilder {
+        AnalyzerBuilder::default()
+    }
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl<V: Variant> Analyzer<V> {
+    pub fn config(&self) -> &AnalyzerConfig {
+        &self.config
+    }
+
+    pub fn variant(&self) -> &V {
+        &self.variant
+    }
+
+    pub fn analyze(&self, input: &str) -> Analysis {
+        let setup = Setup {
+            config: &self.config,
+            variant: &self.variant,
+            grammar: self.grammar.as_ref(),
+            table: self.table.as_ref(),
+            keep_tokens: self.collect_tokens || self.collect_cst,
+            events: Events::default(),
+        };
+        let mut trace = Trace::default();
+        let sink = std::cell::RefCell::new(&mut trace as &mut dyn EventSink);
+        let setup = match self.collect_trace {
+            true => Setup {
+                events: Events::new(&sink),
+                ..setup
+            },
+            false => setup,
+        };
+        let mut outcome = run(input, setup);
+        let tokens = outcome.tokens.take();
+        let cst = match (&tokens, &outcome.ast, &self.table) {
+            (Some(tokens), Some(_), Some(table)) if self.collect_cst => {
+                concrete(table, tokens, input)
+            }
+            _ => None,
+        };
+        let ast = outcome.ast.take().filter(|_| self.collect_ast);
+        let diagnostics = diagnostics(&outcome, input);
+        let report = build_report(input, &self.config, outcome);
+        Analysis {
+            diagnostics,
+            report,
+            tokens: tokens.filter(|_| self.collect_tokens),
+            ast,
+            cst,
+            trace: Some(trace).filter(|_| self.collect_trace),
+        }
+    }
+}
+
+/// Результат анализа одного оператора из многооператорного текста.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementResult {
+    /// Смещение начала оператора во входном тексте в байтах
+    pub offset: usize,
+    /// Текст оператора, включая завершающий символ
+    pub text: String,
+    /// Позиции в отчёте об ошибках отсчитываются от начала оператора
+    pub result: Result<Success, ErrorReport>,
+    /// Нарушения, скрытые комментарием `(* taafl:allow(...) *)` перед оператором
+    pub suppressed: Vec<Diagnostic>,
+    /// Замечания к комментарию-разрешению: неизвестные правила в нём пропущены
+    pub notes: Vec<String>,
+}
+
+/// Анализирует оператор с учётом комментариев-разрешений в его начале.
+fn analyze_statement(offset: usize, text: &str, config: &AnalyzerConfig) -> StatementResult {
+    let (allow, stripped) = Allow::extract(text);
+    let mut allowed = config.clone();
+    allow.apply(&mut allowed);
+    StatementResult {
+        offset,
+        text: text.to_string(),
+        result: analyze_line_with(&stripped, &allowed),
+        suppressed: suppressed(&stripped, config, &allow),
+        notes: allow.notes,
+    }
+}
+
+/// Диагностики, которые разрешения `allow` скрывают из результата анализа строки.
+/// Ищутся в режиме восстановления, чтобы были перечислены все скрытые нарушения.
+pub fn suppressed(input: &str, config: &AnalyzerConfig, allow: &Allow) -> Vec<Diagnostic> {
+    if allow.rules.is_empty() {
+        return Vec::new();
+    }
+    let mut config = config.clone();
+    config.recovery = true;
+    diagnose(input, &config)
+        .into_iter()
+        .filter(|d| allow.rules.iter().any(|rule| rule.id() == d.code))
+        .collect()
+}
+
+/// Разбивает текст на операторы по завершающему символу (по умолчанию ';') и
+/// анализирует каждый независимо.
+///
+/// Комментарий `(* taafl:allow(правило, ...) *)` перед оператором отключает для него
+/// перечисленные правила; скрытые нарушения перечисляются в
+/// [`StatementResult::suppressed`].
+///
+/// ```
+/// use taafl::analyzer::analyze_many;
+///
+/// let results = analyze_many("A := 0; (* taafl:allow(constant-range) *) B := 0;");
+/// assert!(results[0].result.is_err());
+/// assert!(results[1].result.is_ok());
+/// assert_eq!(results[1].suppressed[0].code, "constant-range");
+/// ```
+pub fn analyze_many(input: &str) -> Vec<StatementResult> {
+    analyze_many_with(input, &AnalyzerConfig::default())
+}
+
+/// То же, что и [`analyze_many`], но с заданными настройками. Сводную таблицу
+/// символов всех операторов строит [`sequence::analyze_sequence`].
+pub fn analyze_many_with(input: &str, config: &AnalyzerConfig) -> Vec<StatementResult> {
+    split_statements_with(input, config)
+        .into_iter()
+        .map(|(offset, text)| analyze_statement(offset, text, config))
+        .collect()
+}
+
+/// Делит текст на операторы: каждый заканчивается ';' (включительно), кроме, возможно,
+/// последнего. Пробелы между операторами отбрасываются, пустые операторы пропускаются.
+///
+/// Возвращает пары (смещение начала оператора, текст оператора).
+pub fn split_statements(input: &str) -> Vec<(usize, &str)> {
+    split_statements_with(input, &AnalyzerConfig::default())
+}
+
+/// То же, что и [`split_statements`], но с заданными настройками: конец оператора
+/// берётся из [`AnalyzerConfig::terminator`]. Без завершающего символа
+/// ([`Terminator::None`]) каждая строка - отдельный оператор.
+///
+/// ```
+/// use taafl::analyzer::split_statements_with;
+/// use taafl::config::{AnalyzerConfig, Terminator};
+///
+/// let mut config = AnalyzerConfig { terminator: Terminator::Dot, ..Default::default() };
+/// let statements = split_statements_with("A := 1. B := 2.", &config);
+/// assert_eq!(statements, [(0, "A := 1."), (8, "B := 2.")]);
+///
+/// // С вещественными константами точка перед цифрой - дробная часть, как в лексере
+/// config.reals = true;
+/// let statements = split_statements_with("A := 1.5. B := 2.", &config);
+/// assert_eq!(statements, [(0, "A := 1.5."), (10, "B := 2.")]);
+///
+/// config.terminator = Terminator::None;
+/// let statements = split_statements_with("A := 1\n\nB := 2\n", &config);
+/// assert_eq!(statements, [(0, "A := 1"), (8, "B := 2")]);
+///
+/// // Внутри комментария, в том числе вложенного, оператор не заканчивается
+/// let config = AnalyzerConfig::default();
+/// let statements = split_statements_with("A := 1 (* (* ; *) ; *); B := 2;", &config);
+/// assert_eq!(statements, [(0, "A := 1 (* (* ; *) ; *);"), (24, "B := 2;")]);
+///
+/// // Как и внутри литерала
+/// let statements = split_statements_with("A := ';(*'; B := 2;", &config);
+/// assert_eq!(statements, [(0, "A := ';(*';"), (12, "B := 2;")]);
+/// ```
+pub fn split_statements_with<'a>(input: &'a str, config: &AnalyzerConfig) -> Vec<(usize, &'a str)> {
+    let terminator = config.terminator;
+    let mut statements = Vec::new();
+    let mut start = 0;
+    // Завершающий символ внутри комментария или литерала оператор не заканчивает
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(open) if c == open => {
+                quote = None;
+                continue;
+            }
+            Some(_) if c != '\n' => continue,
+            // Незакрытый литерал заканчивается вместе со строкой
+            Some(_) => quote = None,
+            None => {}
+        }
+        match (c, chars.peek().map(|&(_, next)| next)) {
+            ('\'' | '"', _) if depth == 0 => {
+                quote = Some(c);
+                continue;
+            }
+            ('(', Some('*')) => {
+                chars.next();
+                depth = depth.saturating_add(1);
+                continue;
+            }
+            ('*', Some(')')) if depth > 0 => {
+                chars.next();
+                depth = depth.saturating_sub(1);
+                continue;
+            }
+            _ if depth > 0 => continue,
+            _ => {}
+        }
+        let before = input.as_bytes().get(start..i).unwrap_or_default();
+        let next = chars.peek().map(|&(_, next)| next);
+        match terminator.symbol() {
+            // Завершающий символ и перевод строки - по одному байту
+            Some(symbol) if c == symbol && !is_fraction(config, c, before, next) => {
+                push_statement(&mut statements, input, start, i.saturating_add(1));
+                start = i.saturating_add(1);
+            }
+            None if c == '\n' => {
+                push_statement(&mut statements, input, start, i);
+                start = i.saturating_add(1);
+            }
+            _ => {}
+        }
+    }
+    push_statement(&mut statements, input, start, input.len());
+    statements
+}
+
+fn push_statement<'a>(
+    statements: &mut Vec<(usize, &'a str)>,
+    input: &'a str,
+    start: usize,
+    end: usize,
+) {
+    let text = input.get(start..end).unwrap_or_default();
+    if let Some((offset, text)) = trim_statement(text) {
+        statements.push((start.saturating_add(offset), text));
+    }
+}
+
+/// Начинает ли точка `c` после текста `before` дробную часть вещественной константы:
+/// по правилу лексера перед ней только цифры, после - цифра.
+fn is_fraction(config: &AnalyzerConfig, c: char, before: &[u8], next: Option<char>) -> bool {
+    let word = before
+        .iter()
+        .rev()
+        .take_while(|c| c.is_ascii_alphanumeric() || **c == b'_')
+        .count();
+    let number = before.get(before.len().saturating_sub(word)..).unwrap_or_default();
+    config.reals
+        && c == '.'
+        && !number.is_empty()
+        && number.iter().all(u8::is_ascii_digit)
+        && next.is_some_and(|c| c.is_ascii_digit())
+}
+
+/// Отбрасывает пробелы вокруг оператора. Возвращает смещение его начала во фрагменте
+/// и сам оператор либо `None`, если фрагмент пуст.
+fn trim_statement(text: &str) -> Option<(usize, &str)> {
+    let trimmed = text.trim_start();
+    let offset = text.len().saturating_sub(trimmed.len());
+    let trimmed = trimmed.trim_end();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some((offset, trimmed))
+    }
+}
+
+/// Анализирует операторы из потока по мере их чтения, не загружая весь ввод в память:
+/// в памяти одновременно находится только текущий оператор.
+///
+/// Операторы выделяются так же, как в [`split_statements_with`]. Ошибка чтения или
+/// недопустимая последовательность UTF-8 завершают итерацию.
+///
+/// ```
+/// use taafl::analyzer::analyze_reader;
+///
+/// let input = "A := 1 (* B := 2; *); C := 3;".as_bytes();
+/// let results: Vec<_> = analyze_reader(input).map(Result::unwrap).collect();
+/// assert_eq!(results[0].text, "A := 1 (* B := 2; *);");
+/// assert!(results.iter().all(|statement| statement.result.is_ok()));
+/// ```
+pub fn analyze_reader<R: Read>(reader: R) -> StatementReader<R> {
+    analyze_reader_with(reader, AnalyzerConfig::default())
+}
+
+/// То же, что и [`analyze_reader`], но с заданными настройками.
+pub fn analyze_reader_with<R: Read>(reader: R, config: AnalyzerConfig) -> StatementReader<R> {
+    StatementReader {
+        reader: BufReader::new(reader),
+        config,
+        offset: 0,
+        done: false,
+    }
+}
+
+/// Итератор результатов [`analyze_reader`].
+pub struct StatementReader<R> {
+    reader: BufReader<R>,
+    config: AnalyzerConfig,
+    /// Сколько байт уже прочитано
+    offset: usize,
+    done: bool,
+}
+
+impl<R: Read> Iterator for StatementReader<R> {
+    type Item = io::Result<StatementResult>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buffer = Vec::new();
+        while !self.done {
+            buffer.clear();
+            let start = self.offset;
+            let delimiter = self.config.terminator.symbol().map_or(b'\n', |c| c as u8);
+            // Завершающий символ внутри комментария или литерала оператор не заканчивает
+            loop {
+                match self.reader.read_until(delimiter, &mut buffer) {
+                    Ok(0) => self.done = true,
+                    Ok(n) => self.offset = self.offset.saturating_add(n),
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                }
+                if self.done || (!unfinished(&buffer) && !self.fraction_follows(&buffer)) {
+                    break;
+                }
+            }
+
+            let text = match std::str::from_utf8(&buffer) {
+                Ok(text) => text,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(io::Error::new(io::ErrorKind::InvalidData, e)));
+                }
+            };
+            if let Some((offset, text)) = trim_statement(text) {
+                let start = start.saturating_add(offset);
+                return Some(Ok(analyze_statement(start, text, &self.config)));
+            }
+        }
+        None
+    }
+}
+
+impl<R: Read> StatementReader<R> {
+    /// Заканчивается ли прочитанное точкой дробной части: следующий байт смотрится
+    /// в буфере, не читая его.
+    fn fraction_follows(&mut self, buffer: &[u8]) -> bool {
+        let Some((&last, before)) = buffer.split_last() else {
+            return false;
+        };
+        let next = match self.reader.fill_buf() {
+            Ok(rest) => rest.first().map(|&c| char::from(c)),
+            // Ошибку вернёт следующее чтение
+            Err(_) => None,
+        };
+        is_fraction(&self.config, char::from(last), before, next)
+    }
+}
+
+/// Остался ли в конце `text` незакрытый комментарий `(* ... *)` или литерал.
+fn unfinished(text: &[u8]) -> bool {
+    let (mut depth, mut quote, mut i) = (0usize, None, 0usize);
+    while let Some(&c) = text.get(i) {
+        let next = i.saturating_add(1);
+        if let Some(open) = quote {
+            if c == open || c == b'\n' {
+                quote = None;
+            }
+            i = next;
+            continue;
+        }
+        match (c, text.get(next)) {
+            (b'\'' | b'"', _) if depth == 0 => quote = Some(c),
+            (b'(', Some(b'*')) => {
+                depth = depth.saturating_add(1);
+                i = next;
+            }
+            (b'*', Some(b')')) if depth > 0 => {
+                depth = depth.saturating_sub(1);
+                i = next;
+            }
+            _ => {}
+        }
+        i = i.saturating_add(1);
+    }
+    depth > 0 || quote.is_some()
+}
+
+/// Итог анализа строки: лексика -> синтаксис -> семантика.
+struct Outcome<'a> {
+    /// Семантический анализ, если строка разобрана без синтаксических ошибок
+    checker: Option<Checker<'a>>,
+    /// Лексемы, если их требовалось сохранить
+    tokens: Option<Vec<(Span, Token)>>,
+    /// Дерево разбора, если разбор завершился без синтаксических ошибок
+    ast: Option<ast::Assignment>,
+    /// Таблица промежутков между лексемами разобранной строки
+    gaps: Vec<model::Gap>,
+    /// Левый вывод разобранной строки, если грамматика варианта - LL(1)
+    derivation: Vec<model::DerivationStep>,
+    errors: Vec<Error>,
+    warnings: Vec<Error>,
+}
+
+impl Outcome<'_> {
+    /// Анализ остановлен на ошибках `failed`, обычно одной; до них лексер
+    /// пропустил символы `skipped`.
+    fn failed(
+        skipped: Vec<Error>,
+        failed: impl IntoIterator<Item = Error>,
+        tokens: Option<Vec<(Span, Token)>>,
+    ) -> Self {
+        let mut errors = skipped;
+        errors.extend(failed);
+        Outcome {
+            checker: None,
+            tokens,
+            ast: None,
+            gaps: Vec::new(),
+            derivation: Vec::new(),
+            errors,
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// Всё, что нужно для анализа одной строки.
+#[derive(Clone, Copy)]
+struct Setup<'a> {
+    config: &'a AnalyzerConfig,
+    variant: &'a dyn Variant,
+    /// Грамматика варианта для разбора Эрли и левого вывода
+    grammar: Option<&'a Grammar>,
+    table: Option<&'a Ll1Table>,
+    keep_tokens: bool,
+    events: Events<'a>,
+}
+
+impl<'a> Setup<'a> {
+    /// Вариант 20 с заданными настройками.
+    fn new(config: &'a AnalyzerConfig) -> Self {
+        Setup {
+            config,
+            variant: &Variant20,
+            grammar: ll1_table(config).map(Ll1Table::grammar),
+            table: ll1_table(config),
+            keep_tokens: false,
+            events: Events::default(),
+        }
+    }
+}
+
+/// Анализ строки с проверкой инвариантов результата (см. [`invariants`]).
+fn run<'a>(input: &'a str, setup: Setup<'a>) -> Outcome<'a> {
+    let outcome = run_stages(input, setup);
+    invariants::check_outcome(input, &outcome);
+    outcome
+}
+
+fn run_stages<'a>(input: &'a str, setup: Setup<'a>) -> Outcome<'a> {
+    let Setup {
+        config,
+        variant,
+        grammar,
+        table,
+        keep_tokens,
+        events,
+    } = setup;
+    let _span = debug_span!("analyze", input).entered();
+
+    // Таблице LL(1) и сохранению лексем нужны все лексемы сразу, иначе синтаксический
+    // анализатор получает их по одной
+    let table_mode = config.parser == ParserKind::Table;
+    let earley_mode = config.parser == ParserKind::Earley;
+    // Ошибки о символах, пропущенных лексером в режиме восстановления
+    let mut skipped = Vec::new();
+    let tokens = if keep_tokens || table_mode || earley_mode {
+        let mut lexer = Lexer::configured(input, config);
+        lexer.events = events;
+        let lexed = debug_span!("lex").in_scope(|| lexer.tokenize());
+        skipped = std::mem::take(&mut lexer.skipped);
+        match lexed {
+            Ok(t) => Some(t),
+            Err(e) => return Outcome::failed(skipped, [e], None),
+        }
+    } else {
+        None
+    };
+    let kept = tokens.clone().filter(|_| keep_tokens);
+
+    // Левый вывод, если его построила таблица LL(1)
+    let mut applied = None;
+    if let (true, Some(tokens)) = (table_mode, &tokens) {
+        let Some(table) = table else {
+            let message = format!("Грамматика варианта '{}' не является LL(1)", variant.name());
+            return Outcome::failed(skipped, [Error::Syntax(0..0, message)], kept);
+        };
+        let terminals = terminals(tokens);
+        let end = end_position(input);
+        let checked = debug_span!("ll1").in_scope(|| {
+            table.trace(&terminals, end, |step| events.emit(|| Event::TableConsulted(step)))
+        });
+        match checked {
+            Ok(checked) => applied = Some(checked),
+            Err((pos, message)) => {
+                let error = table_error(tokens, pos, &message, config);
+                let errors = descent_errors(tokens, pos, input, config, variant);
+                let errors = errors.unwrap_or_else(|| vec![error]).into_iter();
+                let errors = errors.map(|e| e.with_expected(table, &terminals, end, config));
+                return Outcome::failed(skipped, errors, kept);
+            }
+        }
+    }
+
+    // Разбор Эрли строит левый вывод и по грамматике не LL(1); второй вывод того же
+    // оператора становится предупреждением
+    let mut ambiguity = None;
+    if let (true, Some(tokens)) = (earley_mode, &tokens) {
+        let Some(grammar) = grammar else {
+            let message = format!("Грамматика варианта '{}' не разобрана", variant.name());
+            return Outcome::failed(skipped, [Error::Syntax(0..0, message)], kept);
+        };
+        let terminals = terminals(tokens);
+        let end = end_position(input);
+        match debug_span!("earley").in_scope(|| earley::parse(grammar, &terminals, end)) {
+            Ok(parse) => {
+                ambiguity = ambiguity_warning(grammar, &parse, tokens, input);
+                applied = Some(parse.derivation);
+            }
+            Err((pos, message)) => {
+                let error = table_error(tokens, pos, &message, config);
+                let Some(errors) = descent_errors(tokens, pos, input, config, variant) else {
+                    return Outcome::failed(skipped, [error], kept);
+                };
+                let errors = errors.into_iter().map(|e| match table {
+                    Some(table) => e.with_expected(table, &terminals, end, config),
+                    None => e,
+                });
+                return Outcome::failed(skipped, errors, kept);
+            }
+        }
+    }
+
+    // Лексемы, прочитанные парсером: по ним строится таблица промежутков
+    let mut lexed = Vec::new();
+    let parsed = debug_span!("parse").in_scope(|| match tokens {
+        Some(tokens) => {
+            lexed.clone_from(&tokens);
+            let tokens = tokens.into_iter().map(Ok);
+            Parser::new(tokens, input.to_string(), config, variant, events).parse()
+        }
+        None => {
+            let mut lexer = Lexer::configured(input, config);
+            lexer.events = events;
+            let tokens = (&mut lexer).inspect(|token| {
+                if let Ok(token) = token {
+                    lexed.push(token.clone());
+                }
+            });
+            let parsed = Parser::new(tokens, input.to_string(), config, variant, events).parse();
+            skipped = std::mem::take(&mut lexer.skipped);
+            parsed
+        }
+    });
+    let ast = match parsed {
+        Ok(ast) => ast,
+        Err(errors) => {
+            // Рекурсивный спуск проверяет те же лексемы, что и таблица: она и
+            // называет допустимые на месте ошибки терминалы
+            let errors = match table {
+                Some(table) => {
+                    let (terminals, end) = (terminals(&lexed), end_position(input));
+                    let expected = |e: Error| e.with_expected(table, &terminals, end, config);
+                    errors.into_iter().map(expected).collect()
+                }
+                None => errors,
+            };
+            return Outcome::failed(skipped, errors, kept);
+        }
+    };
+
+    // Вывод по таблице: в её режиме и при разборе Эрли он уже построен при проверке
+    let derivation = match grammar {
+        Some(grammar) => {
+            let end = end_position(input);
+            let applied = applied.or_else(|| {
+                let table = table?;
+                table.derivation(&terminals(&lexed), end).ok()
+            });
+            derivation_steps(grammar, &applied.unwrap_or_default())
         }
-        Error::SemanticError(pos, msg) => {
-            format_error_with_cursor(input, pos, &format!("Семантическая ошибка: {}", msg))
+        None => Vec::new(),
+    };
+
+    let mut checker = Checker::new(config, variant, input, &lexed);
+    let gaps = gap_table(input, &lexed);
+    let mut result = debug_span!("check").in_scope(|| checker.check(&ast));
+    if result.is_ok() && config.whitespace.is_active() {
+        let whitespace = || checker.check_whitespace(&lexed, &gaps);
+        result = debug_span!("whitespace").in_scope(whitespace);
+    }
+    if result.is_ok() && config.limits.is_active() {
+        let limits = || checker.check_limits(&lexed);
+        result = debug_span!("limits").in_scope(limits);
+    }
+    let mut errors = skipped;
+    errors.append(&mut checker.reporter.errors);
+    let mut warnings = std::mem::take(&mut checker.reporter.warnings);
+    warnings.extend(ambiguity);
+    if let Err(e) = result {
+        errors.push(e);
+    }
+    debug!(
+        errors = errors.len(),
+        warnings = warnings.len(),
+        "анализ завершён"
+    );
+
+    Outcome {
+        checker: Some(checker),
+        tokens: kept,
+        ast: Some(ast),
+        gaps,
+        derivation,
+        errors,
+        warnings,
+    }
+}
+
+/// Промежутки исходного текста вокруг лексем `tokens`: перед первой, между
+/// соседними и после последней.
+fn gap_table(input: &str, tokens: &[(Span, Token)]) -> Vec<model::Gap> {
+    let starts = tokens.iter().map(|(span, _)| span.start).chain([input.len()]);
+    let ends = [0].into_iter().chain(tokens.iter().map(|(span, _)| span.end));
+    ends.zip(starts)
+        .map(|(position, end)| model::Gap {
+            position,
+            end,
+            text: input.get(position..end).unwrap_or_default().to_string(),
+        })
+        .collect()
+}
+
+/// Терминалы грамматики для лексем с позициями.
+fn terminals(tokens: &[(Span, Token)]) -> Vec<(usize, &'static str)> {
+    tokens
+        .iter()
+        .map(|(span, token)| (span.start, token.terminal()))
+        .collect()
+}
+
+/// Позиция конца ввода для таблицы LL(1) и разбора Эрли: начало последнего символа
+/// строки `input`, чтобы отрезок ошибки на ней не делил многобайтный символ.
+fn end_position(input: &str) -> usize {
+    input.char_indices().next_back().map_or(0, |(i, _)| i)
+}
+
+/// Предупреждение о втором выводе оператора `input`: шаг, на котором выводы
+/// расходятся, и продукции на нём. Указывает на лексемы нетерминала этого шага.
+fn ambiguity_warning(
+    grammar: &Grammar,
+    parse: &earley::Parse,
+    tokens: &[(Span, Token)],
+    input: &str,
+) -> Option<Error> {
+    let (step, first, second) = parse.divergence()?;
+    let production = |index: usize| {
+        let text = grammar.productions.get(index).map(ToString::to_string);
+        format!("{} ({})", index.saturating_add(1), text.unwrap_or_default())
+    };
+    let message = format!(
+        "Оператор выводится в грамматике варианта двумя способами: на шаге {} \
+         применима продукция {} и продукция {}",
+        step.saturating_add(1),
+        production(first),
+        production(second)
+    );
+    let spans: Vec<Span> = tokens.iter().map(|(span, _)| span.clone()).collect();
+    let tree = cst::Node::build(grammar, &parse.derivation, &spans, input);
+    let span = tree.as_ref().and_then(|tree| Some(tree.rules().get(step)?.span()));
+    Some(Error::Syntax(span.unwrap_or(0..0), message))
+}
+
+/// Сообщение разбора по таблице: присваивание в грамматике - литерал ":=",
+/// в сообщении - настроенные лексемы.
+fn table_message(message: &str, config: &AnalyzerConfig) -> String {
+    let assign = AssignOperator::describe(&config.assign_operators());
+    message.replace("':='", &assign)
+}
+
+/// Терминалы, которые таблица допускает на месте ошибки с отрезком `span`, и
+/// сообщение разбора по таблице о них. Если разбор всех лексем останавливается
+/// в другом месте, берутся терминалы, допустимые после лексем перед ошибкой.
+fn expected_at(
+    table: &Ll1Table,
+    terminals: &[(usize, &str)],
+    end: usize,
+    span: &Span,
+) -> Option<(Vec<String>, String)> {
+    let at = |pos: usize| span.contains(&pos) || pos == span.start;
+    match table.expected(terminals, end) {
+        Some((pos, expected)) if at(pos) => {
+            let (_, message) = table.derivation(terminals, end).err()?;
+            return Some((expected, message));
         }
+        _ => {}
+    }
+    let before = terminals
+        .iter()
+        .take_while(|(pos, _)| *pos < span.start)
+        .count();
+    let (pos, expected) = table.expected(terminals.get(..before)?, span.start)?;
+    if pos != span.start {
+        return None;
+    }
+    let found = terminals
+        .get(before)
+        .map_or(grammar::END, |(_, terminal)| *terminal);
+    let names: Vec<&str> = expected.iter().map(String::as_str).collect();
+    let message = grammar::expected_message(&names, found);
+    Some((expected, message))
+}
+
+/// Ошибки рекурсивного спуска на лексемах `tokens`, если он отвергает их там же,
+/// где разбор по грамматике остановился на `pos`: его сообщения точнее, например
+/// о пропущенном операнде или пустом списке индексов. `None`, если спуск принимает
+/// строку или ошибается в другом месте - у грамматики варианта свой язык.
+fn descent_errors(
+    tokens: &[(Span, Token)],
+    pos: usize,
+    input: &str,
+    config: &AnalyzerConfig,
+    variant: &dyn Variant,
+) -> Option<Vec<Error>> {
+    let tokens = tokens.iter().cloned().map(Ok);
+    let events = Events::default();
+    let mut parser = Parser::new(tokens, input.to_string(), config, variant, events);
+    let errors = parser.parse().err()?;
+    let span = errors.iter().min_by_key(|e| e.position())?.span();
+    (span.contains(&pos) || span.start == pos).then_some(errors)
+}
+
+/// Ошибка разбора по таблице LL(1) в позиции `pos`.
+fn table_error(
+    tokens: &[(Span, Token)],
+    pos: usize,
+    message: &str,
+    config: &AnalyzerConfig,
+) -> Error {
+    let message = table_message(message, config);
+    // Ошибка указывает на лексему в позиции, если она там есть
+    let span = tokens
+        .iter()
+        .find(|(span, _)| span.start == pos && !span.is_empty())
+        .map_or(pos..pos, |(span, _)| span.clone());
+    Error::Syntax(span, message)
+}
+
+fn format_errors(mut errors: Vec<Error>, input: &str, max_errors: usize) -> ErrorReport {
+    errors.sort_by_key(Error::position);
+    let errors: Vec<model::ErrorEntry> = errors.iter().map(Error::to_entry).collect();
+    format_entries(&errors, input, max_errors)
+}
+
+/// Текст ошибок отчёта, уже упорядоченных по позиции.
+fn format_entries(errors: &[model::ErrorEntry], input: &str, max_errors: usize) -> ErrorReport {
+    let shown = if max_errors == 0 {
+        errors.len()
+    } else {
+        errors.len().min(max_errors)
+    };
+    let hidden = errors.len().saturating_sub(shown);
+
+    let mut text = String::from(input);
+    for err in errors.iter().take(shown) {
+        text.push('\n');
+        text.push_str(&format_error(err, input));
     }
+    if hidden > 0 {
+        text.push_str(&format!(
+            "\n... и ещё {} {}",
+            hidden,
+            plural(hidden, "ошибка", "ошибки", "ошибок")
+        ));
+    }
+
+    ErrorReport { text, hidden }
+}
+
+fn format_warnings(mut warnings: Vec<Error>, input: &str) -> Option<String> {
+    warnings.sort_by_key(Error::position);
+    let warnings: Vec<model::ErrorEntry> = warnings.iter().map(Error::to_entry).collect();
+    format_warning_entries(&warnings, input)
+}
+
+fn format_warning_entries(warnings: &[model::ErrorEntry], input: &str) -> Option<String> {
+    if warnings.is_empty() {
+        return None;
+    }
+
+    let mut text = String::from(input);
+    for warning in warnings {
+        text.push('\n');
+        text.push_str(&format_error_with_cursor(
+            input,
+            warning.position..warning.end,
+            &format!("Предупреждение: {}", warning.message),
+        ));
+    }
+    Some(text)
 }
 
-fn format_error_with_cursor(input: &str, pos: usize, msg: &str) -> String {
-    let mut cursor_pos = pos;
-    if cursor_pos > input.len() {
-        cursor_pos = input.len();
+/// Выбирает форму слова для числа `n`: "1 ошибка", "2 ошибки", "5 ошибок".
+fn plural(n: usize, one: &'static str, few: &'static str, many: &'static str) -> &'static str {
+    match (n % 10, n % 100) {
+        (1, m) if m != 11 => one,
+        (2..=4, m) if !(12..=14).contains(&m) => few,
+        _ => many,
     }
+}
+
+fn format_error(err: &model::ErrorEntry, input: &str) -> String {
+    let kind = match err.kind {
+        model::ErrorKind::Lexical => "Лексическая",
+        model::ErrorKind::Syntax => "Синтаксическая",
+        model::ErrorKind::Semantic => "Семантическая",
+    };
+    let message = format!("{} ошибка: {}", kind, err.message);
+    format_error_with_cursor(input, err.position..err.end, &message)
+}
+
+/// Строка с курсором под отрезком ошибки и её описание. Курсор подчёркивает
+/// ошибочную лексему целиком, пустой отрезок отмечается одним символом.
+///
+/// В многострочном тексте курсору предшествуют строка и столбец ошибки и сама
+/// строка, в которой она найдена.
+fn format_error_with_cursor(input: &str, span: Span, msg: &str) -> String {
+    let map = SourceMap::new(input);
     let mut result = String::new();
-    result.push_str(input);
-    result.push('\n');
-    for _ in 0..cursor_pos {
-        result.push(' ');
+    if map.is_multiline() {
+        let line = map.location(span.start).line;
+        result.push_str(&format!("{}:\n{}\n", map.describe(span.start), map.line(line)));
     }
-    result.push('^');
+    result.push_str(&map.cursor(span));
     result.push('\n');
     result.push_str(msg);
     result
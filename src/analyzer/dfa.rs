@@ -0,0 +1,466 @@
+//! Лексический анализ по явной таблице переходов конечного автомата - вместо
+//! посимвольного разбора, если так задано настройкой
+//! [`scanner`](crate::config::AnalyzerConfig::scanner).
+//!
+//! Автомат определяет, где кончается лексема и какого она вида. Значение лексемы
+//! и сообщения об ошибках лексер строит так же, как при посимвольном разборе,
+//! поэтому результаты анализа не зависят от способа. Все переходы перечисляет
+//! [`Dfa::transitions`], например, чтобы нарисовать автомат.
+//!
+//! ```
+//! use taafl::analyzer::dfa::{Class, Dfa, State};
+//! use taafl::config::AnalyzerConfig;
+//!
+//! let dfa = Dfa::new(&AnalyzerConfig::default());
+//! assert_eq!(dfa.run("COUNT1 := 0;"), (State::Identifier, 6));
+//! assert_eq!(dfa.run(":= 0;"), (State::Assign, 2));
+//! assert_eq!(dfa.run("<>1"), (State::Compound, 2));
+//! // Двоеточие без '=' - незавершённая лексема, о ней лексер сообщит ошибкой
+//! assert_eq!(dfa.run(":1"), (State::Colon, 1));
+//! assert_eq!(dfa.next(State::Colon, Class::Equals), Some(State::Assign));
+//!
+//! // Вещественные константы - только если они разрешены
+//! assert_eq!(dfa.run("1.5"), (State::Decimal, 1));
+//! let reals = AnalyzerConfig { reals: true, ..AnalyzerConfig::default() };
+//! assert_eq!(Dfa::new(&reals).run("1.5E-3"), (State::Exponent, 6));
+//! assert_eq!(Dfa::new(&reals).run("1.X"), (State::Decimal, 1));
+//! ```
+
+use crate::config::AnalyzerConfig;
+
+/// Класс символа - столбец таблицы переходов.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Class {
+    /// Латинская буква, кроме `E`
+    Letter,
+    /// `E` или `e`: буква и знак порядка вещественной константы
+    LetterE,
+    /// Буква другого алфавита, если они разрешены настройкой unicode
+    Foreign,
+    Digit,
+    /// `_`, если подчёркивание разрешено настройкой underscores
+    Underscore,
+    Dot,
+    Plus,
+    Minus,
+    /// `*`, `/`, `#` и `%`
+    Operator,
+    Less,
+    Greater,
+    Equals,
+    Colon,
+    /// `[`, `]`, `(` и `)`: какие из них скобки индексов, решает лексер
+    Bracket,
+    Comma,
+    Semicolon,
+    Arrow,
+    Quote,
+    DoubleQuote,
+    Newline,
+    /// Прочие символы
+    Other,
+}
+
+impl Class {
+    pub const ALL: [Class; 21] = [
+        Class::Letter,
+        Class::LetterE,
+        Class::Foreign,
+        Class::Digit,
+        Class::Underscore,
+        Class::Dot,
+        Class::Plus,
+        Class::Minus,
+        Class::Operator,
+        Class::Less,
+        Class::Greater,
+        Class::Equals,
+        Class::Colon,
+        Class::Bracket,
+        Class::Comma,
+        Class::Semicolon,
+        Class::Arrow,
+        Class::Quote,
+        Class::DoubleQuote,
+        Class::Newline,
+        Class::Other,
+    ];
+
+    /// Подпись столбца таблицы или дуги автомата.
+    pub fn title(self) -> &'static str {
+        match self {
+            Class::Letter => "буква",
+            Class::LetterE => "E",
+            Class::Foreign => "буква другого алфавита",
+            Class::Digit => "цифра",
+            Class::Underscore => "_",
+            Class::Dot => ".",
+            Class::Plus => "+",
+            Class::Minus => "-",
+            Class::Operator => "* / # %",
+            Class::Less => "<",
+            Class::Greater => ">",
+            Class::Equals => "=",
+            Class::Colon => ":",
+            Class::Bracket => "скобка",
+            Class::Comma => ",",
+            Class::Semicolon => ";",
+            Class::Arrow => "←",
+            Class::Quote => "'",
+            Class::DoubleQuote => "\"",
+            Class::Newline => "перевод строки",
+            Class::Other => "прочие",
+        }
+    }
+}
+
+/// Состояние автомата - строка таблицы переходов.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum State {
+    Start,
+    Identifier,
+    /// Только десятичные цифры
+    Decimal,
+    /// Цифры и буквы за ними: константа с суффиксом системы счисления или ошибка
+    Number,
+    /// Точка за целой частью вещественной константы
+    DecimalDot,
+    Fraction,
+    /// `E` после дробной части
+    ExponentMark,
+    /// Знак порядка после `E`
+    ExponentSign,
+    Exponent,
+    /// Буквы сразу за вещественной константой
+    BadReal,
+    /// Операция из одного знака
+    Operator,
+    Less,
+    Greater,
+    /// `<=`, `>=` или `<>`
+    Compound,
+    /// `=`: присваивание или операция сравнения
+    Equals,
+    Colon,
+    Assign,
+    Arrow,
+    /// Внутри литерала в одинарных кавычках
+    Quoted,
+    /// Внутри литерала в двойных кавычках
+    DoubleQuoted,
+    Literal,
+    /// Скобка, запятая или конец оператора; что это, решает лексер по настройкам
+    Punctuation,
+    /// Символ, с которого не начинается ни одна лексема
+    Invalid,
+}
+
+impl State {
+    pub const ALL: [State; 23] = [
+        State::Start,
+        State::Identifier,
+        State::Decimal,
+        State::Number,
+        State::DecimalDot,
+        State::Fraction,
+        State::ExponentMark,
+        State::ExponentSign,
+        State::Exponent,
+        State::BadReal,
+        State::Operator,
+        State::Less,
+        State::Greater,
+        State::Compound,
+        State::Equals,
+        State::Colon,
+        State::Assign,
+        State::Arrow,
+        State::Quoted,
+        State::DoubleQuoted,
+        State::Literal,
+        State::Punctuation,
+        State::Invalid,
+    ];
+
+    /// Лексема, на которой автомат остановился в этом состоянии, закончена.
+    /// В незавершённом состоянии лексер сообщает об ошибке; исключение -
+    /// [`State::DecimalDot`], из которого автомат возвращается к целой части.
+    pub fn accepting(self) -> bool {
+        !matches!(
+            self,
+            State::Start
+                | State::DecimalDot
+                | State::ExponentMark
+                | State::ExponentSign
+                | State::Colon
+                | State::Quoted
+                | State::DoubleQuoted
+        )
+    }
+
+    /// Подпись состояния для изображения автомата.
+    pub fn title(self) -> &'static str {
+        match self {
+            State::Start => "начало",
+            State::Identifier => "идентификатор",
+            State::Decimal => "десятичная константа",
+            State::Number => "константа с суффиксом",
+            State::DecimalDot => "точка после цифр",
+            State::Fraction => "дробная часть",
+            State::ExponentMark => "E порядка",
+            State::ExponentSign => "знак порядка",
+            State::Exponent => "порядок",
+            State::BadReal => "буквы после константы",
+            State::Operator => "операция",
+            State::Less => "<",
+            State::Greater => ">",
+            State::Compound => "операция из двух знаков",
+            State::Equals => "=",
+            State::Colon => ":",
+            State::Assign => ":=",
+            State::Arrow => "←",
+            State::Quoted => "литерал в ''",
+            State::DoubleQuoted => "литерал в \"\"",
+            State::Literal => "литерал",
+            State::Punctuation => "знак препинания",
+            State::Invalid => "недопустимый символ",
+        }
+    }
+}
+
+type Row = [Option<State>; Class::ALL.len()];
+
+/// Автомат лексем для заданных настроек: классы символов зависят от того,
+/// разрешены ли подчёркивание и буквы других алфавитов, а переходы - от того,
+/// разрешены ли вещественные константы.
+#[derive(Debug, Clone)]
+pub struct Dfa {
+    /// Переходы по номеру состояния и номеру класса
+    table: Vec<Row>,
+    underscores: bool,
+    unicode: bool,
+}
+
+impl Dfa {
+    pub fn new(config: &AnalyzerConfig) -> Self {
+        use Class as C;
+        use State as S;
+
+        let mut table = vec![[None; Class::ALL.len()]; State::ALL.len()];
+        let mut set = |from: State, classes: &[Class], to: State| {
+            for &class in classes {
+                if let Some(cell) = table
+                    .get_mut(from as usize)
+                    .and_then(|row: &mut Row| row.get_mut(class as usize))
+                {
+                    *cell = Some(to);
+                }
+            }
+        };
+
+        set(
+            S::Start,
+            &[C::Letter, C::LetterE, C::Foreign],
+            S::Identifier,
+        );
+        set(S::Start, &[C::Digit], S::Decimal);
+        set(S::Start, &[C::Plus, C::Minus, C::Operator], S::Operator);
+        set(S::Start, &[C::Less], S::Less);
+        set(S::Start, &[C::Greater], S::Greater);
+        set(S::Start, &[C::Equals], S::Equals);
+        set(S::Start, &[C::Colon], S::Colon);
+        set(S::Start, &[C::Arrow], S::Arrow);
+        set(S::Start, &[C::Quote], S::Quoted);
+        set(S::Start, &[C::DoubleQuote], S::DoubleQuoted);
+        let punctuation = [C::Bracket, C::Comma, C::Semicolon, C::Dot];
+        set(S::Start, &punctuation, S::Punctuation);
+        let invalid = [C::Underscore, C::Newline, C::Other];
+        set(S::Start, &invalid, S::Invalid);
+
+        let word = [C::Letter, C::LetterE, C::Digit, C::Underscore];
+        set(S::Identifier, &word, S::Identifier);
+        set(S::Identifier, &[C::Foreign], S::Identifier);
+        set(S::Decimal, &[C::Digit], S::Decimal);
+        set(
+            S::Decimal,
+            &[C::Letter, C::LetterE, C::Underscore],
+            S::Number,
+        );
+        set(S::Number, &word, S::Number);
+        if config.reals {
+            set(S::Decimal, &[C::Dot], S::DecimalDot);
+            set(S::DecimalDot, &[C::Digit], S::Fraction);
+            set(S::Fraction, &[C::Digit], S::Fraction);
+            set(S::Fraction, &[C::LetterE], S::ExponentMark);
+            set(S::Fraction, &[C::Letter], S::BadReal);
+            set(S::ExponentMark, &[C::Plus, C::Minus], S::ExponentSign);
+            set(S::ExponentMark, &[C::Digit], S::Exponent);
+            set(S::ExponentSign, &[C::Digit], S::Exponent);
+            set(S::Exponent, &[C::Digit], S::Exponent);
+            set(S::Exponent, &[C::Letter, C::LetterE], S::BadReal);
+            set(S::BadReal, &[C::Letter, C::LetterE, C::Digit], S::BadReal);
+        }
+
+        set(S::Less, &[C::Equals, C::Greater], S::Compound);
+        set(S::Greater, &[C::Equals], S::Compound);
+        set(S::Colon, &[C::Equals], S::Assign);
+
+        // Литерал заканчивается такой же кавычкой в той же строке
+        for class in Class::ALL {
+            if class != C::Newline {
+                let (quoted, double) = match class {
+                    C::Quote => (S::Literal, S::DoubleQuoted),
+                    C::DoubleQuote => (S::Quoted, S::Literal),
+                    _ => (S::Quoted, S::DoubleQuoted),
+                };
+                set(S::Quoted, &[class], quoted);
+                set(S::DoubleQuoted, &[class], double);
+            }
+        }
+
+        Dfa {
+            table,
+            underscores: config.underscores,
+            unicode: config.unicode_identifiers,
+        }
+    }
+
+    /// Класс символа `c`.
+    pub fn class(&self, c: char) -> Class {
+        match c {
+            'E' | 'e' => Class::LetterE,
+            _ if c.is_ascii_alphabetic() => Class::Letter,
+            _ if c.is_ascii_digit() => Class::Digit,
+            '_' if self.underscores => Class::Underscore,
+            '.' => Class::Dot,
+            '+' => Class::Plus,
+            '-' => Class::Minus,
+            '*' | '/' | '#' | '%' => Class::Operator,
+            '<' => Class::Less,
+            '>' => Class::Greater,
+            '=' => Class::Equals,
+            ':' => Class::Colon,
+            '[' | ']' | '(' | ')' => Class::Bracket,
+            ',' => Class::Comma,
+            ';' => Class::Semicolon,
+            '←' => Class::Arrow,
+            '\'' => Class::Quote,
+            '"' => Class::DoubleQuote,
+            '\n' => Class::Newline,
+            _ if self.unicode && c.is_alphabetic() => Class::Foreign,
+            _ => Class::Other,
+        }
+    }
+
+    /// Переход из состояния `state` по символу класса `class`; `None`, если его нет.
+    pub fn next(&self, state: State, class: Class) -> Option<State> {
+        self.table
+            .get(state as usize)
+            .and_then(|row| row.get(class as usize))
+            .copied()
+            .flatten()
+    }
+
+    /// Все переходы: (из состояния, по классу, в состояние).
+    pub fn transitions(&self) -> impl Iterator<Item = (State, Class, State)> + '_ {
+        State::ALL.into_iter().flat_map(move |from| {
+            Class::ALL
+                .into_iter()
+                .filter_map(move |class| Some((from, class, self.next(from, class)?)))
+        })
+    }
+
+    /// Самая длинная лексема в начале `text`: состояние, в котором автомат
+    /// остановился, и длина лексемы в байтах. В начале пустой строки -
+    /// [`State::Start`] и 0.
+    pub fn run(&self, text: &str) -> (State, usize) {
+        let (mut state, mut len) = (State::Start, 0);
+        let mut accepted = (State::Start, 0);
+        for (i, c) in text.char_indices() {
+            let Some(next) = self.next(state, self.class(c)) else {
+                break;
+            };
+            (state, len) = (next, i.saturating_add(c.len_utf8()));
+            if state.accepting() {
+                accepted = (state, len);
+            }
+        }
+        if state == State::DecimalDot {
+            accepted
+        } else {
+            (state, len)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{Cases, CASES};
+
+    /// Автомат лексем даёт те же лексемы, отчёты и ошибки, что и посимвольный разбор.
+    #[test]
+    fn dfa_scanner_matches_direct_scanner() {
+        use crate::analyzer::{analyze_report, tokenize_full};
+        use crate::config::{AnalyzerConfig, AssignOperator, Brackets, ScannerKind, Terminator};
+
+        const EDGES: &[&str] = &[
+            "X := 1.5E+3 + 2.;",
+            "X := 1.5E;",
+            "X := 1.5e-q;",
+            "X := 1.5AB + 3.0E2Z;",
+            "X := 1_000 + 0FFH + 377B + 19B + 99999;",
+            "MAX_LEN[I_1] := _A;",
+            "МАСС[И] := Ж + x;",
+            "X := 'abc' + \"d'e\" + 'f",
+            "X := \"g\nH;",
+            "X := A <= B >= C <> D = E # F < G > H;",
+            "X : 1; Y :",
+            "X = A = B;",
+            "X ← A(1, 2).",
+            "X := A (* комментарий (* вложенный *) *) + B;",
+            "X := × – ” $ @ ~;",
+            "X[] := ;;",
+            "",
+        ];
+        let mut extended = AnalyzerConfig {
+            reals: true,
+            underscores: true,
+            unicode_identifiers: true,
+            recovery: true,
+            ..AnalyzerConfig::default()
+        };
+        extended.assign.insert(AssignOperator::Equals);
+        extended.assign.insert(AssignOperator::Arrow);
+        let round = AnalyzerConfig {
+            brackets: Brackets::Round,
+            terminator: Terminator::Dot,
+            ..extended.clone()
+        };
+        let mut mutants = Cases::new(13);
+        let inputs: Vec<String> = EDGES
+            .iter()
+            .map(ToString::to_string)
+            .chain((0..CASES).map(|_| mutants.mutant().0))
+            .collect();
+
+        for direct in [AnalyzerConfig::default(), extended, round] {
+            let dfa = AnalyzerConfig {
+                scanner: ScannerKind::Dfa,
+                ..direct.clone()
+            };
+            for input in &inputs {
+                assert_eq!(
+                    tokenize_full(input, &dfa),
+                    tokenize_full(input, &direct),
+                    "{}",
+                    input
+                );
+                // Отчёты расходятся только записанным в них сканером
+                let mut report = analyze_report(input, &dfa);
+                let expected = analyze_report(input, &direct);
+                report.config.settings = expected.config.settings.clone();
+                assert_eq!(report, expected, "{}", input);
+            }
+        }
+    }
+}
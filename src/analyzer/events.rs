@@ -0,0 +1,232 @@
+//! События анализа для оболочек со своим интерфейсом: пошаговый просмотр,
+//! анимация и трассировка получают лексемы, шаги разбора и диагностики по мере
+//! того, как анализ до них доходит, а не только итоговый отчёт.
+//!
+//! ```
+//! use taafl::analyzer::analyze_with_events;
+//! use taafl::analyzer::events::Event;
+//! use taafl::config::AnalyzerConfig;
+//!
+//! let mut events = Vec::new();
+//! let report = analyze_with_events("X := 0;", &AnalyzerConfig::default(), &mut |event| {
+//!     events.push(event)
+//! });
+//! assert!(!report.accepted);
+//! assert!(matches!(events[0], Event::RuleEntered { rule: "statement", position: 0 }));
+//! let Event::DiagnosticEmitted(diagnostic) = &events[events.len() - 2] else {
+//!     panic!("перед завершением - диагностика");
+//! };
+//! assert_eq!(diagnostic.code, "constant-range");
+//! assert_eq!(events.last(), Some(&Event::Finished { accepted: false }));
+//! ```
+
+use super::{Span, Token};
+use crate::diagnostic::Diagnostic;
+use crate::grammar::TableStep;
+use std::cell::RefCell;
+
+/// Событие анализа одной строки.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Event {
+    /// Лексический анализатор выдал лексему. Рекурсивному спуску лексемы выдаются
+    /// по запросу, поэтому лексема, просмотренная вперёд, приходит раньше шага
+    /// разбора, который её возьмёт. Если лексемы нужны все сразу - разбору по
+    /// таблице, Earley или для сохранения лексем, - все они приходят до первого
+    /// [`RuleEntered`](Event::RuleEntered)
+    TokenProduced { span: Span, token: Token },
+    /// Синтаксический анализатор начал разбор нетерминала грамматики
+    /// [`GRAMMAR`](crate::grammar::GRAMMAR); `position` - конец последней взятой лексемы
+    RuleEntered { rule: &'static str, position: usize },
+    /// Синтаксический анализатор закончил разбор нетерминала, удачно или нет;
+    /// `position` - конец последней взятой лексемы
+    RuleExited { rule: &'static str, position: usize },
+    /// Синтаксический анализатор взял лексему
+    TokenConsumed { span: Span, token: Token },
+    /// Разбор по таблице LL(1) ([`ParserKind::Table`](crate::config::ParserKind::Table))
+    /// обратился к клетке таблицы. Такие события идут после всех лексем и до шагов
+    /// рекурсивного спуска, который строит дерево разбора
+    TableConsulted(TableStep),
+    /// Найдена ошибка или нарушено правило со строгостью "предупреждение".
+    /// Диагностики приходят в порядке позиций после разбора и проверки строки.
+    DiagnosticEmitted(Diagnostic),
+    /// Анализ завершён; это событие последнее
+    Finished { accepted: bool },
+}
+
+/// Получатель событий анализа.
+pub trait EventSink {
+    fn event(&mut self, event: Event);
+}
+
+impl<F: FnMut(Event)> EventSink for F {
+    fn event(&mut self, event: Event) {
+        self(event)
+    }
+}
+
+/// Трассировка рекурсивного спуска: входы в нетерминалы, выходы из них и взятые
+/// лексемы по порядку - для пошагового просмотра и отладки грамматики. Остальные
+/// события в неё не попадают.
+///
+/// ```
+/// use taafl::analyzer::analyze_with_events;
+/// use taafl::analyzer::events::Trace;
+///
+/// let mut trace = Trace::default();
+/// analyze_with_events("A := B;", &Default::default(), &mut trace);
+/// let lines = trace.lines();
+/// assert_eq!(lines[1], "  вход в left на 0");
+/// assert_eq!(lines[2], r#"    взята Identifier("A") на 0..1"#);
+/// assert_eq!(lines.last().unwrap(), "выход из statement на 7");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    pub steps: Vec<Event>,
+}
+
+impl EventSink for Trace {
+    fn event(&mut self, event: Event) {
+        if matches!(
+            event,
+            Event::RuleEntered { .. } | Event::RuleExited { .. } | Event::TokenConsumed { .. }
+        ) {
+            self.steps.push(event);
+        }
+    }
+}
+
+impl Trace {
+    /// Шаги по строке на шаг; вложенные нетерминалы - с отступом.
+    pub fn lines(&self) -> Vec<String> {
+        let mut depth = 0usize;
+        let mut lines = Vec::new();
+        for step in &self.steps {
+            if let Event::RuleExited { .. } = step {
+                depth = depth.saturating_sub(1);
+            }
+            let indent = "  ".repeat(depth);
+            lines.push(match step {
+                Event::RuleEntered { rule, position } => {
+                    depth = depth.saturating_add(1);
+                    format!("{}вход в {} на {}", indent, rule, position)
+                }
+                Event::RuleExited { rule, position } => {
+                    format!("{}выход из {} на {}", indent, rule, position)
+                }
+                Event::TokenConsumed { span, token } => {
+                    format!(
+                        "{}взята {:?} на {}..{}",
+                        indent, token, span.start, span.end
+                    )
+                }
+                _ => continue,
+            });
+        }
+        lines
+    }
+}
+
+/// Получатель событий, общий для стадий анализа одной строки; без получателя
+/// события не создаются.
+#[derive(Clone, Copy, Default)]
+pub(super) struct Events<'a>(Option<&'a RefCell<&'a mut dyn EventSink>>);
+
+impl<'a> Events<'a> {
+    pub(super) fn new(sink: &'a RefCell<&'a mut dyn EventSink>) -> Self {
+        Events(Some(sink))
+    }
+
+    pub(super) fn emit(self, event: impl FnOnce() -> Event) {
+        if let Some(sink) = self.0 {
+            sink.borrow_mut().event(event());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn events_follow_tokens_and_rules_in_order() {
+        use crate::analyzer::analyze_with_events;
+        use crate::analyzer::events::Event;
+        use crate::config::{AnalyzerConfig, ParserKind};
+
+        let collect = |config: &AnalyzerConfig| {
+            let mut events = Vec::new();
+            analyze_with_events("A[I] := 1;", config, &mut |event| events.push(event));
+            // Выходы из нетерминалов и взятые лексемы проверяет трассировка
+            events
+                .into_iter()
+                .filter_map(|event| match event {
+                    Event::TokenProduced { token, .. } => Some(token.terminal().to_string()),
+                    Event::RuleEntered { rule, .. } => Some(format!("<{}>", rule)),
+                    Event::TableConsulted(step) => {
+                        Some(format!("{}/{}", step.nonterminal, step.terminal))
+                    }
+                    Event::DiagnosticEmitted(diagnostic) => Some(diagnostic.code.to_string()),
+                    Event::Finished { accepted } => Some(format!("accepted={}", accepted)),
+                    Event::RuleExited { .. } | Event::TokenConsumed { .. } => None,
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let mut config = AnalyzerConfig::default();
+        assert_eq!(
+            collect(&config),
+            "<statement> <left> IDENT [ IDENT <index_list> <index> <index_term> ] := <right> <term> \
+             CONST ; accepted=true"
+        );
+
+        // Таблице LL(1) лексемы нужны до разбора
+        config.parser = ParserKind::Table;
+        assert_eq!(
+            collect(&config),
+            "IDENT [ IDENT ] := CONST ; statement/IDENT left/IDENT field/[ left_tail/[ \
+             index_list/IDENT index/IDENT index_term/IDENT field/] index_tail/] index_rest/] \
+             field/:= left_tail/:= right/CONST term/CONST right_rest/; <statement> <left> <index_list> <index> <index_term> <right> <term> \
+             accepted=true"
+        );
+    }
+
+    #[test]
+    fn trace_balances_rules_around_consumed_tokens() {
+        use crate::analyzer::events::Event;
+        use crate::analyzer::Analyzer;
+
+        let analyzer = Analyzer::builder().collect_trace(true).build();
+        let trace = analyzer.analyze("A[I] := F(1);").trace.unwrap();
+        let lines = trace.lines();
+        assert_eq!(
+            lines[..6],
+            [
+                "вход в statement на 0",
+                "  вход в left на 0",
+                r#"    взята Identifier("A") на 0..1"#,
+                "    взята LSquare на 1..2",
+                "    вход в index_list на 2",
+                "      вход в index на 2",
+            ]
+        );
+        assert!(lines.contains(&"      вход в arguments на 10".to_string()));
+        assert!(lines.contains(&"    выход из index_list на 3".to_string()));
+
+        // Каждый вход закрыт выходом, взяты все лексемы
+        let counts = |steps: &[Event]| {
+            let count = |f: fn(&Event) -> bool| steps.iter().filter(|s| f(s)).count();
+            (
+                count(|s| matches!(s, Event::RuleEntered { .. })),
+                count(|s| matches!(s, Event::RuleExited { .. })),
+                count(|s| matches!(s, Event::TokenConsumed { .. })),
+            )
+        };
+        let (enters, exits, tokens) = counts(&trace.steps);
+        assert_eq!((enters, tokens), (exits, 10));
+
+        // И при синтаксической ошибке; без запроса трассировки нет
+        let trace = analyzer.analyze("A[I := 1;").trace.unwrap();
+        let (enters, exits, _) = counts(&trace.steps);
+        assert_eq!(enters, exits);
+        assert!(Analyzer::default().analyze("A := 1;").trace.is_none());
+    }
+}
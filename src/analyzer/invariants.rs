@@ -0,0 +1,323 @@
+//! Проверка согласованности результатов анализа. Выполняется после каждого анализа:
+//! в отладочной сборке и в тестах нарушение - паника, в выпускной - запись в журнал.
+//!
+//! Инварианты:
+//! - отрезки ошибок и позиции символов лежат внутри строки и на границах символов;
+//! - текст каждого промежутка между лексемами совпадает с отрезком строки;
+//! - роль каждого вхождения символа совпадает с местом узла в дереве разбора;
+//! - массив из левой части не попадает в идентификаторы правой части, ни в имена,
+//!   ни в массивы, ни в функции, если правило [`Rule::ArrayInExpression`] - ошибка;
+//! - ошибки, предупреждения и диагностики упорядочены по началу отрезка.
+
+use super::{Checker, Occurrences, Outcome, Span};
+use crate::ast;
+use crate::config::Rule;
+use crate::diagnostic::Diagnostic;
+use crate::model;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// Сообщает о нарушенном инварианте.
+#[allow(
+    clippy::panic,
+    reason = "нарушение ловят в тестах, в выпускной сборке - только журнал"
+)]
+fn violated(input: &str, what: &str) {
+    if cfg!(debug_assertions) {
+        panic!("нарушен инвариант анализа {:?}: {}", input, what);
+    }
+    tracing::error!(input, "нарушен инвариант анализа: {}", what);
+}
+
+fn within(input: &str, span: &Span) -> bool {
+    span.start <= span.end
+        && span.end <= input.len()
+        && input.is_char_boundary(span.start)
+        && input.is_char_boundary(span.end)
+}
+
+fn sorted(starts: impl Iterator<Item = usize>) -> bool {
+    starts.is_sorted()
+}
+
+/// Итог анализа: отрезки ошибок, роли символов и разделение массивов и выражений.
+pub(super) fn check_outcome(input: &str, outcome: &Outcome) {
+    for error in outcome.errors.iter().chain(&outcome.warnings) {
+        if !within(input, &error.span()) {
+            violated(
+                input,
+                &format!("отрезок ошибки {:?} вне строки", error.span()),
+            );
+        }
+    }
+    if let (Some(checker), Some(assignment)) = (&outcome.checker, &outcome.ast) {
+        check_roles(input, checker, assignment);
+    }
+}
+
+/// Вхождения каждой роли - подмножество узлов дерева в этой роли: при остановке
+/// на ошибке часть узлов остаётся не записанной.
+fn check_roles(input: &str, checker: &Checker, assignment: &ast::Assignment) {
+    let mut array = HashSet::new();
+    let mut expr_array = HashSet::new();
+    let mut functions = HashSet::new();
+    let mut fields = HashSet::new();
+    let mut index = Nodes::default();
+    let mut expr = Nodes::default();
+    let target = &assignment.target;
+    match &target.indices {
+        Some(indices) => {
+            array.insert((target.name.clone(), target.position));
+            insert_indices(&mut index, indices);
+            for access in &target.chain {
+                match access {
+                    ast::Access::Field { name, position } => {
+                        fields.insert((name.clone(), *position));
+                    }
+                    ast::Access::Indices(indices) => insert_indices(&mut index, indices),
+                }
+            }
+        }
+        None => {
+            expr.identifiers
+                .insert((target.name.clone(), target.position));
+        }
+    }
+    insert_right(
+        &assignment.value,
+        &mut expr,
+        &mut expr_array,
+        &mut functions,
+        &mut index,
+    );
+
+    let roles = [
+        ("массив", recorded(&checker.ids_array, &array)),
+        (
+            "массив правой части",
+            recorded(&checker.ids_expr_array, &expr_array),
+        ),
+        (
+            "идентификатор-функция",
+            recorded(&checker.ids_function, &functions),
+        ),
+        ("идентификатор-поле", recorded(&checker.ids_field, &fields)),
+        (
+            "идентификатор-индекс",
+            recorded(&checker.ids_index, &index.identifiers),
+        ),
+        (
+            "идентификатор-выражение",
+            recorded(&checker.ids_expr, &expr.identifiers),
+        ),
+        (
+            "константа-индекс",
+            recorded(&checker.const_index, &index.constants),
+        ),
+        (
+            "константа-выражение",
+            recorded(&checker.const_expr, &expr.constants),
+        ),
+        (
+            "вещественная константа-индекс",
+            recorded(&checker.real_index, &index.reals),
+        ),
+        (
+            "вещественная константа-выражение",
+            recorded(&checker.real_expr, &expr.reals),
+        ),
+        (
+            "символьная или строковая константа-выражение",
+            recorded(&checker.literal_expr, &expr.literals),
+        ),
+    ];
+    for (role, consistent) in roles {
+        if !consistent {
+            violated(
+                input,
+                &format!("вхождения роли '{}' не совпадают с деревом", role),
+            );
+        }
+    }
+
+    if checker.is_error(Rule::ArrayInExpression)
+        && checker.ids_array.keys().any(|name| {
+            checker.ids_expr.contains_key(name)
+                || checker.ids_expr_array.contains_key(name)
+                || checker.ids_function.contains_key(name)
+        })
+    {
+        violated(
+            input,
+            "массив из левой части - среди идентификаторов правой части",
+        );
+    }
+}
+
+/// Символы с позициями узлов дерева в одной роли: индексе или выражении.
+#[derive(Default)]
+struct Nodes {
+    identifiers: HashSet<(String, usize)>,
+    constants: HashSet<(i32, usize)>,
+    reals: HashSet<(ast::Real, usize)>,
+    literals: HashSet<(String, usize)>,
+}
+
+/// Узлы выражения правой части: элементы массивов, вызовы и, рекурсивно,
+/// их аргументы.
+fn insert_right(
+    value: &ast::Expression,
+    expr: &mut Nodes,
+    expr_array: &mut HashSet<(String, usize)>,
+    functions: &mut HashSet<(String, usize)>,
+    index: &mut Nodes,
+) {
+    for term in value.terms() {
+        match &term.operand {
+            ast::Operand::Element {
+                name,
+                position,
+                indices,
+                ..
+            } => {
+                expr_array.insert((name.clone(), *position));
+                insert_indices(index, indices);
+            }
+            ast::Operand::Call {
+                name,
+                position,
+                arguments,
+                ..
+            } => {
+                functions.insert((name.clone(), *position));
+                for argument in arguments {
+                    insert_right(argument, expr, expr_array, functions, index);
+                }
+            }
+            _ => insert_term(expr, term),
+        }
+    }
+}
+
+fn insert_indices(nodes: &mut Nodes, indices: &[ast::Expression]) {
+    for term in indices.iter().flat_map(ast::Expression::terms) {
+        insert_term(nodes, term);
+    }
+}
+
+fn insert_term(nodes: &mut Nodes, term: &ast::Term) {
+    match (&term.operand, term.constant()) {
+        // Константа со знаком записана со значением вместе со знаком
+        (ast::Operand::Constant { position, .. }, Some(value)) => {
+            nodes.constants.insert((value, *position));
+        }
+        (operand, _) => insert(nodes, operand),
+    }
+}
+
+fn insert(nodes: &mut Nodes, operand: &ast::Operand) {
+    match operand {
+        ast::Operand::Identifier { name, position, .. } => {
+            nodes.identifiers.insert((name.clone(), *position));
+        }
+        ast::Operand::Constant { value, position } => {
+            nodes.constants.insert((*value, *position));
+        }
+        ast::Operand::Real { value, position } => {
+            nodes.reals.insert((*value, *position));
+        }
+        ast::Operand::Literal { value, position } => {
+            nodes.literals.insert((value.clone(), *position));
+        }
+        // Элементы массивов и вызовы есть только в правой части, их разбирает insert_right
+        ast::Operand::Element { .. } | ast::Operand::Call { .. } => {}
+    }
+}
+
+fn recorded<T: Hash + Eq + Clone>(
+    occurrences: &Occurrences<T>,
+    nodes: &HashSet<(T, usize)>,
+) -> bool {
+    occurrences.iter().all(|(symbol, positions)| {
+        positions
+            .iter()
+            .all(|&position| nodes.contains(&(symbol.clone(), position)))
+    })
+}
+
+/// Отчёт: позиции символов внутри строки, ошибки и предупреждения по порядку.
+pub(super) fn check_report(report: &model::AnalysisReport) {
+    let input = report.input.as_str();
+    let positions = report
+        .identifiers
+        .iter()
+        .flat_map(|id| &id.positions)
+        .chain(report.constants.iter().flat_map(|c| &c.positions))
+        .chain(report.reals.iter().flat_map(|r| &r.positions))
+        .chain(report.literals.iter().flat_map(|l| &l.positions));
+    for &position in positions {
+        if position >= input.len() || !input.is_char_boundary(position) {
+            violated(input, &format!("позиция символа {} вне строки", position));
+        }
+    }
+    for gap in &report.gaps {
+        if input.get(gap.position..gap.end) != Some(gap.text.as_str()) {
+            violated(
+                input,
+                &format!("промежуток {:?} не совпадает со строкой", gap),
+            );
+        }
+    }
+    for (kind, entries) in [
+        ("ошибки", &report.errors),
+        ("предупреждения", &report.warnings),
+    ] {
+        if !sorted(entries.iter().map(|entry| entry.position)) {
+            violated(input, &format!("{} не упорядочены по позициям", kind));
+        }
+    }
+    if report.accepted != report.errors.is_empty() {
+        violated(input, "строка принята, но в отчёте есть ошибки");
+    }
+}
+
+/// Диагностики: внутри строки и по порядку.
+pub(super) fn check_diagnostics(input: &str, diagnostics: &[Diagnostic]) {
+    if !sorted(diagnostics.iter().map(|d| d.span.start)) {
+        violated(input, "диагностики не упорядочены по отрезкам");
+    }
+    // Пустой отрезок в конце строки расширяется на один байт за её конец
+    let end = input.len().saturating_add(1);
+    if let Some(d) = diagnostics.iter().find(|d| d.span.end > end) {
+        violated(
+            input,
+            &format!("отрезок диагностики {:?} вне строки", d.span),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::Analyzer;
+    use crate::testing::{forall, Cases, CASES};
+
+    /// Нарушение инварианта анализа в отладочной сборке - паника, поэтому
+    /// достаточно проанализировать мутанты во всех профилях всеми тремя парсерами.
+    #[test]
+    fn results_keep_invariants_in_every_profile() {
+        use crate::config::{AnalyzerConfig, ParserKind, Profile};
+
+        for profile in Profile::ALL {
+            for parser in [ParserKind::Descent, ParserKind::Table, ParserKind::Earley] {
+                let mut config = AnalyzerConfig::from_profile(profile);
+                config.parser = parser;
+                let analyzer = Analyzer::builder().config(config).collect_ast(true).build();
+                let result = forall(CASES, 8, Cases::mutant, |(text, _)| {
+                    analyzer.analyze(text);
+                    true
+                });
+                assert_eq!(result, Ok(()));
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Правило [`Rule::Limits`]: размер оператора по ограничениям
+//! [`AnalyzerConfig::limits`](crate::config::AnalyzerConfig::limits). Проверяются
+//! лексемы, поэтому ошибка указывает на лишнюю скобку или на лишние слагаемые
+//! и индексы вместе с разделителем перед ними: `- C`, `, K`.
+
+use super::{bracket_tokens, terminator_token, Checker, Error, Span, Token};
+use crate::config::{Brackets, Rule};
+
+/// Открытая скобка индексов или вызова.
+struct Group {
+    /// Скобка индексов: запятые в ней разделяют индексы, а не аргументы
+    indices: bool,
+    /// Сколько индексов начато
+    count: usize,
+    /// Начало индексов сверх ограничения
+    excess: Option<usize>,
+}
+
+impl Checker<'_> {
+    /// Проверяет размер оператора с лексемами `tokens`.
+    pub(super) fn check_limits(&mut self, tokens: &[(Span, Token)]) -> Result<(), Error> {
+        let config = self.reporter.config;
+        let limits = config.limits;
+        let (open, close) = bracket_tokens(config.brackets);
+        // В круглых скобках индексов вызовов нет
+        let calls = config.brackets != Brackets::Round;
+        let terminator = terminator_token(config.terminator);
+
+        let mut groups: Vec<Group> = Vec::new();
+        // Правая часть: после присваивания вне скобок
+        let mut right = false;
+        // Последняя лексема правой части вне скобок завершает операнд
+        let mut operand = false;
+        let mut operations = 0usize;
+        let mut excess_terms = None;
+        // Конец последней лексемы перед текущей
+        let mut end = 0;
+
+        for (span, token) in tokens {
+            if *token == open || (calls && *token == Token::LParen) {
+                groups.push(Group {
+                    indices: *token == open,
+                    count: 1,
+                    excess: None,
+                });
+                if limits.max_nesting > 0 && groups.len() == limits.max_nesting.saturating_add(1) {
+                    let message = format!("Вложенность скобок больше {}", limits.max_nesting);
+                    self.violation(Rule::Limits, span.clone(), message)?;
+                }
+            } else if *token == close || (calls && *token == Token::RParen) {
+                if let Some(Group {
+                    excess: Some(start),
+                    ..
+                }) = groups.pop()
+                {
+                    let message = format!("Индексов в списке больше {}", limits.max_indices);
+                    self.violation(Rule::Limits, start..end, message)?;
+                }
+            } else if let Some(group) = groups.last_mut() {
+                if group.indices && *token == Token::Comma {
+                    group.count = group.count.saturating_add(1);
+                    let excess = limits.max_indices.saturating_add(1);
+                    if limits.max_indices > 0 && group.count == excess {
+                        group.excess = Some(span.start);
+                    }
+                }
+            } else if *token == Token::Assign {
+                right = true;
+            } else if right && Some(token) == terminator.as_ref() {
+                break;
+            } else if right {
+                match token {
+                    Token::Operation(_) if operand => {
+                        operand = false;
+                        operations = operations.saturating_add(1);
+                        if limits.max_terms > 0 && operations == limits.max_terms {
+                            excess_terms = Some(span.start);
+                        }
+                    }
+                    Token::Identifier(_)
+                    | Token::Constant(..)
+                    | Token::Real(_)
+                    | Token::Literal(_) => operand = true,
+                    _ => {}
+                }
+            }
+            end = span.end;
+        }
+
+        if let Some(start) = excess_terms {
+            let message = format!("Слагаемых в правой части больше {}", limits.max_terms);
+            self.violation(Rule::Limits, start..end, message)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn limits_bound_terms_indices_and_nesting() {
+        use crate::analyzer::diagnose;
+        use crate::config::{AnalyzerConfig, FileDirective};
+
+        let large = "A[I, J, K] := F(B[I, J]) + C + D - 1;";
+        assert!(diagnose(large, &AnalyzerConfig::default()).is_empty());
+
+        let mut config = AnalyzerConfig {
+            recovery: true,
+            ..AnalyzerConfig::default()
+        };
+        let directive = FileDirective::parse("(* taafl: max-indices=2; max-terms=3 *)").unwrap();
+        assert!(directive.apply(&mut config).is_empty());
+        assert_eq!(config.limits.max_indices, 2);
+        let diagnostics = diagnose(large, &config);
+        let spans: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.code, d.span.clone()))
+            .collect();
+        assert_eq!(spans, [("limits", 6..9), ("limits", 33..36)]);
+        assert!(diagnostics[0]
+            .message
+            .starts_with("Индексов в списке больше 2"));
+        assert!(diagnostics[1]
+            .message
+            .starts_with("Слагаемых в правой части больше 3"));
+
+        // Знак перед первым слагаемым и слагаемые аргументов не считаются
+        assert!(diagnose("X := -A + F(B + C + D + E) - 1;", &config).is_empty());
+        // Запятые между аргументами вызова - не индексы
+        assert!(diagnose("X := F(A, B, C);", &config).is_empty());
+
+        config.limits.max_nesting = 2;
+        let diagnostics = diagnose("X := F(G(A[I]));", &config);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span, 10..11);
+        assert!(diagnose("X := F(A[I]);", &config).is_empty());
+    }
+}
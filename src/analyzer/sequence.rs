@@ -0,0 +1,329 @@
+//! Последовательность операторов в одном тексте: каждый оператор анализируется
+//! отдельно, как в [`analyze_many_with`](super::analyze_many_with), а символы всех
+//! операторов сводятся в одну таблицу. Символ в одной роли занимает в ней одну
+//! строку с вхождениями из всех операторов; позиции отсчитываются от начала текста.
+//!
+//! ```
+//! use taafl::analyzer::sequence::analyze_sequence;
+//! use taafl::model::IdentifierRole;
+//!
+//! let report = analyze_sequence("A := B; C[A] := B + 1;", &Default::default());
+//! assert!(report.accepted);
+//! assert_eq!(report.statements[1].offset, 8);
+//! let b = report.identifiers.iter().find(|id| id.name == "B").unwrap();
+//! assert_eq!((b.role, b.occurrences), (IdentifierRole::Expression, 2));
+//! assert_eq!(b.positions, [5, 16]);
+//! ```
+
+use super::{analyze_report, split_statements_with, symbol_lists};
+use crate::ast;
+use crate::config::{Allow, AnalyzerConfig, SymbolOrder};
+use crate::model::{
+    self, Constant, ConstantRole, Identifier, IdentifierRole, LiteralConstant, Origin,
+    RealConstant, SequenceReport,
+};
+use crate::variant::{Variant, Variant20};
+
+/// Роли идентификаторов в порядке таблицы отчёта оператора.
+const IDENTIFIER_ROLES: [IdentifierRole; 6] = [
+    IdentifierRole::Array,
+    IdentifierRole::Field,
+    IdentifierRole::ExpressionArray,
+    IdentifierRole::Function,
+    IdentifierRole::Index,
+    IdentifierRole::Expression,
+];
+
+/// Роли констант в порядке таблицы отчёта оператора.
+const CONSTANT_ROLES: [ConstantRole; 6] = [
+    ConstantRole::Index,
+    ConstantRole::Expression,
+    ConstantRole::RealIndex,
+    ConstantRole::RealExpression,
+    ConstantRole::CharacterExpression,
+    ConstantRole::StringExpression,
+];
+
+fn rank<T: PartialEq>(roles: &[T], role: &T) -> usize {
+    roles.iter().position(|r| r == role).unwrap_or(roles.len())
+}
+
+/// Записи символа, число вхождений, их позиции и происхождение.
+type Occurrences<'a> = (
+    &'a mut Vec<String>,
+    &'a mut usize,
+    &'a mut Vec<usize>,
+    &'a mut Vec<Origin>,
+);
+
+/// Строка таблицы символов: вхождения одного символа в одной роли.
+trait Entry {
+    type Symbol: Ord;
+
+    /// Место роли в таблице и символ: по ним строки совпадают и упорядочиваются.
+    fn key(&self) -> (usize, Self::Symbol);
+
+    fn occurrences(&mut self) -> Occurrences<'_>;
+}
+
+impl Entry for Identifier {
+    type Symbol = String;
+
+    fn key(&self) -> (usize, String) {
+        (rank(&IDENTIFIER_ROLES, &self.role), self.name.clone())
+    }
+
+    fn occurrences(&mut self) -> Occurrences<'_> {
+        let Self {
+            lexemes,
+            occurrences,
+            positions,
+            origins,
+            ..
+        } = self;
+        (lexemes, occurrences, positions, origins)
+    }
+}
+
+impl Entry for Constant {
+    type Symbol = i32;
+
+    fn key(&self) -> (usize, i32) {
+        (rank(&CONSTANT_ROLES, &self.role), self.value)
+    }
+
+    fn occurrences(&mut self) -> Occurrences<'_> {
+        let Self {
+            lexemes,
+            occurrences,
+            positions,
+            origins,
+            ..
+        } = self;
+        (lexemes, occurrences, positions, origins)
+    }
+}
+
+impl Entry for RealConstant {
+    type Symbol = ast::Real;
+
+    fn key(&self) -> (usize, ast::Real) {
+        (rank(&CONSTANT_ROLES, &self.role), ast::Real(self.value))
+    }
+
+    fn occurrences(&mut self) -> Occurrences<'_> {
+        let Self {
+            lexemes,
+            occurrences,
+            positions,
+            origins,
+            ..
+        } = self;
+        (lexemes, occurrences, positions, origins)
+    }
+}
+
+impl Entry for LiteralConstant {
+    type Symbol = String;
+
+    fn key(&self) -> (usize, String) {
+        (rank(&CONSTANT_ROLES, &self.role), self.value.clone())
+    }
+
+    fn occurrences(&mut self) -> Occurrences<'_> {
+        let Self {
+            lexemes,
+            occurrences,
+            positions,
+            origins,
+            ..
+        } = self;
+        (lexemes, occurrences, positions, origins)
+    }
+}
+
+/// Добавляет к сводной таблице `merged` строки оператора со смещением `offset`.
+fn merge<T: Entry>(merged: &mut Vec<T>, entries: Vec<T>, offset: usize) {
+    for mut entry in entries {
+        let (_, _, positions, origins) = entry.occurrences();
+        positions
+            .iter_mut()
+            .for_each(|position| *position = position.saturating_add(offset));
+        origins
+            .iter_mut()
+            .for_each(|origin| origin.position = origin.position.saturating_add(offset));
+
+        let key = entry.key();
+        let Some(row) = merged.iter_mut().find(|row| row.key() == key) else {
+            merged.push(entry);
+            continue;
+        };
+        let (lexemes, occurrences, positions, origins) = entry.occurrences();
+        let (row_lexemes, row_occurrences, row_positions, row_origins) = row.occurrences();
+        for lexeme in lexemes.drain(..) {
+            if !row_lexemes.contains(&lexeme) {
+                row_lexemes.push(lexeme);
+            }
+        }
+        *row_occurrences = row_occurrences.saturating_add(*occurrences);
+        row_positions.append(positions);
+        row_origins.append(origins);
+    }
+}
+
+/// Упорядочивает сводную таблицу так же, как таблицу оператора: по ролям, внутри
+/// роли - по первому появлению или по символу.
+fn arrange<T: Entry>(rows: &mut [T], order: SymbolOrder) {
+    match order {
+        SymbolOrder::Appearance => rows.sort_by_key(|row| row.key().0),
+        SymbolOrder::Sorted => rows.sort_by_key(Entry::key),
+    }
+}
+
+/// Разбивает `input` на операторы по завершающему символу, анализирует каждый
+/// с учётом его комментариев-разрешений и сводит их символы в одну таблицу.
+pub fn analyze_sequence(input: &str, config: &AnalyzerConfig) -> SequenceReport {
+    let mut report = SequenceReport {
+        schema_version: model::SCHEMA_VERSION,
+        input: input.to_string(),
+        source: None,
+        accepted: true,
+        statements: Vec::new(),
+        identifiers: Vec::new(),
+        constants: Vec::new(),
+        reals: Vec::new(),
+        literals: Vec::new(),
+    };
+    for (offset, text) in split_statements_with(input, config) {
+        let (allow, text) = Allow::extract(text);
+        let mut allowed = config.clone();
+        allow.apply(&mut allowed);
+        let statement = model::AnalysisReport {
+            offset,
+            ..analyze_report(&text, &allowed)
+        };
+        report.accepted &= statement.accepted;
+        merge(
+            &mut report.identifiers,
+            statement.identifiers.clone(),
+            offset,
+        );
+        merge(&mut report.constants, statement.constants.clone(), offset);
+        merge(&mut report.reals, statement.reals.clone(), offset);
+        merge(&mut report.literals, statement.literals.clone(), offset);
+        report.statements.push(statement);
+    }
+    arrange(&mut report.identifiers, config.order);
+    arrange(&mut report.constants, config.order);
+    arrange(&mut report.reals, config.order);
+    arrange(&mut report.literals, config.order);
+    report
+}
+
+/// Сводные списки идентификаторов и констант последовательности для текстового
+/// вывода, как у одного оператора, с диапазоном констант из `config`; `None`,
+/// если символов нет.
+pub fn sequence_lists(
+    report: &SequenceReport,
+    config: &AnalyzerConfig,
+) -> (Option<String>, Option<String>) {
+    let symbols = (
+        report.identifiers.clone(),
+        report.constants.clone(),
+        report.reals.clone(),
+        report.literals.clone(),
+    );
+    let range = config.constant_range(Variant20.constant_range());
+    symbol_lists(
+        &report.input,
+        &symbols,
+        &Variant20,
+        Some(&range),
+        config.preserve_case,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn sequence_merges_symbols_of_all_statements() {
+        use crate::analyzer::sequence::analyze_sequence;
+        use crate::config::{AnalyzerConfig, SymbolOrder};
+        use crate::model::{ConstantRole, IdentifierRole};
+
+        let input = "X := b + 1;\n(* taafl:allow(constant-range) *) Y[B] := 07 + 0;\nZ := ;";
+        let mut config = AnalyzerConfig::default();
+        let report = analyze_sequence(input, &config);
+        assert!(!report.accepted);
+        let accepted: Vec<_> = report.statements.iter().map(|s| s.accepted).collect();
+        assert_eq!(accepted, [true, true, false]);
+        assert_eq!(report.statements[1].offset, 12);
+
+        let roles: Vec<_> = report
+            .identifiers
+            .iter()
+            .map(|id| (id.name.as_str(), id.role, id.positions.clone()))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                ("Y", IdentifierRole::Array, vec![46]),
+                ("B", IdentifierRole::Index, vec![48]),
+                ("X", IdentifierRole::Expression, vec![0]),
+                ("B", IdentifierRole::Expression, vec![5]),
+            ]
+        );
+        assert_eq!(report.identifiers[1].lexemes, ["B"]);
+        let origin = &report.identifiers[1].origins[0];
+        assert_eq!(origin.position, 48);
+
+        let constants: Vec<_> = report
+            .constants
+            .iter()
+            .map(|c| (c.value, c.role, c.occurrences, c.lexemes.clone()))
+            .collect();
+        assert_eq!(
+            constants,
+            [
+                (1, ConstantRole::Expression, 1, vec!["1".to_string()]),
+                (7, ConstantRole::Expression, 1, vec!["07".to_string()]),
+                (0, ConstantRole::Expression, 1, vec!["0".to_string()]),
+            ]
+        );
+
+        // Одна строка на символ в роли: вхождения из разных операторов вместе
+        config.order = SymbolOrder::Sorted;
+        let report = analyze_sequence("A := B + 2; C := B + 2;", &config);
+        let b = &report.identifiers[1];
+        assert_eq!((b.name.as_str(), b.occurrences), ("B", 2));
+        assert_eq!(b.positions, [5, 17]);
+        assert_eq!(report.constants[0].positions, [9, 21]);
+        let names: Vec<_> = report
+            .identifiers
+            .iter()
+            .map(|id| id.name.as_str())
+            .collect();
+        assert_eq!(names, ["A", "B", "C"]);
+    }
+
+    #[test]
+    fn sequence_lists_show_range_bounds() {
+        use crate::analyzer::sequence::{analyze_sequence, sequence_lists};
+        use crate::config::{AnalyzerConfig, ConstantRange};
+
+        let mut config = AnalyzerConfig::default();
+        let report = analyze_sequence(
+            "A := 1; (* taafl:allow(constant-range) *) B := 40000;",
+            &config,
+        );
+        let (_, consts) = sequence_lists(&report, &config);
+        let consts = consts.unwrap();
+        assert!(consts.contains("запись 1, десятичная, в диапазоне [1..32767]\n"));
+        assert!(consts.contains("вне диапазона [1..32767]: больше максимума"));
+
+        config.constants = ConstantRange::Custom { min: -5, max: 5 };
+        let report = analyze_sequence("A := 1;", &config);
+        let (_, consts) = sequence_lists(&report, &config);
+        assert!(consts.unwrap().contains("в диапазоне [-5..5]"));
+    }
+}
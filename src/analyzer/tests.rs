@@ -0,0 +1,1353 @@
+//! Проверки анализатора: диагностики частых ошибок, роли символов, настройки
+//! варианта и свойства на случайных правильных и искажённых операторах.
+
+// Отрезки в проверках берутся из диагностик: паника на них - провал теста
+#![allow(clippy::string_slice)]
+
+use super::Analyzer;
+use crate::testing::{forall, Cases, CASES};
+
+/// Частые ошибки: оператор, код диагностики, её отрезок и начало сообщения.
+const CORPUS: &[(&str, &str, std::ops::Range<usize>, &str)] = &[
+    ("A[] := 1;", "syntax", 1..3, "Пустой список индексов"),
+    ("A[ ] := 1;", "syntax", 1..4, "Пустой список индексов"),
+    (
+        "A := B ++ C;",
+        "operand-order",
+        7..9,
+        "Две операции подряд: '+' и '+'",
+    ),
+    (
+        "A := B + * C;",
+        "operand-order",
+        7..10,
+        "Две операции подряд: '+' и '*'",
+    ),
+    (
+        "A := B C;",
+        "operand-order",
+        5..8,
+        "Два операнда подряд: 'B' и 'C'",
+    ),
+    (
+        "A := 1 + 2 3;",
+        "operand-order",
+        9..12,
+        "Два операнда подряд: '2' и '3'",
+    ),
+    ("A := (* B;", "lexical", 5..7, "Комментарий не закрыт"),
+    (
+        "Div := 1;",
+        "syntax",
+        0..3,
+        "Ожидался идентификатор: DIV - ключевое слово",
+    ),
+    (
+        "X := NOT Or;",
+        "syntax",
+        9..11,
+        "Ожидался идентификатор или константа в правой части: OR - ключевое слово",
+    ),
+    (
+        "X := 'AB;",
+        "lexical",
+        5..6,
+        "Литерал не закрыт: нет парной кавычки '",
+    ),
+    (
+        "A['I'] := 1;",
+        "syntax",
+        2..5,
+        "Ожидался идентификатор или константа в индексе: литерал",
+    ),
+];
+
+#[test]
+fn mutants_never_panic_and_rejections_have_errors() {
+    let analyzer = Analyzer::default();
+    let result = forall(CASES, 3, Cases::mutant, |(text, _)| {
+        let analysis = analyzer.analyze(text);
+        analysis.report.accepted || !analysis.diagnostics.is_empty()
+    });
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn corpus_gets_targeted_diagnostics() {
+    for (text, code, span, message) in CORPUS {
+        let diagnostics = crate::analyzer::diagnose(text, &Default::default());
+        let first = &diagnostics[0];
+        assert_eq!((first.code, &first.span), (*code, span), "{}", text);
+        assert!(
+            first.message.starts_with(message),
+            "{}: {}",
+            text,
+            first.message
+        );
+    }
+}
+
+/// Символы без позиций: имя или значение, роль и число вхождений.
+fn symbol_table(text: &str) -> Vec<String> {
+    let report = Analyzer::default().analyze(text).report;
+    let identifiers = report
+        .identifiers
+        .iter()
+        .map(|id| format!("{} {:?} {}", id.name, id.role, id.occurrences));
+    let constants = report
+        .constants
+        .iter()
+        .map(|c| format!("{} {:?} {}", c.value, c.role, c.occurrences));
+    identifiers.chain(constants).collect()
+}
+
+#[test]
+fn real_constants_are_expressions_and_rejected_in_indices() {
+    use crate::config::AnalyzerConfig;
+    use crate::model::ConstantRole;
+
+    let config = AnalyzerConfig {
+        reals: true,
+        ..Default::default()
+    };
+    let analyzer = Analyzer::builder().config(config.clone()).build();
+    let report = analyzer.analyze("X := 2.5 + 1.0E5 * 25.0E-1;").report;
+    assert!(report.accepted);
+    let reals: Vec<_> = report.reals.iter().map(|r| (r.value, r.role)).collect();
+    assert_eq!(
+        reals,
+        [
+            (2.5, ConstantRole::RealExpression),
+            (100000.0, ConstantRole::RealExpression)
+        ]
+    );
+    assert_eq!(report.reals[0].lexemes, ["2.5", "25.0E-1"]);
+
+    let diagnostics = crate::analyzer::diagnose("A[1.5] := 1;", &config);
+    assert_eq!(
+        (diagnostics[0].code, &diagnostics[0].span),
+        ("real-index", &(2..5))
+    );
+    let diagnostics = crate::analyzer::diagnose("X := 3.14;", &Default::default());
+    assert_eq!(diagnostics[0].code, "syntax");
+}
+
+#[test]
+fn real_constant_lexeme_stops_before_dot_terminator() {
+    use crate::config::{AnalyzerConfig, Terminator};
+
+    let config = AnalyzerConfig {
+        reals: true,
+        terminator: Terminator::Dot,
+        ..Default::default()
+    };
+    let analyzer = Analyzer::builder().config(config.clone()).build();
+    let report = analyzer.analyze("A := 1.5.").report;
+    assert!(report.accepted);
+    assert_eq!(report.reals[0].lexemes, ["1.5"]);
+
+    let diagnostics = crate::analyzer::diagnose("A[2.5] := 1.", &config);
+    assert_eq!(
+        (diagnostics[0].code, &diagnostics[0].span),
+        ("real-index", &(2..5))
+    );
+}
+
+#[test]
+fn statement_splitters_keep_fractions_with_dot_terminator() {
+    use crate::analyzer::{analyze_many_with, analyze_reader_with};
+    use crate::config::{AnalyzerConfig, Terminator};
+
+    let config = AnalyzerConfig {
+        reals: true,
+        terminator: Terminator::Dot,
+        ..Default::default()
+    };
+    let input = "A := 1.5. B := 2. C := A1.";
+    let texts = ["A := 1.5.", "B := 2.", "C := A1."];
+    let many = analyze_many_with(input, &config);
+    let read: Vec<_> = analyze_reader_with(input.as_bytes(), config)
+        .map(Result::unwrap)
+        .collect();
+    for results in [many, read] {
+        let statements: Vec<_> = results.iter().map(|r| r.text.as_str()).collect();
+        assert_eq!(statements, texts);
+        assert!(results.iter().all(|r| r.result.is_ok()));
+    }
+}
+
+#[test]
+fn underscores_in_identifiers_count_toward_length() {
+    use crate::analyzer::diagnose;
+    use crate::config::AnalyzerConfig;
+
+    let config = AnalyzerConfig {
+        underscores: true,
+        ..Default::default()
+    };
+    let analyzer = Analyzer::builder().config(config.clone()).build();
+    let report = analyzer.analyze("MAX_LEN := A_1 + B_;").report;
+    assert!(report.accepted);
+    let names: Vec<_> = report
+        .identifiers
+        .iter()
+        .map(|id| id.name.as_str())
+        .collect();
+    assert_eq!(names, ["MAX_LEN", "A_1", "B_"]);
+
+    let diagnostics = diagnose("MAX_LEN_1 := 1;", &config);
+    assert_eq!(
+        (diagnostics[0].code, &diagnostics[0].span),
+        ("identifier-length", &(0..9))
+    );
+    for (input, span) in [("X := _A;", 5..6), ("X := 1_A;", 5..8)] {
+        assert_eq!(diagnose(input, &config)[0].span, span, "{:?}", input);
+    }
+
+    let diagnostics = diagnose("MAX_LEN := 1;", &Default::default());
+    assert_eq!(diagnostics[0].span, 3..4);
+    assert!(diagnostics[0].message.contains("underscores"));
+}
+
+#[test]
+fn unicode_identifiers_are_uppercased_and_counted_in_characters() {
+    use crate::analyzer::diagnose;
+    use crate::config::AnalyzerConfig;
+
+    let config = AnalyzerConfig {
+        unicode_identifiers: true,
+        ..Default::default()
+    };
+    let analyzer = Analyzer::builder().config(config.clone()).build();
+    let report = analyzer.analyze("масс[и] := Счётчик + straße;").report;
+    assert!(report.accepted);
+    let names: Vec<_> = report
+        .identifiers
+        .iter()
+        .map(|id| id.name.as_str())
+        .collect();
+    assert_eq!(names, ["МАСС", "И", "СЧЁТЧИК", "STRASSE"]);
+
+    // Восемь символов - шестнадцать байт - допустимы, девять - нет
+    assert!(diagnose("ПЕРЕМЕНН := 1;", &config).is_empty());
+    let diagnostics = diagnose("ПЕРЕМЕННАЯ := 1;", &config);
+    assert_eq!(
+        (diagnostics[0].code, &diagnostics[0].span),
+        ("identifier-length", &(0..20))
+    );
+
+    let diagnostics = diagnose("МАСС := 1;", &Default::default());
+    assert_eq!(diagnostics[0].span, 0..2);
+    assert!(diagnostics[0].message.contains("unicode"));
+}
+
+#[test]
+fn cursor_follows_tabs_and_wide_characters() {
+    use crate::analyzer::analyze_line;
+
+    let report = analyze_line("X :=\t1 +\t$;").unwrap_err();
+    let cursor = report.lines().nth(1).unwrap();
+    assert_eq!(cursor, format!("{}^", " ".repeat(16)));
+
+    // Полноширинная буква - две позиции, ошибочный символ охватывается целиком
+    let report = analyze_line("X := Ａ;").unwrap_err();
+    assert_eq!(report.lines().nth(1), Some("     ^^"));
+}
+
+#[test]
+fn reserved_words_are_rejected_as_names() {
+    use crate::analyzer::diagnose;
+    use crate::config::{AnalyzerConfig, Rule, Severity};
+
+    let diagnostics = diagnose("X[end] := Begin + MODULES;", &Default::default());
+    let rejected: Vec<_> = diagnostics
+        .iter()
+        .map(|d| (d.code, d.span.clone()))
+        .collect();
+    assert_eq!(rejected, [("reserved-word", 2..5)]);
+
+    let mut config = AnalyzerConfig {
+        recovery: true,
+        ..Default::default()
+    };
+    let diagnostics = diagnose("IF := ARRAY + BEGINS;", &config);
+    let spans: Vec<_> = diagnostics.iter().map(|d| d.span.clone()).collect();
+    assert_eq!(spans, [0..2, 6..11]);
+
+    config.set_severity(Rule::ReservedWord, Severity::Off);
+    assert!(diagnose("IF := ARRAY;", &config).is_empty());
+}
+
+#[test]
+fn recovery_skips_invalid_characters_and_keeps_lexing() {
+    use crate::analyzer::{derivation, diagnose};
+    use crate::config::{AnalyzerConfig, ParserKind};
+
+    let input = "X$ := A @ + 1 ?;";
+    let mut config = AnalyzerConfig::default();
+    let spans = |config: &AnalyzerConfig| -> Vec<_> {
+        diagnose(input, config)
+            .iter()
+            .map(|d| d.span.clone())
+            .collect()
+    };
+    // Без восстановления лексер останавливается на первом символе
+    assert_eq!(spans(&config).len(), 1);
+
+    config.recovery = true;
+    assert_eq!(spans(&config), [1..2, 8..9, 14..15]);
+    config.parser = ParserKind::Table;
+    assert_eq!(spans(&config), [1..2, 8..9, 14..15]);
+
+    // Разбор по-прежнему останавливается на синтаксической ошибке
+    let diagnostics = diagnose("X := A @ + ;", &config);
+    let codes: Vec<_> = diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(codes, ["syntax", "syntax"]);
+    assert!(derivation("X := A @ 1;", &config).is_err());
+}
+
+#[test]
+fn recovery_resumes_parsing_at_synchronization_tokens() {
+    use crate::analyzer::diagnose;
+    use crate::config::AnalyzerConfig;
+
+    let spans = |input: &str, config: &AnalyzerConfig| -> Vec<_> {
+        diagnose(input, config)
+            .iter()
+            .map(|d| (d.code, d.span.start))
+            .collect()
+    };
+    let mut config = AnalyzerConfig::default();
+    let input = "A[+, I J] := B + ;";
+    assert_eq!(spans(input, &config), [("syntax", 3)]);
+
+    // Индекс пропускается до ',', пара операндов - до ']', правая часть - до ';'
+    config.recovery = true;
+    assert_eq!(
+        spans(input, &config),
+        [("syntax", 3), ("operand-order", 5), ("syntax", 17)]
+    );
+    assert_eq!(
+        spans("A B := F(+, 1) + C[2 ;", &config),
+        [("syntax", 2), ("syntax", 10), ("syntax", 21)]
+    );
+    // Ошибка на месте предыдущей - её следствие
+    assert_eq!(spans("A[1 := 2;", &config), [("syntax", 4)]);
+    assert!(diagnose("A[I, 1] := F(B, 2);", &config).is_empty());
+}
+
+#[test]
+fn constant_range_follows_preset_and_zero() {
+    use crate::analyzer::diagnose;
+    use crate::config::{AnalyzerConfig, ConstantRange, FileDirective};
+
+    let codes = |input: &str, config: &AnalyzerConfig| -> Vec<_> {
+        diagnose(input, config).iter().map(|d| d.code).collect()
+    };
+    let mut config = AnalyzerConfig::default();
+    assert_eq!(codes("A := 40000;", &config), ["constant-range"]);
+    assert_eq!(codes("A := 0;", &config), ["constant-range"]);
+
+    config.constants = ConstantRange::Cardinal;
+    assert!(codes("A := 40000 + 0;", &config).is_empty());
+    assert_eq!(codes("A := 65536;", &config), ["constant-range"]);
+    // Подсказка называет действующий диапазон, а не диапазон варианта
+    config.hints = true;
+    let notes = &diagnose("A := 65536;", &config)[0].notes;
+    assert!(notes.iter().any(|n| n.contains("[0..65535]")), "{:?}", notes);
+    config.hints = false;
+
+    let directive = FileDirective::parse("(* taafl: constants=10..99; zero=on *)").unwrap();
+    assert!(directive.apply(&mut config).is_empty());
+    assert_eq!(config.constants, ConstantRange::Custom { min: 10, max: 99 });
+    assert!(codes("A := 10 + 99;", &config).is_empty());
+    // Ноль допускается только вместо нижней границы 1
+    assert_eq!(codes("A := 0;", &config), ["constant-range"]);
+
+    let text = toml::to_string_pretty(&config).unwrap();
+    assert_eq!(toml::from_str::<AnalyzerConfig>(&text).unwrap(), config);
+}
+
+#[test]
+fn file_directive_accepts_only_russian_language() {
+    use crate::config::{AnalyzerConfig, FileDirective, Profile};
+
+    let mut config = AnalyzerConfig::default();
+    let directive = FileDirective::parse("(* taafl: profile=extended; lang=RU *)").unwrap();
+    assert!(directive.apply(&mut config).is_empty());
+    assert_eq!(config.profile, Some(Profile::Extended));
+
+    let directive = FileDirective::parse("(* taafl: profile=extended; lang=en *)").unwrap();
+    assert_eq!(
+        directive.apply(&mut config),
+        ["Сообщения есть только на русском, lang=en пропущен"]
+    );
+}
+
+#[test]
+fn typographic_characters_get_a_targeted_diagnostic_or_are_replaced() {
+    use crate::analyzer::diagnose;
+    use crate::config::{AnalyzerConfig, FileDirective};
+    use crate::sanitize::normalize_typography;
+
+    let mut config = AnalyzerConfig::default();
+    let input = "X := A × B;";
+    let diagnostics = diagnose(input, &config);
+    assert_eq!(
+        (diagnostics[0].code, &diagnostics[0].span),
+        ("syntax", &(7..9))
+    );
+    assert_eq!(
+        diagnostics[0].message,
+        "Недопустимый символ: '×' - похоже на типографский знак"
+    );
+    assert!(diagnostics[0].notes[0].contains("вместо него - '*'"));
+
+    let directive = FileDirective::parse("(* taafl: typography=on *)").unwrap();
+    assert!(directive.apply(&mut config).is_empty());
+    assert!(config.typography);
+    let (text, _) = normalize_typography("X\u{00A0}:= A ≤ B – 1;");
+    assert_eq!(text, "X := A <= B - 1;");
+    assert!(diagnose(&text, &config).is_empty());
+}
+
+#[test]
+fn preserved_case_shows_spellings_and_keeps_comparison() {
+    use crate::analyzer::{analyze_line_with, analyze_report};
+    use crate::config::AnalyzerConfig;
+
+    let input = "Arr[i, I] := Total + total;";
+    let mut config = AnalyzerConfig::default();
+    let ids = analyze_line_with(input, &config).unwrap().ids.unwrap();
+    assert!(ids.starts_with("ARR - идентификатор-массив (столбец 1)\nI - "));
+
+    config.preserve_case = true;
+    let ids = analyze_line_with(input, &config).unwrap().ids.unwrap();
+    let names: Vec<&str> = ids
+        .lines()
+        .map(|line| line.split(" - ").next().unwrap())
+        .collect();
+    assert_eq!(names, ["Arr", "i, I", "Total, total"]);
+
+    let report = analyze_report(input, &config);
+    let total = &report.identifiers[2];
+    assert_eq!((total.name.as_str(), total.occurrences), ("TOTAL", 2));
+    assert_eq!(total.lexemes, ["Total", "total"]);
+}
+
+#[test]
+fn token_table_shows_lines_in_multiline_text() {
+    use crate::analyzer::{token_table, TokenColumn};
+
+    let config = Default::default();
+    let columns = [TokenColumn::Position, TokenColumn::Lexeme];
+    let table = token_table("X :=\n  Ёж;", &config, &columns).unwrap_err();
+    assert_eq!(table.code, "syntax");
+
+    let table = token_table("X :=\n  10;", &config, &columns).unwrap();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "Позиция  Лексема");
+    assert_eq!(
+        &lines[2..],
+        ["    1:1  X", "    1:3  :=", "    2:3  10", "    2:5  ;"]
+    );
+}
+
+#[test]
+fn full_tokens_reconstruct_input_exactly() {
+    use crate::analyzer::{tokenize_full, Trivia};
+
+    let config = Default::default();
+    for input in [
+        "",
+        "   ",
+        "X:=1;",
+        "(* (* вложенный *) *)A [ I,\r\n J ] := 'a (* не комментарий *)' ;\n(* конец *)",
+    ] {
+        let lexemes = tokenize_full(input, &config).unwrap();
+        let text: String = lexemes
+            .iter()
+            .flat_map(|lexeme| {
+                lexeme
+                    .leading
+                    .iter()
+                    .map(Trivia::span)
+                    .chain([&lexeme.span])
+            })
+            .map(|span| &input[span.clone()])
+            .collect();
+        assert_eq!(text, input);
+    }
+    assert!(tokenize_full("X := (* 1;", &config).is_err());
+}
+
+#[test]
+fn nested_comments_close_at_their_own_pair() {
+    use crate::analyzer::{tokenize, Token};
+
+    let tokens = tokenize("(* (* *) *) X := 1;").unwrap();
+    assert_eq!(tokens[0], (12..13, Token::Identifier("X".to_string())));
+
+    // Внутренний комментарий закрыт, внешний - нет: указывается открывающая
+    // скобка внешнего
+    let error = tokenize("A := (* (* *) 1;").unwrap_err();
+    assert_eq!(error.code, "lexical");
+    assert_eq!(error.span, 5..7);
+    assert_eq!(
+        error.message,
+        "Комментарий не закрыт: нет '*)' для этого '(*'"
+    );
+    assert_eq!(tokenize("(* (* *)").unwrap_err().span, 0..2);
+
+    // Кавычка в комментарии не начинает литерал
+    let tokens = tokenize("A := (* it's *) 1;").unwrap();
+    let kinds: Vec<_> = tokens.iter().map(|(_, token)| token.terminal()).collect();
+    assert_eq!(kinds, ["IDENT", ":=", "CONST", ";"]);
+    assert_eq!(tokens[2].0, 16..17);
+}
+
+#[test]
+fn literals_are_character_or_string_constants() {
+    use crate::model::ConstantRole;
+
+    let analyzer = Analyzer::builder().collect_ast(true).build();
+    let analysis = analyzer.analyze("X := 'a' + \"a\" + 'Текст' + \"it's\";");
+    let literals: Vec<_> = analysis
+        .report
+        .literals
+        .iter()
+        .map(|l| (l.value.as_str(), l.role, l.lexemes.len()))
+        .collect();
+    assert_eq!(
+        literals,
+        [
+            ("a", ConstantRole::CharacterExpression, 2),
+            ("Текст", ConstantRole::StringExpression, 1),
+            ("it's", ConstantRole::StringExpression, 1),
+        ]
+    );
+    let printed = analysis.ast.unwrap().to_string();
+    assert_eq!(printed, "X := \"a\" + \"a\" + \"Текст\" + \"it's\";");
+}
+
+#[test]
+fn printed_ast_parses_to_the_same_ast_and_symbols() {
+    let analyzer = Analyzer::builder()
+        .collect_ast(true)
+        .syntax_only(true)
+        .build();
+    let result = forall(CASES, 4, Cases::mutant, |(text, _)| {
+        let Some(ast) = analyzer.analyze(text).ast else {
+            return true;
+        };
+        let printed = ast.to_string();
+        let reparsed = analyzer.analyze(&printed).ast;
+        reparsed.map(|reparsed| reparsed.without_positions()) == Some(ast.without_positions())
+            && symbol_table(&printed) == symbol_table(text)
+    });
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn lazy_lexing_accepts_the_same_statements() {
+    let lazy = Analyzer::default();
+    let collected = Analyzer::builder().collect_tokens(true).build();
+    let result = forall(CASES, 6, Cases::mutant, |(text, _)| {
+        lazy.analyze(text).report.accepted == collected.analyze(text).report.accepted
+    });
+    assert_eq!(result, Ok(()));
+}
+
+/// Разностная проверка: рекурсивный спуск, LL(1)-таблица и Earley (с возможностью
+/// `lalrpop` - и парсер LALRPOP) одинаково принимают операторы и указывают одно
+/// место первой ошибки. Место сравнивается только без лексических ошибок: спуск
+/// берёт лексемы по одной и не доходит до лексической ошибки дальше синтаксической,
+/// таблица и Earley читают строку целиком.
+#[test]
+fn parsers_agree_on_acceptance_and_first_error() {
+    use crate::analyzer::{diagnose, tokenize};
+    use crate::config::{AnalyzerConfig, ParserKind};
+
+    let configs =
+        [ParserKind::Descent, ParserKind::Table, ParserKind::Earley].map(|parser| AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        });
+    let outcome = |text: &str, config: &AnalyzerConfig| {
+        let diagnostics = diagnose(text, config);
+        let lexed = tokenize(text).is_ok();
+        let first = diagnostics.first().map(|d| d.span.start);
+        (diagnostics.is_empty(), first.filter(|_| lexed))
+    };
+    let agree = |text: &str| {
+        let descent = outcome(text, &configs[0]);
+        configs[1..]
+            .iter()
+            .all(|config| outcome(text, config) == descent)
+    };
+    let result = forall(CASES, 7, Cases::statement, |(text, _)| agree(text));
+    assert_eq!(result, Ok(()));
+    let result = forall(CASES, 8, Cases::mutant, |(text, _)| agree(text));
+    assert_eq!(result, Ok(()));
+
+    // Парсер LALRPOP разбирает те же терминалы, что и таблица, и останавливается
+    // на той же лексеме: у LR(1) и LL(1) ошибка обнаруживается на первой лексеме,
+    // с которой префикс не продолжается до оператора
+    #[cfg(feature = "lalrpop")]
+    {
+        use crate::grammar::{lalrpop, Ll1Table};
+
+        let table = Ll1Table::builtin();
+        let agree = |text: &str| {
+            let Ok(tokens) = tokenize(text) else {
+                return true;
+            };
+            let terminals: Vec<_> = tokens
+                .iter()
+                .map(|(span, token)| (span.start, token.terminal()))
+                .collect();
+            let expected = table.parse(&terminals, text.len()).map_err(|(pos, _)| pos);
+            lalrpop::parse(&terminals, text.len()) == expected
+        };
+        let result = forall(CASES, 7, Cases::statement, |(text, _)| agree(text));
+        assert_eq!(result, Ok(()));
+        let result = forall(CASES, 8, Cases::mutant, |(text, _)| agree(text));
+        assert_eq!(result, Ok(()));
+    }
+}
+
+/// Конец ввода у таблицы и Earley - начало последнего символа: ошибка на
+/// многобайтном символе в конце строки не делит его (инвариант отрезков).
+#[test]
+fn table_and_earley_end_errors_stay_on_char_boundaries() {
+    use crate::config::{AnalyzerConfig, ParserKind, ScannerKind};
+
+    for parser in [ParserKind::Table, ParserKind::Earley] {
+        let recovering = AnalyzerConfig {
+            parser,
+            recovery: true,
+            ..AnalyzerConfig::default()
+        };
+        let unicode = AnalyzerConfig {
+            parser,
+            scanner: ScannerKind::Dfa,
+            unicode_identifiers: true,
+            ..AnalyzerConfig::default()
+        };
+        for (input, config) in [
+            ("A[é", &recovering),
+            ("A := B\u{a0}", &recovering),
+            ("A := B\u{a0}", &unicode),
+            ("A := é", &unicode),
+        ] {
+            let diagnostics = Analyzer::builder()
+                .config(config.clone())
+                .build()
+                .analyze(input)
+                .diagnostics;
+            assert!(!diagnostics.is_empty(), "{:?} {:?}", parser, input);
+            for d in &diagnostics {
+                assert!(input.is_char_boundary(d.span.start.min(input.len())), "{:?}", d);
+            }
+        }
+    }
+}
+
+/// Бюджет случайного прогона: ни одна открытая функция анализа не паникует,
+/// в том числе с восстановлением после ошибок.
+#[test]
+fn entry_points_never_panic_within_budget() {
+    use crate::analyzer::{
+        catch_internal, derivation_table, token_table, tokenize_full, try_analyze, TokenColumn,
+    };
+    use crate::config::{AnalyzerConfig, ParserKind, Profile};
+
+    for profile in Profile::ALL {
+        let mut config = AnalyzerConfig::from_profile(profile);
+        config.recovery = true;
+        config.parser = ParserKind::Table;
+        let result = forall(CASES, 9, Cases::mutant, |(text, _)| {
+            let others = catch_internal(text, || {
+                let _ = tokenize_full(text, &config);
+                let _ = token_table(text, &config, &TokenColumn::ALL);
+                let _ = derivation_table(text, &config);
+            });
+            try_analyze(text, &config).is_ok() && others.is_ok()
+        });
+        assert_eq!(result, Ok(()), "{:?}", profile);
+    }
+}
+
+#[test]
+fn diagnostic_spans_cover_whole_characters() {
+    let analyzer = Analyzer::default();
+    let result = forall(CASES, 7, Cases::mutant, |(text, _)| {
+        analyzer.analyze(text).diagnostics.iter().all(|d| {
+            let span = &d.span;
+            span.start < span.end
+                && (span.end <= text.len() || span.start == text.len())
+                && text.get(span.start..span.end.min(text.len())).is_some()
+        })
+    });
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn classified_regions_cover_everything_but_spaces() {
+    let result = forall(CASES, 5, Cases::mutant, |(text, _)| {
+        let regions = crate::analyzer::classify(text);
+        let mut end = 0;
+        for (span, _) in &regions {
+            if span.start < end || !text[end..span.start].trim().is_empty() {
+                return false;
+            }
+            end = span.end;
+        }
+        text[end..].trim().is_empty()
+    });
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn oversized_input_is_not_analyzed() {
+    use crate::config::AnalyzerConfig;
+
+    let config = AnalyzerConfig {
+        max_input: 12,
+        ..AnalyzerConfig::default()
+    };
+    let analyze = |input: &str, config: &AnalyzerConfig| {
+        let analyzer = Analyzer::builder().config(config.clone()).build();
+        analyzer.analyze(input).diagnostics
+    };
+
+    let long = "Б := ББББББ + 1;";
+    let diagnostics = analyze(long, &config);
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].code, "input-size");
+    // Отрезок указывает на первый лишний символ, а не на байт
+    assert_eq!(
+        diagnostics[0].span,
+        long.char_indices().nth(12).map(|(i, _)| i..i + 1).unwrap()
+    );
+    assert!(diagnostics[0].message.contains("12 символов"));
+
+    assert!(analyze("X := Y + 1;", &config).is_empty());
+    let unlimited = AnalyzerConfig {
+        max_input: 0,
+        ..config
+    };
+    let statement = format!("X := {}1;", "Y + ".repeat(5000));
+    assert!(analyze(&statement, &unlimited).is_empty());
+    assert_eq!(
+        analyze(&statement, &AnalyzerConfig::default())[0].code,
+        "input-size"
+    );
+}
+
+#[test]
+fn operation_set_comes_from_config() {
+    use crate::analyzer::{diagnose, tokenize_full};
+    use crate::config::{AnalyzerConfig, FileDirective, OperationSign, ScannerKind};
+
+    let mut config = AnalyzerConfig::default();
+    let directive = FileDirective::parse("(* taafl: operations=+,-,*,%,< *)").unwrap();
+    assert!(directive.apply(&mut config).is_empty());
+    assert!(config.operations.contains(&OperationSign::Percent));
+    assert!(!config.operations.contains(&OperationSign::LessEquals));
+
+    for scanner in [ScannerKind::Direct, ScannerKind::Dfa] {
+        let config = AnalyzerConfig {
+            scanner,
+            ..config.clone()
+        };
+        assert!(diagnose("X := A % 2 - B * C;", &config).is_empty());
+        assert!(diagnose("X := A < B;", &config).is_empty());
+        for (input, sign, span) in [("X := A # 2;", "#", 7..8), ("X := A <= B;", "<=", 7..9)] {
+            let diagnostics = diagnose(input, &config);
+            assert_eq!(diagnostics[0].span, span, "{:?}", scanner);
+            let message = format!("Операция '{}' не допускается в этом варианте", sign);
+            assert!(diagnostics[0].message.starts_with(&message));
+        }
+    }
+    let dfa = AnalyzerConfig {
+        scanner: ScannerKind::Dfa,
+        ..config.clone()
+    };
+    assert_eq!(
+        tokenize_full("A%B<=#", &dfa),
+        tokenize_full("A%B<=#", &config)
+    );
+    // По умолчанию знака '%' в языке нет
+    let diagnostics = diagnose("X := A % 2;", &AnalyzerConfig::default());
+    assert!(diagnostics[0]
+        .message
+        .starts_with("Операция '%' не допускается"));
+}
+
+#[test]
+fn sign_before_first_term_is_part_of_the_constant() {
+    use crate::analyzer::{analyze_report, diagnose};
+    use crate::config::{AnalyzerConfig, ConstantRange, ParserKind};
+
+    for parser in [ParserKind::Descent, ParserKind::Table] {
+        let config = AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        };
+        assert!(diagnose("X := +B;", &config).is_empty(), "{:?}", parser);
+        assert!(diagnose("X := -A * 2;", &config).is_empty(), "{:?}", parser);
+        // Вариант допускает константы от 1: знак охватывается ошибкой
+        let diagnostics = diagnose("X := -1 + A;", &config);
+        assert_eq!(diagnostics[0].code, "constant-range");
+        assert_eq!(diagnostics[0].span, 5..7);
+        // Знак - только перед первым слагаемым
+        assert!(
+            !diagnose("X := A + -B;", &config).is_empty(),
+            "{:?}",
+            parser
+        );
+        assert!(!diagnose("A[-1] := B;", &config).is_empty(), "{:?}", parser);
+
+        let integer = AnalyzerConfig {
+            constants: ConstantRange::Integer,
+            ..config
+        };
+        let report = analyze_report("X := -32768 + 1;", &integer);
+        assert!(report.accepted);
+        assert_eq!(report.constants[0].value, -32768);
+        assert_eq!(report.constants[0].lexemes, ["32768"]);
+        assert!(!analyze_report("X := 32768;", &integer).accepted);
+    }
+}
+
+#[test]
+fn array_elements_on_the_right_have_their_own_role() {
+    use crate::analyzer::{analyze_report, diagnose};
+    use crate::config::{AnalyzerConfig, ParserKind, Rule, Severity};
+    use crate::model::IdentifierRole;
+
+    for parser in [ParserKind::Descent, ParserKind::Table] {
+        let mut config = AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        };
+        let report = analyze_report("A[I] := B[I, 2] + C;", &config);
+        assert!(report.accepted, "{:?}", parser);
+        let roles: Vec<_> = report
+            .identifiers
+            .iter()
+            .map(|id| (id.name.as_str(), id.role))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                ("A", IdentifierRole::Array),
+                ("B", IdentifierRole::ExpressionArray),
+                ("I", IdentifierRole::Index),
+                ("C", IdentifierRole::Expression),
+            ]
+        );
+        assert_eq!(report.identifiers[2].positions, [2, 10]);
+        assert_eq!(report.constants[0].value, 2);
+
+        // Запрет остаётся только для массива левой части, в любой записи
+        let diagnostics = diagnose("A[I] := A[1] + 1;", &config);
+        assert_eq!(diagnostics[0].code, "array-in-expression");
+        assert_eq!(diagnostics[0].span, 8..9);
+        assert!(diagnose("A := A[1] + 1;", &config).is_empty());
+
+        config.set_severity(Rule::ArrayInExpression, Severity::Warning);
+        let report = analyze_report("A[I] := A[1];", &config);
+        assert!(report.accepted);
+        assert_eq!(report.identifiers[1].role, IdentifierRole::ExpressionArray);
+    }
+}
+
+#[test]
+fn index_expressions_classify_their_symbols_as_indices() {
+    use crate::analyzer::{analyze_report, diagnose};
+    use crate::config::{AnalyzerConfig, ParserKind};
+    use crate::model::{ConstantRole, IdentifierRole};
+
+    for parser in [ParserKind::Descent, ParserKind::Table] {
+        let config = AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        };
+        let report = analyze_report("ABC[I+1, 2*K] := B[-J + 3] + 1;", &config);
+        assert!(report.accepted, "{:?}: {:?}", parser, report.errors);
+        let roles: Vec<_> = report
+            .identifiers
+            .iter()
+            .map(|id| (id.name.as_str(), id.role))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                ("ABC", IdentifierRole::Array),
+                ("B", IdentifierRole::ExpressionArray),
+                ("I", IdentifierRole::Index),
+                ("K", IdentifierRole::Index),
+                ("J", IdentifierRole::Index),
+            ]
+        );
+        let constants: Vec<_> = report
+            .constants
+            .iter()
+            .map(|c| (c.value, c.role, c.positions.clone()))
+            .collect();
+        assert_eq!(
+            constants,
+            [
+                (1, ConstantRole::Index, vec![6]),
+                (2, ConstantRole::Index, vec![9]),
+                (3, ConstantRole::Index, vec![24]),
+                (1, ConstantRole::Expression, vec![29]),
+            ]
+        );
+
+        // Знак первого слагаемого индекса входит в значение константы
+        let diagnostics = diagnose("A[-1] := 1;", &config);
+        assert_eq!(diagnostics[0].code, "constant-range");
+        assert_eq!(diagnostics[0].span, 2..4);
+
+        // Литерал и элемент массива в индексе - по-прежнему синтаксические ошибки
+        for input in ["A[I + 'J'] := 1;", "A[B[1]] := 1;", "A[I +] := 1;"] {
+            let diagnostics = diagnose(input, &config);
+            assert_eq!(diagnostics[0].code, "syntax", "{:?}: {}", parser, input);
+        }
+    }
+}
+
+#[test]
+fn record_fields_are_qualified_names() {
+    use crate::analyzer::{analyze_report, diagnose};
+    use crate::config::{AnalyzerConfig, ParserKind, Terminator};
+    use crate::model::IdentifierRole;
+
+    for parser in [ParserKind::Descent, ParserKind::Table] {
+        let config = AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        };
+        let report = analyze_report("Rec.F := A . b + 1;", &config);
+        assert!(report.accepted, "{:?}: {:?}", parser, report.errors);
+        let rec = &report.identifiers[0];
+        assert_eq!(
+            (rec.name.as_str(), rec.role),
+            ("REC.F", IdentifierRole::Expression)
+        );
+        assert_eq!(
+            (rec.path.clone(), rec.lexemes.clone()),
+            (vec!["REC".into(), "F".into()], vec!["Rec.F".into()])
+        );
+        let field = &report.identifiers[1];
+        assert_eq!(
+            (field.name.as_str(), field.positions.clone()),
+            ("A.B", vec![9])
+        );
+        assert_eq!(field.lexemes, ["A.b"]);
+
+        let report = analyze_report("A[R.I] := R.B[1];", &config);
+        let roles: Vec<_> = report
+            .identifiers
+            .iter()
+            .map(|id| (id.name.as_str(), id.role))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                ("A", IdentifierRole::Array),
+                ("R.B", IdentifierRole::ExpressionArray),
+                ("R.I", IdentifierRole::Index),
+            ]
+        );
+
+        // Длина проверяется по компонентам, а не по всему имени
+        assert!(analyze_report("POINT.XCOORD := 1;", &config).accepted);
+        let diagnostics = diagnose("REC.LONGFIELDNAME := 1;", &config);
+        assert_eq!(diagnostics[0].code, "identifier-length");
+        assert_eq!(diagnostics[0].span, 4..17);
+        let diagnostics = diagnose("R.A[1] := R.A;", &config);
+        assert_eq!(diagnostics[0].code, "array-in-expression");
+        assert_eq!(diagnostics[0].span, 10..13);
+
+        for input in ["A := B.;", "A := B.1;", "A. := 1;"] {
+            let diagnostics = diagnose(input, &config);
+            assert_eq!(diagnostics[0].code, "syntax", "{:?}: {}", parser, input);
+        }
+
+        // С точкой в конце оператора полей записей нет
+        let dot = AnalyzerConfig {
+            terminator: Terminator::Dot,
+            ..config
+        };
+        assert!(analyze_report("A := B.", &dot).accepted);
+        for input in ["A := B.C.", "A.F := 1."] {
+            let diagnostics = diagnose(input, &dot);
+            assert_eq!(diagnostics[0].code, "syntax", "{:?}: {}", parser, input);
+        }
+    }
+}
+
+#[test]
+fn lvalue_chains_mix_fields_and_indices() {
+    use crate::analyzer::{analyze_report, diagnose, Analyzer};
+    use crate::config::{AnalyzerConfig, ParserKind, Terminator};
+    use crate::model::IdentifierRole;
+
+    for parser in [ParserKind::Descent, ParserKind::Table] {
+        let config = AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        };
+        for input in ["REC.ARR[I] := 1;", "A[I].F := X;", "A[I][J] := 1;"] {
+            let report = analyze_report(input, &config);
+            assert!(
+                report.accepted,
+                "{:?}: {}: {:?}",
+                parser, input, report.errors
+            );
+        }
+
+        let report = analyze_report("A[I].F[J, 1].G := X;", &config);
+        let roles: Vec<_> = report
+            .identifiers
+            .iter()
+            .map(|id| (id.name.as_str(), id.role, id.positions.clone()))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                ("A", IdentifierRole::Array, vec![0]),
+                ("F", IdentifierRole::Field, vec![5]),
+                ("G", IdentifierRole::Field, vec![13]),
+                ("I", IdentifierRole::Index, vec![2]),
+                ("J", IdentifierRole::Index, vec![7]),
+                ("X", IdentifierRole::Expression, vec![18]),
+            ]
+        );
+        assert_eq!(report.constants[0].positions, [10]);
+
+        // Поле после элемента проверяется как идентификатор
+        let diagnostics = diagnose("A[I].LONGFIELDNAME := 1;", &config);
+        assert_eq!(diagnostics[0].code, "identifier-length");
+        assert_eq!(diagnostics[0].span, 5..18);
+        for input in ["A[I]. := 1;", "A[I].F[] := 1;", "A[I].1 := 1;"] {
+            let diagnostics = diagnose(input, &config);
+            assert_eq!(diagnostics[0].code, "syntax", "{:?}: {}", parser, input);
+        }
+
+        let dot = AnalyzerConfig {
+            terminator: Terminator::Dot,
+            ..config
+        };
+        assert!(analyze_report("A[I] := B.", &dot).accepted);
+        assert_eq!(diagnose("A[I].F := B.", &dot)[0].code, "syntax");
+    }
+
+    let analyzer = Analyzer::builder().collect_ast(true).build();
+    let ast = analyzer.analyze("a[i].f[j,1].g:=x;").ast.unwrap();
+    assert_eq!(ast.to_string(), "A[I].F[J, 1].G := X;");
+    let reparsed = analyzer.analyze(&ast.to_string()).ast.unwrap();
+    assert_eq!(reparsed.without_positions(), ast.without_positions());
+}
+
+#[test]
+fn function_calls_parse_arguments_as_right_parts() {
+    use crate::analyzer::{analyze_report, diagnose};
+    use crate::config::{AnalyzerConfig, Brackets, ParserKind};
+    use crate::model::{ConstantRole, IdentifierRole};
+
+    for parser in [ParserKind::Descent, ParserKind::Table] {
+        let config = AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        };
+        let report = analyze_report("C := CHR(65) + ORD(CHR(B[I] + 1), 'x') + F();", &config);
+        assert!(report.accepted, "{:?}: {:?}", parser, report.errors);
+        let roles: Vec<_> = report
+            .identifiers
+            .iter()
+            .map(|id| (id.name.as_str(), id.role, id.positions.clone()))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                ("B", IdentifierRole::ExpressionArray, vec![23]),
+                ("CHR", IdentifierRole::Function, vec![5, 19]),
+                ("ORD", IdentifierRole::Function, vec![15]),
+                ("F", IdentifierRole::Function, vec![41]),
+                ("I", IdentifierRole::Index, vec![25]),
+                ("C", IdentifierRole::Expression, vec![0]),
+            ]
+        );
+        let constants: Vec<_> = report.constants.iter().map(|c| (c.value, c.role)).collect();
+        assert_eq!(
+            constants,
+            [
+                (65, ConstantRole::Expression),
+                (1, ConstantRole::Expression)
+            ]
+        );
+        assert_eq!(report.literals[0].value, "x");
+
+        // Массив из левой части нельзя передать и в аргументе
+        let diagnostics = diagnose("A[1] := ORD(A);", &config);
+        assert_eq!(diagnostics[0].code, "array-in-expression");
+        assert_eq!(diagnostics[0].span, 12..13);
+
+        for input in ["A := F(1;", "A := F(,);", "A[F(1)] := 1;", "F(1) := 2;"] {
+            let diagnostics = diagnose(input, &config);
+            assert_eq!(diagnostics[0].code, "syntax", "{:?}: {}", parser, input);
+        }
+
+        // Круглые скобки индексов заняты: F(1) - элемент массива
+        let round = AnalyzerConfig {
+            brackets: Brackets::Round,
+            ..config
+        };
+        let report = analyze_report("A := F(1);", &round);
+        assert_eq!(report.identifiers[0].role, IdentifierRole::ExpressionArray);
+    }
+}
+
+#[test]
+fn syntax_errors_list_terminals_expected_by_the_grammar() {
+    use crate::analyzer::{analyze_report, diagnose};
+    use crate::config::{AnalyzerConfig, ParserKind};
+
+    for parser in [ParserKind::Descent, ParserKind::Table] {
+        let config = AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        };
+        // После правой части ')' допустима только внутри вызова
+        let report = analyze_report("A := 1 );", &config);
+        assert_eq!(
+            report.errors[0].expected,
+            [";", "OP", "SIGN"],
+            "{:?}",
+            parser
+        );
+        let diagnostic = &diagnose("A := 1 );", &config)[0];
+        assert_eq!(
+            diagnostic.notes.last().unwrap(),
+            "Ожидалось одно из: ';', операция, знак '+' или '-'"
+        );
+        // Сообщение строится из того же списка, что и в разборе по таблице
+        assert_eq!(
+            diagnostic.message,
+            "Ожидалось: ';' или операция или знак '+' или '-'"
+        );
+        assert!(diagnose("A := 1", &config)[0]
+            .message
+            .ends_with("знак '+' или '-', но достигнут конец"));
+
+        // Если разбор по таблице останавливается не там, список берётся после
+        // лексем перед ошибкой
+        let table = crate::grammar::Ll1Table::builtin();
+        let terminals = [(0, "IDENT"), (2, ":="), (5, "CONST"), (6, ";")];
+        let (expected, message) = super::expected_at(&table, &terminals, 6, &(5..6)).unwrap();
+        assert_eq!(expected, ["CONST", "IDENT", "LITERAL", "NOT", "SIGN"]);
+        assert!(message.starts_with("Ожидалось: константа или идентификатор"));
+        let report = analyze_report("A := F(1;", &config);
+        assert_eq!(report.errors[0].expected, [")", ",", "OP", "SIGN"]);
+        let report = analyze_report("A := 1; B", &config);
+        assert_eq!(report.errors[0].expected, [crate::grammar::END]);
+        let report = analyze_report("A[] := 1;", &config);
+        assert_eq!(report.errors[0].expected, ["CONST", "IDENT", "NOT", "SIGN"]);
+
+        // У несинтаксических ошибок списка нет
+        let report = analyze_report("A := 0;", &config);
+        assert_eq!(report.errors[0].kind, crate::model::ErrorKind::Semantic);
+        assert!(report.errors[0].expected.is_empty());
+    }
+}
+
+#[test]
+fn reports_list_derivation_steps_of_parsed_statements() {
+    use crate::analyzer::{analyze_report, derivation};
+    use crate::config::{AnalyzerConfig, ParserKind};
+    use crate::model::AnalysisReport;
+
+    for parser in [ParserKind::Descent, ParserKind::Table] {
+        let config = AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        };
+        let report = analyze_report("A[I] := 1;", &config);
+        let steps: Vec<_> = report
+            .derivation
+            .iter()
+            .map(|step| (step.lhs.as_str(), step.rhs.join(" ")))
+            .collect();
+        assert_eq!(
+            steps[0],
+            ("statement", r#"left ":=" right ";""#.to_string())
+        );
+        assert_eq!(steps[2], ("field", String::new()));
+        let left_tail = r#""[" index_list "]" field left_tail"#;
+        assert_eq!(steps[3], ("left_tail", left_tail.to_string()));
+        assert_eq!(
+            steps.len(),
+            derivation("A[I] := 1;", &config).unwrap().len()
+        );
+
+        // Семантическая ошибка вывода не отменяет, синтаксическая - да
+        assert!(!analyze_report("A := 0;", &config).derivation.is_empty());
+        assert!(analyze_report("A := ;", &config).derivation.is_empty());
+    }
+
+    // Отчёт без вывода по-прежнему читается
+    let json = serde_json::to_value(analyze_report("A := B;", &Default::default())).unwrap();
+    let mut json = json.as_object().unwrap().clone();
+    json.remove("derivation");
+    let json = serde_json::to_string(&json).unwrap();
+    assert!(AnalysisReport::from_json(&json)
+        .unwrap()
+        .derivation
+        .is_empty());
+}
+
+#[test]
+fn report_lists_gaps_around_every_token() {
+    use crate::analyzer::analyze_report;
+    use crate::config::AnalyzerConfig;
+
+    let report = analyze_report(" A[I] :=(*c*)B;\n", &AnalyzerConfig::default());
+    let gaps: Vec<_> = report.gaps.iter().map(|gap| gap.text.as_str()).collect();
+    assert_eq!(gaps, [" ", "", "", "", " ", "(*c*)", "", "\n"]);
+    assert_eq!((report.gaps[5].position, report.gaps[5].end), (8, 13));
+    assert!(report.gaps[4].is_spaced() && !report.gaps[5].is_spaced());
+
+    // Строка не разобрана - промежутков нет
+    assert!(analyze_report("A[I :=", &AnalyzerConfig::default())
+        .gaps
+        .is_empty());
+}
+
+#[test]
+fn descent_errors_are_reported_in_every_parser_mode() {
+    use crate::analyzer::diagnose;
+    use crate::config::{AnalyzerConfig, ParserKind};
+
+    for parser in [ParserKind::Descent, ParserKind::Table, ParserKind::Earley] {
+        let config = AnalyzerConfig {
+            parser,
+            ..AnalyzerConfig::default()
+        };
+        let diagnostics = diagnose("A := B ++ C;", &config);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.code, d.span.clone()))
+            .collect();
+        assert_eq!(found, [("operand-order", 7..9)], "{:?}", parser);
+
+        let diagnostics = diagnose("A[] := 1;", &config);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.span.clone(), d.message.as_str()))
+            .collect();
+        assert_eq!(found, [(1..3, "Пустой список индексов")], "{:?}", parser);
+    }
+}
+
+#[test]
+fn allow_comments_apply_to_the_next_statement_only() {
+    use crate::analyzer::analyze_many;
+
+    let text = "(* taafl:allow(constant-range) *) A := 0; B := 0;";
+    let results = analyze_many(text);
+    assert!(results[0].result.is_ok());
+    assert_eq!(results[0].suppressed[0].code, "constant-range");
+    assert!(results[0].notes.is_empty());
+    assert!(results[1].result.is_err());
+    assert!(results[1].suppressed.is_empty());
+
+    let results = analyze_many("(* taafl:allow(no-such-rule, constant-range) *) A := 0;");
+    assert!(results[0].result.is_ok());
+    assert_eq!(
+        results[0].notes,
+        ["Неизвестное правило 'no-such-rule' в allow пропущено"]
+    );
+}
+
+#[test]
+fn suppressed_diagnostics_point_into_the_statement_with_its_comment() {
+    use crate::analyzer::analyze_many;
+
+    let text = "B := 1; (* taafl:allow(constant-range) *) A := 0;";
+    let results = analyze_many(text);
+    let statement = &results[1];
+    assert_eq!(statement.offset, 8);
+    assert!(statement.text.starts_with("(* taafl:allow"));
+    let span = statement.suppressed[0].span.clone();
+    assert_eq!(span.start, statement.text.find('0').unwrap());
+    assert_eq!(&statement.text[span], "0");
+}
+
+#[test]
+fn rendered_report_matches_live_analysis() {
+    use crate::analyzer::{analyze_line_with, analyze_report, render_report};
+    use crate::config::{AnalyzerConfig, ConstantRange, Rule, Severity};
+    use crate::model::AnalysisReport;
+
+    let mut config = AnalyzerConfig {
+        constants: ConstantRange::Custom { min: -5, max: 5 },
+        ..AnalyzerConfig::default()
+    };
+    config.set_severity(Rule::ConstantRange, Severity::Warning);
+    for input in ["A[I] := 07 + 3;", "A := 9;"] {
+        let json = serde_json::to_string(&analyze_report(input, &config)).unwrap();
+        let report = AnalysisReport::from_json(&json).unwrap();
+        // Настройки при открытии - по умолчанию: диапазон берётся из отчёта
+        let rendered = render_report(&report, &AnalyzerConfig::default()).unwrap();
+        let live = analyze_line_with(input, &config).unwrap();
+        assert_eq!(rendered.consts, live.consts, "{}", input);
+        assert!(live.consts.unwrap().contains("[-5..5]"));
+    }
+}
+
+#[test]
+fn report_config_replays_analysis() {
+    use crate::analyzer::analyze_report;
+    use crate::config::{
+        AnalyzerConfig, Brackets, ConstantRange, ParserKind, Profile, Rule, ScannerKind, Severity,
+        SymbolOrder, Terminator,
+    };
+
+    let mut config = AnalyzerConfig {
+        recovery: true,
+        order: SymbolOrder::Sorted,
+        parser: ParserKind::Table,
+        scanner: ScannerKind::Dfa,
+        terminator: Terminator::Dot,
+        brackets: Brackets::Round,
+        reals: true,
+        constants: ConstantRange::Custom { min: -5, max: 5 },
+        ..AnalyzerConfig::default()
+    };
+    config.set_severity(Rule::ConstantRange, Severity::Warning);
+    for input in ["B(I) := 07 + 3.", "Z := A * 9.; X := 1.5.", "A := ."] {
+        let report = analyze_report(input, &config);
+        let json = serde_json::to_string(&report).unwrap();
+        let entry = crate::model::AnalysisReport::from_json(&json)
+            .unwrap()
+            .config;
+        let replayed = AnalyzerConfig::from_entry(&entry).unwrap();
+        assert_eq!(analyze_report(input, &replayed), report, "{}", input);
+    }
+
+    let profiled = AnalyzerConfig::from_profile(Profile::ALL[0]);
+    let entry = analyze_report("A := 1;", &profiled).config;
+    assert_eq!(
+        AnalyzerConfig::from_entry(&entry).unwrap().profile,
+        profiled.profile
+    );
+
+    let mut old = entry;
+    old.settings = None;
+    assert!(AnalyzerConfig::from_entry(&old).is_err());
+}
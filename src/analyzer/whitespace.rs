@@ -0,0 +1,116 @@
+//! Правило [`Rule::Whitespace`]: пробелы между лексемами по требованиям
+//! [`AnalyzerConfig::whitespace`](crate::config::AnalyzerConfig::whitespace).
+//! Проверяются промежутки из таблицы [`gap_table`](super::gap_table), поэтому
+//! ошибка указывает на сам лишний пробел или на присваивание без пробела.
+
+use super::{bracket_tokens, Checker, Error, Span, Token};
+use crate::config::Rule;
+use crate::model::Gap;
+
+/// Отрезки непрерывных пробельных символов промежутка.
+fn whitespace_runs(gap: &Gap) -> Vec<Span> {
+    let mut runs: Vec<Span> = Vec::new();
+    for (i, c) in gap.text.char_indices().filter(|(_, c)| c.is_whitespace()) {
+        let start = gap.position.saturating_add(i);
+        let end = start.saturating_add(c.len_utf8());
+        match runs.last_mut() {
+            Some(run) if run.end == start => run.end = end,
+            _ => runs.push(start..end),
+        }
+    }
+    runs
+}
+
+impl Checker<'_> {
+    /// Проверяет пробелы вокруг лексем `tokens` оператора; `gaps` - их таблица
+    /// промежутков, на один длиннее `tokens`.
+    pub(super) fn check_whitespace(
+        &mut self,
+        tokens: &[(Span, Token)],
+        gaps: &[Gap],
+    ) -> Result<(), Error> {
+        let policy = self.reporter.config.whitespace;
+        let (open, close) = bracket_tokens(self.reporter.config.brackets);
+
+        let mut depth = 0usize;
+        for (i, (span, token)) in tokens.iter().enumerate() {
+            if *token == open {
+                depth = depth.saturating_add(1);
+            } else if *token == close {
+                depth = depth.saturating_sub(1);
+            }
+            let (before, after) = (gaps.get(i), gaps.get(i.saturating_add(1)));
+
+            if policy.compact_indices && depth > 0 {
+                for run in after.map(whitespace_runs).unwrap_or_default() {
+                    let message = "Пробел в списке индексов: индексы пишутся слитно".to_string();
+                    self.violation(Rule::Whitespace, run, message)?;
+                }
+            }
+
+            if policy.spaced_assign && *token == Token::Assign {
+                let lexeme = self.input.get(span.clone()).unwrap_or_default();
+                let message = match (
+                    before.is_some_and(Gap::is_spaced),
+                    after.is_some_and(Gap::is_spaced),
+                ) {
+                    (true, true) => continue,
+                    (false, true) => format!("Перед '{}' нужен пробел", lexeme),
+                    (true, false) => format!("После '{}' нужен пробел", lexeme),
+                    (false, false) => format!("Вокруг '{}' нужны пробелы", lexeme),
+                };
+                self.violation(Rule::Whitespace, span.clone(), message)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn whitespace_policy_points_at_the_offending_gap() {
+        use crate::analyzer::diagnose;
+        use crate::config::{AnalyzerConfig, Brackets, FileDirective};
+
+        let loose = "A[ I,  J ] := B;";
+        assert!(diagnose(loose, &AnalyzerConfig::default()).is_empty());
+
+        let mut config = AnalyzerConfig {
+            recovery: true,
+            ..AnalyzerConfig::default()
+        };
+        let directive = FileDirective::parse("(* taafl: compact-indices=on *)").unwrap();
+        assert!(directive.apply(&mut config).is_empty());
+        assert!(diagnose("A[I,J] := B;", &config).is_empty());
+        let spans: Vec<_> = diagnose(loose, &config)
+            .into_iter()
+            .map(|d| (d.code, d.span))
+            .collect();
+        let code = "whitespace";
+        assert_eq!(spans, [(code, 2..3), (code, 5..7), (code, 8..9)]);
+        // Пробелы вне скобок индексов не ограничены
+        assert!(diagnose("A[I]   :=   B;", &config).is_empty());
+        let round = AnalyzerConfig {
+            brackets: Brackets::Round,
+            ..config.clone()
+        };
+        assert_eq!(diagnose("A( I ) := B;", &round).len(), 2);
+
+        config.whitespace.spaced_assign = true;
+        assert!(diagnose("A[I] := B;", &config).is_empty());
+        for (input, message) in [
+            ("X:= 1;", "Перед ':=' нужен пробел"),
+            ("X :=1;", "После ':=' нужен пробел"),
+            ("X:=1;", "Вокруг ':=' нужны пробелы"),
+        ] {
+            let diagnostics = diagnose(input, &config);
+            assert_eq!(diagnostics.len(), 1, "{}", input);
+            assert_eq!(
+                diagnostics[0].span,
+                input.find(':').unwrap()..input.find('=').unwrap() + 1
+            );
+            assert!(diagnostics[0].message.starts_with(message), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,548 @@
+//! Дерево разбора оператора присваивания.
+//!
+//! Позиции - смещения в байтах от начала анализируемой строки. Имена идентификаторов
+//! хранятся в верхнем регистре, как их выдаёт лексический анализатор; поле записи -
+//! составным именем через точку, `REC.F`, с позициями имён полей отдельно.
+//!
+//! [`Display`](fmt::Display) записывает узлы в каноническом виде: разбор записи даёт
+//! то же дерево с точностью до позиций.
+//!
+//! ```
+//! use taafl::analyzer::Analyzer;
+//!
+//! let analyzer = Analyzer::builder().collect_ast(true).build();
+//! let ast = analyzer.analyze("x[ 1,i+1 ]:=a.f+b[i,2]+chr(007);").ast.unwrap();
+//! assert_eq!(ast.to_string(), "X[1, I + 1] := A.F + B[I, 2] + CHR(7);");
+//!
+//! let reparsed = analyzer.analyze(&ast.to_string()).ast.unwrap();
+//! assert_eq!(reparsed.without_positions(), ast.without_positions());
+//! ```
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// `<левая часть> := <правая часть>;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub target: Target,
+    pub value: Expression,
+}
+
+/// Индексы или аргументы через запятую, как их записывает [`Display`](fmt::Display): `1, I + 1`.
+fn index_list(indices: &[Expression]) -> String {
+    let indices: Vec<String> = indices.iter().map(Expression::to_string).collect();
+    indices.join(", ")
+}
+
+/// Компоненты составного имени `name` с позициями: первая - на `position`,
+/// остальные - поля на позициях `fields`.
+///
+/// ```
+/// use taafl::ast::components;
+///
+/// let path: Vec<_> = components("REC.F", 0, &[4]).collect();
+/// assert_eq!(path, [("REC", 0), ("F", 4)]);
+/// ```
+pub fn components<'a>(
+    name: &'a str,
+    position: usize,
+    fields: &'a [usize],
+) -> impl Iterator<Item = (&'a str, usize)> {
+    name.split('.')
+        .zip(std::iter::once(position).chain(fields.iter().copied()))
+}
+
+/// Левая часть: идентификатор, поле записи или элемент массива, а за элементом -
+/// цепочка полей и списков индексов: `REC.ARR[I]`, `A[I].F`, `A[I].F[J]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    /// Имя; у поля записи - составное, через точку: `REC.F`
+    pub name: String,
+    pub position: usize,
+    /// Позиции имён полей составного имени после первой компоненты
+    pub fields: Vec<usize>,
+    /// Список индексов, если слева стоит элемент массива
+    pub indices: Option<Vec<Expression>>,
+    /// Доступы после списка индексов в порядке записи; без индексов цепочка пуста
+    pub chain: Vec<Access>,
+}
+
+/// Доступ в цепочке левой части после элемента массива.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Access {
+    /// Поле элемента: `.F` в `A[I].F`
+    Field { name: String, position: usize },
+    /// Следующий список индексов: `[J]` в `A[I].F[J]`
+    Indices(Vec<Expression>),
+}
+
+/// Правая часть или индекс: слагаемые, соединённые бинарными операциями, без учёта
+/// приоритета. В индексе слагаемые - только идентификаторы и константы.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expression {
+    pub first: Term,
+    /// Последующие пары (операция, слагаемое) в порядке записи
+    pub rest: Vec<(Operation, Term)>,
+}
+
+/// Операнд правой части с предшествующими ему унарными операциями.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Term {
+    /// Унарные операции из [`UNARY_OPERATIONS`], а у первого слагаемого ещё и знак
+    /// из [`SIGNS`], в порядке записи: первая применяется последней
+    pub unary: Vec<Operation>,
+    pub operand: Operand,
+}
+
+/// Знаки операций языка по умолчанию
+/// ([`OperationSign::DEFAULT`](crate::config::OperationSign::DEFAULT)).
+/// Двухсимвольные стоят раньше односимвольных, с которых они начинаются.
+pub const OPERATIONS: &[&str] = &["<=", ">=", "<>", "+", "-", "*", "/", ">", "<", "=", "#"];
+
+/// Бинарные операции, которые записываются ключевыми словами: целочисленное
+/// деление, остаток, логические "и" и "или". Как идентификаторы эти слова
+/// использовать нельзя.
+pub const KEYWORD_OPERATIONS: &[&str] = &["DIV", "MOD", "AND", "OR"];
+
+/// Унарные операции, которые записываются перед операндом: логическое отрицание.
+/// Это тоже ключевые слова.
+pub const UNARY_OPERATIONS: &[&str] = &["NOT"];
+
+/// Знаки, которые могут стоять перед первым слагаемым правой части: `-1 + A`, `+B`.
+/// Ограничение диапазона констант применяется к значению со знаком.
+pub const SIGNS: &[&str] = &["+", "-"];
+
+/// Является ли слово (в верхнем регистре) ключевым словом операции.
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORD_OPERATIONS.contains(&word) || UNARY_OPERATIONS.contains(&word)
+}
+
+/// Прочие зарезервированные слова Modula-2. Лексический анализатор читает их как
+/// идентификаторы, а семантический отвергает (правило
+/// [`Rule::ReservedWord`](crate::config::Rule::ReservedWord)).
+pub const RESERVED_WORDS: &[&str] = &[
+    "ARRAY",
+    "BEGIN",
+    "BY",
+    "CASE",
+    "CONST",
+    "DEFINITION",
+    "DO",
+    "ELSE",
+    "ELSIF",
+    "END",
+    "EXIT",
+    "EXPORT",
+    "FOR",
+    "FROM",
+    "IF",
+    "IMPLEMENTATION",
+    "IMPORT",
+    "IN",
+    "LOOP",
+    "MODULE",
+    "OF",
+    "POINTER",
+    "PROCEDURE",
+    "QUALIFIED",
+    "RECORD",
+    "REPEAT",
+    "RETURN",
+    "SET",
+    "THEN",
+    "TO",
+    "TYPE",
+    "UNTIL",
+    "VAR",
+    "WHILE",
+    "WITH",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Operation {
+    /// Знак операции из [`OPERATIONS`] или ключевое слово из [`KEYWORD_OPERATIONS`]
+    /// и [`UNARY_OPERATIONS`]
+    pub symbol: &'static str,
+    pub position: usize,
+}
+
+/// Род операции: по нему проверка типов различает числовые и логические операнды.
+///
+/// ```
+/// use taafl::ast::OperationKind;
+///
+/// assert_eq!(OperationKind::of("MOD"), OperationKind::Arithmetic);
+/// assert_eq!(OperationKind::of("<>"), OperationKind::Relational);
+/// assert_eq!(OperationKind::of("NOT"), OperationKind::Logical);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationKind {
+    /// `+ - * / % DIV MOD`: числа в числа
+    Arithmetic,
+    /// `= # < > <= >= <>`: числа в логическое значение
+    Relational,
+    /// `AND OR NOT`: логические значения в логическое значение
+    Logical,
+}
+
+impl OperationKind {
+    pub fn of(symbol: &str) -> Self {
+        match symbol {
+            "=" | "#" | "<" | ">" | "<=" | ">=" | "<>" => OperationKind::Relational,
+            "AND" | "OR" | "NOT" => OperationKind::Logical,
+            _ => OperationKind::Arithmetic,
+        }
+    }
+}
+
+impl Operation {
+    pub fn kind(&self) -> OperationKind {
+        OperationKind::of(self.symbol)
+    }
+}
+
+/// Операнд выражения: идентификатор или константа; в правой части ещё литерал
+/// и элемент массива. Имя идентификатора и массива может быть составным, как
+/// у [`Target`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    Identifier {
+        name: String,
+        position: usize,
+        fields: Vec<usize>,
+    },
+    Constant {
+        value: i32,
+        position: usize,
+    },
+    /// Вещественная константа, если она разрешена настройками
+    Real {
+        value: Real,
+        position: usize,
+    },
+    /// Символьная (`'A'`) или строковая (`"text"`) константа: текст без кавычек.
+    /// Есть только в правой части.
+    Literal {
+        value: String,
+        position: usize,
+    },
+    /// Элемент массива `B[I, 1]` - только в правой части: индексы в нём те же,
+    /// что в левой
+    Element {
+        name: String,
+        position: usize,
+        fields: Vec<usize>,
+        indices: Vec<Expression>,
+    },
+    /// Вызов функции `CHR(65)` - только в правой части; аргументы - выражения
+    /// правой части, возможно пустые: `F()`
+    Call {
+        name: String,
+        position: usize,
+        fields: Vec<usize>,
+        arguments: Vec<Expression>,
+    },
+}
+
+impl Operand {
+    pub fn position(&self) -> usize {
+        match self {
+            Operand::Identifier { position, .. }
+            | Operand::Constant { position, .. }
+            | Operand::Real { position, .. }
+            | Operand::Literal { position, .. }
+            | Operand::Element { position, .. }
+            | Operand::Call { position, .. } => *position,
+        }
+    }
+}
+
+/// Литерал `value`, записанный так, чтобы лексический анализатор прочитал его
+/// снова: в двойных кавычках, а если они есть в тексте - в одинарных.
+///
+/// ```
+/// use taafl::ast::quoted;
+///
+/// assert_eq!(quoted("A"), "\"A\"");
+/// assert_eq!(quoted("a \"b\""), "'a \"b\"'");
+/// ```
+pub fn quoted(value: &str) -> String {
+    let quote = if value.contains('"') { '\'' } else { '"' };
+    format!("{}{}{}", quote, value, quote)
+}
+
+/// Символьный ли литерал: ровно один символ, как `'A'` или `"A"` в Modula-2.
+pub fn is_character(value: &str) -> bool {
+    value.chars().count() == 1
+}
+
+/// Значение вещественной константы. Сравнивается побитово, поэтому годится
+/// в ключи таблицы символов: лексический анализатор не выдаёт ни NaN, ни
+/// бесконечностей.
+#[derive(Debug, Clone, Copy)]
+pub struct Real(pub f64);
+
+impl PartialEq for Real {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for Real {}
+
+impl Hash for Real {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl PartialOrd for Real {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Real {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl fmt::Display for Real {
+    /// Запись, которую лексический анализатор снова прочитает как вещественную:
+    /// с точкой и хотя бы одной цифрой после неё.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = self.0.to_string();
+        if text.contains('.') {
+            f.write_str(&text)
+        } else {
+            write!(f, "{}.0", text)
+        }
+    }
+}
+
+impl Expression {
+    /// Все слагаемые в порядке записи.
+    pub fn terms(&self) -> impl Iterator<Item = &Term> {
+        std::iter::once(&self.first).chain(self.rest.iter().map(|(_, term)| term))
+    }
+
+    /// Все операнды в порядке записи.
+    pub fn operands(&self) -> impl Iterator<Item = &Operand> {
+        self.terms().map(|term| &term.operand)
+    }
+}
+
+impl Assignment {
+    /// Копия дерева с нулевыми позициями, для сравнения деревьев разных записей.
+    pub fn without_positions(&self) -> Assignment {
+        Assignment {
+            target: Target {
+                name: self.target.name.clone(),
+                position: 0,
+                fields: vec![0; self.target.fields.len()],
+                indices: self
+                    .target
+                    .indices
+                    .as_ref()
+                    .map(|indices| indices.iter().map(Expression::without_positions).collect()),
+                chain: self
+                    .target
+                    .chain
+                    .iter()
+                    .map(Access::without_positions)
+                    .collect(),
+            },
+            value: self.value.without_positions(),
+        }
+    }
+}
+
+impl Access {
+    fn without_positions(&self) -> Access {
+        match self {
+            Access::Field { name, .. } => Access::Field {
+                name: name.clone(),
+                position: 0,
+            },
+            Access::Indices(indices) => {
+                Access::Indices(indices.iter().map(Expression::without_positions).collect())
+            }
+        }
+    }
+}
+
+impl Expression {
+    fn without_positions(&self) -> Expression {
+        Expression {
+            first: self.first.without_positions(),
+            rest: self
+                .rest
+                .iter()
+                .map(|(operation, term)| (operation.without_position(), term.without_positions()))
+                .collect(),
+        }
+    }
+}
+
+impl Term {
+    /// Знак из [`SIGNS`] непосредственно перед операндом.
+    pub fn sign(&self) -> Option<&Operation> {
+        self.unary.last().filter(|op| SIGNS.contains(&op.symbol))
+    }
+
+    /// Значение операнда-константы вместе со знаком перед ним: у `-1` это -1.
+    ///
+    /// ```
+    /// use taafl::analyzer::Analyzer;
+    ///
+    /// let analyzer = Analyzer::builder().collect_ast(true).build();
+    /// let ast = analyzer.analyze("X := -7 + 2;").ast.unwrap();
+    /// let constants: Vec<_> = ast.value.terms().map(|term| term.constant()).collect();
+    /// assert_eq!(constants, [Some(-7), Some(2)]);
+    /// ```
+    pub fn constant(&self) -> Option<i32> {
+        let Operand::Constant { value, .. } = self.operand else {
+            return None;
+        };
+        match self.sign() {
+            Some(sign) if sign.symbol == "-" => Some(value.saturating_neg()),
+            _ => Some(value),
+        }
+    }
+
+    fn without_positions(&self) -> Term {
+        Term {
+            unary: self.unary.iter().map(Operation::without_position).collect(),
+            operand: self.operand.without_position(),
+        }
+    }
+}
+
+impl Operation {
+    fn without_position(&self) -> Operation {
+        Operation {
+            position: 0,
+            ..*self
+        }
+    }
+}
+
+impl Operand {
+    fn without_position(&self) -> Operand {
+        match self {
+            Operand::Identifier { name, fields, .. } => Operand::Identifier {
+                name: name.clone(),
+                position: 0,
+                fields: vec![0; fields.len()],
+            },
+            Operand::Constant { value, .. } => Operand::Constant {
+                value: *value,
+                position: 0,
+            },
+            Operand::Real { value, .. } => Operand::Real {
+                value: *value,
+                position: 0,
+            },
+            Operand::Literal { value, .. } => Operand::Literal {
+                value: value.clone(),
+                position: 0,
+            },
+            Operand::Element {
+                name,
+                fields,
+                indices,
+                ..
+            } => Operand::Element {
+                name: name.clone(),
+                position: 0,
+                fields: vec![0; fields.len()],
+                indices: indices.iter().map(Expression::without_positions).collect(),
+            },
+            Operand::Call {
+                name,
+                fields,
+                arguments,
+                ..
+            } => Operand::Call {
+                name: name.clone(),
+                position: 0,
+                fields: vec![0; fields.len()],
+                arguments: arguments
+                    .iter()
+                    .map(Expression::without_positions)
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} := {};", self.target, self.value)
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(indices) = &self.indices {
+            write!(f, "[{}]", index_list(indices))?;
+        }
+        for access in &self.chain {
+            write!(f, "{}", access)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::Field { name, .. } => write!(f, ".{}", name),
+            Access::Indices(indices) => write!(f, "[{}]", index_list(indices)),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.first)?;
+        for (operation, term) in &self.rest {
+            write!(f, " {} {}", operation, term)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for operation in &self.unary {
+            write!(f, "{} ", operation)?;
+        }
+        write!(f, "{}", self.operand)
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol)
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Identifier { name, .. } => f.write_str(name),
+            Operand::Constant { value, .. } => write!(f, "{}", value),
+            Operand::Real { value, .. } => write!(f, "{}", value),
+            Operand::Literal { value, .. } => f.write_str(&quoted(value)),
+            Operand::Element { name, indices, .. } => {
+                write!(f, "{}[{}]", name, index_list(indices))
+            }
+            Operand::Call {
+                name, arguments, ..
+            } => write!(f, "{}({})", name, index_list(arguments)),
+        }
+    }
+}
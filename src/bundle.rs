@@ -0,0 +1,640 @@
+//! Все материалы анализа одного оператора в одном ZIP-архиве: ввод, настройки,
+//! отчёт в JSON, таблицы символов в CSV, дерево разбора в DOT, SVG и текстом,
+//! левый вывод и отчёт в Markdown.
+//!
+//! Архив записывается без сжатия и с постоянной датой файлов, поэтому один и тот же
+//! оператор с одними и теми же настройками всегда даёт один и тот же архив. Последний
+//! файл архива - [`MANIFEST`] с суммами SHA-256 остальных; [`verify`] проверяет суммы
+//! и повторяет анализ, так что исправленные вручную результаты обнаруживаются.
+//!
+//! ```
+//! use taafl::bundle::{entries, to_zip};
+//! use taafl::config::AnalyzerConfig;
+//!
+//! let entries = entries("X[I] := I + 1;", &AnalyzerConfig::default());
+//! let names: Vec<&str> = entries.iter().map(|entry| entry.name).collect();
+//! assert!(names.contains(&"report.json") && names.contains(&"tree.svg"));
+//! assert!(to_zip(&entries).starts_with(b"PK\x03\x04"));
+//! ```
+
+use crate::analyzer::{self, Analyzer};
+use crate::ast;
+use crate::config::AnalyzerConfig;
+use crate::diagnostic::SourceMap;
+use crate::model::{AnalysisReport, ConstantRole};
+use crate::tree::Tree;
+use crate::variant::{Variant, Variant20};
+
+/// Имя архива, который графический интерфейс записывает в текущий каталог.
+pub const BUNDLE_FILE: &str = "taafl-export.zip";
+
+/// Суммы SHA-256 файлов архива в формате `sha256sum`: `сумма  имя` в каждой строке.
+pub const MANIFEST: &str = "manifest.sha256";
+
+/// Файл архива.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: &'static str,
+    pub contents: String,
+}
+
+/// Файлы архива для оператора `input`. Дерева разбора нет, если строка не
+/// разобрана синтаксически.
+pub fn entries(input: &str, config: &AnalyzerConfig) -> Vec<Entry> {
+    let analysis = Analyzer::builder()
+        .config(config.clone())
+        .collect_ast(true)
+        .build()
+        .analyze(input);
+    let report = &analysis.report;
+    let derivation = analyzer::derivation(input, config);
+    let derivation = match &derivation {
+        Ok(applied) => applied.clone(),
+        Err(diagnostic) => vec![format!("Вывод прерван: {}", diagnostic)],
+    };
+
+    let mut entries = vec![
+        entry("input.txt", input.to_string()),
+        entry(
+            "config.toml",
+            toml::to_string_pretty(config).unwrap_or_else(|e| format!("# {}\n", e)),
+        ),
+        entry(
+            "report.json",
+            serde_json::to_string_pretty(report).unwrap_or_else(|e| e.to_string()),
+        ),
+        entry("identifiers.csv", identifiers_csv(report)),
+        entry("constants.csv", constants_csv(report)),
+    ];
+    let tree = analysis.ast.as_ref().map(Tree::of);
+    if let Some(tree) = &tree {
+        entries.push(entry("tree.dot", tree.to_dot()));
+        entries.push(entry("tree.svg", tree.to_svg()));
+        entries.push(entry("tree.txt", tree.to_text()));
+    }
+    let table = match analyzer::derivation_table(input, config) {
+        Ok(table) => table,
+        Err(diagnostic) => format!("Вывод прерван: {}\n", diagnostic),
+    };
+    entries.push(entry("derivation.txt", table));
+    let diagnostics: Vec<String> = analysis
+        .diagnostics
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    entries.push(entry(
+        "report.md",
+        markdown(report, &diagnostics, tree.as_ref(), &derivation),
+    ));
+    entries.push(manifest(&entries));
+    entries
+}
+
+/// Манифест [`MANIFEST`] для файлов `entries`, кроме прежнего манифеста.
+///
+/// ```
+/// use taafl::bundle::{manifest, Entry};
+///
+/// let entry = Entry { name: "abc", contents: "abc".to_string() };
+/// assert_eq!(
+///     manifest(&[entry]).contents,
+///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  abc\n"
+/// );
+/// ```
+pub fn manifest(entries: &[Entry]) -> Entry {
+    let contents = entries
+        .iter()
+        .filter(|entry| entry.name != MANIFEST)
+        .map(|entry| {
+            format!(
+                "{}  {}\n",
+                hex(&sha256(entry.contents.as_bytes())),
+                entry.name
+            )
+        })
+        .collect();
+    entry(MANIFEST, contents)
+}
+
+/// Проверяет архив: каждый файл из манифеста на месте и не изменён, других файлов
+/// нет, а повторный анализ записанного ввода с записанными настройками даёт те же
+/// файлы. Возвращает описания всех найденных расхождений.
+pub fn verify(archive: &[u8]) -> Result<(), Vec<String>> {
+    let files = read_zip(archive).map_err(|e| vec![e])?;
+    let file = |name: &str| {
+        files
+            .iter()
+            .find(|(file, _)| file == name)
+            .map(|(_, data)| data.as_slice())
+    };
+    let text = |name: &str| {
+        let data = file(name).ok_or_else(|| format!("В архиве нет {}", name))?;
+        std::str::from_utf8(data).map_err(|_| format!("{} - не текст UTF-8", name))
+    };
+    let manifest = text(MANIFEST).map_err(|e| vec![e])?;
+
+    let mut problems = Vec::new();
+    let mut listed = Vec::new();
+    for line in manifest.lines() {
+        let Some((sum, name)) = line.split_once("  ") else {
+            problems.push(format!("Строка манифеста без суммы: {}", line));
+            continue;
+        };
+        listed.push(name);
+        match file(name) {
+            Some(data) if hex(&sha256(data)) == sum => {}
+            Some(_) => problems.push(format!("{}: сумма SHA-256 не совпадает", name)),
+            None => problems.push(format!("В архиве нет {}", name)),
+        }
+    }
+    for (name, _) in &files {
+        if name != MANIFEST && !listed.contains(&name.as_str()) {
+            problems.push(format!("{}: файла нет в манифесте", name));
+        }
+    }
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    let config = text("config.toml").and_then(|config| {
+        toml::from_str::<AnalyzerConfig>(config).map_err(|e| format!("config.toml: {}", e))
+    });
+    let (input, config) = match (text("input.txt"), config) {
+        (Ok(input), Ok(config)) => (input, config),
+        (input, config) => return Err(input.err().into_iter().chain(config.err()).collect()),
+    };
+    for expected in entries(input, &config) {
+        match file(expected.name) {
+            Some(data) if data == expected.contents.as_bytes() => {}
+            Some(_) if expected.name == MANIFEST => {}
+            Some(_) => problems.push(format!(
+                "{}: не совпадает с повторным анализом ввода",
+                expected.name
+            )),
+            None => problems.push(format!("В архиве нет {}", expected.name)),
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Архив оператора `input`: [`entries`], записанные [`to_zip`].
+pub fn export(input: &str, config: &AnalyzerConfig) -> Vec<u8> {
+    to_zip(&entries(input, config))
+}
+
+fn entry(name: &'static str, contents: String) -> Entry {
+    Entry { name, contents }
+}
+
+/// Столбцы вхождений, а в многострочном тексте - строки и столбцы.
+fn places(positions: &[usize], map: &SourceMap, multiline: bool) -> String {
+    let places: Vec<String> = positions
+        .iter()
+        .map(|&position| {
+            let location = map.location(position);
+            if multiline {
+                format!("{}:{}", location.line, location.column)
+            } else {
+                location.column.to_string()
+            }
+        })
+        .collect();
+    places.join(" ")
+}
+
+/// Символ таблицы констант: запись значения, роль, число вхождений, записи и позиции.
+struct Row<'a> {
+    value: String,
+    role: &'a str,
+    occurrences: usize,
+    lexemes: String,
+    positions: &'a [usize],
+}
+
+fn constant_rows(report: &AnalysisReport) -> Vec<Row<'_>> {
+    let role = |role: ConstantRole| Variant20.constant_role(role);
+    let mut rows = Vec::new();
+    for c in &report.constants {
+        rows.push(Row {
+            value: c.value.to_string(),
+            role: role(c.role),
+            occurrences: c.occurrences,
+            lexemes: c.lexemes.join(" "),
+            positions: &c.positions,
+        });
+    }
+    for r in &report.reals {
+        rows.push(Row {
+            value: ast::Real(r.value).to_string(),
+            role: role(r.role),
+            occurrences: r.occurrences,
+            lexemes: r.lexemes.join(" "),
+            positions: &r.positions,
+        });
+    }
+    for l in &report.literals {
+        rows.push(Row {
+            value: ast::quoted(&l.value),
+            role: role(l.role),
+            occurrences: l.occurrences,
+            lexemes: l.lexemes.join(" "),
+            positions: &l.positions,
+        });
+    }
+    rows
+}
+
+/// Поле CSV: в кавычках, если в нём есть разделитель, кавычка или перевод строки.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn csv(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut text = header.join(",") + "\n";
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        text.push_str(&fields.join(","));
+        text.push('\n');
+    }
+    text
+}
+
+fn identifiers_csv(report: &AnalysisReport) -> String {
+    let map = SourceMap::new(&report.input);
+    let multiline = report.input.contains('\n');
+    let rows = report
+        .identifiers
+        .iter()
+        .map(|id| {
+            vec![
+                id.name.clone(),
+                Variant20.identifier_role(id.role).to_string(),
+                id.occurrences.to_string(),
+                places(&id.positions, &map, multiline),
+            ]
+        })
+        .collect();
+    csv(&["имя", "роль", "вхождений", "столбцы"], rows)
+}
+
+fn constants_csv(report: &AnalysisReport) -> String {
+    let map = SourceMap::new(&report.input);
+    let multiline = report.input.contains('\n');
+    let rows = constant_rows(report)
+        .into_iter()
+        .map(|row| {
+            vec![
+                row.value,
+                row.role.to_string(),
+                row.occurrences.to_string(),
+                row.lexemes,
+                places(row.positions, &map, multiline),
+            ]
+        })
+        .collect();
+    csv(
+        &["значение", "роль", "вхождений", "записи", "столбцы"],
+        rows,
+    )
+}
+
+/// Ячейка таблицы Markdown: `|` и переводы строк её не разрывают.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn markdown(
+    report: &AnalysisReport,
+    diagnostics: &[String],
+    tree: Option<&Tree>,
+    derivation: &[String],
+) -> String {
+    let map = SourceMap::new(&report.input);
+    let multiline = report.input.contains('\n');
+    let mut text = String::from("# Анализ оператора\n\n```\n");
+    text.push_str(&report.input);
+    text.push_str("\n```\n\n");
+    text.push_str(if report.accepted {
+        "**Строка принадлежит языку.**\n"
+    } else {
+        "**Строка не принадлежит языку.**\n"
+    });
+
+    if !diagnostics.is_empty() {
+        text.push_str("\n## Ошибки и предупреждения\n\n");
+        for diagnostic in diagnostics {
+            text.push_str(&format!("- {}\n", diagnostic.replace('\n', "\n  ")));
+        }
+    }
+
+    if !report.identifiers.is_empty() {
+        text.push_str("\n## Идентификаторы\n\n| Имя | Роль | Столбцы |\n|---|---|---|\n");
+        for id in &report.identifiers {
+            text.push_str(&format!(
+                "| {} | {} | {} |\n",
+                cell(&id.name),
+                Variant20.identifier_role(id.role),
+                places(&id.positions, &map, multiline)
+            ));
+        }
+    }
+
+    let constants = constant_rows(report);
+    if !constants.is_empty() {
+        text.push_str(
+            "\n## Константы\n\n| Значение | Роль | Записи | Столбцы |\n|---|---|---|---|\n",
+        );
+        for row in constants {
+            text.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                cell(&row.value),
+                row.role,
+                cell(&row.lexemes),
+                places(row.positions, &map, multiline)
+            ));
+        }
+    }
+
+    if let Some(tree) = tree {
+        text.push_str("\n## Дерево разбора\n\n```\n");
+        text.push_str(&tree.to_text());
+        text.push_str("```\n");
+    }
+
+    text.push_str("\n## Левый вывод\n\n");
+    for (i, line) in derivation.iter().enumerate() {
+        text.push_str(&format!("{}. `{}`\n", i + 1, line));
+    }
+    text
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Сумма SHA-256 по FIPS 180-4.
+fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+
+    // Дополнение: бит 1, нули и длина сообщения в битах
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let choice = (e & f) ^ (!e & g);
+            let t1 = h
+                .wrapping_add(s1)
+                .wrapping_add(choice)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let majority = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(majority);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Контрольная сумма CRC-32 (многочлен 0xEDB88320), как в формате ZIP.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Записывает файлы в ZIP-архив без сжатия. Имена - в UTF-8, дата файлов -
+/// 1 января 1980 года, самая ранняя в формате.
+pub fn to_zip(entries: &[Entry]) -> Vec<u8> {
+    // Версия 2.0, имена в UTF-8 (бит 11), без сжатия, время 00:00, дата 1980-01-01
+    const VERSION: u16 = 20;
+    const UTF8_NAMES: u16 = 1 << 11;
+    const DATE: u16 = (1 << 5) | 1;
+
+    fn u16le(out: &mut Vec<u8>, value: u16) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    fn u32le(out: &mut Vec<u8>, value: u32) {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    /// Общая часть локального заголовка и записи каталога: от версии до длины имени.
+    fn header(out: &mut Vec<u8>, crc: u32, size: u32, name_len: u16) {
+        u16le(out, VERSION);
+        u16le(out, UTF8_NAMES);
+        u16le(out, 0);
+        u16le(out, 0);
+        u16le(out, DATE);
+        u32le(out, crc);
+        u32le(out, size);
+        u32le(out, size);
+        u16le(out, name_len);
+        u16le(out, 0);
+    }
+
+    let mut zip = Vec::new();
+    let mut directory = Vec::new();
+    for entry in entries {
+        let (name, data) = (entry.name.as_bytes(), entry.contents.as_bytes());
+        let (crc, size, name_len) = (crc32(data), data.len() as u32, name.len() as u16);
+        let offset = zip.len() as u32;
+
+        u32le(&mut zip, 0x0403_4b50);
+        header(&mut zip, crc, size, name_len);
+        zip.extend_from_slice(name);
+        zip.extend_from_slice(data);
+
+        u32le(&mut directory, 0x0201_4b50);
+        u16le(&mut directory, VERSION);
+        header(&mut directory, crc, size, name_len);
+        // Комментарий, номер диска, внутренние и внешние атрибуты
+        u16le(&mut directory, 0);
+        u16le(&mut directory, 0);
+        u16le(&mut directory, 0);
+        u32le(&mut directory, 0);
+        u32le(&mut directory, offset);
+        directory.extend_from_slice(name);
+    }
+
+    let (start, size) = (zip.len() as u32, directory.len() as u32);
+    zip.append(&mut directory);
+    u32le(&mut zip, 0x0605_4b50);
+    u16le(&mut zip, 0);
+    u16le(&mut zip, 0);
+    u16le(&mut zip, entries.len() as u16);
+    u16le(&mut zip, entries.len() as u16);
+    u32le(&mut zip, size);
+    u32le(&mut zip, start);
+    u16le(&mut zip, 0);
+    zip
+}
+
+/// Файлы архива, записанного без сжатия, как их записывает [`to_zip`]: имена и
+/// содержимое в порядке центрального каталога.
+pub fn read_zip(archive: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let broken = || "Архив повреждён или это не ZIP".to_string();
+    let u16_at = |at: usize| -> Result<usize, String> {
+        let bytes = archive.get(at..at + 2).ok_or_else(broken)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+    };
+    let u32_at = |at: usize| -> Result<usize, String> {
+        let bytes = archive.get(at..at + 4).ok_or_else(broken)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    };
+
+    // Конец центрального каталога ищется с конца: за ним может быть комментарий
+    let end = (0..archive.len().saturating_sub(21))
+        .rev()
+        .find(|&at| archive[at..].starts_with(b"PK\x05\x06"))
+        .ok_or_else(broken)?;
+    let (count, mut at) = (u16_at(end + 10)?, u32_at(end + 16)?);
+
+    let mut files = Vec::new();
+    for _ in 0..count {
+        if u32_at(at)? != 0x0201_4b50 {
+            return Err(broken());
+        }
+        let (method, crc, size) = (u16_at(at + 10)?, u32_at(at + 16)?, u32_at(at + 20)?);
+        let name_len = u16_at(at + 28)?;
+        let skipped = name_len + u16_at(at + 30)? + u16_at(at + 32)?;
+        let local = u32_at(at + 42)?;
+        let name = archive
+            .get(at + 46..at + 46 + name_len)
+            .ok_or_else(broken)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        if method != 0 {
+            return Err(format!("{}: сжатые файлы не поддерживаются", name));
+        }
+
+        let start = local + 30 + u16_at(local + 26)? + u16_at(local + 28)?;
+        let data = archive.get(start..start + size).ok_or_else(broken)?;
+        if crc32(data) as usize != crc {
+            return Err(format!("{}: контрольная сумма CRC-32 не совпадает", name));
+        }
+        files.push((name, data.to_vec()));
+        at += 46 + skipped;
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn bundle_of_rejected_statement_has_no_tree() {
+        use crate::bundle::{entries, to_zip};
+
+        let config = Default::default();
+        let accepted = entries("X := 1;", &config);
+        assert!(accepted.iter().any(|entry| entry.name == "tree.svg"));
+        let derivation = accepted.iter().find(|entry| entry.name == "derivation.txt");
+        let rows: Vec<&str> = derivation.unwrap().contents.lines().collect();
+        assert!(rows[0].starts_with("Шаг  Продукция"));
+        assert!(rows[2].starts_with("  0 ") && rows[2].ends_with(" statement"));
+
+        let rejected = entries("X := ;", &config);
+        assert!(rejected
+            .iter()
+            .all(|entry| !entry.name.starts_with("tree.")));
+        let derivation = rejected.iter().find(|entry| entry.name == "derivation.txt");
+        assert!(derivation.unwrap().contents.contains("Вывод прерван"));
+
+        // Конец центрального каталога: сигнатура и число файлов
+        let zip = to_zip(&rejected);
+        let end = &zip[zip.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(
+            u16::from_le_bytes([end[10], end[11]]) as usize,
+            rejected.len()
+        );
+    }
+
+    #[test]
+    fn verify_detects_edited_bundle_results() {
+        use crate::bundle::{entries, manifest, to_zip, verify, MANIFEST};
+
+        let original = entries("X[I] := I + 1;", &Default::default());
+        assert_eq!(original.last().unwrap().name, MANIFEST);
+        assert_eq!(verify(&to_zip(&original)), Ok(()));
+
+        let mut edited = original.clone();
+        let report = edited.iter_mut().find(|e| e.name == "report.json").unwrap();
+        report.contents = report.contents.replace("false", "true");
+        let problems = verify(&to_zip(&edited)).unwrap_err();
+        assert_eq!(problems, ["report.json: сумма SHA-256 не совпадает"]);
+
+        // Пересчитанный манифест не помогает: расходится повторный анализ
+        let last = edited.len() - 1;
+        edited[last] = manifest(&edited);
+        let problems = verify(&to_zip(&edited)).unwrap_err();
+        assert_eq!(
+            problems,
+            ["report.json: не совпадает с повторным анализом ввода"]
+        );
+    }
+}
@@ -0,0 +1,1142 @@
+use std::io::{ErrorKind, Read, Write};
+use std::path::Path;
+use taafl::analyzer::sequence::{analyze_sequence, sequence_lists};
+use taafl::analyzer::{
+    analyze_line_with, analyze_report, split_statements_with, suppressed, token_dump, token_table,
+    Analyzer, TokenColumn,
+};
+use taafl::bundle;
+use taafl::compare::compare;
+use taafl::config::{
+    Allow, AnalyzerConfig, AssignOperator, Brackets, ConstantRange, FileDirective,
+    OperationSign, ParserKind, Profile, Rule, ScannerKind, SymbolOrder, Terminator, CONFIG_FILE,
+};
+#[cfg(feature = "corpus")]
+use taafl::corpus::{records, Query};
+use taafl::generator::Generator;
+use taafl::grammar::ambiguity::{self, Derivation, Verdict};
+use taafl::grammar::{export, scaffold, Grammar};
+use taafl::model::{AnalysisReport, SequenceReport};
+use taafl::sanitize::{normalize_typography, sanitize};
+#[cfg(feature = "corpus")]
+use taafl::storage::FileStorage;
+use taafl::tree::Tree;
+use taafl::variant::{Variant, Variant20};
+
+#[cfg(test)]
+mod tests;
+
+/// Как `print!`, но закрытый читателем вывод (`taafl ... | head`) завершает
+/// программу успешно, а не паникой; см. [`emit`].
+macro_rules! out {
+    ($($arg:tt)*) => {
+        emit(format_args!($($arg)*))
+    };
+}
+
+/// Как `println!`, с обработкой закрытого вывода из [`out!`].
+macro_rules! outln {
+    () => {
+        emit(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        emit(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+/// Записывает текст в стандартный вывод. Если читатель закрыл канал, выводить
+/// больше некому: программа завершается с кодом 0. Другая ошибка записи -
+/// код 2.
+fn emit(text: std::fmt::Arguments) {
+    match std::io::stdout().lock().write_fmt(text) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => std::process::exit(0),
+        Err(e) => {
+            eprintln!("Не удалось записать результат: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+const USAGE: &str = "\
+Использование: taafl [ОПЦИИ] [ОПЕРАТОР]
+       taafl verify АРХИВ
+       taafl corpus add МЕТКА [ФАЙЛ]
+       taafl corpus query [--code КОД] [--tag МЕТКА] [--from ДАТА] [--to ДАТА]
+       taafl tokens [--columns СПИСОК] [ОПЕРАТОР]
+       taafl scaffold --grammar ФАЙЛ [--out КАТАЛОГ]
+
+Без аргументов запускается графический интерфейс, --deterministic этого не
+меняет, но символы в нём и в его экспорте идут по алфавиту. С единственной
+опцией --software-render он рисуется на процессоре, без видеокарты: для машин,
+на которых окно не открывается или отображается с искажениями.
+Если ОПЕРАТОР не указан или равен '-', он читается из стандартного ввода.
+taafl verify проверяет архив --export: суммы SHA-256 файлов и совпадение
+результатов с повторным анализом записанного ввода с записанными настройками.
+taafl corpus ведёт базу корпусов taafl-corpus.json (со сборкой sqlite - базу
+SQLite taafl-corpus.db) в текущем каталоге: add разбивает ФАЙЛ (или стандартный
+ввод) на операторы и добавляет их результаты с МЕТКОЙ и сегодняшней датой, query
+выводит операторы с кодом диагностики, меткой и датами добавления (ГГГГ-ММ-ДД)
+в заданных границах (возможность сборки corpus, включена по умолчанию).
+taafl tokens выводит только таблицу лексем ОПЕРАТОРА с настройками taafl.toml.
+taafl scaffold строит по грамматике LL(1) из ФАЙЛА (в той же записи, что для
+--ambiguity) заготовку рекурсивного спуска на Rust: перечисление лексем и функцию
+разбора на каждый нетерминал. Заготовка записывается в файл parser.rs КАТАЛОГА,
+без --out - выводится; существующий файл не перезаписывается.
+Первая строка ввода может задавать настройки для этого ввода (файла):
+  (* taafl: profile=extended; recovery=on; hints=off; order=sorted; max-errors=5 *)
+Сообщения выводятся только на русском: lang=ru принимается, другой язык
+пропускается с замечанием.
+Комментарий перед оператором отключает для него семантические правила:
+  (* taafl:allow(constant-range, identifier-length) *) A := 0;
+
+Опции:
+  --config ФАЙЛ      файл настроек (по умолчанию taafl.toml, если он есть);
+                     остальные опции переопределяют его значения
+  --profile ИМЯ      профиль настроек: strict (Вариант 20 строго),
+                     extended (Расширенный), teaching (Учебный с подсказками)
+  --recover          не останавливаться на первой ошибке
+                     и пропускать недопустимые символы
+  --max-errors N     выводить не более N ошибок (0 - все)
+  --max-input N      не анализировать ввод длиннее N символов (по умолчанию
+                     10000, 0 - без ограничения)
+  --format ФОРМАТ    формат вывода: text (по умолчанию), json или diagnostics
+                     (все ошибки и предупреждения, по одной в строке)
+                     отчёт json, записанный в taafl-report.json, открывается
+                     в графическом интерфейсе кнопкой «Открыть отчёт»
+  --file ФАЙЛ        читать ввод из файла (можно указать несколько раз)
+  --many             разбить ввод на операторы по завершающему символу
+                     и проверить каждый (в формате json - массив отчётов)
+  --sequence         то же, что --many, и сводная таблица символов всех
+                     операторов: символ в одной роли - одной строкой (в формате
+                     json - отчёт последовательности с отчётами операторов)
+  -v, --verbose      перечислять нарушения, скрытые комментариями allow
+  -vv                то же и таблица левого вывода по грамматике (только text)
+  --tree             нарисовать дерево разбора псевдографикой (только text)
+  --tokens           вывести таблицу лексем (только text)
+  --columns СПИСОК   столбцы таблицы лексем через запятую: number (№), position,
+                     kind (тип), lexeme, value (по умолчанию - все)
+  --order ПОРЯДОК    порядок символов: appearance (по появлению, по умолчанию)
+                     или sorted (по алфавиту и по возрастанию)
+  --preserve-case    выводить имена в записанном регистре, а не в верхнем
+  --parser ВИД       синтаксический анализ: descent (рекурсивный спуск,
+                     по умолчанию), table (таблица LL(1) по грамматике)
+                     или earley (алгоритм Эрли, сообщает о неоднозначности)
+  --scanner ВИД      лексический анализ: direct (посимвольный, по умолчанию)
+                     или dfa (таблица переходов конечного автомата)
+  --terminator ВИД   конец оператора: semicolon (';', по умолчанию), dot ('.')
+                     или none (конец строки; с --many - по оператору в строке);
+                     с dot точка только заканчивает оператор, полей записей
+                     (A.F) нет
+  --brackets ВИД     скобки индексов: square ('[ ]', по умолчанию) или round ('( )')
+  --assign СПИСОК    лексемы присваивания через запятую: colon-equals (':=',
+                     по умолчанию), equals ('=') и arrow ('←')
+  --operations СПИСОК
+                     знаки операций через запятую (по умолчанию
+                     +,-,*,/,=,#,<,>,<=,>=,<>; есть ещё % - остаток)
+  --reals            разрешить вещественные константы (3.14, 1.0E5)
+  --underscores      разрешить подчёркивание внутри идентификаторов (MAX_LEN)
+  --unicode          разрешить буквы любых алфавитов в идентификаторах (МАСС[И])
+  --constants ДИАП   диапазон констант: variant (варианта, по умолчанию),
+                     integer ([-32768..32767]), cardinal ([0..65535]) или МИН..МАКС
+  --zero             допускать константу 0 при диапазоне от 1
+  --typography       заменять типографские знаки (×, ÷, –, ”, неразрывный пробел)
+                     знаками ASCII
+  --compact-indices  писать список индексов без пробелов (A[I,J])
+  --spaced-assign    отделять присваивание пробелами с обеих сторон (X := 1)
+  --max-terms N      не более N слагаемых в правой части (0 - без ограничения)
+  --max-indices N    не более N индексов в одном списке индексов
+  --max-nesting N    вложенность скобок индексов и вызовов не больше N
+  --compare А,Б      сравнить результаты с профилями А и Б (только text)
+  --export ФАЙЛ      записать в ZIP-архив ввод, настройки, отчёт JSON, таблицы
+                     CSV, дерево DOT, SVG и текстом, левый вывод, отчёт Markdown
+                     и суммы SHA-256 (один оператор; проверка - taafl verify)
+  --rules            перечислить семантические правила
+  --grammar ФОРМАТ   вывести грамматику: ebnf, antlr (файл .g4),
+                     yacc (файл .y) или lalrpop; операции - по --operations
+  --ambiguity ФАЙЛ   проверить, имеет ли ОПЕРАТОР два различных вывода
+                     в грамматике из ФАЙЛА (только text)
+  --generate N       вывести N случайных правильных операторов
+  --seed ЧИСЛО       зерно для --generate (по умолчанию - от текущего времени)
+  --deterministic    воспроизводимые результаты для сравнения запусков: символы
+                     по алфавиту (как --order sorted), зерно --generate 0 вместо
+                     времени, дата corpus add 1970-01-01, журнал без времени
+                     и цвета; от локали вывод не зависит и без этой опции
+  -h, --help         показать эту справку
+
+Журнал отладки выводится в stderr, если задана переменная TAAFL_LOG,
+например TAAFL_LOG=taafl=debug.";
+
+/// Опция воспроизводимых результатов: допустима везде, в том числе с подкомандами.
+pub const DETERMINISTIC: &str = "--deterministic";
+
+/// Опции, за которыми следует значение: [`DETERMINISTIC`] на месте значения -
+/// это значение, а не опция.
+const VALUE_OPTIONS: &[&str] = &[
+    "--grammar", "--generate", "--seed", "--ambiguity", "--export", "--config", "--profile",
+    "--compare", "--columns", "--file", "--max-errors", "--max-input", "--format", "--order",
+    "--parser", "--scanner", "--terminator", "--brackets", "--assign", "--operations",
+    "--constants", "--max-terms", "--max-indices", "--max-nesting", "--out", "--code", "--tag",
+    "--from", "--to",
+];
+
+/// Убирает [`DETERMINISTIC`] оттуда, где ожидается опция, и сообщает, был ли он.
+/// Значения опций (`--file --deterministic`) и метка `corpus add` остаются.
+pub fn take_deterministic(args: Vec<String>) -> (bool, Vec<String>) {
+    let mut deterministic = false;
+    let mut kept: Vec<String> = Vec::with_capacity(args.len());
+    let mut operand = false;
+    for arg in args {
+        if arg == DETERMINISTIC && !operand {
+            deterministic = true;
+            continue;
+        }
+        operand = !operand && VALUE_OPTIONS.contains(&arg.as_str());
+        kept.push(arg);
+        operand |= kept == ["corpus", "add"];
+    }
+    (deterministic, kept)
+}
+
+/// Зерно `--generate` без `--seed` в режиме [`DETERMINISTIC`].
+const DETERMINISTIC_SEED: u64 = 0;
+
+/// Дата записей `corpus add` в режиме [`DETERMINISTIC`].
+#[cfg(feature = "corpus")]
+const DETERMINISTIC_DATE: &str = "1970-01-01";
+
+/// `taafl verify АРХИВ`: 0 - архив цел, 1 - найдены расхождения, 2 - ошибка запуска.
+fn verify(args: &[String]) -> i32 {
+    let [path] = args else {
+        eprintln!("verify: ожидался один путь к архиву\n\n{}", USAGE);
+        return 2;
+    };
+    let archive = match std::fs::read(path) {
+        Ok(archive) => archive,
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            return 2;
+        }
+    };
+    match bundle::verify(&archive) {
+        Ok(()) => {
+            outln!("{}: архив цел, результаты совпадают с повторным анализом", path);
+            0
+        }
+        Err(problems) => {
+            for problem in problems {
+                outln!("{}: {}", path, problem);
+            }
+            1
+        }
+    }
+}
+
+/// `taafl corpus add|query ...`: 0 - успех, 2 - ошибка запуска или хранилища.
+/// С `deterministic` записи добавляются с постоянной датой.
+#[cfg(feature = "corpus")]
+fn corpus(args: &[String], deterministic: bool) -> i32 {
+    let mut storage = FileStorage::default();
+    let mut backend = match taafl::corpus::open(&mut storage) {
+        Ok(backend) => backend,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    match args {
+        [command, tag, source @ ..] if command == "add" && source.len() <= 1 => {
+            let config = match AnalyzerConfig::load_or_default(Path::new(CONFIG_FILE)) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return 2;
+                }
+            };
+            let text = match source.first().map(String::as_str) {
+                Some(path) if path != "-" => std::fs::read_to_string(path)
+                    .map_err(|e| format!("Не удалось прочитать {}: {}", path, e)),
+                _ => {
+                    let mut text = String::new();
+                    std::io::stdin()
+                        .read_to_string(&mut text)
+                        .map(|_| text)
+                        .map_err(|e| format!("Не удалось прочитать стандартный ввод: {}", e))
+                }
+            };
+            let text = match text {
+                Ok(text) if config.typography => normalize_typography(&sanitize(&text).0).0,
+                Ok(text) => sanitize(&text).0,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return 2;
+                }
+            };
+            let date = if deterministic {
+                DETERMINISTIC_DATE.to_string()
+            } else {
+                taafl::corpus::today()
+            };
+            let records = records(&text, tag, &date, &config);
+            let added = records.len();
+            if let Err(e) = backend.append(records) {
+                eprintln!("{}", e);
+                return 2;
+            }
+            outln!("Добавлено операторов: {} (метка {})", added, tag);
+            0
+        }
+        [command, options @ ..] if command == "query" => {
+            let mut query = Query::default();
+            let mut options = options.iter();
+            while let Some(option) = options.next() {
+                let field = match option.as_str() {
+                    "--code" => &mut query.code,
+                    "--tag" => &mut query.tag,
+                    "--from" => &mut query.from,
+                    "--to" => &mut query.to,
+                    _ => {
+                        eprintln!("corpus query: неизвестная опция {}\n\n{}", option, USAGE);
+                        return 2;
+                    }
+                };
+                match options.next() {
+                    Some(value) => *field = Some(value.clone()),
+                    None => {
+                        eprintln!("{}: ожидалось значение", option);
+                        return 2;
+                    }
+                }
+            }
+            let found = backend.query(&query).and_then(|found| Ok((found, backend.count()?)));
+            let (found, total) = match found {
+                Ok(found) => found,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return 2;
+                }
+            };
+            for record in &found {
+                outln!("{}", record);
+            }
+            outln!("Найдено: {} из {}", found.len(), total);
+            0
+        }
+        _ => {
+            eprintln!("corpus: ожидалось add МЕТКА [ФАЙЛ] или query [ОПЦИИ]\n\n{}", USAGE);
+            2
+        }
+    }
+}
+
+/// `taafl tokens [--columns СПИСОК] [ОПЕРАТОР]`: 0 - лексемы выделены, 1 - лексическая
+/// ошибка, 2 - ошибка запуска.
+fn tokens(args: &[String]) -> i32 {
+    let (columns, statement) = match args {
+        [option, list, statement @ ..] if option == "--columns" => {
+            match TokenColumn::parse_list(list) {
+                Some(columns) => (columns, statement),
+                None => {
+                    eprintln!("--columns: ожидались столбцы number, position, kind, lexeme, value");
+                    return 2;
+                }
+            }
+        }
+        _ => (TokenColumn::ALL.to_vec(), args),
+    };
+    let config = match AnalyzerConfig::load_or_default(Path::new(CONFIG_FILE)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    let input = if statement.is_empty() || statement == ["-"] {
+        let mut buffer = String::new();
+        if let Err(e) = std::io::stdin().read_to_string(&mut buffer) {
+            eprintln!("Не удалось прочитать стандартный ввод: {}", e);
+            return 2;
+        }
+        buffer
+    } else {
+        statement.join(" ")
+    };
+    let mut input = sanitize(&input).0;
+    if config.typography {
+        input = normalize_typography(&input).0;
+    }
+    let input = input.trim_end_matches('\n');
+    match token_dump(input, &config) {
+        Ok(dump) => {
+            out!("{}", dump.table(&columns));
+            0
+        }
+        Err(diagnostic) => {
+            outln!("{}", diagnostic);
+            1
+        }
+    }
+}
+
+/// `taafl scaffold --grammar ФАЙЛ [--out КАТАЛОГ]`: 0 - заготовка построена,
+/// 1 - грамматика не LL(1), 2 - ошибка запуска.
+fn scaffold(args: &[String]) -> i32 {
+    let (mut grammar_path, mut out) = (None, None);
+    let mut options = args.iter();
+    while let Some(option) = options.next() {
+        let field = match option.as_str() {
+            "--grammar" => &mut grammar_path,
+            "--out" => &mut out,
+            _ => {
+                eprintln!("scaffold: неизвестная опция {}\n\n{}", option, USAGE);
+                return 2;
+            }
+        };
+        match options.next() {
+            Some(value) => *field = Some(Path::new(value)),
+            None => {
+                eprintln!("{}: ожидался путь", option);
+                return 2;
+            }
+        }
+    }
+    let Some(grammar_path) = grammar_path else {
+        eprintln!("scaffold: ожидался --grammar ФАЙЛ\n\n{}", USAGE);
+        return 2;
+    };
+    let grammar = std::fs::read_to_string(grammar_path)
+        .map_err(|e| format!("Не удалось прочитать {}: {}", grammar_path.display(), e))
+        .and_then(|text| {
+            Grammar::parse(&text).map_err(|e| format!("{}: {}", grammar_path.display(), e))
+        });
+    let grammar = match grammar {
+        Ok(grammar) => grammar,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    let code = match scaffold::to_rust(&grammar) {
+        Ok(code) => code,
+        Err(conflicts) => {
+            outln!("{}: грамматика не LL(1), ветви разбора не выбрать:", grammar_path.display());
+            for conflict in conflicts {
+                outln!("  {}", conflict);
+            }
+            return 1;
+        }
+    };
+    let Some(out) = out else {
+        out!("{}", code);
+        return 0;
+    };
+    let path = out.join(scaffold::SCAFFOLD_FILE);
+    if path.exists() {
+        eprintln!("{} уже существует; укажите другой каталог", path.display());
+        return 2;
+    }
+    match std::fs::write(&path, code) {
+        Ok(()) => {
+            outln!("Записан {}", path.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("Не удалось записать {}: {}", path.display(), e);
+            2
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    /// Отчёт [`taafl::model::AnalysisReport`] в JSON
+    Json,
+    /// Строки [`taafl::diagnostic::Diagnostic`]
+    Diagnostics,
+}
+
+/// Консольный режим для аргументов без [`DETERMINISTIC`] (см. [`take_deterministic`]).
+/// Возвращает код завершения процесса.
+pub fn run(args: &[String], deterministic: bool) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("verify") => return verify(&args[1..]),
+        #[cfg(feature = "corpus")]
+        Some("corpus") => return corpus(&args[1..], deterministic),
+        #[cfg(not(feature = "corpus"))]
+        Some("corpus") => {
+            eprintln!("corpus: taafl собран без возможности corpus");
+            return 2;
+        }
+        Some("tokens") => return tokens(&args[1..]),
+        Some("scaffold") => return scaffold(&args[1..]),
+        _ => {}
+    }
+    let config_path = match args.iter().position(|arg| arg == "--config") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) => Some(Path::new(path)),
+            None => {
+                eprintln!("--config: ожидался путь к файлу");
+                return 2;
+            }
+        },
+        None => None,
+    };
+    let config = match config_path {
+        Some(path) => AnalyzerConfig::load(path),
+        None => AnalyzerConfig::load_or_default(Path::new(CONFIG_FILE)),
+    };
+    let mut config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    let mut format = Format::Text;
+    let mut many = false;
+    let mut sequence = false;
+    let mut verbose = false;
+    let mut tree = false;
+    let mut steps = false;
+    let mut token_columns: Option<Vec<TokenColumn>> = None;
+    let mut files: Vec<&str> = Vec::new();
+    let mut profiles: Option<(Profile, Profile)> = None;
+    let mut statement: Vec<&str> = Vec::new();
+    let mut generate: Option<usize> = None;
+    let mut grammar_format: Option<&str> = None;
+    let mut seed: Option<u64> = None;
+    let mut grammar_path: Option<&str> = None;
+    let mut export_path: Option<&str> = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                outln!("{}", USAGE);
+                return 0;
+            }
+            "--rules" => {
+                print_rules(&config);
+                return 0;
+            }
+            "--grammar" => match args.next().map(String::as_str) {
+                Some(format @ ("ebnf" | "antlr" | "yacc" | "lalrpop")) => {
+                    grammar_format = Some(format)
+                }
+                _ => {
+                    eprintln!("--grammar: ожидалось ebnf, antlr, yacc или lalrpop");
+                    return 2;
+                }
+            },
+            "--generate" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) => generate = Some(n),
+                _ => {
+                    eprintln!("--generate: ожидалось неотрицательное целое число");
+                    return 2;
+                }
+            },
+            "--seed" => match args.next().map(|n| n.parse::<u64>()) {
+                Some(Ok(n)) => seed = Some(n),
+                _ => {
+                    eprintln!("--seed: ожидалось неотрицательное целое число");
+                    return 2;
+                }
+            },
+            "--ambiguity" => match args.next() {
+                Some(path) => grammar_path = Some(path),
+                None => {
+                    eprintln!("--ambiguity: ожидался путь к файлу грамматики");
+                    return 2;
+                }
+            },
+            "--export" => match args.next() {
+                Some(path) => export_path = Some(path),
+                None => {
+                    eprintln!("--export: ожидался путь к архиву");
+                    return 2;
+                }
+            },
+            "--config" => {
+                // Уже прочитан выше
+                args.next();
+            }
+            "--profile" => match args.next().and_then(|id| Profile::from_id(id)) {
+                Some(profile) => config.apply_profile(profile),
+                None => {
+                    eprintln!("--profile: ожидалось strict, extended или teaching");
+                    return 2;
+                }
+            },
+            "--compare" => {
+                let pair = args.next().and_then(|arg| arg.split_once(','));
+                match pair.and_then(|(a, b)| Some((Profile::from_id(a)?, Profile::from_id(b)?))) {
+                    Some(pair) => profiles = Some(pair),
+                    None => {
+                        eprintln!("--compare: ожидались два профиля через запятую, например strict,extended");
+                        return 2;
+                    }
+                }
+            }
+            "--many" => many = true,
+            "--sequence" => {
+                many = true;
+                sequence = true;
+            }
+            "-v" | "--verbose" => verbose = true,
+            "-vv" => {
+                verbose = true;
+                steps = true;
+            }
+            "--tree" => tree = true,
+            "--tokens" => {
+                token_columns.get_or_insert_with(|| TokenColumn::ALL.to_vec());
+            }
+            "--columns" => match args.next().and_then(|list| TokenColumn::parse_list(list)) {
+                Some(columns) => token_columns = Some(columns),
+                None => {
+                    eprintln!("--columns: ожидались столбцы number, position, kind, lexeme, value");
+                    return 2;
+                }
+            },
+            "--file" => match args.next() {
+                Some(path) => files.push(path),
+                None => {
+                    eprintln!("--file: ожидался путь к файлу");
+                    return 2;
+                }
+            },
+            "--recover" => {
+                config.recovery = true;
+                config.profile = None;
+            }
+            "--max-errors" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) => config.max_errors = n,
+                _ => {
+                    eprintln!("--max-errors: ожидалось неотрицательное целое число");
+                    return 2;
+                }
+            },
+            "--max-input" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) => config.max_input = n,
+                _ => {
+                    eprintln!("--max-input: ожидалось неотрицательное целое число");
+                    return 2;
+                }
+            },
+            "--format" => match args.next().map(String::as_str) {
+                Some("text") => format = Format::Text,
+                Some("json") => format = Format::Json,
+                Some("diagnostics") => format = Format::Diagnostics,
+                _ => {
+                    eprintln!("--format: ожидалось text, json или diagnostics");
+                    return 2;
+                }
+            },
+            "--order" => match args.next().and_then(|id| SymbolOrder::from_id(id)) {
+                Some(order) => config.order = order,
+                None => {
+                    eprintln!("--order: ожидалось appearance или sorted");
+                    return 2;
+                }
+            },
+            "--preserve-case" => config.preserve_case = true,
+            "--parser" => match args.next().and_then(|id| ParserKind::from_id(id)) {
+                Some(parser) => config.parser = parser,
+                None => {
+                    eprintln!("--parser: ожидалось descent, table или earley");
+                    return 2;
+                }
+            },
+            "--scanner" => match args.next().and_then(|id| ScannerKind::from_id(id)) {
+                Some(scanner) => config.scanner = scanner,
+                None => {
+                    eprintln!("--scanner: ожидалось direct или dfa");
+                    return 2;
+                }
+            },
+            "--terminator" => match args.next().and_then(|id| Terminator::from_id(id)) {
+                Some(terminator) => config.terminator = terminator,
+                None => {
+                    eprintln!("--terminator: ожидалось semicolon, dot или none");
+                    return 2;
+                }
+            },
+            "--brackets" => match args.next().and_then(|id| Brackets::from_id(id)) {
+                Some(brackets) => config.brackets = brackets,
+                None => {
+                    eprintln!("--brackets: ожидалось square или round");
+                    return 2;
+                }
+            },
+            "--assign" => match args.next().and_then(|list| AssignOperator::parse_list(list)) {
+                Some(assign) => config.assign = assign,
+                None => {
+                    eprintln!("--assign: ожидался список из colon-equals, equals, arrow");
+                    return 2;
+                }
+            },
+            "--operations" => match args.next().and_then(|list| OperationSign::parse_list(list)) {
+                Some(operations) => config.operations = operations,
+                None => {
+                    eprintln!("--operations: ожидался список из + - * / % = # < > <= >= <>");
+                    return 2;
+                }
+            },
+            "--reals" => config.reals = true,
+            "--underscores" => config.underscores = true,
+            "--unicode" => config.unicode_identifiers = true,
+            "--constants" => match args.next().and_then(|id| ConstantRange::from_id(id)) {
+                Some(constants) => config.constants = constants,
+                None => {
+                    eprintln!("--constants: ожидалось variant, integer, cardinal или МИН..МАКС");
+                    return 2;
+                }
+            },
+            "--zero" => {
+                config.zero = true;
+                config.profile = None;
+            }
+            "--typography" => config.typography = true,
+            "--compact-indices" => config.whitespace.compact_indices = true,
+            "--spaced-assign" => config.whitespace.spaced_assign = true,
+            "--max-terms" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) => config.limits.max_terms = n,
+                _ => {
+                    eprintln!("--max-terms: ожидалось неотрицательное целое число");
+                    return 2;
+                }
+            },
+            "--max-indices" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) => config.limits.max_indices = n,
+                _ => {
+                    eprintln!("--max-indices: ожидалось неотрицательное целое число");
+                    return 2;
+                }
+            },
+            "--max-nesting" => match args.next().map(|n| n.parse::<usize>()) {
+                Some(Ok(n)) => config.limits.max_nesting = n,
+                _ => {
+                    eprintln!("--max-nesting: ожидалось неотрицательное целое число");
+                    return 2;
+                }
+            },
+            _ if arg.starts_with("--") => {
+                eprintln!("Неизвестная опция: {}\n\n{}", arg, USAGE);
+                return 2;
+            }
+            _ => statement.push(arg),
+        }
+    }
+
+    if deterministic {
+        config.order = SymbolOrder::Sorted;
+    }
+
+    // Грамматика выводится после разбора всех аргументов: классы операций в ней -
+    // по --operations
+    if let Some(format) = grammar_format {
+        let grammar = Grammar::builtin();
+        match format {
+            "ebnf" => out!("{}", export::to_ebnf(&grammar, &config)),
+            "antlr" => out!("{}", export::to_antlr(&grammar, "Taafl", &config)),
+            "yacc" => out!("{}", export::to_yacc(&grammar)),
+            _ => out!("{}", export::to_lalrpop(&grammar, &config)),
+        }
+        return 0;
+    }
+
+    if let Some(count) = generate {
+        let seed = seed.or(deterministic.then_some(DETERMINISTIC_SEED));
+        let seed = seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64)
+        });
+        for statement in Generator::new(seed).take(count) {
+            outln!("{}", statement);
+        }
+        return 0;
+    }
+
+    if profiles.is_some() && format != Format::Text {
+        eprintln!("--compare поддерживает только формат text");
+        return 2;
+    }
+    if grammar_path.is_some() && (format != Format::Text || profiles.is_some()) {
+        eprintln!("--ambiguity поддерживает только формат text без --compare");
+        return 2;
+    }
+    if sequence && (profiles.is_some() || grammar_path.is_some()) {
+        eprintln!("--sequence не сочетается с --compare и --ambiguity");
+        return 2;
+    }
+    if export_path.is_some() && (many || files.len() > 1) {
+        eprintln!("--export записывает архив одного оператора: без --many и нескольких --file");
+        return 2;
+    }
+    let grammar = match grammar_path.map(|path| {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Не удалось прочитать {}: {}", path, e))?;
+        Grammar::parse(&text).map_err(|e| format!("{}: {}", path, e))
+    }) {
+        Some(Ok(grammar)) => Some(grammar),
+        Some(Err(e)) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+        None => None,
+    };
+
+    // Источники ввода: (имя файла, текст)
+    let mut sources: Vec<(Option<&str>, String)> = Vec::new();
+    if !files.is_empty() {
+        if !statement.is_empty() {
+            eprintln!("Нельзя одновременно указывать ОПЕРАТОР и --file");
+            return 2;
+        }
+        for path in &files {
+            match std::fs::read_to_string(path) {
+                Ok(text) => sources.push((Some(path), text)),
+                Err(e) => {
+                    eprintln!("Не удалось прочитать {}: {}", path, e);
+                    return 2;
+                }
+            }
+        }
+    } else if statement.is_empty() || statement == ["-"] {
+        let mut buffer = String::new();
+        if let Err(e) = std::io::stdin().read_to_string(&mut buffer) {
+            eprintln!("Не удалось прочитать стандартный ввод: {}", e);
+            return 2;
+        }
+        sources.push((None, buffer));
+    } else {
+        sources.push((None, statement.join(" ")));
+    }
+
+    let several = many || sources.len() > 1;
+    let mut reports: Vec<AnalysisReport> = Vec::new();
+    let mut sequences: Vec<SequenceReport> = Vec::new();
+    let mut code = 0;
+    for (source, text) in sources {
+        let (text, notes) = sanitize(&text);
+        for note in notes {
+            eprintln!("{}: {}", source.unwrap_or("<ввод>"), note);
+        }
+        let text = text.trim_end_matches('\n');
+
+        // Комментарий в первой строке переопределяет настройки для этого источника
+        let mut config = config.clone();
+        let (base, text) = match FileDirective::parse(text) {
+            Some(directive) => {
+                for note in directive.apply(&mut config) {
+                    eprintln!("{}: {}", source.unwrap_or("<ввод>"), note);
+                }
+                directive.strip(text)
+            }
+            None => (0, text.to_string()),
+        };
+        // Комментарий не отменяет воспроизводимого порядка
+        if deterministic {
+            config.order = SymbolOrder::Sorted;
+        }
+        let text = if config.typography {
+            let (text, notes) = normalize_typography(&text);
+            for note in notes {
+                eprintln!("{}: {}", source.unwrap_or("<ввод>"), note);
+            }
+            text
+        } else {
+            text
+        };
+
+        let statements = if many {
+            split_statements_with(&text, &config)
+        } else {
+            vec![(0, text.as_str())]
+        };
+
+        // Разрешения (* taafl:allow(...) *) перед каждым оператором
+        let statements: Vec<(usize, Allow, String)> = statements
+            .into_iter()
+            .map(|(offset, statement)| {
+                let (allow, statement) = Allow::extract(statement);
+                for note in &allow.notes {
+                    eprintln!("{}: {}", source.unwrap_or("<ввод>"), note);
+                }
+                (offset, allow, statement)
+            })
+            .collect();
+
+        // Архив пишется до выбора вывода: его получают и JSON, и --ambiguity
+        if let Some(path) = export_path {
+            for (_, allow, statement) in &statements {
+                let mut allowed = config.clone();
+                allow.apply(&mut allowed);
+                if let Err(e) = std::fs::write(path, bundle::export(statement, &allowed)) {
+                    eprintln!("Не удалось записать {}: {}", path, e);
+                    return 2;
+                }
+            }
+        }
+
+        if format == Format::Json && sequence {
+            sequences.push(SequenceReport {
+                source: source.map(str::to_string),
+                ..analyze_sequence(&text, &config)
+            });
+            continue;
+        }
+        if format == Format::Json {
+            for (offset, allow, statement) in statements {
+                let mut config = config.clone();
+                allow.apply(&mut config);
+                reports.push(AnalysisReport {
+                    offset: base + offset,
+                    source: source.map(str::to_string),
+                    ..analyze_report(&statement, &config)
+                });
+            }
+            continue;
+        }
+
+        if let Some(source) = source {
+            outln!("\n##### {}", source);
+        }
+        if let (None, Some(profile)) = (profiles, config.profile) {
+            outln!("Профиль: {}", profile);
+        }
+        for (i, (offset, allow, statement)) in statements.iter().enumerate() {
+            if many {
+                outln!("\n=== Оператор {} (смещение {}) ===", i + 1, base + offset);
+            }
+            if let Some(grammar) = &grammar {
+                code = code.max(print_ambiguity(statement, grammar));
+                continue;
+            }
+            let mut allowed = config.clone();
+            allow.apply(&mut allowed);
+            match profiles {
+                Some((a, b)) => print_comparison(statement, a, b),
+                None if format == Format::Diagnostics => {
+                    code = code.max(print_diagnostics(statement, &allowed))
+                }
+                None => {
+                    code = code.max(print_text(statement, &allowed));
+                    if let Some(columns) = &token_columns {
+                        print_tokens(statement, &allowed, columns);
+                    }
+                    if tree {
+                        print_tree(statement, &allowed);
+                    }
+                    if steps {
+                        print_derivation(statement, &allowed);
+                    }
+                }
+            }
+            if verbose {
+                let hidden = suppressed(statement, &config, allow);
+                if !hidden.is_empty() {
+                    outln!("\nПодавлено комментарием allow:");
+                    for diagnostic in hidden {
+                        outln!("{}", diagnostic);
+                    }
+                }
+            }
+        }
+        if sequence && format == Format::Text {
+            print_sequence(&text, &config);
+        }
+    }
+
+    if format == Format::Json && sequence {
+        let json = match sequences.as_slice() {
+            [sequence] => serde_json::to_string_pretty(sequence),
+            sequences => serde_json::to_string_pretty(sequences),
+        };
+        match json {
+            Ok(json) => outln!("{}", json),
+            Err(e) => {
+                eprintln!("Не удалось сформировать JSON: {}", e);
+                return 2;
+            }
+        }
+        if !sequences.iter().all(|sequence| sequence.accepted) {
+            code = 1;
+        }
+    } else if format == Format::Json {
+        let json = if several {
+            serde_json::to_string_pretty(&reports)
+        } else {
+            serde_json::to_string_pretty(&reports[0])
+        };
+        match json {
+            Ok(json) => outln!("{}", json),
+            Err(e) => {
+                eprintln!("Не удалось сформировать JSON: {}", e);
+                return 2;
+            }
+        }
+        if !reports.iter().all(|report| report.accepted) {
+            code = 1;
+        }
+    }
+
+    code
+}
+
+/// Текстовый вывод результата анализа одного оператора.
+fn print_text(input: &str, config: &AnalyzerConfig) -> i32 {
+    match analyze_line_with(input, config) {
+        Ok(success) => {
+            outln!("{}\nСтрока принадлежит языку.", input);
+            if let Some(warnings) = success.warnings {
+                outln!("\n{}", warnings);
+            }
+            if let Some(ids) = success.ids.filter(|ids| !ids.is_empty()) {
+                outln!("\nСписок идентификаторов:\n{}", ids.trim_end());
+            }
+            if let Some(consts) = success.consts.filter(|consts| !consts.is_empty()) {
+                outln!("\nСписок констант:\n{}", consts.trim_end());
+            }
+            0
+        }
+        Err(report) => {
+            outln!("{}", report.text);
+            1
+        }
+    }
+}
+
+/// Сводная таблица символов всех операторов текста.
+fn print_sequence(text: &str, config: &AnalyzerConfig) {
+    let report = analyze_sequence(text, config);
+    let accepted = report.statements.iter().filter(|s| s.accepted).count();
+    outln!(
+        "\n=== Последовательность: принято операторов {} из {} ===",
+        accepted,
+        report.statements.len()
+    );
+    let (ids, consts) = sequence_lists(&report, config);
+    if let Some(ids) = ids.filter(|ids| !ids.is_empty()) {
+        outln!("\nСводный список идентификаторов:\n{}", ids.trim_end());
+    }
+    if let Some(consts) = consts.filter(|consts| !consts.is_empty()) {
+        outln!("\nСводный список констант:\n{}", consts.trim_end());
+    }
+}
+
+/// Дерево разбора, если оператор разобран синтаксически.
+fn print_tree(input: &str, config: &AnalyzerConfig) {
+    let analysis = Analyzer::builder()
+        .config(config.clone())
+        .collect_ast(true)
+        .build()
+        .analyze(input);
+    if let Some(assignment) = &analysis.ast {
+        out!("\nДерево разбора:\n{}", Tree::of(assignment).to_text());
+    }
+}
+
+/// Таблица лексем, если лексический анализ прошёл без ошибок.
+fn print_tokens(input: &str, config: &AnalyzerConfig, columns: &[TokenColumn]) {
+    if let Ok(table) = token_table(input, config, columns) {
+        out!("\nЛексемы:\n{}", table);
+    }
+}
+
+/// Левый вывод таблицей шагов, если оператор выводится в грамматике.
+fn print_derivation(input: &str, config: &AnalyzerConfig) {
+    if let Ok(table) = taafl::analyzer::derivation_table(input, config) {
+        out!("\nЛевый вывод:\n{}", table);
+    }
+}
+
+/// Все диагностики оператора, по одной в строке.
+fn print_diagnostics(input: &str, config: &AnalyzerConfig) -> i32 {
+    let result = taafl::core::analyze(input, config);
+    for diagnostic in &result.diagnostics {
+        outln!("{}", diagnostic);
+    }
+    if result.accepted {
+        0
+    } else {
+        1
+    }
+}
+
+/// Результат поиска неоднозначности: 0 - вывод единственный, 1 - иначе.
+fn print_ambiguity(input: &str, grammar: &Grammar) -> i32 {
+    let analysis = Analyzer::builder()
+        .syntax_only(true)
+        .collect_tokens(true)
+        .build()
+        .analyze(input);
+    let Some(tokens) = analysis.tokens else {
+        // Лексическая ошибка: анализ не дошёл до разбора
+        for diagnostic in &analysis.diagnostics {
+            outln!("{}", diagnostic);
+        }
+        return 1;
+    };
+    let terminals: Vec<&str> = tokens.iter().map(|(_, token)| token.terminal()).collect();
+    outln!("{}", input);
+    outln!("Терминалы: {}\n", terminals.join(" "));
+
+    let print_derivation = |title: &str, derivation: &Derivation| {
+        outln!("{}:", title);
+        for (i, form) in derivation.sentential_forms(grammar).iter().enumerate() {
+            outln!("  {} {}", if i == 0 { " " } else { "⇒" }, form);
+        }
+    };
+    match ambiguity::check(grammar, &terminals) {
+        Verdict::Unique(derivation) => {
+            print_derivation("Вывод единственный", &derivation);
+            0
+        }
+        Verdict::Ambiguous(first, second) => {
+            outln!("Грамматика неоднозначна: два различных левых вывода.\n");
+            print_derivation("Вывод 1", &first);
+            outln!();
+            print_derivation("Вывод 2", &second);
+            1
+        }
+        Verdict::NotDerivable => {
+            outln!("Цепочка не выводится из грамматики.");
+            1
+        }
+        Verdict::Undecided => {
+            outln!(
+                "Не удалось определить: превышен лимит перебора ({} шагов, вывод до {} продукций).",
+                ambiguity::STEP_LIMIT,
+                ambiguity::DEPTH_LIMIT
+            );
+            1
+        }
+    }
+}
+
+/// Таблица различий результатов анализа с двумя профилями.
+fn print_comparison(input: &str, a: Profile, b: Profile) {
+    let comparison = compare(
+        input,
+        (a.title(), &AnalyzerConfig::from_profile(a)),
+        (b.title(), &AnalyzerConfig::from_profile(b)),
+    );
+    outln!("{}\n", input);
+    outln!("{}", comparison.render(36));
+    if comparison.is_same() {
+        outln!("\nРезультаты совпадают.");
+    }
+}
+
+/// Справка по правилам из реестра [`Rule::ALL`] с их текущей строгостью.
+fn print_rules(config: &AnalyzerConfig) {
+    let constants = config.constant_range(Variant20.constant_range());
+    for rule in Rule::ALL {
+        outln!("{} ({}): {}", rule.id(), config.severity(rule), rule.title());
+        outln!("    {}", rule.description(&constants));
+        outln!("    Пример нарушения: {}", rule.example());
+    }
+}
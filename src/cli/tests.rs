@@ -0,0 +1,72 @@
+//! Проверки подкоманд, которые пишут файлы: каждая работает в своём временном
+//! каталоге, который удаляется после проверки.
+
+use std::path::PathBuf;
+
+/// Пустой временной каталог проверки `name`.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("taafl-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn scaffold_does_not_overwrite_an_existing_parser() {
+    let dir = scratch("scaffold");
+    let grammar = dir.join("grammar.bnf");
+    std::fs::write(&grammar, "statement = IDENT \":=\" CONST \";\" ;").unwrap();
+    let parser = dir.join(taafl::grammar::scaffold::SCAFFOLD_FILE);
+    std::fs::write(&parser, "// свой разбор\n").unwrap();
+
+    let args = [
+        "--grammar".to_string(),
+        grammar.display().to_string(),
+        "--out".to_string(),
+        dir.display().to_string(),
+    ];
+    assert_eq!(super::scaffold(&args), 2);
+    assert_eq!(
+        std::fs::read_to_string(&parser).unwrap(),
+        "// свой разбор\n"
+    );
+
+    std::fs::remove_file(&parser).unwrap();
+    assert_eq!(super::scaffold(&args), 0);
+    assert!(std::fs::read_to_string(&parser)
+        .unwrap()
+        .contains("fn parse_statement"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn deterministic_is_taken_only_where_an_option_is_expected() {
+    use super::take_deterministic;
+
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        take_deterministic(args(&["--deterministic"])),
+        (true, args(&[]))
+    );
+    assert_eq!(
+        take_deterministic(args(&["--deterministic", "--software-render"])),
+        (true, args(&["--software-render"]))
+    );
+    assert_eq!(
+        take_deterministic(args(&["--file", "--deterministic"])),
+        (false, args(&["--file", "--deterministic"]))
+    );
+    assert_eq!(
+        take_deterministic(args(&[
+            "corpus",
+            "add",
+            "--deterministic",
+            "--deterministic"
+        ])),
+        (true, args(&["corpus", "add", "--deterministic"]))
+    );
+    assert_eq!(
+        take_deterministic(args(&["--file", "--seed", "--deterministic"])),
+        (true, args(&["--file", "--seed"]))
+    );
+}
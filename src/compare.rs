@@ -0,0 +1,256 @@
+//! Сравнение результатов анализа одной строки с двумя разными настройками (профилями),
+//! а также отчёта, записанного ранее, с повторным анализом его строки.
+
+use crate::analyzer::analyze_report;
+use crate::config::AnalyzerConfig;
+use crate::model::{AnalysisReport, ErrorEntry, IdentifierRole};
+
+/// Результаты анализа одной строки с двумя настройками.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub left: Side,
+    pub right: Side,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Side {
+    /// Название настроек (обычно имя профиля)
+    pub label: String,
+    pub report: AnalysisReport,
+}
+
+/// Вид диагностики в таблице сравнения.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Error,
+    Warning,
+}
+
+/// Строка таблицы: одна диагностика и её наличие с каждой стороны.
+struct Row<'a> {
+    position: usize,
+    message: &'a str,
+    left: Option<Level>,
+    right: Option<Level>,
+}
+
+/// Анализирует `input` с настройками `left` и `right`.
+pub fn compare(
+    input: &str,
+    (left_label, left): (&str, &AnalyzerConfig),
+    (right_label, right): (&str, &AnalyzerConfig),
+) -> Comparison {
+    Comparison {
+        left: Side {
+            label: left_label.to_string(),
+            report: analyze_report(input, left),
+        },
+        right: Side {
+            label: right_label.to_string(),
+            report: analyze_report(input, right),
+        },
+    }
+}
+
+/// Сравнивает отчёт `stored`, записанный ранее, с повторным анализом его строки
+/// с настройками `config`. Различия означают, что с тех пор изменился анализатор
+/// или настройки.
+///
+/// ```
+/// use taafl::analyzer::analyze_report;
+/// use taafl::compare::recheck;
+/// use taafl::config::AnalyzerConfig;
+///
+/// let config = AnalyzerConfig::default();
+/// let mut stored = analyze_report("A := B + 1;", &config);
+/// assert!(recheck(&stored, ("Сейчас", &config)).is_same());
+///
+/// stored.identifiers.pop();
+/// let comparison = recheck(&stored, ("Сейчас", &config));
+/// assert!(!comparison.is_same());
+/// assert!(comparison.render(40).contains("* символы: A, 1"));
+/// ```
+pub fn recheck(stored: &AnalysisReport, (label, config): (&str, &AnalyzerConfig)) -> Comparison {
+    Comparison {
+        left: Side {
+            label: "Сохранённый отчёт".to_string(),
+            report: stored.clone(),
+        },
+        right: Side {
+            label: label.to_string(),
+            report: analyze_report(&stored.input, config),
+        },
+    }
+}
+
+impl Comparison {
+    /// Совпадают ли вердикты, таблицы символов и диагностики с обеих сторон.
+    pub fn is_same(&self) -> bool {
+        self.rows().iter().all(|row| row.left == row.right)
+            && self.left.report.accepted == self.right.report.accepted
+            && symbols(&self.left.report) == symbols(&self.right.report)
+    }
+
+    /// Таблица из двух колонок шириной `width` символов. Строки, которые
+    /// различаются, отмечены `*` слева.
+    pub fn render(&self, width: usize) -> String {
+        let mut lines = vec![
+            pair(' ', &self.left.label, &self.right.label, width),
+            format!("  {}-+-{}", "-".repeat(width), "-".repeat(width)),
+            pair(
+                mark(self.left.report.accepted != self.right.report.accepted),
+                verdict(&self.left.report),
+                verdict(&self.right.report),
+                width,
+            ),
+        ];
+
+        // Таблицы символов показываются, только если они различаются
+        let (left, right) = (symbols(&self.left.report), symbols(&self.right.report));
+        if left != right {
+            let describe = |symbols: Vec<String>| format!("символы: {}", symbols.join(", "));
+            push_wrapped(&mut lines, '*', &describe(left), &describe(right), width);
+        }
+
+        for row in self.rows() {
+            let describe = |level: Option<Level>| match level {
+                Some(level) => format!(
+                    "{}, столбец {}: {}",
+                    match level {
+                        Level::Error => "ошибка",
+                        Level::Warning => "предупреждение",
+                    },
+                    row.position + 1,
+                    row.message
+                ),
+                None => "-".to_string(),
+            };
+            lines.push(pair(' ', "", "", width));
+            push_wrapped(
+                &mut lines,
+                mark(row.left != row.right),
+                &describe(row.left),
+                &describe(row.right),
+                width,
+            );
+        }
+
+        lines.join("\n")
+    }
+
+    /// Объединение диагностик обеих сторон в порядке позиций.
+    fn rows(&self) -> Vec<Row<'_>> {
+        let mut rows: Vec<Row> = Vec::new();
+        let sides = [(&self.left.report, true), (&self.right.report, false)];
+        for (report, is_left) in sides {
+            let entries = report
+                .errors
+                .iter()
+                .map(|e| (e, Level::Error))
+                .chain(report.warnings.iter().map(|w| (w, Level::Warning)));
+            for (entry, level) in entries {
+                let message = first_line(entry);
+                let index = rows
+                    .iter()
+                    .position(|row| row.position == entry.position && row.message == message);
+                let index = index.unwrap_or_else(|| {
+                    rows.push(Row {
+                        position: entry.position,
+                        message,
+                        left: None,
+                        right: None,
+                    });
+                    rows.len() - 1
+                });
+                let row = &mut rows[index];
+                if is_left {
+                    row.left = Some(level);
+                } else {
+                    row.right = Some(level);
+                }
+            }
+        }
+        rows.sort_by_key(|row| row.position);
+        rows
+    }
+}
+
+/// Сообщение без подсказок, которые добавляет учебный профиль.
+fn first_line(entry: &ErrorEntry) -> &str {
+    entry.message.lines().next().unwrap_or_default()
+}
+
+/// Таблица символов без позиций и происхождения вхождений: имена с ролями
+/// и значения констант в порядке отчёта.
+fn symbols(report: &AnalysisReport) -> Vec<String> {
+    let identifiers = report.identifiers.iter().map(|id| match id.role {
+        IdentifierRole::Array => format!("{}[]", id.name),
+        IdentifierRole::Index => format!("[{}]", id.name),
+        IdentifierRole::Expression => id.name.clone(),
+        IdentifierRole::ExpressionArray => format!("{}[] справа", id.name),
+        IdentifierRole::Function => format!("{}()", id.name),
+        IdentifierRole::Field => format!("[].{}", id.name),
+    });
+    let constants = report.constants.iter().map(|c| c.value.to_string());
+    let reals = report.reals.iter().map(|r| r.value.to_string());
+    let literals = report.literals.iter().map(|l| format!("'{}'", l.value));
+    identifiers
+        .chain(constants)
+        .chain(reals)
+        .chain(literals)
+        .collect()
+}
+
+fn verdict(report: &AnalysisReport) -> &'static str {
+    if report.accepted {
+        "Строка принадлежит языку"
+    } else {
+        "Строка не принадлежит языку"
+    }
+}
+
+fn mark(differs: bool) -> char {
+    if differs {
+        '*'
+    } else {
+        ' '
+    }
+}
+
+fn pair(marker: char, left: &str, right: &str, width: usize) -> String {
+    let padding = width.saturating_sub(left.chars().count());
+    format!("{} {}{} | {}", marker, left, " ".repeat(padding), right)
+        .trim_end()
+        .to_string()
+}
+
+/// Две колонки текста, перенесённого по ширине; `marker` - только у первой строки.
+fn push_wrapped(lines: &mut Vec<String>, marker: char, left: &str, right: &str, width: usize) {
+    let (left, right) = (wrap(left, width), wrap(right, width));
+    for i in 0..left.len().max(right.len()) {
+        lines.push(pair(
+            if i == 0 { marker } else { ' ' },
+            left.get(i).map_or("", String::as_str),
+            right.get(i).map_or("", String::as_str),
+            width,
+        ));
+    }
+}
+
+/// Разбивает текст на строки не длиннее `width` символов по пробелам.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let len = line.chars().count();
+        if len > 0 && len + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
@@ -0,0 +1,1306 @@
+//! Настройки анализатора и их хранение в файле `taafl.toml`.
+
+use crate::model;
+use crate::storage::Storage;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::{Range, RangeInclusive};
+use std::path::Path;
+
+/// Файл настроек, который ищется в текущем каталоге.
+pub const CONFIG_FILE: &str = "taafl.toml";
+
+/// Ограничение на число выводимых ошибок по умолчанию.
+pub const DEFAULT_MAX_ERRORS: usize = 20;
+
+/// Ограничение на длину ввода по умолчанию, в символах: с запасом для любого
+/// оператора, но не для случайно вставленного файла.
+pub const DEFAULT_MAX_INPUT: usize = 10_000;
+
+/// Настройки анализа.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnalyzerConfig {
+    /// Режим восстановления: семантические ошибки не прерывают анализ,
+    /// а накапливаются и выводятся списком после разбора всей строки.
+    /// Недопустимые символы лексер пропускает, сообщив о каждом. После
+    /// синтаксической ошибки рекурсивный спуск пропускает лексемы до точки
+    /// синхронизации (`,`, `]`, `:=`, `;`) и продолжает разбор; таблица LL(1)
+    /// по-прежнему останавливается на первой ошибке.
+    pub recovery: bool,
+    /// Сколько ошибок выводить; остальные сворачиваются в строку "и ещё N ошибок".
+    /// Значение 0 снимает ограничение.
+    pub max_errors: usize,
+    /// Наибольшая длина ввода в символах. Более длинный ввод не анализируется:
+    /// о нём - одна ошибка с кодом `input-size`. Значение 0 снимает ограничение.
+    pub max_input: usize,
+    /// Порядок вывода идентификаторов и констант внутри каждой роли.
+    pub order: SymbolOrder,
+    /// Выводить имена так, как они записаны во вводе (`Count`), а не в верхнем
+    /// регистре. Вхождения по-прежнему сравниваются без учёта регистра.
+    pub preserve_case: bool,
+    /// Строгость семантических правил. Правила, которых здесь нет, считаются ошибками.
+    pub rules: BTreeMap<Rule, Severity>,
+    /// Дополнять сообщения о нарушении правил подсказкой с описанием правила.
+    pub hints: bool,
+    /// Профиль, из которого получены настройки; `None`, если они изменены вручную.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<Profile>,
+    /// Реализация синтаксического анализа.
+    pub parser: ParserKind,
+    /// Реализация лексического анализа.
+    pub scanner: ScannerKind,
+    /// Чем заканчивается оператор.
+    pub terminator: Terminator,
+    /// В каких скобках записываются индексы.
+    pub brackets: Brackets,
+    /// Допустимые лексемы присваивания; пустой набор означает `:=`.
+    pub assign: BTreeSet<AssignOperator>,
+    /// Знаки операций варианта. О знаке не из набора лексер сообщает как
+    /// о недопустимом; операции-слова (`DIV`, `MOD`, `AND`, `OR`) настройка не затрагивает.
+    pub operations: BTreeSet<OperationSign>,
+    /// Вещественные константы: `3.14`, `1.0E5`, `2.5E-3`. Индексом такая
+    /// константа быть не может (правило [`Rule::RealIndex`]).
+    pub reals: bool,
+    /// Подчёркивание внутри идентификаторов: `MAX_LEN`. Идентификатор по-прежнему
+    /// начинается с буквы, а `_` засчитывается в его длину
+    /// (правило [`Rule::IdentifierLength`]).
+    pub underscores: bool,
+    /// Буквы любых алфавитов в идентификаторах, а не только латинские: `МАСС[И]`.
+    /// Длина идентификатора считается в символах.
+    pub unicode_identifiers: bool,
+    /// Диапазон констант (правило [`Rule::ConstantRange`]).
+    pub constants: ConstantRange,
+    /// Ноль - допустимая константа и при диапазоне, который начинается с 1.
+    pub zero: bool,
+    /// Заменять перед анализом типографские знаки (`×`, `–`, `”`, неразрывный
+    /// пробел) знаками ASCII; без этого лексер сообщает о них как о недопустимых.
+    pub typography: bool,
+    /// Где пробелы обязательны или запрещены (раздел `[whitespace]`); нарушения
+    /// проверяет правило [`Rule::Whitespace`].
+    pub whitespace: WhitespacePolicy,
+    /// Ограничения размера оператора (раздел `[limits]`); их соблюдение
+    /// проверяет правило [`Rule::Limits`].
+    pub limits: Limits,
+}
+
+/// Требования варианта к пробелам. По умолчанию их нет: пробелы, переводы строк
+/// и комментарии допустимы между любыми лексемами.
+///
+/// ```
+/// use taafl::analyzer::diagnose;
+/// use taafl::config::AnalyzerConfig;
+///
+/// let mut config = AnalyzerConfig::default();
+/// config.whitespace.compact_indices = true;
+/// config.whitespace.spaced_assign = true;
+/// assert!(diagnose("A[I,J] := 1;", &config).is_empty());
+/// let diagnostics = diagnose("A[I, J]:= 1;", &config);
+/// assert_eq!(diagnostics[0].code, "whitespace");
+/// assert_eq!(diagnostics[0].span, 4..5);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WhitespacePolicy {
+    /// Список индексов пишется слитно: `A[I,J]`, а не `A[ I, J ]`.
+    pub compact_indices: bool,
+    /// Присваивание отделено пробелами с обеих сторон: `X := 1`, а не `X:=1`.
+    pub spaced_assign: bool,
+}
+
+impl WhitespacePolicy {
+    /// Задано хотя бы одно требование.
+    pub fn is_active(&self) -> bool {
+        self.compact_indices || self.spaced_assign
+    }
+}
+
+/// Ограничения варианта на размер оператора. Значение 0 снимает ограничение;
+/// по умолчанию ограничений нет.
+///
+/// ```
+/// use taafl::analyzer::diagnose;
+/// use taafl::config::AnalyzerConfig;
+///
+/// let mut config = AnalyzerConfig { recovery: true, ..Default::default() };
+/// config.limits.max_terms = 2;
+/// config.limits.max_nesting = 1;
+/// assert!(diagnose("A := B[I] + 1;", &config).is_empty());
+/// let diagnostics = diagnose("A := F(B[I]) + 1 - C;", &config);
+/// let spans: Vec<_> = diagnostics.iter().map(|d| (d.code, d.span.clone())).collect();
+/// assert_eq!(spans, [("limits", 8..9), ("limits", 17..20)]);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Наибольшее число слагаемых правой части: в `X := A + B - 1` их три.
+    /// Слагаемые аргументов вызова и индексов не считаются.
+    pub max_terms: usize,
+    /// Наибольшее число индексов в одном списке индексов.
+    pub max_indices: usize,
+    /// Наибольшая вложенность скобок индексов и вызовов: в `X := F(B[I])` она равна 2.
+    pub max_nesting: usize,
+}
+
+impl Limits {
+    /// Задано хотя бы одно ограничение.
+    pub fn is_active(&self) -> bool {
+        self.max_terms > 0 || self.max_indices > 0 || self.max_nesting > 0
+    }
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        Self {
+            recovery: false,
+            max_errors: DEFAULT_MAX_ERRORS,
+            max_input: DEFAULT_MAX_INPUT,
+            order: SymbolOrder::default(),
+            preserve_case: false,
+            rules: BTreeMap::new(),
+            hints: false,
+            profile: None,
+            parser: ParserKind::default(),
+            scanner: ScannerKind::default(),
+            terminator: Terminator::default(),
+            brackets: Brackets::default(),
+            assign: BTreeSet::from([AssignOperator::default()]),
+            operations: OperationSign::DEFAULT.into(),
+            reals: false,
+            underscores: false,
+            unicode_identifiers: false,
+            constants: ConstantRange::default(),
+            zero: false,
+            typography: false,
+            whitespace: WhitespacePolicy::default(),
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl AnalyzerConfig {
+    /// Настройки заданного профиля.
+    pub fn from_profile(profile: Profile) -> Self {
+        let mut config = Self {
+            profile: Some(profile),
+            ..Self::default()
+        };
+        match profile {
+            Profile::Strict => {}
+            Profile::Extended => {
+                config.recovery = true;
+                config.zero = true;
+                config.set_severity(Rule::IdentifierLength, Severity::Warning);
+                config.set_severity(Rule::ArrayInExpression, Severity::Warning);
+            }
+            Profile::Teaching => {
+                config.recovery = true;
+                config.zero = true;
+                config.max_errors = 0;
+                config.hints = true;
+            }
+        }
+        config
+    }
+
+    /// Применяет профиль, сохраняя настройки вывода (порядок символов, регистр имён),
+    /// выбор реализации анализа и лексемы языка: присваивание, знаки операций,
+    /// скобки индексов, конец оператора, вещественные константы, подчёркивание
+    /// и алфавит идентификаторов, диапазон констант, замену типографских знаков
+    /// и требования к пробелам.
+    pub fn apply_profile(&mut self, profile: Profile) {
+        let (order, parser, terminator) = (self.order, self.parser, self.terminator);
+        let (preserve_case, scanner) = (self.preserve_case, self.scanner);
+        let (brackets, assign) = (self.brackets, std::mem::take(&mut self.assign));
+        let operations = std::mem::take(&mut self.operations);
+        let (reals, underscores) = (self.reals, self.underscores);
+        let (unicode_identifiers, constants) = (self.unicode_identifiers, self.constants);
+        let (typography, whitespace) = (self.typography, self.whitespace);
+        *self = Self::from_profile(profile);
+        self.order = order;
+        self.preserve_case = preserve_case;
+        self.parser = parser;
+        self.scanner = scanner;
+        self.terminator = terminator;
+        self.brackets = brackets;
+        self.assign = assign;
+        self.operations = operations;
+        self.reals = reals;
+        self.underscores = underscores;
+        self.unicode_identifiers = unicode_identifiers;
+        self.constants = constants;
+        self.typography = typography;
+        self.whitespace = whitespace;
+    }
+
+    /// Допустимые значения констант: [`ConstantRange`] с диапазоном варианта
+    /// `variant` по умолчанию, а с [`zero`](Self::zero) нижняя граница 1
+    /// опускается до 0.
+    ///
+    /// ```
+    /// use taafl::config::{AnalyzerConfig, ConstantRange, Profile};
+    ///
+    /// let mut config = AnalyzerConfig::default();
+    /// assert_eq!(config.constant_range(1..=32767), 1..=32767);
+    /// config.constants = ConstantRange::Cardinal;
+    /// assert_eq!(config.constant_range(1..=32767), 0..=65535);
+    ///
+    /// let config = AnalyzerConfig::from_profile(Profile::Extended);
+    /// assert_eq!(config.constant_range(1..=32767), 0..=32767);
+    /// ```
+    pub fn constant_range(&self, variant: RangeInclusive<i32>) -> RangeInclusive<i32> {
+        let range = self.constants.range(variant);
+        if self.zero && *range.start() == 1 {
+            0..=*range.end()
+        } else {
+            range
+        }
+    }
+
+    /// Допустимые лексемы присваивания с учётом значения по умолчанию.
+    pub fn assign_operators(&self) -> Vec<AssignOperator> {
+        if self.assign.is_empty() {
+            vec![AssignOperator::default()]
+        } else {
+            self.assign.iter().copied().collect()
+        }
+    }
+
+    /// Строгость правила с учётом значения по умолчанию.
+    pub fn severity(&self, rule: Rule) -> Severity {
+        self.rules.get(&rule).copied().unwrap_or_default()
+    }
+
+    pub fn set_severity(&mut self, rule: Rule, severity: Severity) {
+        self.rules.insert(rule, severity);
+    }
+
+    /// Настройки, записанные в отчёт [`model::ConfigEntry`]: с ними анализ того же
+    /// ввода даёт тот же отчёт. Диапазон констант задаётся своими границами.
+    /// Ошибка - если в отчёте нет настроек или в них незнакомое значение.
+    ///
+    /// ```
+    /// use taafl::analyzer::analyze_report;
+    /// use taafl::config::{AnalyzerConfig, Terminator};
+    ///
+    /// let config = AnalyzerConfig { terminator: Terminator::Dot, ..Default::default() };
+    /// let report = analyze_report("A := 1.", &config);
+    /// let replayed = AnalyzerConfig::from_entry(&report.config).unwrap();
+    /// assert_eq!(replayed.terminator, Terminator::Dot);
+    /// assert_eq!(analyze_report("A := 1.", &replayed), report);
+    /// ```
+    pub fn from_entry(entry: &model::ConfigEntry) -> Result<Self, String> {
+        let (Some(range), Some(settings)) = (entry.constant_range, &entry.settings) else {
+            return Err("В отчёте нет настроек анализа".to_string());
+        };
+        let unknown =
+            |field: &str, value: &str| format!("Незнакомое значение {}: '{}'", field, value);
+        let mut config = Self {
+            recovery: entry.recovery,
+            hints: entry.hints,
+            profile: match &entry.profile {
+                Some(id) => Some(Profile::from_id(id).ok_or_else(|| unknown("profile", id))?),
+                None => None,
+            },
+            max_errors: settings.max_errors,
+            max_input: settings.max_input,
+            order: SymbolOrder::from_id(&settings.order)
+                .ok_or_else(|| unknown("order", &settings.order))?,
+            preserve_case: settings.preserve_case,
+            parser: ParserKind::from_id(&settings.parser)
+                .ok_or_else(|| unknown("parser", &settings.parser))?,
+            scanner: ScannerKind::from_id(&settings.scanner)
+                .ok_or_else(|| unknown("scanner", &settings.scanner))?,
+            terminator: Terminator::from_id(&settings.terminator)
+                .ok_or_else(|| unknown("terminator", &settings.terminator))?,
+            brackets: Brackets::from_id(&settings.brackets)
+                .ok_or_else(|| unknown("brackets", &settings.brackets))?,
+            reals: settings.reals,
+            underscores: settings.underscores,
+            unicode_identifiers: settings.unicode_identifiers,
+            constants: ConstantRange::Custom { min: range.min, max: range.max },
+            zero: false,
+            typography: settings.typography,
+            whitespace: WhitespacePolicy {
+                compact_indices: settings.compact_indices,
+                spaced_assign: settings.spaced_assign,
+            },
+            limits: Limits {
+                max_terms: settings.max_terms,
+                max_indices: settings.max_indices,
+                max_nesting: settings.max_nesting,
+            },
+            ..Self::default()
+        };
+        config.assign = settings
+            .assign
+            .iter()
+            .map(|id| AssignOperator::from_id(id).ok_or_else(|| unknown("assign", id)))
+            .collect::<Result<_, _>>()?;
+        config.operations = settings
+            .operations
+            .iter()
+            .map(|sign| OperationSign::from_lexeme(sign).ok_or_else(|| unknown("operations", sign)))
+            .collect::<Result<_, _>>()?;
+        for (id, severity) in &entry.rules {
+            let rule = Rule::from_id(id).ok_or_else(|| unknown("rules", id))?;
+            let severity = match severity {
+                model::Severity::Error => Severity::Error,
+                model::Severity::Warning => Severity::Warning,
+                model::Severity::Off => Severity::Off,
+            };
+            config.set_severity(rule, severity);
+        }
+        Ok(config)
+    }
+
+    /// Читает настройки из TOML-файла.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Не удалось прочитать {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("Ошибка в {}: {}", path.display(), e))
+    }
+
+    /// Читает настройки из файла, если он существует, иначе возвращает настройки по умолчанию.
+    pub fn load_or_default(path: &Path) -> Result<Self, String> {
+        if path.exists() {
+            Self::load(path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Сохраняет настройки в TOML-файл.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = toml::to_string_pretty(self)
+            .map_err(|e| format!("Не удалось сохранить настройки: {}", e))?;
+        std::fs::write(path, text)
+            .map_err(|e| format!("Не удалось записать {}: {}", path.display(), e))
+    }
+
+    /// Читает настройки из записи `name` хранилища; если записи нет - настройки
+    /// по умолчанию.
+    pub fn load_from(storage: &dyn Storage, name: &str) -> Result<Self, String> {
+        let Some(data) = storage.read(name)? else {
+            return Ok(Self::default());
+        };
+        let text = String::from_utf8(data).map_err(|_| format!("{} - не текст UTF-8", name))?;
+        toml::from_str(&text).map_err(|e| format!("Ошибка в {}: {}", name, e))
+    }
+
+    /// Сохраняет настройки в запись `name` хранилища.
+    pub fn save_to(&self, storage: &mut dyn Storage, name: &str) -> Result<(), String> {
+        let text = toml::to_string_pretty(self)
+            .map_err(|e| format!("Не удалось сохранить настройки: {}", e))?;
+        storage.write(name, text.as_bytes())
+    }
+}
+
+/// Настройки для отдельного файла из комментария в его первой строке:
+/// `(* taafl: profile=extended; recovery=on *)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDirective {
+    /// Положение комментария в тексте, включая `(*` и `*)`
+    pub span: Range<usize>,
+    /// Пары "параметр=значение" в порядке записи
+    pub options: Vec<(String, String)>,
+}
+
+impl FileDirective {
+    const PREFIX: &'static str = "taafl:";
+
+    /// Ищет комментарий с настройками в начале первой строки текста.
+    pub fn parse(input: &str) -> Option<Self> {
+        let first_line = input.lines().next()?;
+        let start = first_line.len() - first_line.trim_start().len();
+        let body_start = start + first_line[start..].strip_prefix("(*").map(|_| 2)?;
+        let body_len = first_line[body_start..].find("*)")?;
+        let body = first_line[body_start..body_start + body_len].trim();
+        let body = body.strip_prefix(Self::PREFIX)?;
+        if body.trim_start().starts_with(Allow::KEYWORD) {
+            // Разрешение для первого оператора, а не настройки файла
+            return None;
+        }
+
+        let options = body
+            .split(';')
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+            .map(|option| match option.split_once('=') {
+                Some((key, value)) => (key.trim().to_lowercase(), value.trim().to_string()),
+                None => (option.to_lowercase(), String::new()),
+            })
+            .collect();
+
+        Some(Self {
+            span: start..body_start + body_len + 2,
+            options,
+        })
+    }
+
+    /// Убирает комментарий с настройками из текста.
+    ///
+    /// Если комментарий занимает всю первую строку, строка удаляется целиком, и вместе
+    /// с текстом возвращается её длина - смещение оставшегося текста в исходном.
+    /// Иначе комментарий заменяется пробелами, чтобы позиции остальных символов
+    /// не сдвинулись, и смещение равно нулю.
+    pub fn strip(&self, input: &str) -> (usize, String) {
+        let rest = &input[self.span.end..];
+        let line_end = rest.find('\n').unwrap_or(rest.len());
+        if rest[..line_end].trim().is_empty() {
+            let offset = (self.span.end + line_end + 1).min(input.len());
+            return (offset, input[offset..].to_string());
+        }
+
+        let mut text = String::with_capacity(input.len());
+        text.push_str(&input[..self.span.start]);
+        text.push_str(&" ".repeat(self.span.len()));
+        text.push_str(rest);
+        (0, text)
+    }
+
+    /// Применяет параметры к настройкам. Возвращает замечания о параметрах,
+    /// которые не удалось применить.
+    pub fn apply(&self, config: &mut AnalyzerConfig) -> Vec<String> {
+        let mut notes = Vec::new();
+        for (key, value) in &self.options {
+            let applied = match key.as_str() {
+                "profile" => Profile::from_id(value).map(|profile| config.apply_profile(profile)),
+                "recovery" => parse_switch(value).map(|on| {
+                    config.recovery = on;
+                    config.profile = None;
+                }),
+                "hints" => parse_switch(value).map(|on| {
+                    config.hints = on;
+                    config.profile = None;
+                }),
+                "max-errors" => value.parse().ok().map(|n| config.max_errors = n),
+                "max-input" => value.parse().ok().map(|n| config.max_input = n),
+                "order" => SymbolOrder::from_id(value).map(|order| config.order = order),
+                "preserve-case" => parse_switch(value).map(|on| config.preserve_case = on),
+                "parser" => ParserKind::from_id(value).map(|parser| config.parser = parser),
+                "scanner" => ScannerKind::from_id(value).map(|kind| config.scanner = kind),
+                "terminator" => Terminator::from_id(value).map(|t| config.terminator = t),
+                "brackets" => Brackets::from_id(value).map(|b| config.brackets = b),
+                "assign" => AssignOperator::parse_list(value).map(|set| config.assign = set),
+                "operations" => {
+                    OperationSign::parse_list(value).map(|set| config.operations = set)
+                }
+                "reals" => parse_switch(value).map(|on| config.reals = on),
+                "underscores" => parse_switch(value).map(|on| config.underscores = on),
+                "unicode" => parse_switch(value).map(|on| config.unicode_identifiers = on),
+                "constants" => ConstantRange::from_id(value).map(|c| config.constants = c),
+                "zero" => parse_switch(value).map(|on| {
+                    config.zero = on;
+                    config.profile = None;
+                }),
+                "typography" => parse_switch(value).map(|on| config.typography = on),
+                "compact-indices" => {
+                    parse_switch(value).map(|on| config.whitespace.compact_indices = on)
+                }
+                "spaced-assign" => {
+                    parse_switch(value).map(|on| config.whitespace.spaced_assign = on)
+                }
+                "max-terms" => value.parse().ok().map(|n| config.limits.max_terms = n),
+                "max-indices" => value.parse().ok().map(|n| config.limits.max_indices = n),
+                "max-nesting" => value.parse().ok().map(|n| config.limits.max_nesting = n),
+                // Сообщения есть только на русском: выбирать нечего
+                "lang" => {
+                    if !value.eq_ignore_ascii_case("ru") {
+                        let note = format!("Сообщения есть только на русском, lang={} пропущен", value);
+                        notes.push(note);
+                    }
+                    continue;
+                }
+                _ => {
+                    notes.push(format!("Неизвестный параметр '{}' пропущен", key));
+                    continue;
+                }
+            };
+            if applied.is_none() {
+                notes.push(format!("Недопустимое значение '{}' для '{}'", value, key));
+            }
+        }
+        notes
+    }
+}
+
+/// Разрешения из комментариев перед оператором, например
+/// `(* taafl:allow(constant-range, identifier-length) *)`: нарушения перечисленных
+/// правил в этом операторе не сообщаются.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Allow {
+    /// Правила в порядке записи, без повторов
+    pub rules: Vec<Rule>,
+    /// Замечания о неизвестных правилах
+    pub notes: Vec<String>,
+}
+
+impl Allow {
+    const KEYWORD: &'static str = "allow(";
+
+    /// Разбирает комментарии-разрешения в начале оператора. Возвращает их вместе
+    /// с текстом, в котором они заменены пробелами, чтобы позиции не сдвинулись.
+    ///
+    /// ```
+    /// use taafl::config::{Allow, Rule};
+    ///
+    /// let (allow, text) = Allow::extract("(* taafl:allow(constant-range) *) A := 0;");
+    /// assert_eq!(allow.rules, [Rule::ConstantRange]);
+    /// assert_eq!(text.trim_start(), "A := 0;");
+    /// ```
+    pub fn extract(statement: &str) -> (Self, String) {
+        let mut allow = Allow::default();
+        let mut text = statement.to_string();
+        let mut pos = 0;
+        loop {
+            let rest = &statement[pos..];
+            let start = pos + rest.len() - rest.trim_start().len();
+            let Some(comment) = statement[start..].strip_prefix("(*") else {
+                break;
+            };
+            let Some(len) = comment.find("*)") else {
+                break;
+            };
+            let body = comment[..len].trim();
+            let Some(list) = body
+                .strip_prefix(FileDirective::PREFIX)
+                .map(str::trim_start)
+                .and_then(|body| body.strip_prefix(Self::KEYWORD))
+                .and_then(|list| list.strip_suffix(')'))
+            else {
+                break;
+            };
+
+            for id in list.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                match Rule::from_id(id) {
+                    Some(rule) if !allow.rules.contains(&rule) => allow.rules.push(rule),
+                    Some(_) => {}
+                    None => allow
+                        .notes
+                        .push(format!("Неизвестное правило '{}' в allow пропущено", id)),
+                }
+            }
+            let end = start + 2 + len + 2;
+            text.replace_range(start..end, &" ".repeat(end - start));
+            pos = end;
+        }
+        (allow, text)
+    }
+
+    /// Отключает разрешённые правила в настройках.
+    pub fn apply(&self, config: &mut AnalyzerConfig) {
+        for rule in &self.rules {
+            config.set_severity(*rule, Severity::Off);
+            config.profile = None;
+        }
+    }
+}
+
+fn parse_switch(value: &str) -> Option<bool> {
+    match value {
+        "on" | "true" | "yes" => Some(true),
+        "off" | "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Порядок вывода символов.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolOrder {
+    /// В порядке первого появления в строке
+    #[default]
+    Appearance,
+    /// Идентификаторы по алфавиту, константы по возрастанию
+    Sorted,
+}
+
+impl SymbolOrder {
+    pub fn id(self) -> &'static str {
+        match self {
+            SymbolOrder::Appearance => "appearance",
+            SymbolOrder::Sorted => "sorted",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "appearance" => Some(SymbolOrder::Appearance),
+            "sorted" => Some(SymbolOrder::Sorted),
+            _ => None,
+        }
+    }
+}
+
+/// Реализация синтаксического анализатора.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParserKind {
+    /// Рекурсивный спуск
+    #[default]
+    Descent,
+    /// Таблица LL(1), построенная по грамматике [`crate::grammar::GRAMMAR`].
+    /// Дерево разбора после проверки строится рекурсивным спуском.
+    Table,
+    /// Алгоритм Эрли ([`crate::grammar::earley`]) по грамматике варианта: она может
+    /// быть и не LL(1). Два вывода одного оператора - предупреждение о
+    /// неоднозначности; дерево разбора строится рекурсивным спуском.
+    Earley,
+}
+
+impl ParserKind {
+    pub fn id(self) -> &'static str {
+        match self {
+            ParserKind::Descent => "descent",
+            ParserKind::Table => "table",
+            ParserKind::Earley => "earley",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "descent" => Some(ParserKind::Descent),
+            "table" => Some(ParserKind::Table),
+            "earley" => Some(ParserKind::Earley),
+            _ => None,
+        }
+    }
+}
+
+/// Реализация лексического анализатора.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScannerKind {
+    /// Посимвольный разбор
+    #[default]
+    Direct,
+    /// Таблица переходов конечного автомата [`Dfa`](crate::analyzer::dfa::Dfa).
+    /// Лексемы и ошибки - те же, что при посимвольном разборе.
+    Dfa,
+}
+
+impl ScannerKind {
+    pub fn id(self) -> &'static str {
+        match self {
+            ScannerKind::Direct => "direct",
+            ScannerKind::Dfa => "dfa",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "direct" => Some(ScannerKind::Direct),
+            "dfa" => Some(ScannerKind::Dfa),
+            _ => None,
+        }
+    }
+}
+
+/// Завершающий символ оператора.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Terminator {
+    /// `X := 1;`
+    #[default]
+    Semicolon,
+    /// `X := 1.`: точка только заканчивает оператор, полей записей (`A.F`) нет
+    Dot,
+    /// Оператор заканчивается вместе со строкой: `X := 1`
+    None,
+}
+
+impl Terminator {
+    pub const ALL: [Terminator; 3] = [Terminator::Semicolon, Terminator::Dot, Terminator::None];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Terminator::Semicolon => "semicolon",
+            Terminator::Dot => "dot",
+            Terminator::None => "none",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "semicolon" => Some(Terminator::Semicolon),
+            "dot" => Some(Terminator::Dot),
+            "none" => Some(Terminator::None),
+            _ => None,
+        }
+    }
+
+    /// Символ в конце оператора; `None`, если оператор заканчивается концом строки.
+    pub fn symbol(self) -> Option<char> {
+        match self {
+            Terminator::Semicolon => Some(';'),
+            Terminator::Dot => Some('.'),
+            Terminator::None => None,
+        }
+    }
+
+    /// Пояснение для сообщений об ошибках: "оператор заканчивается ';'".
+    pub fn ending(self) -> String {
+        match self.symbol() {
+            Some(c) => format!("оператор заканчивается '{}'", c),
+            None => "оператор заканчивается концом строки".to_string(),
+        }
+    }
+
+    /// Название для сообщений: `';'`, `'.'` или "конец строки".
+    pub fn describe(self) -> String {
+        match self.symbol() {
+            Some(c) => format!("'{}'", c),
+            None => "конец строки".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Terminator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Terminator::Semicolon => "Точка с запятой",
+            Terminator::Dot => "Точка",
+            Terminator::None => "Конец строки",
+        })
+    }
+}
+
+/// Скобки, в которых записывается список индексов.
+///
+/// ```
+/// use taafl::analyzer::analyze_line_with;
+/// use taafl::config::{AnalyzerConfig, Brackets, ParserKind};
+///
+/// let mut config = AnalyzerConfig {
+///     brackets: Brackets::Round,
+///     ..AnalyzerConfig::default()
+/// };
+/// assert!(analyze_line_with("A(I, 1) := B;", &config).is_ok());
+/// let report = analyze_line_with("A[I] := B;", &config).unwrap_err();
+/// assert!(report.text.contains("индексы записываются в скобках '(' ')'"));
+///
+/// // Таблица LL(1) строится по грамматике с теми же скобками
+/// config.parser = ParserKind::Table;
+/// assert!(analyze_line_with("A(I, 1) := B;", &config).is_ok());
+/// let report = analyze_line_with("A(I := B;", &config).unwrap_err();
+/// assert!(report.text.contains("')'"));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Brackets {
+    /// `A[I, 1] := B;`, как в Модуле-2
+    #[default]
+    Square,
+    /// `A(I, 1) := B;`, как в Фортране и Бейсике
+    Round,
+}
+
+impl Brackets {
+    pub const ALL: [Brackets; 2] = [Brackets::Square, Brackets::Round];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Brackets::Square => "square",
+            Brackets::Round => "round",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "square" => Some(Brackets::Square),
+            "round" => Some(Brackets::Round),
+            _ => None,
+        }
+    }
+
+    /// Открывающая и закрывающая скобки.
+    pub fn pair(self) -> (char, char) {
+        match self {
+            Brackets::Square => ('[', ']'),
+            Brackets::Round => ('(', ')'),
+        }
+    }
+
+    /// Пояснение для сообщений об ошибках: "индексы записываются в скобках '[' ']'".
+    pub fn usage(self) -> String {
+        let (open, close) = self.pair();
+        format!("индексы записываются в скобках '{}' '{}'", open, close)
+    }
+}
+
+impl fmt::Display for Brackets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (open, close) = self.pair();
+        let name = match self {
+            Brackets::Square => "Квадратные",
+            Brackets::Round => "Круглые",
+        };
+        write!(f, "{} {} {}", name, open, close)
+    }
+}
+
+/// Диапазон допустимых констант: диапазон варианта, один из целых типов
+/// Модулы-2 или свой.
+///
+/// ```
+/// use taafl::config::ConstantRange;
+///
+/// assert_eq!(ConstantRange::from_id("integer"), Some(ConstantRange::Integer));
+/// assert_eq!(ConstantRange::from_id("10..99"), Some(ConstantRange::Custom { min: 10, max: 99 }));
+/// assert_eq!(ConstantRange::from_id("99..10"), None);
+/// assert_eq!(ConstantRange::Integer.to_string(), "INTEGER [-32768..32767]");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConstantRange {
+    /// Диапазон варианта: [1..32767] в варианте 20
+    #[default]
+    Variant,
+    /// `INTEGER`: [-32768..32767]
+    Integer,
+    /// `CARDINAL`: [0..65535]
+    Cardinal,
+    /// Границы включаются в диапазон
+    Custom { min: i32, max: i32 },
+}
+
+impl ConstantRange {
+    /// Готовые диапазоны для выбора в интерфейсе.
+    pub const PRESETS: [ConstantRange; 3] = [
+        ConstantRange::Variant,
+        ConstantRange::Integer,
+        ConstantRange::Cardinal,
+    ];
+
+    /// `variant`, `integer`, `cardinal` или свой диапазон `МИН..МАКС`.
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "variant" => Some(ConstantRange::Variant),
+            "integer" => Some(ConstantRange::Integer),
+            "cardinal" => Some(ConstantRange::Cardinal),
+            _ => {
+                let (min, max) = id.split_once("..")?;
+                let (min, max) = (min.trim().parse().ok()?, max.trim().parse().ok()?);
+                (min <= max).then_some(ConstantRange::Custom { min, max })
+            }
+        }
+    }
+
+    /// Допустимые значения; `variant` - диапазон варианта.
+    pub fn range(self, variant: RangeInclusive<i32>) -> RangeInclusive<i32> {
+        match self {
+            ConstantRange::Variant => variant,
+            ConstantRange::Integer => -32768..=32767,
+            ConstantRange::Cardinal => 0..=65535,
+            ConstantRange::Custom { min, max } => min..=max,
+        }
+    }
+}
+
+impl fmt::Display for ConstantRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConstantRange::Variant => f.write_str("Как в варианте"),
+            ConstantRange::Integer => f.write_str("INTEGER [-32768..32767]"),
+            ConstantRange::Cardinal => f.write_str("CARDINAL [0..65535]"),
+            ConstantRange::Custom { min, max } => write!(f, "[{}..{}]", min, max),
+        }
+    }
+}
+
+/// Лексема присваивания.
+///
+/// ```
+/// use taafl::analyzer::analyze_line_with;
+/// use taafl::config::{AnalyzerConfig, AssignOperator};
+///
+/// let mut config = AnalyzerConfig::default();
+/// config.assign = [AssignOperator::Equals, AssignOperator::Arrow].into();
+/// assert!(analyze_line_with("X = A = 1;", &config).is_ok());
+/// assert!(analyze_line_with("X ← 1;", &config).is_ok());
+/// let report = analyze_line_with("X := 1;", &config).unwrap_err();
+/// assert!(report.text.contains("Присваивание записывается как '=' или '←'"));
+/// ```
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum AssignOperator {
+    /// `X := 1;`
+    #[default]
+    ColonEquals,
+    /// `X = 1;`, как в Бейсике. Присваиванием считается только первый знак `=`
+    /// оператора, остальные - операция сравнения.
+    Equals,
+    /// `X ← 1;`
+    Arrow,
+}
+
+impl AssignOperator {
+    pub const ALL: [AssignOperator; 3] = [
+        AssignOperator::ColonEquals,
+        AssignOperator::Equals,
+        AssignOperator::Arrow,
+    ];
+
+    pub fn id(self) -> &'static str {
+        match self {
+            AssignOperator::ColonEquals => "colon-equals",
+            AssignOperator::Equals => "equals",
+            AssignOperator::Arrow => "arrow",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|op| op.id() == id)
+    }
+
+    /// Набор из списка через запятую: `colon-equals,arrow`.
+    pub fn parse_list(list: &str) -> Option<BTreeSet<Self>> {
+        list.split(',').map(|id| Self::from_id(id.trim())).collect()
+    }
+
+    pub fn lexeme(self) -> &'static str {
+        match self {
+            AssignOperator::ColonEquals => ":=",
+            AssignOperator::Equals => "=",
+            AssignOperator::Arrow => "←",
+        }
+    }
+
+    /// Набор лексем для сообщений: `':=' или '←'`.
+    pub fn describe(operators: &[AssignOperator]) -> String {
+        let lexemes: Vec<String> = operators
+            .iter()
+            .map(|op| format!("'{}'", op.lexeme()))
+            .collect();
+        lexemes.join(" или ")
+    }
+}
+
+/// Знак операции. Набор знаков варианта задаёт [`AnalyzerConfig::operations`].
+///
+/// ```
+/// use taafl::analyzer::analyze_line_with;
+/// use taafl::config::{AnalyzerConfig, OperationSign};
+///
+/// let mut config = AnalyzerConfig::default();
+/// config.operations.remove(&OperationSign::Hash);
+/// config.operations.insert(OperationSign::Percent);
+/// assert!(analyze_line_with("X := A % 2;", &config).is_ok());
+/// let report = analyze_line_with("X := A # 2;", &config).unwrap_err();
+/// assert!(report.text.contains("Операция '#' не допускается в этом варианте"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum OperationSign {
+    #[serde(rename = "+")]
+    Plus,
+    #[serde(rename = "-")]
+    Minus,
+    #[serde(rename = "*")]
+    Times,
+    #[serde(rename = "/")]
+    Divide,
+    /// Остаток от деления, как `MOD`; в наборе по умолчанию его нет
+    #[serde(rename = "%")]
+    Percent,
+    #[serde(rename = "=")]
+    Equals,
+    /// Неравенство в записи Modula-2
+    #[serde(rename = "#")]
+    Hash,
+    #[serde(rename = "<")]
+    Less,
+    #[serde(rename = ">")]
+    Greater,
+    #[serde(rename = "<=")]
+    LessEquals,
+    #[serde(rename = ">=")]
+    GreaterEquals,
+    #[serde(rename = "<>")]
+    NotEquals,
+}
+
+impl OperationSign {
+    pub const ALL: [OperationSign; 12] = [
+        OperationSign::Plus,
+        OperationSign::Minus,
+        OperationSign::Times,
+        OperationSign::Divide,
+        OperationSign::Percent,
+        OperationSign::Equals,
+        OperationSign::Hash,
+        OperationSign::Less,
+        OperationSign::Greater,
+        OperationSign::LessEquals,
+        OperationSign::GreaterEquals,
+        OperationSign::NotEquals,
+    ];
+
+    /// Знаки языка по умолчанию - те же, что в [`crate::ast::OPERATIONS`].
+    pub const DEFAULT: [OperationSign; 11] = [
+        OperationSign::Plus,
+        OperationSign::Minus,
+        OperationSign::Times,
+        OperationSign::Divide,
+        OperationSign::Equals,
+        OperationSign::Hash,
+        OperationSign::Less,
+        OperationSign::Greater,
+        OperationSign::LessEquals,
+        OperationSign::GreaterEquals,
+        OperationSign::NotEquals,
+    ];
+
+    pub fn lexeme(self) -> &'static str {
+        match self {
+            OperationSign::Plus => "+",
+            OperationSign::Minus => "-",
+            OperationSign::Times => "*",
+            OperationSign::Divide => "/",
+            OperationSign::Percent => "%",
+            OperationSign::Equals => "=",
+            OperationSign::Hash => "#",
+            OperationSign::Less => "<",
+            OperationSign::Greater => ">",
+            OperationSign::LessEquals => "<=",
+            OperationSign::GreaterEquals => ">=",
+            OperationSign::NotEquals => "<>",
+        }
+    }
+
+    pub fn from_lexeme(lexeme: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|sign| sign.lexeme() == lexeme)
+    }
+
+    /// Самый длинный знак, с которого начинается `text`: `<=` раньше `<`.
+    pub fn prefix_of(text: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .filter(|sign| text.starts_with(sign.lexeme()))
+            .max_by_key(|sign| sign.lexeme().len())
+    }
+
+    /// Набор из списка через запятую: `+,-,*,/,%`.
+    pub fn parse_list(list: &str) -> Option<BTreeSet<Self>> {
+        list.split(',')
+            .map(|lexeme| Self::from_lexeme(lexeme.trim()))
+            .collect()
+    }
+
+    /// Набор знаков для сообщений: `'+', '-', '*'`.
+    pub fn describe(signs: &BTreeSet<OperationSign>) -> String {
+        let lexemes: Vec<String> = signs
+            .iter()
+            .map(|sign| format!("'{}'", sign.lexeme()))
+            .collect();
+        lexemes.join(", ")
+    }
+}
+
+/// Семантическое правило, строгость которого настраивается.
+///
+/// ```
+/// use taafl::analyzer::diagnose;
+/// use taafl::config::{AnalyzerConfig, Rule, Severity};
+///
+/// let mut config = AnalyzerConfig::default();
+/// let diagnostics = diagnose("A := B + * C;", &config);
+/// assert_eq!(diagnostics[0].code, "operand-order");
+/// assert!(diagnostics[0].message.starts_with("Две операции подряд: '+' и '*'"));
+///
+/// // Без правила остаётся общее сообщение синтаксического анализа
+/// config.set_severity(Rule::OperandOrder, Severity::Off);
+/// assert_eq!(diagnose("A := B + * C;", &config)[0].code, "syntax");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Rule {
+    /// Константа в допустимом диапазоне ([`AnalyzerConfig::constant_range`])
+    ConstantRange,
+    /// Идентификатор не длиннее 8 символов
+    IdentifierLength,
+    /// Массив из левой части не используется в правой
+    ArrayInExpression,
+    /// Операнды и операции в правой части чередуются. Оператор с нарушением не
+    /// принадлежит языку при любой строгости, кроме "выкл.": правило лишь заменяет
+    /// общее сообщение синтаксического анализа точным.
+    OperandOrder,
+    /// Индекс - не вещественная константа. Правило действует, если вещественные
+    /// константы разрешены настройками.
+    RealIndex,
+    /// Идентификатор - не зарезервированное слово Modula-2 (`BEGIN`, `END`, `IF`...)
+    ReservedWord,
+    /// Пробелы расставлены по требованиям варианта. Правило действует, если
+    /// требования заданы настройками ([`AnalyzerConfig::whitespace`]).
+    Whitespace,
+    /// Оператор не больше ограничений варианта: слагаемые правой части, индексы
+    /// в списке, вложенность скобок. Правило действует, если ограничения заданы
+    /// настройками ([`AnalyzerConfig::limits`]).
+    Limits,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 8] = [
+        Rule::ConstantRange,
+        Rule::IdentifierLength,
+        Rule::ArrayInExpression,
+        Rule::OperandOrder,
+        Rule::RealIndex,
+        Rule::ReservedWord,
+        Rule::Whitespace,
+        Rule::Limits,
+    ];
+
+    /// Имя правила в файле настроек и в отчётах.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::ConstantRange => "constant-range",
+            Rule::IdentifierLength => "identifier-length",
+            Rule::ArrayInExpression => "array-in-expression",
+            Rule::OperandOrder => "operand-order",
+            Rule::RealIndex => "real-index",
+            Rule::ReservedWord => "reserved-word",
+            Rule::Whitespace => "whitespace",
+            Rule::Limits => "limits",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|rule| rule.id() == id)
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Rule::ConstantRange => "Диапазон констант",
+            Rule::IdentifierLength => "Длина идентификатора",
+            Rule::ArrayInExpression => "Массив левой части справа",
+            Rule::OperandOrder => "Чередование операндов",
+            Rule::RealIndex => "Вещественный индекс",
+            Rule::ReservedWord => "Зарезервированное слово",
+            Rule::Whitespace => "Пробелы",
+            Rule::Limits => "Размер оператора",
+        }
+    }
+
+    /// Краткое описание того, что проверяет правило; `constants` - действующий
+    /// диапазон констант ([`AnalyzerConfig::constant_range`]).
+    pub fn description(&self, constants: &RangeInclusive<i32>) -> String {
+        let text = match self {
+            Rule::ConstantRange => {
+                return format!(
+                    "Константа - целое число в диапазоне [{}..{}].",
+                    constants.start(),
+                    constants.end()
+                );
+            }
+            Rule::IdentifierLength => "Идентификатор содержит не более 8 символов.",
+            Rule::ArrayInExpression => {
+                "Массив из левой части нельзя использовать в правой части оператора."
+            }
+            Rule::OperandOrder => {
+                "Операнды и операции чередуются: две операции или два операнда подряд недопустимы."
+            }
+            Rule::RealIndex => "Индекс массива - целое число: вещественная константа недопустима.",
+            Rule::ReservedWord => {
+                "Ключевые слова Modula-2 (MODULE, BEGIN, END, IF, ARRAY...) не могут быть именами."
+            }
+            Rule::Whitespace => {
+                "Список индексов пишется без пробелов, присваивание отделяется пробелами."
+            }
+            Rule::Limits => {
+                "Число слагаемых, индексов в списке и вложенность скобок не больше заданных."
+            }
+        };
+        text.to_string()
+    }
+
+    /// Пример оператора, нарушающего правило.
+    pub fn example(&self) -> &'static str {
+        match self {
+            Rule::ConstantRange => "A := 40000;",
+            Rule::IdentifierLength => "COUNTER123 := 1;",
+            Rule::ArrayInExpression => "A[I] := A + 1;",
+            Rule::OperandOrder => "A := B + * C;",
+            Rule::RealIndex => "A[1.5] := 1;",
+            Rule::ReservedWord => "A[END] := 1;",
+            Rule::Whitespace => "A[ I ]:=1;",
+            Rule::Limits => "A[I, J, K] := F(B[I]) + C + D;",
+        }
+    }
+}
+
+/// Готовый набор настроек.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Profile {
+    /// Требования варианта без послаблений: анализ до первой ошибки
+    Strict,
+    /// Восстановление после ошибок, длина идентификатора и массив
+    /// в правой части - предупреждения
+    Extended,
+    /// Все ошибки сразу, с подсказками к нарушенным правилам
+    Teaching,
+}
+
+impl Profile {
+    pub const ALL: [Profile; 3] = [Profile::Strict, Profile::Extended, Profile::Teaching];
+
+    /// Имя профиля в командной строке и в файле настроек.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Profile::Strict => "strict",
+            Profile::Extended => "extended",
+            Profile::Teaching => "teaching",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|profile| profile.id() == id)
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Profile::Strict => "Вариант 20 строго",
+            Profile::Extended => "Расширенный",
+            Profile::Teaching => "Учебный с подсказками",
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.title())
+    }
+}
+
+/// Строгость правила.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Severity {
+    /// Нарушение - ошибка, строка не принадлежит языку
+    #[default]
+    Error,
+    /// Нарушение выводится, но строка принимается
+    Warning,
+    /// Правило не проверяется
+    Off,
+}
+
+impl Severity {
+    pub const ALL: [Severity; 3] = [Severity::Error, Severity::Warning, Severity::Off];
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "Ошибка",
+            Severity::Warning => "Предупреждение",
+            Severity::Off => "Отключено",
+        })
+    }
+}
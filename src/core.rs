@@ -0,0 +1,70 @@
+//! Стабильный интерфейс библиотеки для внешних оболочек: графического интерфейса,
+//! командной строки, привязок к другим языкам.
+//!
+//! Остальные модули меняются вместе с заданием, а этот - по правилам semver:
+//! до смены старшей версии имена и сигнатуры отсюда не удаляются и не меняются.
+//! Добавляться могут новые поля структур и новые варианты перечислений, поэтому
+//! все структуры и перечисления отсюда помечены `#[non_exhaustive]`: вне
+//! библиотеки их не построить литералом, а сопоставление с ними должно иметь
+//! ветвь `_`. Исключение - настройки [`AnalyzerConfig`]: их строят от
+//! `AnalyzerConfig::default()` и изменяют по полям.
+//!
+//! ```
+//! use taafl::core::{analyze, AnalyzerConfig, IdentifierRole};
+//!
+//! let result = analyze("X[I] := I + 1;", &AnalyzerConfig::default());
+//! assert!(result.accepted);
+//! let roles: Vec<_> = result.symbols.identifiers.iter().map(|id| id.role).collect();
+//! assert_eq!(roles, [IdentifierRole::Array, IdentifierRole::Index, IdentifierRole::Expression]);
+//!
+//! let result = analyze("X := 1 +;", &AnalyzerConfig::default());
+//! assert_eq!(result.diagnostics[0].code, "syntax");
+//! ```
+
+pub use crate::analyzer::{tokenize, Radix, Span, Token};
+/// Строгость правила в настройках, в отличие от строгости [`Diagnostic`].
+pub use crate::config::Severity as RuleSeverity;
+pub use crate::config::{AnalyzerConfig, Rule};
+pub use crate::diagnostic::{Diagnostic, Severity};
+pub use crate::model::{
+    Constant, ConstantRole, Identifier, IdentifierRole, LiteralConstant, RealConstant,
+};
+
+use crate::analyzer;
+
+/// Результат [`analyze`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct AnalysisResult {
+    /// Строка принадлежит языку: ошибок нет, предупреждения допускаются
+    pub accepted: bool,
+    pub symbols: Symbols,
+    /// Ошибки и предупреждения в порядке их положения
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Идентификаторы и константы по ролям, в порядке `config.order`.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Symbols {
+    pub identifiers: Vec<Identifier>,
+    pub constants: Vec<Constant>,
+    /// Вещественные константы: пусто, если `config.reals` выключен
+    pub reals: Vec<RealConstant>,
+    pub literals: Vec<LiteralConstant>,
+}
+
+/// Анализ одного оператора с заданными настройками.
+pub fn analyze(input: &str, config: &AnalyzerConfig) -> AnalysisResult {
+    let (report, diagnostics) = analyzer::analyze_full(input, config);
+    AnalysisResult {
+        accepted: report.accepted,
+        symbols: Symbols {
+            identifiers: report.identifiers,
+            constants: report.constants,
+            reals: report.reals,
+            literals: report.literals,
+        },
+        diagnostics,
+    }
+}
@@ -0,0 +1,296 @@
+//! База корпусов: наборы операторов с результатами их анализа, накапливаемые
+//! от запуска к запуску, и выборка из них по коду диагностики, дате и метке.
+//!
+//! Модуль собирается с возможностью `corpus` (она включена по умолчанию). Где
+//! лежит база, решает [`Backend`]: [`JsonBackend`] хранит её одной записью
+//! [`CORPUS_FILE`] в [`Storage`] в виде JSON, а с возможностью `sqlite` база
+//! хранится в файле SQLite (модуль `sqlite`). Какую из них взять для
+//! хранилища, решает [`open`].
+//!
+//! ```
+//! use taafl::config::AnalyzerConfig;
+//! use taafl::corpus::{records, Backend, JsonBackend, Query};
+//! use taafl::storage::MemoryStorage;
+//!
+//! let mut storage = MemoryStorage::default();
+//! let mut backend = JsonBackend::new(&mut storage);
+//! let config = AnalyzerConfig::default();
+//! backend.append(records("A := 1; B := 0; C := ;", "лаб-1", "2026-10-14", &config)).unwrap();
+//! let query = Query { code: Some("constant-range".to_string()), ..Query::default() };
+//! let found = backend.query(&query).unwrap();
+//! assert_eq!(found.len(), 1);
+//! assert_eq!(found[0].input, "B := 0;");
+//! assert_eq!(backend.count(), Ok(3));
+//! ```
+
+use crate::analyzer::{diagnose, split_statements_with};
+use crate::config::AnalyzerConfig;
+use crate::diagnostic::Severity;
+use crate::storage::Storage;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+/// Запись базы в хранилище.
+pub const CORPUS_FILE: &str = "taafl-corpus.json";
+
+/// Оператор корпуса и результат его анализа на момент добавления.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    pub input: String,
+    /// Метка набора, например номер лабораторной работы
+    pub tag: String,
+    /// Дата добавления `ГГГГ-ММ-ДД`
+    pub date: String,
+    pub accepted: bool,
+    /// Коды ошибок и предупреждений: `syntax`, `constant-range`...
+    pub codes: Vec<String>,
+}
+
+/// Строка выборки: дата, метка, оператор и коды его диагностик.
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let codes = if self.codes.is_empty() {
+            "принят".to_string()
+        } else {
+            self.codes.join(", ")
+        };
+        write!(
+            f,
+            "{}  {}  {}  [{}]",
+            self.date, self.tag, self.input, codes
+        )
+    }
+}
+
+/// Условия выборки; невыставленное условие подходит для любой записи.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    /// Среди диагностик оператора есть такой код
+    pub code: Option<String>,
+    pub tag: Option<String>,
+    /// Добавлен не раньше этой даты `ГГГГ-ММ-ДД`
+    pub from: Option<String>,
+    /// Добавлен не позже этой даты `ГГГГ-ММ-ДД`
+    pub to: Option<String>,
+}
+
+impl Query {
+    pub fn matches(&self, record: &Record) -> bool {
+        self.code
+            .as_ref()
+            .is_none_or(|code| record.codes.contains(code))
+            && self.tag.as_ref().is_none_or(|tag| record.tag == *tag)
+            && self.from.as_ref().is_none_or(|from| &record.date >= from)
+            && self.to.as_ref().is_none_or(|to| &record.date <= to)
+    }
+}
+
+/// Все записи базы в порядке добавления.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Corpus {
+    pub records: Vec<Record>,
+}
+
+impl Corpus {
+    /// Читает базу из хранилища; если её ещё нет - пустая база.
+    pub fn load(storage: &dyn Storage) -> Result<Self, String> {
+        match storage.read(CORPUS_FILE)? {
+            Some(data) => serde_json::from_slice(&data)
+                .map_err(|e| format!("Ошибка в {}: {}", CORPUS_FILE, e)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Не удалось записать {}: {}", CORPUS_FILE, e))?;
+        storage.write(CORPUS_FILE, json.as_bytes())
+    }
+
+    /// Разбивает `text` на операторы, анализирует каждый и добавляет их с меткой
+    /// `tag` и датой `date`. Возвращает число добавленных операторов.
+    pub fn add_batch(
+        &mut self,
+        text: &str,
+        tag: &str,
+        date: &str,
+        config: &AnalyzerConfig,
+    ) -> usize {
+        let records = records(text, tag, date, config);
+        let added = records.len();
+        self.records.extend(records);
+        added
+    }
+
+    /// Записи, подходящие под условия, в порядке добавления.
+    pub fn query(&self, query: &Query) -> Vec<&Record> {
+        self.records
+            .iter()
+            .filter(|record| query.matches(record))
+            .collect()
+    }
+}
+
+/// Записи операторов `text` с меткой `tag` и датой `date` и результатами их
+/// анализа с настройками `config`.
+pub fn records(text: &str, tag: &str, date: &str, config: &AnalyzerConfig) -> Vec<Record> {
+    split_statements_with(text, config)
+        .into_iter()
+        .map(|(_, statement)| {
+            let diagnostics = diagnose(statement, config);
+            let mut codes: Vec<String> = Vec::new();
+            for diagnostic in &diagnostics {
+                if !codes.iter().any(|code| code == diagnostic.code) {
+                    codes.push(diagnostic.code.to_string());
+                }
+            }
+            Record {
+                input: statement.to_string(),
+                tag: tag.to_string(),
+                date: date.to_string(),
+                accepted: diagnostics.iter().all(|d| d.severity != Severity::Error),
+                codes,
+            }
+        })
+        .collect()
+}
+
+/// Хранилище базы корпусов. Ошибки - готовые сообщения для пользователя.
+pub trait Backend: fmt::Debug {
+    /// Добавляет записи `records` в конец базы.
+    fn append(&mut self, records: Vec<Record>) -> Result<(), String>;
+
+    /// Записи, подходящие под условия `query`, в порядке добавления.
+    fn query(&self, query: &Query) -> Result<Vec<Record>, String>;
+
+    /// Число записей в базе.
+    fn count(&self) -> Result<usize, String>;
+}
+
+/// База одной записью JSON [`CORPUS_FILE`] в хранилище `storage`: каждое
+/// обращение читает её целиком.
+#[derive(Debug)]
+pub struct JsonBackend<'a> {
+    storage: &'a mut dyn Storage,
+}
+
+impl<'a> JsonBackend<'a> {
+    pub fn new(storage: &'a mut dyn Storage) -> Self {
+        Self { storage }
+    }
+}
+
+impl Backend for JsonBackend<'_> {
+    fn append(&mut self, records: Vec<Record>) -> Result<(), String> {
+        let mut corpus = Corpus::load(self.storage)?;
+        corpus.records.extend(records);
+        corpus.save(self.storage)
+    }
+
+    fn query(&self, query: &Query) -> Result<Vec<Record>, String> {
+        let corpus = Corpus::load(self.storage)?;
+        Ok(corpus.query(query).into_iter().cloned().collect())
+    }
+
+    fn count(&self) -> Result<usize, String> {
+        Ok(Corpus::load(self.storage)?.records.len())
+    }
+}
+
+/// База корпусов в хранилище `storage`. С возможностью `sqlite` это файл SQLite
+/// `taafl-corpus.db`, если хранилище держит записи в файлах; иначе,
+/// как и без неё, - запись JSON [`CORPUS_FILE`].
+pub fn open(storage: &mut dyn Storage) -> Result<Box<dyn Backend + '_>, String> {
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = storage.file(sqlite::CORPUS_DATABASE) {
+        return Ok(Box::new(sqlite::SqliteBackend::open(&path)?));
+    }
+    Ok(Box::new(JsonBackend::new(storage)))
+}
+
+/// Сегодняшняя дата (UTC) в виде `ГГГГ-ММ-ДД`.
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    date_of_day((seconds / 86_400) as i64)
+}
+
+/// Дата по номеру дня от 1970-01-01 в григорианском календаре.
+pub(crate) fn date_of_day(day: i64) -> String {
+    // Годы отсчитываются от 1 марта, чтобы високосный день был последним в году
+    let shifted = day + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn corpus_survives_storage_and_filters_by_tag_and_date() {
+        use crate::corpus::{date_of_day, Corpus, Query};
+        use crate::storage::MemoryStorage;
+
+        assert_eq!(date_of_day(0), "1970-01-01");
+        assert_eq!(date_of_day(11016), "2000-02-29");
+        assert_eq!(date_of_day(20740), "2026-10-14");
+
+        let config = Default::default();
+        let mut corpus = Corpus::default();
+        corpus.add_batch("A := 1; B := ;", "лаб-1", "2026-10-01", &config);
+        corpus.add_batch("C := D;", "лаб-2", "2026-10-14", &config);
+        let mut storage = MemoryStorage::default();
+        corpus.save(&mut storage).unwrap();
+        let corpus = Corpus::load(&storage).unwrap();
+
+        let inputs = |query: Query| -> Vec<String> {
+            corpus
+                .query(&query)
+                .iter()
+                .map(|record| record.input.clone())
+                .collect()
+        };
+        let tag = Some("лаб-1".to_string());
+        assert_eq!(
+            inputs(Query {
+                tag,
+                ..Query::default()
+            }),
+            ["A := 1;", "B := ;"]
+        );
+        let from = Some("2026-10-02".to_string());
+        assert_eq!(
+            inputs(Query {
+                from,
+                ..Query::default()
+            }),
+            ["C := D;"]
+        );
+        let to = Some("2026-10-01".to_string());
+        let code = Some("syntax".to_string());
+        assert_eq!(
+            inputs(Query {
+                code,
+                to,
+                ..Query::default()
+            }),
+            ["B := ;"]
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! База корпусов в файле SQLite (возможность `sqlite`). Записи лежат в таблице
+//! `records`, коды их диагностик - в `codes`; выборку по коду, метке и датам
+//! делает сама база по индексам, не читая записи целиком, как [`JsonBackend`].
+//!
+//! [`JsonBackend`]: super::JsonBackend
+//!
+//! ```
+//! use taafl::config::AnalyzerConfig;
+//! use taafl::corpus::sqlite::SqliteBackend;
+//! use taafl::corpus::{records, Backend, Query};
+//!
+//! let mut backend = SqliteBackend::in_memory().unwrap();
+//! let config = AnalyzerConfig::default();
+//! backend.append(records("A := 1; B := 0;", "лаб-1", "2026-10-14", &config)).unwrap();
+//! let query = Query { code: Some("constant-range".to_string()), ..Query::default() };
+//! assert_eq!(backend.query(&query).unwrap()[0].input, "B := 0;");
+//! ```
+
+use super::{Backend, Query, Record};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+/// Файл базы SQLite в хранилище.
+pub const CORPUS_DATABASE: &str = "taafl-corpus.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS records (
+    id INTEGER PRIMARY KEY,
+    input TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    date TEXT NOT NULL,
+    accepted INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS codes (
+    record INTEGER NOT NULL REFERENCES records (id),
+    position INTEGER NOT NULL,
+    code TEXT NOT NULL,
+    PRIMARY KEY (record, position)
+);
+CREATE INDEX IF NOT EXISTS records_tag ON records (tag);
+CREATE INDEX IF NOT EXISTS records_date ON records (date);
+CREATE INDEX IF NOT EXISTS codes_code ON codes (code);
+";
+
+/// Невыставленное условие (`NULL`) подходит для любой записи, как в [`Query::matches`].
+const SELECT: &str = "
+SELECT id, input, tag, date, accepted FROM records
+WHERE (?1 IS NULL OR EXISTS (SELECT 1 FROM codes WHERE record = records.id AND code = ?1))
+    AND (?2 IS NULL OR tag = ?2)
+    AND (?3 IS NULL OR date >= ?3)
+    AND (?4 IS NULL OR date <= ?4)
+ORDER BY id
+";
+
+/// База корпусов в соединении SQLite; схема создаётся при открытии.
+#[derive(Debug)]
+pub struct SqliteBackend {
+    connection: Connection,
+    /// Имя базы в сообщениях об ошибках
+    name: String,
+}
+
+impl SqliteBackend {
+    /// Открывает файл базы `path`, создавая его, если его ещё нет.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let name = path.display().to_string();
+        let connection =
+            Connection::open(path).map_err(|e| format!("Не удалось открыть {}: {}", name, e))?;
+        Self::with_schema(connection, name)
+    }
+
+    /// База в памяти; пропадает вместе с соединением.
+    pub fn in_memory() -> Result<Self, String> {
+        let connection = Connection::open_in_memory()
+            .map_err(|e| format!("Не удалось открыть базу в памяти: {}", e))?;
+        Self::with_schema(connection, "база в памяти".to_string())
+    }
+
+    fn with_schema(connection: Connection, name: String) -> Result<Self, String> {
+        let backend = Self { connection, name };
+        backend
+            .connection
+            .execute_batch(SCHEMA)
+            .map_err(|e| backend.failed(e))?;
+        Ok(backend)
+    }
+
+    fn failed(&self, e: rusqlite::Error) -> String {
+        format!("Ошибка базы {}: {}", self.name, e)
+    }
+
+    /// Коды диагностик записи `id` в порядке их появления.
+    fn codes(&self, id: i64) -> rusqlite::Result<Vec<String>> {
+        let mut statement = self
+            .connection
+            .prepare_cached("SELECT code FROM codes WHERE record = ?1 ORDER BY position")?;
+        let codes = statement.query_map(params![id], |row| row.get(0))?;
+        codes.collect()
+    }
+}
+
+impl Backend for SqliteBackend {
+    fn append(&mut self, records: Vec<Record>) -> Result<(), String> {
+        let name = self.name.clone();
+        let failed = |e: rusqlite::Error| format!("Ошибка базы {}: {}", name, e);
+        // Набор добавляется целиком или не добавляется вовсе
+        let transaction = self.connection.transaction().map_err(failed)?;
+        for record in records {
+            transaction
+                .execute(
+                    "INSERT INTO records (input, tag, date, accepted) VALUES (?1, ?2, ?3, ?4)",
+                    params![record.input, record.tag, record.date, record.accepted],
+                )
+                .map_err(failed)?;
+            let id = transaction.last_insert_rowid();
+            for (position, code) in (0_i64..).zip(&record.codes) {
+                transaction
+                    .execute(
+                        "INSERT INTO codes (record, position, code) VALUES (?1, ?2, ?3)",
+                        params![id, position, code],
+                    )
+                    .map_err(failed)?;
+            }
+        }
+        transaction.commit().map_err(failed)
+    }
+
+    fn query(&self, query: &Query) -> Result<Vec<Record>, String> {
+        let mut statement = self
+            .connection
+            .prepare_cached(SELECT)
+            .map_err(|e| self.failed(e))?;
+        let conditions = params![query.code, query.tag, query.from, query.to];
+        let rows = statement
+            .query_map(conditions, |row| {
+                let id: i64 = row.get(0)?;
+                let record = Record {
+                    input: row.get(1)?,
+                    tag: row.get(2)?,
+                    date: row.get(3)?,
+                    accepted: row.get(4)?,
+                    codes: Vec::new(),
+                };
+                Ok((id, record))
+            })
+            .map_err(|e| self.failed(e))?;
+        let mut found = Vec::new();
+        for row in rows {
+            let (id, mut record) = row.map_err(|e| self.failed(e))?;
+            record.codes = self.codes(id).map_err(|e| self.failed(e))?;
+            found.push(record);
+        }
+        Ok(found)
+    }
+
+    fn count(&self) -> Result<usize, String> {
+        let count: i64 = self
+            .connection
+            .query_row("SELECT COUNT(*) FROM records", [], |row| row.get(0))
+            .map_err(|e| self.failed(e))?;
+        Ok(usize::try_from(count).unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn database_keeps_records_and_filters_like_json() {
+        use super::SqliteBackend;
+        use crate::corpus::{records, Backend, JsonBackend, Query};
+        use crate::storage::MemoryStorage;
+
+        let config = Default::default();
+        let batches = [
+            records("A := 1; B := ;", "лаб-1", "2026-10-01", &config),
+            records("C := 0 + D;", "лаб-2", "2026-10-14", &config),
+        ];
+        let mut storage = MemoryStorage::default();
+        let mut json = JsonBackend::new(&mut storage);
+        let mut sqlite = SqliteBackend::in_memory().unwrap();
+        for batch in batches {
+            json.append(batch.clone()).unwrap();
+            sqlite.append(batch).unwrap();
+        }
+        assert_eq!(sqlite.count(), Ok(3));
+
+        let text = |value: &str| Some(value.to_string());
+        let queries = [
+            Query::default(),
+            Query {
+                tag: text("лаб-1"),
+                ..Query::default()
+            },
+            Query {
+                from: text("2026-10-02"),
+                ..Query::default()
+            },
+            Query {
+                code: text("syntax"),
+                to: text("2026-10-01"),
+                ..Query::default()
+            },
+            Query {
+                code: text("constant-range"),
+                ..Query::default()
+            },
+        ];
+        for query in &queries {
+            assert_eq!(sqlite.query(query), json.query(query), "{:?}", query);
+        }
+    }
+
+    #[test]
+    fn database_file_survives_reopening() {
+        use super::SqliteBackend;
+        use crate::corpus::{records, Backend, Query};
+
+        let dir = std::env::temp_dir().join(format!("taafl-corpus-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(super::CORPUS_DATABASE);
+        let _ = std::fs::remove_file(&path);
+
+        let config = Default::default();
+        let added = records("A := 1;", "лаб-1", "2026-10-14", &config);
+        SqliteBackend::open(&path)
+            .unwrap()
+            .append(added.clone())
+            .unwrap();
+        let reopened = SqliteBackend::open(&path).unwrap();
+        assert_eq!(reopened.query(&Query::default()), Ok(added));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
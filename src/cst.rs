@@ -0,0 +1,191 @@
+//! Конкретное дерево разбора: в отличие от [`ast`](crate::ast), в нём сохранена
+//! каждая лексема, включая скобки, запятые, присваивание и завершающий символ.
+//! Внутренние узлы - нетерминалы грамматики с применёнными к ним продукциями,
+//! листья - лексемы с байтовыми отрезками; по листьям подсвечивается текст.
+//!
+//! ```
+//! use taafl::analyzer::concrete_tree;
+//!
+//! let cst = concrete_tree("A[I] := 1;", &Default::default()).unwrap();
+//! let leaves: Vec<&str> = cst.leaves().iter().map(|leaf| leaf.lexeme.as_str()).collect();
+//! assert_eq!(leaves, ["A", "[", "I", "]", ":=", "1", ";"]);
+//! assert_eq!(cst.span(), 0..10);
+//! assert_eq!(cst.children[0].name, "left");
+//! ```
+
+use crate::grammar::{Grammar, Symbol};
+use std::ops::Range;
+
+/// Узел конкретного дерева.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// Нетерминал или, у листа, терминал грамматики: `index_list`, `IDENT`, `","`
+    pub name: String,
+    /// Байтовый отрезок ввода от первой лексемы узла до последней. У нетерминала,
+    /// выведенного в `ε`, он пустой и стоит сразу за предыдущей лексемой
+    pub span: Range<usize>,
+    /// Номер применённой продукции грамматики; у листа `None`
+    pub production: Option<usize>,
+    /// Текст лексемы листа; у нетерминала пустой
+    pub lexeme: String,
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    /// Дерево по левому выводу `applied` грамматики `grammar` - номерам продукций,
+    /// как их возвращает [`Ll1Table::derivation`](crate::grammar::Ll1Table::derivation).
+    /// `tokens` - отрезки лексем строки `input` по порядку. `None`, если продукций
+    /// или лексем не хватает или остались лишние.
+    pub fn build(
+        grammar: &Grammar,
+        applied: &[usize],
+        tokens: &[Range<usize>],
+        input: &str,
+    ) -> Option<Node> {
+        let mut builder = Builder {
+            grammar,
+            applied: applied.iter(),
+            tokens: tokens.iter(),
+            input,
+            position: 0,
+        };
+        let root = builder.expand(&Symbol::Nonterminal(grammar.start.clone()))?;
+        let complete = builder.applied.next().is_none() && builder.tokens.next().is_none();
+        complete.then_some(root)
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.production.is_none()
+    }
+
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Листья слева направо - все лексемы строки.
+    pub fn leaves(&self) -> Vec<&Node> {
+        let mut leaves = Vec::new();
+        self.collect_leaves(&mut leaves);
+        leaves
+    }
+
+    /// Внутренние узлы в порядке применения их продукций в левом выводе.
+    pub fn rules(&self) -> Vec<&Node> {
+        let mut rules = Vec::new();
+        self.collect_rules(&mut rules);
+        rules
+    }
+
+    fn collect_rules<'a>(&'a self, rules: &mut Vec<&'a Node>) {
+        if !self.is_leaf() {
+            rules.push(self);
+        }
+        for child in &self.children {
+            child.collect_rules(rules);
+        }
+    }
+
+    fn collect_leaves<'a>(&'a self, leaves: &mut Vec<&'a Node>) {
+        if self.is_leaf() {
+            leaves.push(self);
+        }
+        for child in &self.children {
+            child.collect_leaves(leaves);
+        }
+    }
+}
+
+/// Построение дерева: продукции и лексемы берутся по порядку.
+struct Builder<'a> {
+    grammar: &'a Grammar,
+    applied: std::slice::Iter<'a, usize>,
+    tokens: std::slice::Iter<'a, Range<usize>>,
+    input: &'a str,
+    /// Конец последней взятой лексемы
+    position: usize,
+}
+
+impl Builder<'_> {
+    fn expand(&mut self, symbol: &Symbol) -> Option<Node> {
+        let Symbol::Nonterminal(name) = symbol else {
+            let span = self.tokens.next()?.clone();
+            self.position = span.end;
+            return Some(Node {
+                name: symbol.terminal()?.to_string(),
+                lexeme: self.input.get(span.clone())?.to_string(),
+                span,
+                production: None,
+                children: Vec::new(),
+            });
+        };
+        let index = *self.applied.next()?;
+        let production = self.grammar.productions.get(index)?;
+        if production.lhs != *name {
+            return None;
+        }
+        let children = production
+            .rhs
+            .iter()
+            .map(|symbol| self.expand(symbol))
+            .collect::<Option<Vec<_>>>()?;
+        let mut covered = children.iter().map(Node::span).filter(|s| !s.is_empty());
+        let span = match (covered.next(), covered.next_back()) {
+            (Some(first), Some(last)) => first.start..last.end,
+            (Some(only), None) => only,
+            _ => self.position..self.position,
+        };
+        Some(Node {
+            name: name.clone(),
+            span,
+            production: Some(index),
+            lexeme: String::new(),
+            children,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn concrete_tree_keeps_every_token_as_a_leaf() {
+        use crate::analyzer::Analyzer;
+        use crate::config::{AnalyzerConfig, Brackets};
+        use crate::tree::Tree;
+
+        let input = "P.X[I, 2] := F(A, B) + 1;";
+        let analyzer = Analyzer::builder()
+            .collect_cst(true)
+            .collect_tokens(true)
+            .build();
+        let analysis = analyzer.analyze(input);
+        let cst = analysis.cst.unwrap();
+        let spans: Vec<_> = cst.leaves().iter().map(|leaf| leaf.span()).collect();
+        let tokens: Vec<_> = analysis.tokens.unwrap().into_iter().map(|t| t.0).collect();
+        assert_eq!(spans, tokens);
+        assert_eq!(cst.span(), 0..input.len());
+
+        // Левая часть кончается на ']', без пробела перед присваиванием
+        let left = &cst.children[0];
+        assert_eq!((left.name.as_str(), left.span()), ("left", 0..9));
+        let text = Tree::concrete(&cst).to_text();
+        assert!(text.contains("index_list"), "{}", text);
+        assert!(text.contains('ε'));
+
+        // Без лексем в анализе дерево всё равно строится, при ошибке - нет
+        let analyzer = Analyzer::builder().collect_cst(true).build();
+        let analysis = analyzer.analyze("A := B;");
+        assert!(analysis.tokens.is_none());
+        assert_eq!(analysis.cst.unwrap().leaves().len(), 4);
+        assert!(analyzer.analyze("A := ;").cst.is_none());
+
+        // Дерево строится по грамматике с настроенными скобками
+        let config = AnalyzerConfig {
+            brackets: Brackets::Round,
+            ..AnalyzerConfig::default()
+        };
+        let analyzer = Analyzer::builder().config(config).collect_cst(true).build();
+        let cst = analyzer.analyze("A(I) := 1;").cst.unwrap();
+        let leaves: Vec<_> = cst.leaves().iter().map(|l| l.name.clone()).collect();
+        assert_eq!(leaves, ["IDENT", "(", "IDENT", ")", ":=", "CONST", ";"]);
+    }
+}
@@ -0,0 +1,250 @@
+//! Диагностики анализа: ошибки и предупреждения с кодом, положением и пояснениями,
+//! а также перевод байтовых смещений в строки и столбцы многострочного текста.
+
+use std::fmt;
+use std::ops::Range;
+use unicode_width::UnicodeWidthChar;
+
+/// Одна ошибка или одно предупреждение.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// `lexical`, `syntax` или имя нарушенного семантического правила
+    /// (например, `constant-range`)
+    pub code: &'static str,
+    /// Положение в байтах от начала анализируемой строки: ошибочная конструкция
+    /// целиком, например слишком длинный идентификатор.
+    pub span: Range<usize>,
+    /// Номер строки начала (с единицы), если анализируемый текст многострочный
+    pub line: Option<usize>,
+    /// Столбец начала в символах, с единицы
+    pub column: usize,
+    pub message: String,
+    /// Пояснения, например подсказки учебного профиля; у синтаксической ошибки
+    /// последнее - терминалы, допустимые на её месте по таблице LL(1)
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Severity {
+    /// Строка не принадлежит языку
+    Error,
+    /// Нарушение правила со строгостью "предупреждение"
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "ошибка",
+            Severity::Warning => "предупреждение",
+        })
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    /// `ошибка[constant-range], столбец 6: Константа вне диапазона ...`, пояснения -
+    /// с новой строки. В многострочном тексте перед столбцом указывается строка.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}], ", self.severity, self.code)?;
+        if let Some(line) = self.line {
+            write!(f, "строка {}, ", line)?;
+        }
+        write!(f, "столбец {}: {}", self.column, self.message)?;
+        for note in &self.notes {
+            write!(f, "\n  = {}", note)?;
+        }
+        Ok(())
+    }
+}
+
+/// Шаг позиций табуляции на экране, как в терминале по умолчанию.
+pub const TAB_WIDTH: usize = 8;
+
+/// Позиция на экране после `text`, начатого с позиции `column` (с нуля).
+/// Табуляция продолжается до следующей позиции, кратной [`TAB_WIDTH`], широкие
+/// символы занимают две позиции, а управляющие и комбинируемые - ни одной.
+fn display_width(column: usize, text: &str) -> usize {
+    text.chars().fold(column, |column, c| match c {
+        '\t' => (column / TAB_WIDTH + 1) * TAB_WIDTH,
+        _ => column + c.width().unwrap_or(0),
+    })
+}
+
+/// Таблица текстом: столбцы выровнены по ширине на экране и разделены двумя
+/// пробелами, под заголовком - строка дефисов. Значения столбцов, для которых
+/// `right` истинно, прижаты вправо; заголовки - всегда влево.
+pub(crate) fn table(header: &[&str], rows: &[Vec<String>], right: &[bool]) -> String {
+    let widths: Vec<usize> = (0..header.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| display_width(0, &row[column]))
+                .chain([display_width(0, header[column])])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut text = String::new();
+    let mut line = |cells: &[&str], aligned: bool| {
+        let mut line = String::new();
+        for (column, cell) in cells.iter().enumerate() {
+            let pad = " ".repeat(widths[column] - display_width(0, cell));
+            if column > 0 {
+                line.push_str("  ");
+            }
+            if aligned && right.get(column) == Some(&true) {
+                line.push_str(&pad);
+                line.push_str(cell);
+            } else {
+                line.push_str(cell);
+                line.push_str(&pad);
+            }
+        }
+        text.push_str(line.trim_end());
+        text.push('\n');
+    };
+    line(header, false);
+    let dashes: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+    line(&dashes.iter().map(String::as_str).collect::<Vec<_>>(), false);
+    for row in rows {
+        line(&row.iter().map(String::as_str).collect::<Vec<_>>(), true);
+    }
+    text
+}
+
+/// Строка и столбец в тексте, с единицы. Столбец считается в символах.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Перевод байтовых смещений текста в строки и столбцы.
+///
+/// ```
+/// use taafl::diagnostic::{Location, SourceMap};
+///
+/// let map = SourceMap::new("A :=\n  Б $ 2;");
+/// assert_eq!(map.location(10), Location { line: 2, column: 5 });
+/// assert_eq!(map.line(2), "  Б $ 2;");
+/// assert_eq!(map.describe(10), "строка 2, столбец 5");
+/// assert_eq!(map.cursor(10..11), "    ^");
+///
+/// // Курсор выравнивается по позициям на экране: табуляция - до ближайшей
+/// // позиции, кратной `TAB_WIDTH`, иероглиф - две позиции
+/// let map = SourceMap::new("\tX := 字 $;");
+/// assert_eq!(map.cursor(6..9), "             ^^");
+/// assert_eq!(map.cursor(10..11), "                ^");
+/// ```
+#[derive(Debug, Clone)]
+pub struct SourceMap<'a> {
+    text: &'a str,
+    /// Смещения начал строк; первая строка начинается с нуля
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceMap<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { text, line_starts }
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        self.line_starts.len() > 1
+    }
+
+    /// Строка и столбец смещения. Смещение за концом текста указывает на его конец,
+    /// смещение внутри символа - на начало символа.
+    pub fn location(&self, offset: usize) -> Location {
+        let offset = self.floor(offset);
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let start = self.line_starts[line - 1];
+        Location {
+            line,
+            column: self.text[start..offset].chars().count() + 1,
+        }
+    }
+
+    /// Текст строки с номером `line` (с единицы) без перевода строки.
+    pub fn line(&self, line: usize) -> &'a str {
+        let Some(&start) = self.line_starts.get(line.wrapping_sub(1)) else {
+            return "";
+        };
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.text.len(), |next| next - 1);
+        self.text[start..end].trim_end_matches('\r')
+    }
+
+    /// Положение для сообщений: "столбец 5", а в многострочном тексте -
+    /// "строка 2, столбец 5".
+    pub fn describe(&self, offset: usize) -> String {
+        let location = self.location(offset);
+        if self.is_multiline() {
+            format!("строка {}, столбец {}", location.line, location.column)
+        } else {
+            format!("столбец {}", location.column)
+        }
+    }
+
+    /// Курсор под отрезком: пробелы до его начала и `^` под каждой позицией
+    /// экрана, которую занимает отрезок в пределах строки начала. Пустой отрезок
+    /// отмечается одним `^`.
+    pub fn cursor(&self, span: Range<usize>) -> String {
+        let start = self.floor(span.start);
+        let line_start = self.line_starts[self.location(start).line - 1];
+        let line_end = start + self.text[start..].find('\n').unwrap_or(self.text.len() - start);
+        let end = self.floor(span.end.min(line_end)).max(start);
+        let before = display_width(0, &self.text[line_start..start]);
+        let width = display_width(before, &self.text[start..end]) - before;
+        format!("{}{}", " ".repeat(before), "^".repeat(width.max(1)))
+    }
+
+    fn floor(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Location, SourceMap};
+
+    #[test]
+    fn crlf_ends_the_line_before_the_carriage_return() {
+        let map = SourceMap::new("A :=\r\nB;");
+        assert_eq!(map.location(4), Location { line: 1, column: 5 });
+        assert_eq!(map.location(6), Location { line: 2, column: 1 });
+        assert_eq!(map.line(1), "A :=");
+        assert_eq!(map.describe(7), "строка 2, столбец 2");
+    }
+
+    #[test]
+    fn end_of_input_is_after_the_last_character() {
+        let map = SourceMap::new("A := 1;\n");
+        assert_eq!(map.location(8), Location { line: 2, column: 1 });
+        assert_eq!(map.location(100), Location { line: 2, column: 1 });
+        let map = SourceMap::new("A := 1;");
+        assert_eq!(map.location(7), Location { line: 1, column: 8 });
+        assert_eq!(map.cursor(7..7), "       ^");
+    }
+
+    #[test]
+    fn columns_count_multibyte_characters_once() {
+        let text = "ЁЖ := 'Щ' $;";
+        let map = SourceMap::new(text);
+        let dollar = text.find('$').unwrap();
+        assert_eq!(map.location(dollar), Location { line: 1, column: 11 });
+        // Смещение внутри символа указывает на его начало
+        assert_eq!(map.location(1), Location { line: 1, column: 1 });
+        assert_eq!(map.location(3), Location { line: 1, column: 2 });
+    }
+}
@@ -0,0 +1,352 @@
+//! Генератор случайных правильных операторов: синтаксически верных и без нарушений
+//! семантических правил варианта 20. Для демонстраций, нагрузочных проверок и примеров.
+//!
+//! Генератор детерминирован: одно и то же зерно даёт одну и ту же последовательность
+//! операторов на любой платформе.
+
+use crate::ast::{
+    is_keyword, Assignment, Expression, Operand, Operation, Target, Term, KEYWORD_OPERATIONS,
+    OPERATIONS, RESERVED_WORDS, SIGNS, UNARY_OPERATIONS,
+};
+
+const LETTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const ALPHANUMERIC: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Ограничения на размер генерируемых операторов.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneratorOptions {
+    /// Наибольшее число индексов в левой части и у элемента массива в правой
+    pub max_indices: usize,
+    /// Наибольшее число операндов в правой части
+    pub max_operands: usize,
+    /// Наибольшая длина идентификатора (не более 8)
+    pub max_identifier_length: usize,
+    /// Вставлять случайные пробелы между лексемами
+    pub spacing: bool,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            max_indices: 3,
+            max_operands: 4,
+            max_identifier_length: 8,
+            spacing: true,
+        }
+    }
+}
+
+/// Генератор SplitMix64: простой и достаточный для примеров, без внешних зависимостей.
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Случайное число в диапазоне `[low..=high]`.
+    pub(crate) fn range(&mut self, low: usize, high: usize) -> usize {
+        low + (self.next() % (high - low + 1) as u64) as usize
+    }
+
+    pub(crate) fn chance(&mut self, percent: usize) -> bool {
+        self.range(1, 100) <= percent
+    }
+
+    pub(crate) fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.range(0, items.len() - 1)]
+    }
+}
+
+/// ```
+/// use taafl::analyzer::Analyzer;
+/// use taafl::generator::Generator;
+///
+/// let analyzer = Analyzer::default();
+/// for statement in Generator::new(2024).take(200) {
+///     assert!(analyzer.analyze(&statement).report.accepted, "{}", statement);
+/// }
+/// assert_eq!(
+///     Generator::new(7).take(3).collect::<Vec<_>>(),
+///     Generator::new(7).take(3).collect::<Vec<_>>(),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Generator {
+    rng: Rng,
+    pub options: GeneratorOptions,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Self::with_options(seed, GeneratorOptions::default())
+    }
+
+    pub fn with_options(seed: u64, options: GeneratorOptions) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            options,
+        }
+    }
+
+    /// Дерево разбора случайного оператора. Позиции в нём не заполнены (равны нулю).
+    pub fn assignment(&mut self) -> Assignment {
+        let name = self.identifier();
+        let indices = self.rng.chance(60).then(|| self.indices());
+
+        // Массив из левой части нельзя использовать в правой
+        let excluded = indices.as_ref().map(|_| name.clone());
+        let mut first = self.term(excluded.as_deref());
+        // Знак - только перед идентификатором: константа со знаком может выйти
+        // из диапазона варианта
+        if matches!(first.operand, Operand::Identifier { .. }) && self.rng.chance(10) {
+            let sign = Operation {
+                symbol: self.rng.pick(SIGNS),
+                position: 0,
+            };
+            first.unary.insert(0, sign);
+        }
+        let count = self.rng.range(1, self.options.max_operands.max(1));
+        let rest = (1..count)
+            .map(|_| {
+                let symbol = if self.rng.chance(15) {
+                    self.rng.pick(KEYWORD_OPERATIONS)
+                } else {
+                    self.rng.pick(OPERATIONS)
+                };
+                let operation = Operation {
+                    symbol,
+                    position: 0,
+                };
+                (operation, self.term(excluded.as_deref()))
+            })
+            .collect();
+
+        Assignment {
+            target: Target {
+                name,
+                position: 0,
+                fields: Vec::new(),
+                indices,
+                chain: Vec::new(),
+            },
+            value: Expression { first, rest },
+        }
+    }
+
+    /// Текст случайного оператора.
+    pub fn statement(&mut self) -> String {
+        let assignment = self.assignment();
+        self.render(&assignment)
+    }
+
+    /// Записывает дерево разбора текстом, со случайными пробелами, если они включены.
+    pub fn render(&mut self, assignment: &Assignment) -> String {
+        let mut text = assignment.target.name.clone();
+        if let Some(indices) = &assignment.target.indices {
+            self.render_indices(indices, &mut text);
+        }
+        self.space(&mut text);
+        text.push_str(":=");
+        self.space(&mut text);
+        self.render_expression(&assignment.value, &mut text);
+        text.push(';');
+        text
+    }
+
+    fn render_expression(&mut self, expression: &Expression, text: &mut String) {
+        self.render_term(&expression.first, text);
+        for (operation, term) in &expression.rest {
+            // Ключевое слово отделяется от операндов пробелами всегда
+            let keyword = KEYWORD_OPERATIONS.contains(&operation.symbol);
+            self.space_or(keyword, text);
+            text.push_str(operation.symbol);
+            self.space_or(keyword, text);
+            self.render_term(term, text);
+        }
+    }
+
+    fn render_term(&mut self, term: &Term, text: &mut String) {
+        for operation in &term.unary {
+            text.push_str(operation.symbol);
+            self.space_or(true, text);
+        }
+        match &term.operand {
+            Operand::Element { name, indices, .. } => {
+                text.push_str(name);
+                self.render_indices(indices, text);
+            }
+            Operand::Call {
+                name, arguments, ..
+            } => {
+                text.push_str(name);
+                self.render_list(('(', ')'), arguments, text);
+            }
+            operand => text.push_str(&operand.to_string()),
+        }
+    }
+
+    fn render_indices(&mut self, indices: &[Expression], text: &mut String) {
+        self.render_list(('[', ']'), indices, text);
+    }
+
+    /// Выражения через запятую в скобках `open` и `close`.
+    fn render_list(
+        &mut self,
+        (open, close): (char, char),
+        expressions: &[Expression],
+        text: &mut String,
+    ) {
+        text.push(open);
+        for (i, expression) in expressions.iter().enumerate() {
+            if i > 0 {
+                text.push(',');
+                self.space(text);
+            }
+            self.render_expression(expression, text);
+        }
+        text.push(close);
+    }
+
+    fn space(&mut self, text: &mut String) {
+        if self.options.spacing && self.rng.chance(70) {
+            text.push(' ');
+        }
+    }
+
+    /// Обязательный пробел, если `required`, иначе случайный.
+    fn space_or(&mut self, required: bool, text: &mut String) {
+        if required {
+            text.push(' ');
+        } else {
+            self.space(text);
+        }
+    }
+
+    pub(crate) fn identifier(&mut self) -> String {
+        let len = self
+            .rng
+            .range(1, self.options.max_identifier_length.clamp(1, 8));
+        loop {
+            let mut name = String::new();
+            name.push(self.rng.pick(LETTERS) as char);
+            for _ in 1..len {
+                name.push(self.rng.pick(ALPHANUMERIC) as char);
+            }
+            if !is_keyword(&name) && !RESERVED_WORDS.contains(&name.as_str()) {
+                return name;
+            }
+        }
+    }
+
+    /// Индексы левой части или элемента массива в правой.
+    fn indices(&mut self) -> Vec<Expression> {
+        let count = self.rng.range(1, self.options.max_indices.max(1));
+        (0..count).map(|_| self.index()).collect()
+    }
+
+    /// Индекс: операнд, изредка с ещё одним через операцию.
+    fn index(&mut self) -> Expression {
+        let first = Term {
+            unary: Vec::new(),
+            operand: self.operand(None),
+        };
+        let mut rest = Vec::new();
+        if self.rng.chance(20) {
+            let operation = Operation {
+                symbol: self.rng.pick(OPERATIONS),
+                position: 0,
+            };
+            let term = Term {
+                unary: Vec::new(),
+                operand: self.operand(None),
+            };
+            rest.push((operation, term));
+        }
+        Expression { first, rest }
+    }
+
+    /// Операнд правой части, изредка с отрицанием `NOT`, элемент массива или
+    /// вызов функции с одним аргументом.
+    fn term(&mut self, excluded: Option<&str>) -> Term {
+        let unary = if self.rng.chance(10) {
+            vec![Operation {
+                symbol: self.rng.pick(UNARY_OPERATIONS),
+                position: 0,
+            }]
+        } else {
+            Vec::new()
+        };
+        let operand = match self.operand(excluded) {
+            Operand::Identifier {
+                name,
+                position,
+                fields,
+            } if self.rng.chance(15) => Operand::Element {
+                name,
+                position,
+                fields,
+                indices: self.indices(),
+            },
+            Operand::Identifier {
+                name,
+                position,
+                fields,
+            } if self.rng.chance(5) => Operand::Call {
+                name,
+                position,
+                fields,
+                arguments: vec![Expression {
+                    first: self.term(excluded),
+                    rest: Vec::new(),
+                }],
+            },
+            operand => operand,
+        };
+        Term { unary, operand }
+    }
+
+    fn operand(&mut self, excluded: Option<&str>) -> Operand {
+        if self.rng.chance(50) {
+            let mut name = self.identifier();
+            while Some(name.as_str()) == excluded {
+                name = self.identifier();
+            }
+            // Изредка - поле записи
+            let mut fields = Vec::new();
+            if self.rng.chance(5) {
+                name = format!("{}.{}", name, self.identifier());
+                fields.push(0);
+            }
+            Operand::Identifier {
+                name,
+                position: 0,
+                fields,
+            }
+        } else {
+            // Малые константы чаще, чтобы примеры было удобно читать
+            let high = if self.rng.chance(80) { 100 } else { 32767 };
+            Operand::Constant {
+                value: self.rng.range(1, high) as i32,
+                position: 0,
+            }
+        }
+    }
+}
+
+impl Iterator for Generator {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        Some(self.statement())
+    }
+}
@@ -0,0 +1,792 @@
+//! Грамматика оператора в виде данных и табличный LL(1)-анализатор по ней.
+//!
+//! Грамматика записывается в тексте [`GRAMMAR`] в упрощённой EBNF: правило
+//! `имя = альтернатива | альтернатива ;`, нетерминалы - имена в нижнем регистре,
+//! классы лексем - имена в верхнем регистре (`IDENT`, `CONST`, `LITERAL`, `OP`),
+//! литералы - в кавычках, пустая альтернатива - `ε`, комментарии - `(* ... *)`.
+//! Стартовый нетерминал - левая часть первого правила.
+//!
+//! Чтобы изменить грамматику для другого варианта, достаточно изменить текст:
+//! таблица разбора строится по нему при запуске.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+pub mod ambiguity;
+pub mod earley;
+pub mod export;
+#[cfg(feature = "lalrpop")]
+pub mod lalrpop;
+pub mod scaffold;
+
+/// Грамматика варианта 20 без левой рекурсии.
+pub const GRAMMAR: &str = r#"
+(* <левая часть> := <правая часть>; *)
+statement  = left ":=" right ";" ;
+left       = IDENT field left_tail ;
+field      = "." IDENT field | ε ;
+left_tail  = "[" index_list "]" field left_tail | ε ;
+index_list = index index_rest ;
+index_rest = "," index index_rest | ε ;
+index      = SIGN index_term index_tail | index_term index_tail ;
+index_tail = OP index_term index_tail | SIGN index_term index_tail | ε ;
+index_term = "NOT" index_term | IDENT field | CONST ;
+right      = SIGN term right_rest | term right_rest ;
+right_rest = OP term right_rest | SIGN term right_rest | ε ;
+term       = "NOT" term | IDENT field term_tail | CONST | LITERAL ;
+term_tail  = "[" index_list "]" | "(" arguments ")" | ε ;
+arguments  = right argument_rest | ε ;
+argument_rest = "," right argument_rest | ε ;
+"#;
+
+/// Альтернатива поля записи в [`GRAMMAR`]: с точкой в конце оператора её нет.
+const FIELD_SELECTOR: &str = "\".\" IDENT field | ";
+
+/// Альтернатива вызова функции в [`GRAMMAR`]: если индексы пишутся в круглых
+/// скобках, её нет.
+const CALL: &str = "\"(\" arguments \")\" | ";
+
+/// Текст грамматики с другим завершающим символом оператора: литерал `";"`
+/// заменяется на `symbol`, а при `None` удаляется - оператор заканчивается вместе
+/// со строкой. Если оператор заканчивается точкой, полей записей нет: у `field`
+/// остаётся только `ε`.
+///
+/// ```
+/// use taafl::grammar::{with_terminator, Grammar, GRAMMAR};
+///
+/// let dot = Grammar::parse(&with_terminator(GRAMMAR, Some('.'))).unwrap();
+/// assert_eq!(dot.productions[0].to_string(), r#"statement = left ":=" right ".""#);
+/// assert_eq!(dot.productions[2].to_string(), "field = ε");
+/// let none = Grammar::parse(&with_terminator(GRAMMAR, None)).unwrap();
+/// assert_eq!(none.productions[0].to_string(), r#"statement = left ":=" right"#);
+/// ```
+pub fn with_terminator(text: &str, symbol: Option<char>) -> String {
+    let replacement = symbol.map_or(String::new(), |c| format!("\"{}\"", c));
+    let text = match symbol {
+        Some('.') => text.replace(FIELD_SELECTOR, ""),
+        _ => text.to_string(),
+    };
+    text.replace("\";\"", &replacement)
+}
+
+/// Текст грамматики с другими скобками индексов: литералы `"["` и `"]"`
+/// заменяются на `open` и `close`. Круглые скобки индексов не оставляют места
+/// вызову функции: у `term_tail` его альтернативы нет.
+///
+/// ```
+/// use taafl::grammar::{with_brackets, Grammar, GRAMMAR};
+///
+/// let round = Grammar::parse(&with_brackets(GRAMMAR, ('(', ')'))).unwrap();
+/// let left_tail = round.productions[4].to_string();
+/// assert_eq!(left_tail, r#"left_tail = "(" index_list ")" field left_tail"#);
+/// let term_tail: Vec<String> = round
+///     .productions_of("term_tail")
+///     .map(|(_, production)| production.to_string())
+///     .collect();
+/// assert_eq!(term_tail, [r#"term_tail = "(" index_list ")""#, "term_tail = ε"]);
+/// ```
+pub fn with_brackets(text: &str, (open, close): (char, char)) -> String {
+    let text = match open {
+        '(' => text.replace(CALL, ""),
+        _ => text.to_string(),
+    };
+    text.replace("\"[\"", &format!("\"{}\"", open))
+        .replace("\"]\"", &format!("\"{}\"", close))
+}
+
+/// Маркер конца ввода в таблице разбора.
+pub const END: &str = "$";
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Symbol {
+    /// Класс лексем: `IDENT`, `CONST`, `LITERAL`, `OP`
+    Class(String),
+    /// Литерал: `:=`, `[`
+    Literal(String),
+    Nonterminal(String),
+}
+
+impl Symbol {
+    /// Имя терминала в таблице разбора: имя класса или текст литерала.
+    pub fn terminal(&self) -> Option<&str> {
+        match self {
+            Symbol::Class(name) | Symbol::Literal(name) => Some(name),
+            Symbol::Nonterminal(_) => None,
+        }
+    }
+}
+
+/// Продукция `lhs -> rhs`; пустая `rhs` соответствует `ε`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Production {
+    pub lhs: String,
+    pub rhs: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grammar {
+    pub start: String,
+    /// Продукции в порядке записи
+    pub productions: Vec<Production>,
+}
+
+/// Лексема текста грамматики.
+#[derive(Debug, Clone, PartialEq)]
+enum Item {
+    Name(String),
+    Literal(String),
+    Epsilon,
+    Define,
+    Alternative,
+    End,
+}
+
+impl Grammar {
+    /// Встроенная грамматика [`GRAMMAR`].
+    pub fn builtin() -> Self {
+        Self::parse(GRAMMAR).expect("встроенная грамматика корректна")
+    }
+
+    /// Разбирает текст грамматики.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let items = Self::items(text)?;
+        let mut productions = Vec::new();
+        let mut rules = items.split(|item| *item == Item::End).peekable();
+        while let Some(rule) = rules.next() {
+            if rule.is_empty() && rules.peek().is_none() {
+                break;
+            }
+            let (lhs, body) = match rule {
+                [Item::Name(lhs), Item::Define, body @ ..] if is_nonterminal(lhs) => (lhs, body),
+                _ => return Err(format!("Ожидалось правило 'имя = ... ;': {:?}", rule)),
+            };
+            for alternative in body.split(|item| *item == Item::Alternative) {
+                let mut rhs = Vec::new();
+                for item in alternative {
+                    match item {
+                        Item::Name(name) if is_nonterminal(name) => {
+                            rhs.push(Symbol::Nonterminal(name.clone()))
+                        }
+                        Item::Name(name) => rhs.push(Symbol::Class(name.clone())),
+                        Item::Literal(name) => rhs.push(Symbol::Literal(name.clone())),
+                        Item::Epsilon if alternative.len() == 1 => {}
+                        _ => return Err(format!("Недопустимая альтернатива в правиле {}", lhs)),
+                    }
+                }
+                productions.push(Production {
+                    lhs: lhs.clone(),
+                    rhs,
+                });
+            }
+        }
+
+        let start = match productions.first() {
+            Some(production) => production.lhs.clone(),
+            None => return Err("Грамматика не содержит правил".to_string()),
+        };
+        let grammar = Grammar { start, productions };
+        let defined = grammar.nonterminals();
+        for production in &grammar.productions {
+            for symbol in &production.rhs {
+                if let Symbol::Nonterminal(name) = symbol {
+                    if !defined.contains(name) {
+                        return Err(format!("Нетерминал {} не определён", name));
+                    }
+                }
+            }
+        }
+        Ok(grammar)
+    }
+
+    fn items(text: &str) -> Result<Vec<Item>, String> {
+        let mut items = Vec::new();
+        let mut chars = text.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                _ if c.is_whitespace() => {}
+                '(' if text[i..].starts_with("(*") => match text[i..].find("*)") {
+                    Some(end) => {
+                        while chars.peek().is_some_and(|(j, _)| *j < i + end + 2) {
+                            chars.next();
+                        }
+                    }
+                    None => return Err("Незакрытый комментарий".to_string()),
+                },
+                '"' => {
+                    let mut literal = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '"')) => break,
+                            Some((_, c)) => literal.push(c),
+                            None => return Err("Незакрытый литерал".to_string()),
+                        }
+                    }
+                    items.push(Item::Literal(literal));
+                }
+                '=' => items.push(Item::Define),
+                '|' => items.push(Item::Alternative),
+                ';' => items.push(Item::End),
+                'ε' => items.push(Item::Epsilon),
+                _ if c.is_ascii_alphabetic() => {
+                    let mut name = c.to_string();
+                    while let Some((_, c)) = chars.peek().filter(|(_, c)| is_name_char(*c)) {
+                        name.push(*c);
+                        chars.next();
+                    }
+                    items.push(Item::Name(name));
+                }
+                _ => return Err(format!("Недопустимый символ в грамматике: '{}'", c)),
+            }
+        }
+        Ok(items)
+    }
+
+    /// Нетерминалы в порядке первого определения.
+    pub fn nonterminals(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for production in &self.productions {
+            if !names.contains(&production.lhs) {
+                names.push(production.lhs.clone());
+            }
+        }
+        names
+    }
+
+    /// Продукции нетерминала с их номерами.
+    pub fn productions_of<'a, 'b>(
+        &'a self,
+        nonterminal: &'b str,
+    ) -> impl Iterator<Item = (usize, &'a Production)> + 'b
+    where
+        'a: 'b,
+    {
+        self.productions
+            .iter()
+            .enumerate()
+            .filter(move |(_, production)| production.lhs == nonterminal)
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_nonterminal(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Symbol::Class(name) | Symbol::Nonterminal(name) => f.write_str(name),
+            Symbol::Literal(name) => write!(f, "\"{}\"", name),
+        }
+    }
+}
+
+/// Продукция в записи [`GRAMMAR`]: `left = IDENT left_tail`, пустая - `left_tail = ε`.
+impl fmt::Display for Production {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} =", self.lhs)?;
+        if self.rhs.is_empty() {
+            return f.write_str(" ε");
+        }
+        for symbol in &self.rhs {
+            write!(f, " {}", symbol)?;
+        }
+        Ok(())
+    }
+}
+
+/// Название терминала для сообщений об ошибках.
+pub fn describe_terminal(terminal: &str) -> String {
+    match terminal {
+        "IDENT" => "идентификатор".to_string(),
+        "CONST" => "константа".to_string(),
+        "LITERAL" => "символьная или строковая константа".to_string(),
+        "OP" => "операция".to_string(),
+        "SIGN" => "знак '+' или '-'".to_string(),
+        END => "конец строки".to_string(),
+        _ => format!("'{}'", terminal),
+    }
+}
+
+/// Обращение к таблице LL(1) на шаге разбора: клетка (нетерминал, терминал)
+/// и продукция в ней.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableStep {
+    pub nonterminal: String,
+    pub terminal: String,
+    /// Позиция терминала впереди, у конца ввода - позиция конца
+    pub position: usize,
+    /// Номер продукции в клетке; `None` - клетка пуста и разбор на ней остановился
+    pub production: Option<usize>,
+}
+
+/// Таблица LL(1)-разбора: (нетерминал, терминал) -> номер продукции.
+#[derive(Debug, Clone)]
+pub struct Ll1Table {
+    grammar: Grammar,
+    first: BTreeMap<String, BTreeSet<String>>,
+    follow: BTreeMap<String, BTreeSet<String>>,
+    nullable: BTreeSet<String>,
+    entries: BTreeMap<(String, String), usize>,
+}
+
+impl Ll1Table {
+    /// Строит таблицу. Если грамматика не LL(1), возвращает описания конфликтов.
+    pub fn build(grammar: Grammar) -> Result<Self, Vec<String>> {
+        let mut table = Ll1Table {
+            grammar,
+            first: BTreeMap::new(),
+            follow: BTreeMap::new(),
+            nullable: BTreeSet::new(),
+            entries: BTreeMap::new(),
+        };
+        table.compute_first();
+        table.compute_follow();
+
+        let mut conflicts = Vec::new();
+        for (index, production) in table.grammar.productions.iter().enumerate() {
+            let (mut lookahead, nullable) = table.first_of(&production.rhs);
+            if nullable {
+                lookahead.extend(table.follow[&production.lhs].iter().cloned());
+            }
+            for terminal in lookahead {
+                let key = (production.lhs.clone(), terminal);
+                if let Some(other) = table.entries.get(&key) {
+                    conflicts.push(format!(
+                        "Конфликт в {} по {}: продукции {} и {}",
+                        key.0,
+                        describe_terminal(&key.1),
+                        other + 1,
+                        index + 1
+                    ));
+                } else {
+                    table.entries.insert(key, index);
+                }
+            }
+        }
+
+        if conflicts.is_empty() {
+            Ok(table)
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    /// Таблица для встроенной грамматики.
+    pub fn builtin() -> Self {
+        Self::build(Grammar::builtin()).expect("встроенная грамматика - LL(1)")
+    }
+
+    pub fn grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+
+    pub fn first(&self, nonterminal: &str) -> Option<&BTreeSet<String>> {
+        self.first.get(nonterminal)
+    }
+
+    pub fn follow(&self, nonterminal: &str) -> Option<&BTreeSet<String>> {
+        self.follow.get(nonterminal)
+    }
+
+    pub fn is_nullable(&self, nonterminal: &str) -> bool {
+        self.nullable.contains(nonterminal)
+    }
+
+    /// Номер продукции для нетерминала при данном терминале впереди.
+    pub fn entry(&self, nonterminal: &str, terminal: &str) -> Option<usize> {
+        self.entries
+            .get(&(nonterminal.to_string(), terminal.to_string()))
+            .copied()
+    }
+
+    /// Все заполненные клетки таблицы.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str, usize)> {
+        self.entries.iter().map(|((nonterminal, terminal), index)| {
+            (nonterminal.as_str(), terminal.as_str(), *index)
+        })
+    }
+
+    /// Терминалы, с которых разбор может продолжиться при магазине `stack`
+    /// (вершина - последний символ), в порядке сортировки; [`END`] - если
+    /// магазин может опустеть. В отличие от строки таблицы для нетерминала
+    /// на вершине, ε-продукции проверяются до следующего терминала: `)` после
+    /// правой части годится лишь внутри вызова.
+    fn viable(&self, stack: &[Symbol]) -> Vec<String> {
+        let terminals: BTreeSet<&str> = self
+            .entries()
+            .map(|(_, terminal, _)| terminal)
+            .chain([END])
+            .collect();
+        terminals
+            .into_iter()
+            .filter(|terminal| self.accepts(stack.to_vec(), terminal))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Снимает с магазина символы до терминала `terminal`: берётся ли он.
+    fn accepts(&self, mut stack: Vec<Symbol>, terminal: &str) -> bool {
+        while let Some(symbol) = stack.pop() {
+            match symbol {
+                Symbol::Class(expected) | Symbol::Literal(expected) => return expected == terminal,
+                Symbol::Nonterminal(name) => {
+                    let Some(index) = self.entry(&name, terminal) else {
+                        return false;
+                    };
+                    let rhs = &self.grammar.productions[index].rhs;
+                    stack.extend(rhs.iter().rev().cloned());
+                }
+            }
+        }
+        terminal == END
+    }
+
+    /// FIRST цепочки символов и её выводимость в ε.
+    fn first_of(&self, symbols: &[Symbol]) -> (BTreeSet<String>, bool) {
+        let mut first = BTreeSet::new();
+        for symbol in symbols {
+            match symbol {
+                Symbol::Class(terminal) | Symbol::Literal(terminal) => {
+                    first.insert(terminal.clone());
+                    return (first, false);
+                }
+                Symbol::Nonterminal(name) => {
+                    if let Some(set) = self.first.get(name) {
+                        first.extend(set.iter().cloned());
+                    }
+                    if !self.nullable.contains(name) {
+                        return (first, false);
+                    }
+                }
+            }
+        }
+        (first, true)
+    }
+
+    fn compute_first(&mut self) {
+        for name in self.grammar.nonterminals() {
+            self.first.insert(name, BTreeSet::new());
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in &self.grammar.productions {
+                let (first, nullable) = self.first_of(&production.rhs);
+                let set = self.first.entry(production.lhs.clone()).or_default();
+                let before = set.len();
+                set.extend(first);
+                changed |= set.len() != before;
+                if nullable {
+                    changed |= self.nullable.insert(production.lhs.clone());
+                }
+            }
+        }
+    }
+
+    fn compute_follow(&mut self) {
+        for name in self.grammar.nonterminals() {
+            self.follow.insert(name, BTreeSet::new());
+        }
+        if let Some(set) = self.follow.get_mut(&self.grammar.start) {
+            set.insert(END.to_string());
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for production in &self.grammar.productions {
+                for (i, symbol) in production.rhs.iter().enumerate() {
+                    let Symbol::Nonterminal(name) = symbol else {
+                        continue;
+                    };
+                    let (mut follow, nullable) = self.first_of(&production.rhs[i + 1..]);
+                    if nullable {
+                        follow.extend(self.follow[&production.lhs].iter().cloned());
+                    }
+                    let set = self.follow.entry(name.clone()).or_default();
+                    let before = set.len();
+                    set.extend(follow);
+                    changed |= set.len() != before;
+                }
+            }
+        }
+    }
+
+    /// Проверяет последовательность терминалов с позициями. `end` - позиция конца ввода.
+    ///
+    /// При ошибке возвращает позицию и сообщение вида "Ожидалось: ...".
+    ///
+    /// ```
+    /// use taafl::grammar::Ll1Table;
+    ///
+    /// let table = Ll1Table::builtin();
+    /// let statement = [(0, "IDENT"), (2, ":="), (5, "CONST"), (6, ";")];
+    /// assert!(table.parse(&statement, 6).is_ok());
+    /// assert_eq!(table.parse(&statement[..3], 5).unwrap_err().0, 5);
+    /// ```
+    pub fn parse(&self, input: &[(usize, &str)], end: usize) -> Result<(), (usize, String)> {
+        self.derivation(input, end).map(|_| ())
+    }
+
+    /// То же, что и [`parse`](Self::parse), но возвращает номера продукций левого
+    /// вывода в порядке их применения.
+    ///
+    /// ```
+    /// use taafl::grammar::Ll1Table;
+    ///
+    /// let table = Ll1Table::builtin();
+    /// let statement = [(0, "IDENT"), (2, ":="), (5, "CONST"), (6, ";")];
+    /// let applied: Vec<String> = table
+    ///     .derivation(&statement, 6)
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .map(|i| table.grammar().productions[i].to_string())
+    ///     .collect();
+    /// assert_eq!(applied[0], r#"statement = left ":=" right ";""#);
+    /// assert_eq!(applied[1], "left = IDENT field left_tail");
+    /// assert_eq!(applied.len(), 7);
+    /// ```
+    pub fn derivation(
+        &self,
+        input: &[(usize, &str)],
+        end: usize,
+    ) -> Result<Vec<usize>, (usize, String)> {
+        self.trace(input, end, |_| ())
+    }
+
+    /// То же, что и [`derivation`](Self::derivation), но сообщает `step` о каждом
+    /// обращении к таблице, включая пустую клетку, на которой разбор остановился.
+    ///
+    /// ```
+    /// use taafl::grammar::Ll1Table;
+    ///
+    /// let table = Ll1Table::builtin();
+    /// let statement = [(0, "IDENT"), (2, ":="), (5, ";")];
+    /// let mut steps = Vec::new();
+    /// assert!(table.trace(&statement, 5, |step| steps.push(step)).is_err());
+    /// let cells: Vec<(&str, &str)> = steps
+    ///     .iter()
+    ///     .map(|step| (step.nonterminal.as_str(), step.terminal.as_str()))
+    ///     .collect();
+    /// assert_eq!(cells[..2], [("statement", "IDENT"), ("left", "IDENT")]);
+    /// let last = steps.last().unwrap();
+    /// assert_eq!((last.nonterminal.as_str(), last.terminal.as_str()), ("right", ";"));
+    /// assert_eq!((last.position, last.production), (5, None));
+    /// ```
+    pub fn trace(
+        &self,
+        input: &[(usize, &str)],
+        end: usize,
+        step: impl FnMut(TableStep),
+    ) -> Result<Vec<usize>, (usize, String)> {
+        self.run(input, end, step).map_err(|stop| match stop {
+            Stop::Expected(pos, expected, found) => {
+                let expected: Vec<&str> = expected.iter().map(String::as_str).collect();
+                (pos, expected_message(&expected, found))
+            }
+            Stop::Trailing(pos) => (pos, self.trailing_message()),
+        })
+    }
+
+    /// Место, где разбор последовательности терминалов останавливается, и
+    /// терминалы, которые грамматика допускает на этом месте; `None`, если
+    /// последовательность разбирается целиком. Лишним лексемам после оператора
+    /// предпочитается конец ввода, [`END`].
+    ///
+    /// ```
+    /// use taafl::grammar::{Ll1Table, END};
+    ///
+    /// let table = Ll1Table::builtin();
+    /// let statement = [(0, "IDENT"), (2, ":="), (5, "CONST"), (6, ")")];
+    /// let (pos, expected) = table.expected(&statement, 6).unwrap();
+    /// assert_eq!((pos, expected), (6, vec![";".to_string(), "OP".into(), "SIGN".into()]));
+    /// let call = [(0, "IDENT"), (2, ":="), (5, "IDENT"), (6, "("), (7, "CONST")];
+    /// let (_, expected) = table.expected(&call, 8).unwrap();
+    /// assert_eq!(expected, [")", ",", "OP", "SIGN"]);
+    /// let trailing = [(0, "IDENT"), (2, ":="), (5, "CONST"), (6, ";"), (8, "IDENT")];
+    /// assert_eq!(table.expected(&trailing, 9).unwrap(), (8, vec![END.to_string()]));
+    /// assert_eq!(table.expected(&statement[..3], 5).unwrap().0, 5);
+    /// ```
+    pub fn expected(&self, input: &[(usize, &str)], end: usize) -> Option<(usize, Vec<String>)> {
+        match self.run(input, end, |_| ()) {
+            Ok(_) => None,
+            Err(Stop::Expected(pos, expected, _)) => Some((pos, expected)),
+            Err(Stop::Trailing(pos)) => Some((pos, vec![END.to_string()])),
+        }
+    }
+
+    fn run<'a>(
+        &self,
+        input: &[(usize, &'a str)],
+        end: usize,
+        mut step: impl FnMut(TableStep),
+    ) -> Result<Vec<usize>, Stop<'a>> {
+        let mut applied = Vec::new();
+        let mut stack = vec![Symbol::Nonterminal(self.grammar.start.clone())];
+        // Магазин после последней взятой лексемы: ε-продукции, выбранные по
+        // ошибочной лексеме, сужают допустимые терминалы
+        let mut checkpoint = stack.clone();
+        let mut i = 0;
+        while let Some(symbol) = stack.pop() {
+            let (pos, lookahead) = input.get(i).copied().unwrap_or((end, END));
+            match symbol {
+                Symbol::Class(terminal) | Symbol::Literal(terminal) => {
+                    if terminal != lookahead {
+                        return Err(Stop::Expected(pos, self.viable(&checkpoint), lookahead));
+                    }
+                    i += 1;
+                    checkpoint.clone_from(&stack);
+                }
+                Symbol::Nonterminal(name) => {
+                    let production = self.entry(&name, lookahead);
+                    step(TableStep {
+                        nonterminal: name.clone(),
+                        terminal: lookahead.to_string(),
+                        position: pos,
+                        production,
+                    });
+                    let Some(index) = production else {
+                        return Err(Stop::Expected(pos, self.viable(&checkpoint), lookahead));
+                    };
+                    let rhs = &self.grammar.productions[index].rhs;
+                    stack.extend(rhs.iter().rev().cloned());
+                    applied.push(index);
+                }
+            }
+        }
+        match input.get(i) {
+            Some((pos, _)) => Err(Stop::Trailing(*pos)),
+            None => Ok(applied),
+        }
+    }
+
+    /// Сообщение о лексемах после конца оператора: называет последний литерал
+    /// стартовой продукции, если она им заканчивается.
+    fn trailing_message(&self) -> String {
+        let last = self
+            .grammar
+            .productions_of(&self.grammar.start)
+            .find_map(|(_, production)| match production.rhs.last() {
+                Some(Symbol::Literal(literal)) => Some(literal.as_str()),
+                _ => None,
+            });
+        match last {
+            Some(literal) => format!("После '{}' ничего не ожидается", literal),
+            None => "После оператора ничего не ожидается".to_string(),
+        }
+    }
+}
+
+/// Остановка разбора по таблице.
+enum Stop<'a> {
+    /// Позиция, допустимые на ней терминалы и найденный терминал
+    Expected(usize, Vec<String>, &'a str),
+    /// Позиция первой лексемы после конца оператора
+    Trailing(usize),
+}
+
+pub(crate) fn expected_message(expected: &[&str], found: &str) -> String {
+    let expected: Vec<String> = expected
+        .iter()
+        .map(|terminal| describe_terminal(terminal))
+        .collect();
+    let mut message = format!("Ожидалось: {}", expected.join(" или "));
+    if found == END {
+        message.push_str(", но достигнут конец");
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Grammar, Ll1Table};
+    use crate::analyzer::{tokenize, Analyzer};
+    use crate::testing::{forall, Cases, CASES};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    /// Левая часть начинается одинаково в обеих продукциях, а `tail` может быть
+    /// пустым: грамматика не LL(1).
+    const NOT_LL1: &str = r#"
+        statement = left tail ";" ;
+        left = IDENT | IDENT "[" CONST "]" ;
+        tail = ":=" CONST | ;
+    "#;
+
+    fn set(terminals: &[&str]) -> BTreeSet<String> {
+        terminals.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn first_and_follow_are_computed_for_a_grammar_that_is_not_ll1() {
+        // Ll1Table::build возвращает только конфликты, поэтому множества
+        // считаются на таблице без клеток
+        let mut table = Ll1Table {
+            grammar: Grammar::parse(NOT_LL1).unwrap(),
+            first: BTreeMap::new(),
+            follow: BTreeMap::new(),
+            nullable: BTreeSet::new(),
+            entries: BTreeMap::new(),
+        };
+        table.compute_first();
+        table.compute_follow();
+
+        assert_eq!(table.first("statement"), Some(&set(&["IDENT"])));
+        assert_eq!(table.first("left"), Some(&set(&["IDENT"])));
+        assert_eq!(table.first("tail"), Some(&set(&[":="])));
+        assert!(table.is_nullable("tail"));
+        assert!(!table.is_nullable("left"));
+        assert_eq!(table.follow("left"), Some(&set(&[":=", ";"])));
+        assert_eq!(table.follow("tail"), Some(&set(&[";"])));
+    }
+
+    #[test]
+    fn conflicts_of_a_grammar_that_is_not_ll1_are_reported() {
+        let conflicts = Ll1Table::build(Grammar::parse(NOT_LL1).unwrap()).unwrap_err();
+        assert_eq!(conflicts.len(), 1, "{:?}", conflicts);
+        assert!(
+            conflicts[0].starts_with("Конфликт в left по "),
+            "{}",
+            conflicts[0]
+        );
+        assert!(
+            conflicts[0].ends_with("продукции 2 и 3"),
+            "{}",
+            conflicts[0]
+        );
+    }
+
+    /// Таблица принимает те же операторы, что и рекурсивный спуск.
+    #[test]
+    fn table_agrees_with_descent() {
+        let table = Ll1Table::builtin();
+        let descent = Analyzer::builder().syntax_only(true).build();
+        let agree = |text: &str| {
+            let Ok(tokens) = tokenize(text) else {
+                return true;
+            };
+            let terminals: Vec<_> = tokens
+                .iter()
+                .map(|(span, token)| (span.start, token.terminal()))
+                .collect();
+            let accepted = table.parse(&terminals, text.len()).is_ok();
+            accepted == descent.analyze(text).report.accepted
+        };
+        let result = forall(CASES, 9, Cases::statement, |(text, _)| agree(text));
+        assert_eq!(result, Ok(()));
+        let result = forall(CASES, 10, Cases::mutant, |(text, _)| agree(text));
+        assert_eq!(result, Ok(()));
+    }
+
+    /// Парсер LALRPOP собирается из `statement.lalrpop`: это должен быть текущий
+    /// экспорт встроенной грамматики (`taafl --grammar lalrpop`).
+    #[test]
+    fn lalrpop_grammar_is_the_export() {
+        use crate::config::AnalyzerConfig;
+
+        let exported = super::export::to_lalrpop(&Grammar::builtin(), &AnalyzerConfig::default());
+        assert_eq!(include_str!("grammar/statement.lalrpop"), exported);
+    }
+}
@@ -0,0 +1,226 @@
+//! Поиск неоднозначности грамматики на заданной цепочке терминалов.
+//!
+//! Разбор с возвратами перебирает все левые выводы цепочки, поэтому работает и для
+//! грамматик, не являющихся LL(1), в том числе леворекурсивных. Две различные
+//! последовательности применённых продукций дают два различных дерева разбора.
+//! Ветви, в которых символов, не выводящих ε, больше, чем осталось терминалов,
+//! отсекаются; зацикливание на пустых и цепных правилах ограничено числом шагов и
+//! длиной вывода.
+
+use super::{Grammar, Symbol};
+use std::collections::BTreeSet;
+
+/// Наибольшее число шагов перебора по умолчанию.
+pub const STEP_LIMIT: usize = 100_000;
+
+/// Наибольшая длина вывода: ограничивает глубину перебора.
+pub const DEPTH_LIMIT: usize = 2_000;
+
+/// Левый вывод: номера применённых продукций по порядку.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Derivation(pub Vec<usize>);
+
+impl Derivation {
+    /// Сентенциальные формы вывода, начиная со стартового нетерминала. Пустая
+    /// форма записывается как `ε`.
+    pub fn sentential_forms(&self, grammar: &Grammar) -> Vec<String> {
+        let mut form = vec![Symbol::Nonterminal(grammar.start.clone())];
+        let mut forms = vec![format_form(&form)];
+        for &index in &self.0 {
+            let Some(i) = form
+                .iter()
+                .position(|symbol| matches!(symbol, Symbol::Nonterminal(_)))
+            else {
+                break;
+            };
+            form.splice(i..=i, grammar.productions[index].rhs.iter().cloned());
+            forms.push(format_form(&form));
+        }
+        forms
+    }
+
+    /// Таблица вывода с выровненными столбцами: номер шага, применённая продукция
+    /// и полученная сентенциальная форма. Нулевой шаг - стартовый нетерминал.
+    ///
+    /// ```
+    /// use taafl::grammar::ambiguity::Derivation;
+    /// use taafl::grammar::Grammar;
+    ///
+    /// let grammar = Grammar::parse("s = \"a\" t ; t = \"b\" | ε ;").unwrap();
+    /// let table = Derivation(vec![0, 1]).table(&grammar);
+    /// let lines: Vec<&str> = table.lines().collect();
+    /// assert_eq!(
+    ///     lines,
+    ///     [
+    ///         "Шаг  Продукция  Сентенциальная форма",
+    ///         "---  ---------  --------------------",
+    ///         "  0             s",
+    ///         "  1  s = \"a\" t  \"a\" t",
+    ///         "  2  t = \"b\"    \"a\" \"b\"",
+    ///     ]
+    /// );
+    /// ```
+    pub fn table(&self, grammar: &Grammar) -> String {
+        let forms = self.sentential_forms(grammar);
+        let rows: Vec<Vec<String>> = forms
+            .into_iter()
+            .enumerate()
+            .map(|(step, form)| {
+                let production = match step.checked_sub(1) {
+                    Some(i) => grammar.productions[self.0[i]].to_string(),
+                    None => String::new(),
+                };
+                vec![step.to_string(), production, form]
+            })
+            .collect();
+        let header = ["Шаг", "Продукция", "Сентенциальная форма"];
+        crate::diagnostic::table(&header, &rows, &[true])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Цепочка имеет единственный вывод
+    Unique(Derivation),
+    /// Два различных левых вывода одной цепочки
+    Ambiguous(Derivation, Derivation),
+    /// Цепочка не выводится из грамматики
+    NotDerivable,
+    /// Перебор прерван по лимиту шагов или длины вывода
+    Undecided,
+}
+
+/// Проверяет, имеет ли цепочка терминалов (имён классов и литералов) больше
+/// одного вывода.
+///
+/// ```
+/// use taafl::grammar::ambiguity::{check, Verdict};
+/// use taafl::grammar::Grammar;
+///
+/// let grammar = Grammar::parse("e = e OP e | IDENT ;").unwrap();
+/// let input = ["IDENT", "OP", "IDENT", "OP", "IDENT"];
+/// assert!(matches!(check(&grammar, &input), Verdict::Ambiguous(_, _)));
+///
+/// let builtin = Grammar::builtin();
+/// let input = ["IDENT", ":=", "IDENT", "OP", "CONST", ";"];
+/// assert!(matches!(check(&builtin, &input), Verdict::Unique(_)));
+/// ```
+pub fn check(grammar: &Grammar, input: &[&str]) -> Verdict {
+    check_with_limit(grammar, input, STEP_LIMIT)
+}
+
+pub fn check_with_limit(grammar: &Grammar, input: &[&str], limit: usize) -> Verdict {
+    let mut search = Search {
+        grammar,
+        input,
+        nullable: nullable(grammar),
+        steps: 0,
+        limit,
+        truncated: false,
+        found: Vec::new(),
+    };
+    let mut stack = vec![Symbol::Nonterminal(grammar.start.clone())];
+    search.run(&mut stack, 0, &mut Vec::new());
+
+    let exhausted = search.truncated || search.steps > limit;
+    let mut found = search.found.into_iter();
+    match (found.next(), found.next()) {
+        (Some(first), Some(second)) => Verdict::Ambiguous(first, second),
+        _ if exhausted => Verdict::Undecided,
+        (Some(first), None) => Verdict::Unique(first),
+        (None, _) => Verdict::NotDerivable,
+    }
+}
+
+struct Search<'a> {
+    grammar: &'a Grammar,
+    input: &'a [&'a str],
+    nullable: BTreeSet<String>,
+    steps: usize,
+    limit: usize,
+    /// Были ли отброшены ветви длиннее [`DEPTH_LIMIT`]
+    truncated: bool,
+    found: Vec<Derivation>,
+}
+
+impl Search<'_> {
+    /// Возвращает `true`, когда перебор нужно остановить.
+    fn run(&mut self, stack: &mut Vec<Symbol>, i: usize, derivation: &mut Vec<usize>) -> bool {
+        self.steps += 1;
+        if self.steps > self.limit {
+            return true;
+        }
+        let required = stack
+            .iter()
+            .filter(|symbol| match symbol {
+                Symbol::Nonterminal(name) => !self.nullable.contains(name),
+                _ => true,
+            })
+            .count();
+        if required > self.input.len() - i {
+            return false;
+        }
+
+        let Some(symbol) = stack.pop() else {
+            if i == self.input.len() {
+                self.found.push(Derivation(derivation.clone()));
+            }
+            return self.found.len() >= 2;
+        };
+        let stop = match &symbol {
+            Symbol::Class(terminal) | Symbol::Literal(terminal) => {
+                self.input.get(i) == Some(&terminal.as_str()) && self.run(stack, i + 1, derivation)
+            }
+            Symbol::Nonterminal(_) if derivation.len() >= DEPTH_LIMIT => {
+                self.truncated = true;
+                false
+            }
+            Symbol::Nonterminal(name) => {
+                let mut stop = false;
+                for (index, production) in self.grammar.productions_of(name) {
+                    let depth = stack.len();
+                    stack.extend(production.rhs.iter().rev().cloned());
+                    derivation.push(index);
+                    stop = self.run(stack, i, derivation);
+                    derivation.pop();
+                    stack.truncate(depth);
+                    if stop {
+                        break;
+                    }
+                }
+                stop
+            }
+        };
+        stack.push(symbol);
+        stop
+    }
+}
+
+/// Нетерминалы, выводящие пустую цепочку.
+pub(super) fn nullable(grammar: &Grammar) -> BTreeSet<String> {
+    let mut nullable = BTreeSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for production in &grammar.productions {
+            let empty = production.rhs.iter().all(|symbol| match symbol {
+                Symbol::Nonterminal(name) => nullable.contains(name),
+                _ => false,
+            });
+            if empty {
+                changed |= nullable.insert(production.lhs.clone());
+            }
+        }
+    }
+    nullable
+}
+
+fn format_form(form: &[Symbol]) -> String {
+    if form.is_empty() {
+        return "ε".to_string();
+    }
+    form.iter()
+        .map(Symbol::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
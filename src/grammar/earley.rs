@@ -0,0 +1,388 @@
+//! Разбор цепочки терминалов алгоритмом Эрли.
+//!
+//! В отличие от [`Ll1Table`](super::Ll1Table), разбор не требует, чтобы
+//! грамматика была LL(1): годятся и леворекурсивные, и неоднозначные грамматики,
+//! например набранные при правке грамматики варианта. Пустые продукции
+//! учитываются при предсказании, как у Эйкока и Хорспула. По заполненным
+//! множествам ситуаций восстанавливаются два различных левых вывода, если они
+//! есть; выводы с циклом `A ⇒+ A` на одном отрезке не рассматриваются.
+//!
+//! ```
+//! use taafl::grammar::earley;
+//! use taafl::grammar::Grammar;
+//!
+//! let grammar = Grammar::parse("e = e OP e | IDENT ;").unwrap();
+//! let input = [(0, "IDENT"), (2, "OP"), (4, "IDENT"), (6, "OP"), (8, "IDENT")];
+//! let parse = earley::parse(&grammar, &input, 9).unwrap();
+//! assert_eq!(parse.derivation, [0, 1, 0, 1, 1]);
+//! assert_eq!(parse.alternative, Some(vec![0, 0, 1, 1, 1]));
+//! assert_eq!(parse.divergence(), Some((1, 1, 0)));
+//!
+//! let (pos, message) = earley::parse(&grammar, &input[..2], 3).unwrap_err();
+//! assert_eq!((pos, message.as_str()), (3, "Ожидалось: идентификатор, но достигнут конец"));
+//! ```
+
+use super::{ambiguity, expected_message, Grammar, Symbol, END};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Результат разбора.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parse {
+    /// Номера продукций левого вывода в порядке их применения
+    pub derivation: Vec<usize>,
+    /// Другой левый вывод той же цепочки, если грамматика на ней неоднозначна
+    pub alternative: Option<Vec<usize>>,
+}
+
+impl Parse {
+    pub fn is_ambiguous(&self) -> bool {
+        self.alternative.is_some()
+    }
+
+    /// Номер шага, на котором выводы расходятся, и продукции, применённые на нём
+    /// в первом и втором выводе; `None`, если вывод один.
+    pub fn divergence(&self) -> Option<(usize, usize, usize)> {
+        let alternative = self.alternative.as_ref()?;
+        let mut pairs = self.derivation.iter().zip(alternative);
+        let step = pairs.position(|(first, second)| first != second)?;
+        Some((step, self.derivation[step], alternative[step]))
+    }
+}
+
+/// Разбирает последовательность терминалов с позициями по грамматике `grammar`.
+/// `end` - позиция конца ввода.
+///
+/// При ошибке возвращает позицию первой лексемы, которой не продолжается
+/// ни одна ситуация, и сообщение вида "Ожидалось: ...", как у
+/// [`Ll1Table::parse`](super::Ll1Table::parse).
+pub fn parse(
+    grammar: &Grammar,
+    input: &[(usize, &str)],
+    end: usize,
+) -> Result<Parse, (usize, String)> {
+    let terminals: Vec<&str> = input.iter().map(|(_, terminal)| *terminal).collect();
+    let mut chart = Chart::new(grammar, &terminals);
+    chart.fill();
+
+    let n = terminals.len();
+    let filled = chart
+        .sets
+        .iter()
+        .rposition(|set| !set.is_empty())
+        .unwrap_or(0);
+    if filled < n || !chart.accepts(n) {
+        let (pos, found) = input.get(filled).copied().unwrap_or((end, END));
+        let expected = chart.expected(filled);
+        let expected: Vec<&str> = expected.iter().map(String::as_str).collect();
+        return Err((pos, expected_message(&expected, found)));
+    }
+
+    let mut forest = Forest::new(&chart);
+    let mut derivations = forest.symbol(&grammar.start, 0, n).into_iter();
+    Ok(Parse {
+        derivation: derivations.next().unwrap_or_default(),
+        alternative: derivations.next(),
+    })
+}
+
+/// Ситуация Эрли: продукция, позиция точки в её правой части и номер множества,
+/// в котором продукция предсказана.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Item {
+    production: usize,
+    dot: usize,
+    origin: usize,
+}
+
+impl Item {
+    fn predicted(production: usize, origin: usize) -> Item {
+        Item {
+            production,
+            dot: 0,
+            origin,
+        }
+    }
+
+    fn advance(self) -> Item {
+        Item {
+            dot: self.dot + 1,
+            ..self
+        }
+    }
+}
+
+/// Множества ситуаций: `sets[k]` - ситуации после `k` взятых терминалов.
+struct Chart<'a> {
+    grammar: &'a Grammar,
+    input: &'a [&'a str],
+    nullable: BTreeSet<String>,
+    sets: Vec<Vec<Item>>,
+    seen: Vec<BTreeSet<Item>>,
+}
+
+impl<'a> Chart<'a> {
+    fn new(grammar: &'a Grammar, input: &'a [&'a str]) -> Self {
+        Chart {
+            grammar,
+            input,
+            nullable: ambiguity::nullable(grammar),
+            sets: vec![Vec::new(); input.len() + 1],
+            seen: vec![BTreeSet::new(); input.len() + 1],
+        }
+    }
+
+    fn add(&mut self, k: usize, item: Item) {
+        if self.seen[k].insert(item) {
+            self.sets[k].push(item);
+        }
+    }
+
+    fn next_symbol(&self, item: Item) -> Option<&'a Symbol> {
+        self.grammar.productions[item.production].rhs.get(item.dot)
+    }
+
+    fn fill(&mut self) {
+        let grammar = self.grammar;
+        for (index, _) in grammar.productions_of(&grammar.start) {
+            self.add(0, Item::predicted(index, 0));
+        }
+        for k in 0..self.sets.len() {
+            // Множество растёт по ходу обхода
+            let mut j = 0;
+            while let Some(&item) = self.sets[k].get(j) {
+                j += 1;
+                match self.next_symbol(item) {
+                    None => self.complete(k, item),
+                    Some(Symbol::Nonterminal(name)) => {
+                        for (index, _) in grammar.productions_of(name) {
+                            self.add(k, Item::predicted(index, k));
+                        }
+                        if self.nullable.contains(name) {
+                            self.add(k, item.advance());
+                        }
+                    }
+                    Some(Symbol::Class(terminal) | Symbol::Literal(terminal)) => {
+                        if self.input.get(k) == Some(&terminal.as_str()) {
+                            self.add(k + 1, item.advance());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Продвигает ситуации, ждавшие нетерминал завершённой ситуации `item`.
+    fn complete(&mut self, k: usize, item: Item) {
+        let lhs = &self.grammar.productions[item.production].lhs;
+        let mut j = 0;
+        while let Some(&parent) = self.sets[item.origin].get(j) {
+            j += 1;
+            if matches!(self.next_symbol(parent), Some(Symbol::Nonterminal(name)) if name == lhs) {
+                self.add(k, parent.advance());
+            }
+        }
+    }
+
+    fn is_complete(&self, item: Item) -> bool {
+        self.next_symbol(item).is_none()
+    }
+
+    /// Выводится ли из стартового нетерминала вся цепочка до `k`.
+    fn accepts(&self, k: usize) -> bool {
+        self.sets[k].iter().any(|item| {
+            item.origin == 0
+                && self.is_complete(*item)
+                && self.grammar.productions[item.production].lhs == self.grammar.start
+        })
+    }
+
+    /// Терминалы, которые допускает множество `k`, в порядке сортировки; [`END`] -
+    /// если на нём цепочка может закончиться.
+    fn expected(&self, k: usize) -> BTreeSet<String> {
+        let mut expected: BTreeSet<String> = self.sets[k]
+            .iter()
+            .filter_map(|item| self.next_symbol(*item)?.terminal())
+            .map(str::to_string)
+            .collect();
+        if self.accepts(k) {
+            expected.insert(END.to_string());
+        }
+        expected
+    }
+}
+
+/// Сколько различных выводов восстанавливается.
+const LIMIT: usize = 2;
+
+/// Восстановление выводов по завершённым ситуациям.
+struct Forest<'a> {
+    grammar: &'a Grammar,
+    input: &'a [&'a str],
+    /// `completed[k]` - пары (начало, продукция) ситуаций, завершённых в множестве `k`
+    completed: Vec<BTreeSet<(usize, usize)>>,
+    /// Выводы остатка правой части продукции, начиная с точки, на отрезке ввода.
+    /// Пустая запись на время вычисления отсекает циклы
+    memo: BTreeMap<(usize, usize, usize, usize), Vec<Vec<usize>>>,
+}
+
+impl<'a> Forest<'a> {
+    fn new(chart: &Chart<'a>) -> Self {
+        let completed = chart
+            .sets
+            .iter()
+            .map(|set| {
+                set.iter()
+                    .filter(|item| chart.is_complete(**item))
+                    .map(|item| (item.origin, item.production))
+                    .collect()
+            })
+            .collect();
+        Forest {
+            grammar: chart.grammar,
+            input: chart.input,
+            completed,
+            memo: BTreeMap::new(),
+        }
+    }
+
+    /// Продукции нетерминала `name`, выводящие отрезок `i..k`.
+    fn spanning(&self, name: &str, i: usize, k: usize) -> Vec<usize> {
+        self.completed[k]
+            .range((i, 0)..(i + 1, 0))
+            .map(|(_, production)| *production)
+            .filter(|production| self.grammar.productions[*production].lhs == name)
+            .collect()
+    }
+
+    /// Не больше [`LIMIT`] выводов отрезка `i..k` из нетерминала `name`.
+    fn symbol(&mut self, name: &str, i: usize, k: usize) -> Vec<Vec<usize>> {
+        let mut derivations = Vec::new();
+        for production in self.spanning(name, i, k) {
+            for tail in self.sequence(production, 0, i, k) {
+                derivations.push([vec![production], tail].concat());
+            }
+            if derivations.len() >= LIMIT {
+                break;
+            }
+        }
+        derivations.truncate(LIMIT);
+        derivations
+    }
+
+    /// Не больше [`LIMIT`] выводов отрезка `i..k` из правой части продукции
+    /// `production` от символа `dot`.
+    fn sequence(&mut self, production: usize, dot: usize, i: usize, k: usize) -> Vec<Vec<usize>> {
+        let key = (production, dot, i, k);
+        if let Some(derivations) = self.memo.get(&key) {
+            return derivations.clone();
+        }
+        self.memo.insert(key, Vec::new());
+
+        let grammar = self.grammar;
+        let derivations = match grammar.productions[production].rhs.get(dot) {
+            None if i == k => vec![Vec::new()],
+            None => Vec::new(),
+            Some(Symbol::Class(terminal) | Symbol::Literal(terminal)) => {
+                if i < k && self.input.get(i) == Some(&terminal.as_str()) {
+                    self.sequence(production, dot + 1, i + 1, k)
+                } else {
+                    Vec::new()
+                }
+            }
+            Some(Symbol::Nonterminal(name)) => {
+                let mut derivations = Vec::new();
+                for m in i..=k {
+                    if derivations.len() >= LIMIT {
+                        break;
+                    }
+                    let heads = self.symbol(name, i, m);
+                    if heads.is_empty() {
+                        continue;
+                    }
+                    let tails = self.sequence(production, dot + 1, m, k);
+                    for head in &heads {
+                        for tail in &tails {
+                            derivations.push([head.as_slice(), tail].concat());
+                        }
+                    }
+                }
+                derivations.truncate(LIMIT);
+                derivations
+            }
+        };
+        self.memo.insert(key, derivations.clone());
+        derivations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn earley_parser_reports_ambiguity_of_grammars_that_are_not_ll1() {
+        use crate::analyzer::Analyzer;
+        use crate::config::{AnalyzerConfig, ParserKind};
+        use crate::diagnostic::Severity;
+        use crate::variant::Variant;
+
+        /// Правая часть без приоритетов: `A * B * C` выводится двумя способами.
+        #[derive(Debug)]
+        struct Ambiguous;
+
+        impl Variant for Ambiguous {
+            fn name(&self) -> &str {
+                "Неоднозначная правая часть"
+            }
+
+            fn grammar(&self) -> &str {
+                r#"
+                statement = IDENT ":=" right ";" ;
+                right = right OP right | right SIGN right | SIGN right | IDENT | CONST ;
+                "#
+            }
+        }
+
+        let analyzer = |parser| {
+            let config = AnalyzerConfig {
+                parser,
+                ..AnalyzerConfig::default()
+            };
+            Analyzer::builder()
+                .config(config)
+                .variant(Ambiguous)
+                .build()
+        };
+        let table = analyzer(ParserKind::Table).analyze("X := A;");
+        assert!(!table.report.accepted);
+        assert!(table.diagnostics[0].message.contains("не является LL(1)"));
+
+        let earley = analyzer(ParserKind::Earley);
+        let analysis = earley.analyze("X := A;");
+        assert!(analysis.report.accepted);
+        assert!(analysis.diagnostics.is_empty());
+        let derivation: Vec<&str> = analysis
+            .report
+            .derivation
+            .iter()
+            .map(|step| step.lhs.as_str())
+            .collect();
+        assert_eq!(derivation, ["statement", "right"]);
+
+        let analysis = earley.analyze("X := A * B * C;");
+        assert!(analysis.report.accepted);
+        assert_eq!(analysis.diagnostics.len(), 1);
+        let warning = &analysis.diagnostics[0];
+        assert_eq!(
+            (warning.severity, warning.span.clone()),
+            (Severity::Warning, 5..6)
+        );
+        assert!(warning.message.ends_with(
+            "на шаге 3 применима продукция 5 (right = IDENT) \
+             и продукция 2 (right = right OP right)"
+        ));
+
+        let analysis = earley.analyze("X := A * ;");
+        assert!(!analysis.report.accepted);
+        assert_eq!(analysis.diagnostics[0].code, "syntax");
+        assert_eq!(analysis.diagnostics[0].span, 9..10);
+    }
+}
@@ -0,0 +1,398 @@
+//! Запись грамматики в форматах других инструментов: EBNF (ISO 14977), ANTLR 4,
+//! Yacc/Bison и LALRPOP.
+//!
+//! Экспортируется только синтаксис. Ограничения длины идентификатора и диапазона
+//! констант проверяются семантическим анализом и в грамматику не входят.
+//!
+//! С возможностью `lalrpop` из экспорта LALRPOP встроенной грамматики
+//! (`statement.lalrpop`) собирается ещё один парсер оператора (модуль
+//! `grammar::lalrpop`); разностная проверка сравнивает его с рекурсивным спуском,
+//! LL(1)-таблицей и Earley.
+
+use super::{Grammar, Production, Symbol};
+use crate::ast::{KEYWORD_OPERATIONS, SIGNS};
+use crate::config::AnalyzerConfig;
+
+/// Лексемы классов `OP` и `SIGN` по настройкам: знаки из
+/// [`AnalyzerConfig::operations`], длинные раньше коротких, и операции-слова.
+struct Operations {
+    signs: Vec<&'static str>,
+    others: Vec<&'static str>,
+}
+
+impl Operations {
+    fn of(config: &AnalyzerConfig) -> Self {
+        let mut lexemes: Vec<&'static str> = config.operations.iter().map(|s| s.lexeme()).collect();
+        lexemes.sort_by_key(|lexeme| std::cmp::Reverse(lexeme.len()));
+        let (signs, mut others): (Vec<_>, Vec<_>) = lexemes
+            .into_iter()
+            .partition(|lexeme| SIGNS.contains(lexeme));
+        others.extend_from_slice(KEYWORD_OPERATIONS);
+        Operations { signs, others }
+    }
+
+    /// Лексемы класса `name`; `None`, если это не класс операций или он пуст.
+    fn class(&self, name: &str) -> Option<&[&'static str]> {
+        let lexemes = match name {
+            "OP" => &self.others,
+            "SIGN" => &self.signs,
+            _ => return None,
+        };
+        (!lexemes.is_empty()).then_some(lexemes.as_slice())
+    }
+}
+
+/// Определение класса лексем в виде правила лексера ANTLR.
+fn antlr_class(name: &str, operations: &Operations) -> Option<String> {
+    if let Some(lexemes) = operations.class(name) {
+        let lexemes: Vec<String> = lexemes.iter().map(|l| format!("'{}'", l)).collect();
+        return Some(lexemes.join(" | "));
+    }
+    let definition = match name {
+        "IDENT" => "[A-Za-z] [A-Za-z0-9]*",
+        "CONST" => "[0-9]+ | [0-9] [0-9A-F]* 'H' | [0-7]+ [BC]",
+        "LITERAL" => "'\\'' ~['\\r\\n]* '\\'' | '\"' ~[\"\\r\\n]* '\"'",
+        _ => return None,
+    };
+    Some(definition.to_string())
+}
+
+/// Определение класса лексем в EBNF.
+fn ebnf_class(name: &str, operations: &Operations) -> Option<String> {
+    if let Some(lexemes) = operations.class(name) {
+        let lexemes: Vec<String> = lexemes.iter().map(|l| format!("\"{}\"", l)).collect();
+        return Some(lexemes.join(" | "));
+    }
+    let definition = match name {
+        "IDENT" => "letter, { letter | digit }",
+        "CONST" => {
+            "digit, { digit } | digit, { hex digit }, \"H\" \
+             | octal digit, { octal digit }, ( \"B\" | \"C\" )"
+        }
+        "LITERAL" => "\"'\", { character - \"'\" }, \"'\" | '\"', { character - '\"' }, '\"'",
+        _ => return None,
+    };
+    Some(definition.to_string())
+}
+
+/// Регулярное выражение класса лексем для лексера LALRPOP.
+fn lalrpop_class(name: &str, operations: &Operations) -> Option<String> {
+    if let Some(lexemes) = operations.class(name) {
+        let lexemes: Vec<String> = lexemes.iter().map(|l| regex_escape(l)).collect();
+        // Операции-слова, как и в анализаторе, записываются в любом регистре
+        return Some(format!("r\"(?i){}\"", lexemes.join("|")));
+    }
+    let definition = match name {
+        "IDENT" => "r\"[A-Za-z][A-Za-z0-9]*\"",
+        "CONST" => "r\"[0-9]+|[0-9][0-9A-Fa-f]*[Hh]|[0-7]+[BbCc]\"",
+        "LITERAL" => "r#\"'[^'\\n]*'|\"[^\"\\n]*\"\"#",
+        _ => return None,
+    };
+    Some(definition.to_string())
+}
+
+/// Лексема в регулярном выражении: служебные символы экранируются.
+fn regex_escape(lexeme: &str) -> String {
+    lexeme
+        .chars()
+        .map(|c| match c {
+            '*' | '+' | '.' | '?' | '|' | '(' | ')' | '[' | ']' | '{' | '}' | '^' | '$' | '\\' => {
+                format!("\\{}", c)
+            }
+            _ => c.to_string(),
+        })
+        .collect()
+}
+
+/// Имя лексемы Yacc для многосимвольного терминала; односимвольные записываются
+/// литералом в кавычках.
+fn yacc_token(literal: &str, index: usize) -> String {
+    match literal {
+        ":=" => "ASSIGN".to_string(),
+        _ => format!("TOKEN{}", index),
+    }
+}
+
+/// Продукции, сгруппированные по нетерминалу в порядке первого определения.
+fn rules(grammar: &Grammar) -> Vec<(String, Vec<&Production>)> {
+    grammar
+        .nonterminals()
+        .into_iter()
+        .map(|name| {
+            let productions = grammar.productions_of(&name).map(|(_, p)| p).collect();
+            (name, productions)
+        })
+        .collect()
+}
+
+/// Терминалы-литералы в порядке первого использования.
+fn literals(grammar: &Grammar) -> Vec<&str> {
+    let mut literals = Vec::new();
+    for production in &grammar.productions {
+        for symbol in &production.rhs {
+            if let Symbol::Literal(literal) = symbol {
+                if !literals.contains(&literal.as_str()) {
+                    literals.push(literal.as_str());
+                }
+            }
+        }
+    }
+    literals
+}
+
+/// Классы лексем в порядке первого использования.
+fn classes(grammar: &Grammar) -> Vec<&str> {
+    let mut classes = Vec::new();
+    for production in &grammar.productions {
+        for symbol in &production.rhs {
+            if let Symbol::Class(name) = symbol {
+                if !classes.contains(&name.as_str()) {
+                    classes.push(name.as_str());
+                }
+            }
+        }
+    }
+    classes
+}
+
+/// Грамматика в EBNF по ISO 14977: элементы через запятую, пустая альтернатива
+/// отмечена комментарием `(* ε *)`. Классы операций и констант записываются по
+/// настройкам анализатора `config`.
+///
+/// ```
+/// use taafl::config::{AnalyzerConfig, OperationSign};
+/// use taafl::grammar::{export, Grammar};
+///
+/// let mut config = AnalyzerConfig::default();
+/// let ebnf = export::to_ebnf(&Grammar::builtin(), &config);
+/// assert!(ebnf.contains("statement = left, \":=\", right, \";\" ;"));
+/// assert!(ebnf.contains("op = \"<=\" | \">=\" | \"<>\" | \"*\""));
+///
+/// config.operations.insert(OperationSign::Percent);
+/// assert!(export::to_ebnf(&Grammar::builtin(), &config).contains("\"%\""));
+/// ```
+pub fn to_ebnf(grammar: &Grammar, config: &AnalyzerConfig) -> String {
+    let operations = Operations::of(config);
+    let mut text = String::from("(* Оператор присваивания, вариант 20 *)\n\n");
+    for (name, productions) in rules(grammar) {
+        let alternatives: Vec<String> = productions
+            .iter()
+            .map(|production| {
+                let symbols: Vec<String> = production
+                    .rhs
+                    .iter()
+                    .map(|symbol| match symbol {
+                        Symbol::Class(name) => name.to_lowercase(),
+                        _ => symbol.to_string(),
+                    })
+                    .collect();
+                if symbols.is_empty() {
+                    "(* ε *)".to_string()
+                } else {
+                    symbols.join(", ")
+                }
+            })
+            .collect();
+        text.push_str(&format!("{} = {} ;\n", name, alternatives.join(" | ")));
+    }
+
+    text.push('\n');
+    let classes = classes(grammar);
+    for class in &classes {
+        let definition = ebnf_class(class, &operations);
+        let definition = definition
+            .as_deref()
+            .unwrap_or("? определите класс лексем ?");
+        text.push_str(&format!("{} = {} ;\n", class.to_lowercase(), definition));
+    }
+    if classes.contains(&"LITERAL") {
+        text.push_str("character = ? любой символ, кроме перевода строки ? ;\n");
+    }
+    text.push_str("letter = \"A\" | ... | \"Z\" | \"a\" | ... | \"z\" ;\n");
+    text.push_str("digit = \"0\" | ... | \"9\" ;\n");
+    if classes.contains(&"CONST") {
+        text.push_str("hex digit = digit | \"A\" | ... | \"F\" ;\n");
+        text.push_str("octal digit = \"0\" | ... | \"7\" ;\n");
+    }
+    text
+}
+
+/// Заготовка грамматики ANTLR 4 (`.g4`) с именем `name`. Стартовое правило
+/// дополняется `EOF`, регистр букв в ключевых словах не учитывается. Классы
+/// операций и констант записываются по настройкам `config`.
+///
+/// ```
+/// use taafl::config::AnalyzerConfig;
+/// use taafl::grammar::{export, Grammar};
+///
+/// let antlr = export::to_antlr(&Grammar::builtin(), "Taafl", &AnalyzerConfig::default());
+/// assert!(antlr.contains("CONST : [0-9]+ | [0-9] [0-9A-F]* 'H' | [0-7]+ [BC] ;"));
+/// // Операции-слова совпадают раньше идентификатора
+/// assert!(antlr.find("'DIV'") < antlr.find("IDENT :"));
+/// ```
+pub fn to_antlr(grammar: &Grammar, name: &str, config: &AnalyzerConfig) -> String {
+    let operations = Operations::of(config);
+    let mut text = format!(
+        "// Оператор присваивания, вариант 20\ngrammar {};\n\noptions {{ caseInsensitive = true; }}\n\n",
+        name
+    );
+    for (rule, productions) in rules(grammar) {
+        let alternatives: Vec<String> = productions
+            .iter()
+            .map(|production| {
+                let mut symbols: Vec<String> = production
+                    .rhs
+                    .iter()
+                    .map(|symbol| match symbol {
+                        Symbol::Literal(literal) => format!("'{}'", literal.replace('\'', "\\'")),
+                        _ => symbol.to_string(),
+                    })
+                    .collect();
+                if rule == grammar.start {
+                    symbols.push("EOF".to_string());
+                }
+                if symbols.is_empty() {
+                    "/* ε */".to_string()
+                } else {
+                    symbols.join(" ")
+                }
+            })
+            .collect();
+        text.push_str(&format!(
+            "{}\n    : {}\n    ;\n\n",
+            rule,
+            alternatives.join("\n    | ")
+        ));
+    }
+
+    // Из правил лексера одной длины ANTLR выбирает первое: `DIV` - операция
+    let mut classes = classes(grammar);
+    classes.sort_by_key(|class| operations.class(class).is_none());
+    for class in classes {
+        match antlr_class(class, &operations) {
+            Some(definition) => text.push_str(&format!("{} : {} ;\n", class, definition)),
+            None => text.push_str(&format!(
+                "{} : ~[ \\t\\r\\n]+ ; // определите класс лексем\n",
+                class
+            )),
+        }
+    }
+    text.push_str("WS : [ \\t\\r\\n]+ -> skip ;\n");
+    text
+}
+
+/// Грамматика для Yacc/Bison (`.y`). Многосимвольные терминалы объявлены лексемами
+/// со строковыми псевдонимами Bison, семантические действия не заданы.
+pub fn to_yacc(grammar: &Grammar) -> String {
+    let multichar: Vec<(&str, String)> = literals(grammar)
+        .into_iter()
+        .filter(|literal| literal.chars().count() > 1)
+        .enumerate()
+        .map(|(i, literal)| (literal, yacc_token(literal, i + 1)))
+        .collect();
+
+    let mut text = String::from("/* Оператор присваивания, вариант 20 */\n\n");
+    for class in classes(grammar) {
+        text.push_str(&format!("%token {}\n", class));
+    }
+    for (literal, token) in &multichar {
+        text.push_str(&format!("%token {} \"{}\"\n", token, literal));
+    }
+    text.push_str(&format!("%start {}\n\n%%\n\n", grammar.start));
+
+    for (rule, productions) in rules(grammar) {
+        let alternatives: Vec<String> = productions
+            .iter()
+            .map(|production| {
+                let symbols: Vec<String> = production
+                    .rhs
+                    .iter()
+                    .map(|symbol| match symbol {
+                        Symbol::Literal(literal) => {
+                            match multichar.iter().find(|(l, _)| l == literal) {
+                                Some((_, token)) => token.clone(),
+                                None => format!("'{}'", literal.replace('\'', "\\'")),
+                            }
+                        }
+                        _ => symbol.to_string(),
+                    })
+                    .collect();
+                if symbols.is_empty() {
+                    "/* ε */".to_string()
+                } else {
+                    symbols.join(" ")
+                }
+            })
+            .collect();
+        text.push_str(&format!(
+            "{}\n    : {}\n    ;\n\n",
+            rule,
+            alternatives.join("\n    | ")
+        ));
+    }
+    text.push_str("%%\n");
+    text
+}
+
+/// Грамматика LALRPOP (`.lalrpop`). Все правила возвращают `()`, стартовое правило
+/// публичное; лексер задан блоком `match`, пробелы LALRPOP пропускает сам. Классы
+/// операций стоят в нём раньше остальных: `DIV` - операция, а не идентификатор.
+///
+/// ```
+/// use taafl::config::AnalyzerConfig;
+/// use taafl::grammar::{export, Grammar};
+///
+/// let lalrpop = export::to_lalrpop(&Grammar::builtin(), &AnalyzerConfig::default());
+/// assert!(lalrpop.contains("pub statement: () = {"));
+/// assert!(lalrpop.contains("left \":=\" right \";\" => (),"));
+/// assert!(lalrpop.contains("r\"(?i)<=|>=|<>|\\*|/|=|#|<|>|DIV|MOD|AND|OR\" => OP,\n} else {"));
+/// ```
+pub fn to_lalrpop(grammar: &Grammar, config: &AnalyzerConfig) -> String {
+    let operations = Operations::of(config);
+    let mut text = String::from("// Оператор присваивания, вариант 20\ngrammar;\n\n");
+    for (rule, productions) in rules(grammar) {
+        let visibility = if rule == grammar.start { "pub " } else { "" };
+        text.push_str(&format!("{}{}: () = {{\n", visibility, rule));
+        for production in productions {
+            let symbols: Vec<String> = production
+                .rhs
+                .iter()
+                .map(|symbol| match symbol {
+                    Symbol::Literal(literal) => format!("{:?}", literal),
+                    _ => symbol.to_string(),
+                })
+                .collect();
+            if symbols.is_empty() {
+                text.push_str("    => (),\n");
+            } else {
+                text.push_str(&format!("    {} => (),\n", symbols.join(" ")));
+            }
+        }
+        text.push_str("};\n\n");
+    }
+
+    text.push_str("match {\n");
+    for literal in literals(grammar) {
+        text.push_str(&format!("    {:?},\n", literal));
+    }
+    let (first, rest): (Vec<_>, Vec<_>) = classes(grammar)
+        .into_iter()
+        .partition(|class| operations.class(class).is_some());
+    for (i, classes) in [first, rest].into_iter().enumerate() {
+        // Блок `else` - ниже по приоритету: в нём идентификаторы
+        if i > 0 && !classes.is_empty() {
+            text.push_str("} else {\n");
+        }
+        for class in classes {
+            match lalrpop_class(class, &operations) {
+                Some(definition) => text.push_str(&format!("    {} => {},\n", definition, class)),
+                None => text.push_str(&format!(
+                    "    r\"[^ \\t\\r\\n]+\" => {}, // определите класс лексем\n",
+                    class
+                )),
+            }
+        }
+    }
+    text.push_str("}\n");
+    text
+}
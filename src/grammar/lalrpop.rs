@@ -0,0 +1,77 @@
+//! Парсер оператора, собранный LALRPOP (возможность `lalrpop`): третья, LR(1)
+//! реализация рядом с рекурсивным спуском и LL(1)-таблицей для разностной проверки.
+//!
+//! Грамматика `statement.lalrpop` - вывод [`to_lalrpop`] для встроенной грамматики
+//! и настроек по умолчанию (`taafl --grammar lalrpop`); проверка в `export`
+//! следит, чтобы файл не расходился с экспортом.
+//!
+//! Парсер принимает те же терминалы, что и [`Ll1Table::parse`]: лексемы
+//! анализатора, а не текст. Лексер LALRPOP читает строку, составленную из одного
+//! представителя каждого класса лексем, поэтому регистр ключевых слов, комментарии
+//! и прочие различия лексеров на сравнение не влияют.
+//!
+//! ```
+//! use taafl::grammar::lalrpop;
+//!
+//! let statement = [(0, "IDENT"), (2, ":="), (5, "CONST"), (6, ";")];
+//! assert!(lalrpop::parse(&statement, 6).is_ok());
+//! assert_eq!(lalrpop::parse(&statement[..3], 5), Err(5));
+//! ```
+//!
+//! [`to_lalrpop`]: super::export::to_lalrpop
+//! [`Ll1Table::parse`]: super::Ll1Table::parse
+
+use lalrpop_util::{lalrpop_mod, ParseError};
+
+lalrpop_mod!(
+    #[allow(clippy::all, clippy::pedantic, unused_qualifications)]
+    statement,
+    "/grammar/statement.rs"
+);
+
+/// Представитель терминала `terminal` для лексера LALRPOP.
+fn lexeme(terminal: &str) -> &str {
+    match terminal {
+        "IDENT" => "X",
+        "CONST" => "1",
+        "LITERAL" => "'S'",
+        "SIGN" => "+",
+        "OP" => "*",
+        literal => literal,
+    }
+}
+
+/// Разбирает терминалы `input` с позициями лексем; `end` - позиция конца ввода.
+/// Ошибка - позиция лексемы, на которой разбор остановился, или `end`, как у
+/// [`Ll1Table::parse`](super::Ll1Table::parse).
+pub fn parse(input: &[(usize, &str)], end: usize) -> Result<(), usize> {
+    // Начало каждого представителя в составленной строке и позиция его лексемы
+    let mut text = String::new();
+    let mut starts = Vec::with_capacity(input.len());
+    for &(position, terminal) in input {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        starts.push((text.len(), position));
+        text.push_str(lexeme(terminal));
+    }
+    let position = |offset: usize| {
+        starts
+            .iter()
+            .find(|&&(start, _)| start == offset)
+            .map_or(end, |&(_, position)| position)
+    };
+    match statement::statementParser::new().parse(&text) {
+        Ok(()) => Ok(()),
+        Err(ParseError::InvalidToken { location })
+        | Err(ParseError::UnrecognizedEof { location, .. }) => Err(position(location)),
+        Err(ParseError::UnrecognizedToken {
+            token: (start, _, _),
+            ..
+        })
+        | Err(ParseError::ExtraToken {
+            token: (start, _, _),
+        }) => Err(position(start)),
+        Err(ParseError::User { .. }) => Err(end),
+    }
+}
@@ -0,0 +1,325 @@
+//! Заготовка рекурсивного спуска по грамматике в записи [`GRAMMAR`](super::GRAMMAR):
+//! перечисление лексем и по функции разбора на нетерминал, как в синтаксическом
+//! анализаторе этого крейта. Ветви функций выбираются по таблице LL(1), поэтому
+//! грамматика должна быть LL(1); построение узлов дерева оставлено в `TODO`.
+//!
+//! ```
+//! use taafl::grammar::{scaffold, Grammar};
+//!
+//! let code = scaffold::to_rust(&Grammar::builtin()).unwrap();
+//! assert!(code.contains("    LeftBracket,\n"));
+//! assert!(code.contains("    fn parse_left_tail(&mut self) -> Result<(), Error> {\n"));
+//! assert!(code.contains("            Token::Comma => {\n"));
+//!
+//! let left_recursive = Grammar::parse("sum = sum \"+\" CONST | CONST ;").unwrap();
+//! assert!(scaffold::to_rust(&left_recursive).is_err());
+//! ```
+
+use super::{Grammar, Ll1Table, Symbol, END};
+use std::collections::BTreeMap;
+
+/// Файл, в который `taafl scaffold` записывает заготовку.
+pub const SCAFFOLD_FILE: &str = "parser.rs";
+
+/// Вариант перечисления лексем для литерала из знаков.
+fn punctuation_variant(literal: &str) -> Option<&'static str> {
+    Some(match literal {
+        ":=" => "Assign",
+        "=" => "Equals",
+        "[" => "LeftBracket",
+        "]" => "RightBracket",
+        "(" => "LeftParen",
+        ")" => "RightParen",
+        "{" => "LeftBrace",
+        "}" => "RightBrace",
+        ";" => "Semicolon",
+        "," => "Comma",
+        "." => "Dot",
+        ":" => "Colon",
+        "+" => "Plus",
+        "-" => "Minus",
+        "*" => "Star",
+        "/" => "Slash",
+        "%" => "Percent",
+        "<" => "Less",
+        ">" => "Greater",
+        "#" => "Hash",
+        "←" => "Arrow",
+        _ => return None,
+    })
+}
+
+/// `LEFT_TAIL` или `begin` в записи `LeftTail`, `Begin`.
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let lower = part.to_lowercase();
+            let mut chars = lower.chars();
+            chars.next().map_or(String::new(), |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect()
+}
+
+/// Терминал грамматики и его вариант в перечислении лексем.
+struct Terminal {
+    name: String,
+    variant: String,
+    /// Класс лексем: вариант хранит текст лексемы
+    class: bool,
+}
+
+impl Terminal {
+    /// Образец для `match`: `Token::Ident(_)`, `Token::Comma`.
+    fn pattern(&self) -> String {
+        if self.class {
+            format!("Token::{}(_)", self.variant)
+        } else {
+            format!("Token::{}", self.variant)
+        }
+    }
+}
+
+/// Терминалы в порядке первого появления в продукциях; варианты не повторяются.
+fn terminals(grammar: &Grammar) -> Vec<Terminal> {
+    let mut terminals: Vec<Terminal> = Vec::new();
+    let symbols = grammar.productions.iter().flat_map(|p| &p.rhs);
+    for symbol in symbols {
+        let (name, class) = match symbol {
+            Symbol::Class(name) => (name, true),
+            Symbol::Literal(name) => (name, false),
+            Symbol::Nonterminal(_) => continue,
+        };
+        if terminals.iter().any(|t| t.name == *name) {
+            continue;
+        }
+        let mut variant = match punctuation_variant(name) {
+            Some(variant) => variant.to_string(),
+            None if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => camel_case(name),
+            None => format!("Symbol{}", terminals.len() + 1),
+        };
+        // Класс IDENT и литерал "ident" дали бы один вариант
+        if variant.is_empty() || variant == "End" || terminals.iter().any(|t| t.variant == variant)
+        {
+            variant = format!("{}{}", variant, terminals.len() + 1);
+        }
+        terminals.push(Terminal {
+            name: name.clone(),
+            variant,
+            class,
+        });
+    }
+    terminals
+}
+
+/// Заготовка модуля на Rust; `Err` - конфликты таблицы LL(1), если грамматика
+/// не LL(1) и ветви по одной лексеме впереди не выбрать.
+pub fn to_rust(grammar: &Grammar) -> Result<String, Vec<String>> {
+    let table = Ll1Table::build(grammar.clone())?;
+    let terminals = terminals(grammar);
+    let terminal = |name: &str| terminals.iter().find(|t| t.name == name);
+    let pattern = |name: &str| match terminal(name) {
+        Some(terminal) => terminal.pattern(),
+        None => "Token::End".to_string(),
+    };
+
+    let mut code = String::from(
+        "//! Заготовка рекурсивного спуска, построенная `taafl scaffold` по грамматике:\n//!\n",
+    );
+    for production in &grammar.productions {
+        code.push_str(&format!("//!     {}\n", production));
+    }
+    code.push_str(
+        "//!\n//! Лексемы с позициями готовит лексический анализатор; функции разбора\n\
+         //! проверяют только синтаксис - узлы дерева строятся там, где стоит `TODO`.\n\n",
+    );
+
+    code.push_str("/// Лексема: класс лексем хранит свой текст.\n");
+    code.push_str("#[derive(Debug, Clone, PartialEq)]\npub enum Token {\n");
+    for terminal in &terminals {
+        if terminal.class {
+            code.push_str(&format!(
+                "    /// {}\n    {}(String),\n",
+                terminal.name, terminal.variant
+            ));
+        } else {
+            code.push_str(&format!(
+                "    /// {:?}\n    {},\n",
+                terminal.name, terminal.variant
+            ));
+        }
+    }
+    code.push_str("    /// Конец ввода\n    End,\n}\n\n");
+
+    code.push_str("impl Token {\n");
+    code.push_str("    /// Имя терминала в грамматике.\n");
+    code.push_str("    pub fn terminal(&self) -> &'static str {\n        match self {\n");
+    for terminal in &terminals {
+        code.push_str(&format!(
+            "            {} => {:?},\n",
+            terminal.pattern(),
+            terminal.name
+        ));
+    }
+    code.push_str(&format!(
+        "            Token::End => {:?},\n        }}\n    }}\n}}\n\n",
+        END
+    ));
+
+    code.push_str(
+        "/// Ошибка разбора: позиция лексемы и терминалы, которые ожидались на её месте.\n\
+         #[derive(Debug, Clone, PartialEq)]\n\
+         pub struct Error {\n    \
+             pub position: usize,\n    \
+             pub expected: Vec<&'static str>,\n    \
+             pub found: &'static str,\n\
+         }\n\n\
+         pub struct Parser {\n    \
+             tokens: Vec<(usize, Token)>,\n    \
+             next: usize,\n\
+         }\n\n",
+    );
+
+    code.push_str(&format!(
+        "impl Parser {{\n    \
+             /// Парсер лексем с позициями; конец ввода дописывать не нужно.\n    \
+             pub fn new(tokens: Vec<(usize, Token)>) -> Self {{\n        \
+                 Parser {{ tokens, next: 0 }}\n    \
+             }}\n\n    \
+             /// Разбирает ввод целиком: стартовый нетерминал `{start}` и конец ввода.\n    \
+             pub fn parse(&mut self) -> Result<(), Error> {{\n        \
+                 self.parse_{start}()?;\n        \
+                 self.expect({end:?})?;\n        \
+                 Ok(())\n    \
+             }}\n\n    \
+             fn peek(&self) -> &Token {{\n        \
+                 self.tokens.get(self.next).map_or(&Token::End, |(_, token)| token)\n    \
+             }}\n\n    \
+             fn error(&self, expected: &[&'static str]) -> Error {{\n        \
+                 let position = self.tokens.get(self.next).or(self.tokens.last());\n        \
+                 Error {{\n            \
+                     position: position.map_or(0, |(position, _)| *position),\n            \
+                     expected: expected.to_vec(),\n            \
+                     found: self.peek().terminal(),\n        \
+                 }}\n    \
+             }}\n\n    \
+             /// Берёт лексему, если это терминал `terminal`.\n    \
+             fn expect(&mut self, terminal: &'static str) -> Result<Token, Error> {{\n        \
+                 if self.peek().terminal() != terminal {{\n            \
+                     return Err(self.error(&[terminal]));\n        \
+                 }}\n        \
+                 let token = self.peek().clone();\n        \
+                 self.next += 1;\n        \
+                 Ok(token)\n    \
+             }}\n",
+        start = grammar.start,
+        end = END
+    ));
+
+    for nonterminal in grammar.nonterminals() {
+        // Терминалы впереди, при которых выбирается каждая продукция
+        let mut branches: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+        for (lhs, terminal, index) in table.entries() {
+            if lhs == nonterminal {
+                branches.entry(index).or_default().push(terminal);
+            }
+        }
+        let alternatives: Vec<String> = grammar
+            .productions_of(&nonterminal)
+            .map(|(_, production)| {
+                let text = production.to_string();
+                let prefix = format!("{} = ", nonterminal);
+                text.strip_prefix(&prefix).unwrap_or(&text).to_string()
+            })
+            .collect();
+        code.push_str(&format!(
+            "\n    /// {} = {}\n    fn parse_{}(&mut self) -> Result<(), Error> {{\n        \
+             match self.peek() {{\n",
+            nonterminal,
+            alternatives.join(" | "),
+            nonterminal
+        ));
+        for (index, lookahead) in &branches {
+            let Some(production) = grammar.productions.get(*index) else {
+                continue;
+            };
+            let patterns: Vec<String> = lookahead.iter().map(|name| pattern(name)).collect();
+            code.push_str(&format!(
+                "            // {}\n            {} => {{\n",
+                production,
+                patterns.join(" | ")
+            ));
+            for symbol in &production.rhs {
+                match symbol {
+                    Symbol::Nonterminal(name) => {
+                        code.push_str(&format!("                self.parse_{}()?;\n", name))
+                    }
+                    Symbol::Class(name) | Symbol::Literal(name) => {
+                        code.push_str(&format!("                self.expect({:?})?;\n", name))
+                    }
+                }
+            }
+            code.push_str(&format!(
+                "                // TODO: узел дерева для `{}`\n                Ok(())\n            }}\n",
+                production
+            ));
+        }
+        let expected: Vec<String> = branches
+            .values()
+            .flatten()
+            .map(|name| format!("{:?}", name))
+            .collect();
+        code.push_str(&format!(
+            "            _ => Err(self.error(&[{}])),\n        }}\n    }}\n",
+            expected.join(", ")
+        ));
+    }
+    code.push_str("}\n");
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{terminals, to_rust};
+    use crate::grammar::Grammar;
+
+    #[test]
+    fn builtin_grammar_gets_a_function_per_nonterminal_and_a_variant_per_terminal() {
+        let grammar = Grammar::builtin();
+        let code = to_rust(&grammar).unwrap();
+        for nonterminal in grammar.nonterminals() {
+            let function = format!(
+                "    fn parse_{}(&mut self) -> Result<(), Error> {{\n",
+                nonterminal
+            );
+            assert!(code.contains(&function), "нет parse_{}", nonterminal);
+        }
+        let terminals = terminals(&grammar);
+        assert!(terminals.iter().any(|t| t.name == "IDENT"));
+        assert!(terminals.iter().any(|t| t.name == ":="));
+        for terminal in terminals {
+            let variant = if terminal.class {
+                format!("    {}(String),\n", terminal.variant)
+            } else {
+                format!("    {},\n", terminal.variant)
+            };
+            assert!(
+                code.contains(&variant),
+                "нет варианта для {}",
+                terminal.name
+            );
+            let name = format!(
+                "            {} => {:?},\n",
+                terminal.pattern(),
+                terminal.name
+            );
+            assert!(
+                code.contains(&name),
+                "нет имени терминала {}",
+                terminal.name
+            );
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! Синтаксический анализатор оператора присваивания языка, сходного с фрагментом Modula-2.
+//!
+//! Графический интерфейс и консольный режим находятся в исполняемом файле `taafl`.
+//! Внешним программам предназначен стабильный интерфейс [`core`].
+
+pub mod analyzer;
+pub mod ast;
+pub mod bundle;
+pub mod compare;
+pub mod config;
+pub mod core;
+#[cfg(feature = "corpus")]
+pub mod corpus;
+pub mod cst;
+pub mod diagnostic;
+pub mod generator;
+pub mod grammar;
+pub mod model;
+pub mod operators;
+pub mod quiz;
+pub mod sanitize;
+pub mod storage;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod tree;
+pub mod variant;
@@ -1,10 +1,29 @@
 use iced::{self, window, Font, Settings};
+use tracing_subscriber::EnvFilter;
 use ui::*;
 
-mod analyzer;
+mod cli;
 mod ui;
 
+/// Переменная окружения с фильтром журнала, например `TAAFL_LOG=taafl=debug`.
+const LOG_ENV: &str = "TAAFL_LOG";
+
+/// Опция запуска интерфейса с программной отрисовкой.
+const SOFTWARE_RENDER: &str = "--software-render";
+
+/// Переменная окружения, которой iced выбирает способы отрисовки по порядку.
+const BACKEND_ENV: &str = "ICED_BACKEND";
+
 fn main() -> iced::Result {
+    let (deterministic, args) = cli::take_deterministic(std::env::args().skip(1).collect());
+    init_logging(deterministic);
+
+    if args == [SOFTWARE_RENDER] {
+        select_software_renderer();
+    } else if !args.is_empty() {
+        std::process::exit(cli::run(&args, deterministic));
+    }
+
     let settings: Settings = iced::settings::Settings {
         default_font: Font::MONOSPACE,
         ..Default::default()
@@ -17,12 +36,37 @@ fn main() -> iced::Result {
         ..Default::default()
     };
 
-    iced::application("Синтаксический анализатор. Стародубцев Виктор. Вариант 20.", TaaflUIState::update, TaaflUIState::view)
+    iced::application("Синтаксический анализатор. Стародубцев Виктор. Вариант 20.", TaaflUIState::update, TaaflUIState::view::<iced::Renderer>)
         .centered()
         .settings(settings)
         .window(window_settings)
         .theme(TaaflUIState::theme)
-        .run()
+        .subscription(TaaflUIState::subscription)
+        .run_with(move || TaaflUIState::new(deterministic))
+}
+
+/// Отрисовка на процессоре (tiny-skia) вместо видеокарты (wgpu): для машин без
+/// подходящих драйверов. Без опции iced сам переходит на tiny-skia, если wgpu
+/// не удалось запустить, но на части старых драйверов wgpu запускается и рисует
+/// с ошибками.
+fn select_software_renderer() {
+    tracing::info!("выбрана программная отрисовка");
+    std::env::set_var(BACKEND_ENV, "tiny-skia");
+}
+
+/// Журнал пишется в stderr, чтобы не смешиваться с выводом консольного режима.
+/// По умолчанию выводятся только предупреждения. С `deterministic` записи журнала
+/// не зависят от времени и терминала: без меток времени и цвета.
+fn init_logging(deterministic: bool) {
+    let filter = EnvFilter::try_from_env(LOG_ENV).unwrap_or_else(|_| EnvFilter::new("warn"));
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    if deterministic {
+        subscriber.without_time().with_ansi(false).init();
+    } else {
+        subscriber.init();
+    }
 }
 
 // region: dummy_analyzer
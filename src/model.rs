@@ -0,0 +1,417 @@
+//! Простые типы данных результата анализа для внешних программ (скриптов проверки,
+//! консольного вывода в JSON и т.п.).
+//!
+//! Эти типы не зависят от внутреннего устройства анализатора и меняются только вместе
+//! с [`SCHEMA_VERSION`]:
+//! - добавление новых полей и новых значений перечислений версию не меняет,
+//!   поэтому потребители должны игнорировать незнакомые поля;
+//! - переименование, удаление или изменение смысла существующего поля
+//!   увеличивает версию на единицу.
+//!
+//! Перечисления сериализуются строками в `snake_case`, позиции - смещения в байтах
+//! от начала входной строки.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Текущая версия схемы вывода.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Отчёт в JSON, который графический интерфейс открывает из текущего каталога.
+pub const REPORT_FILE: &str = "taafl-report.json";
+
+/// Наибольшая вложенность массивов и объектов в читаемом отчёте. В отчёте их
+/// не больше пяти уровней; всё, что глубже, - не отчёт, и разбирать его незачем.
+pub const MAX_JSON_DEPTH: usize = 16;
+
+/// Результат анализа одной строки.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisReport {
+    /// Версия схемы, по которой сформирован отчёт.
+    pub schema_version: u32,
+    /// Анализируемая строка.
+    pub input: String,
+    /// Смещение строки в исходном тексте, если она - один из нескольких операторов.
+    /// Позиции в отчёте отсчитываются от начала строки `input`.
+    #[serde(default)]
+    pub offset: usize,
+    /// Файл, из которого прочитана строка, если он был.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Принадлежит ли строка языку (ошибок нет).
+    pub accepted: bool,
+    pub identifiers: Vec<Identifier>,
+    pub constants: Vec<Constant>,
+    /// Вещественные константы, если они разрешены настройками.
+    #[serde(default)]
+    pub reals: Vec<RealConstant>,
+    /// Символьные и строковые константы правой части.
+    #[serde(default)]
+    pub literals: Vec<LiteralConstant>,
+    /// Ошибки в порядке их позиций.
+    pub errors: Vec<ErrorEntry>,
+    /// Нарушения правил со строгостью "предупреждение" в порядке их позиций.
+    #[serde(default)]
+    pub warnings: Vec<ErrorEntry>,
+    /// Настройки, с которыми выполнен анализ.
+    #[serde(default)]
+    pub config: ConfigEntry,
+    /// Промежутки между лексемами: перед первой, между каждыми соседними и после
+    /// последней, так что промежуток `i` стоит перед лексемой `i`. Пусто, если
+    /// разбор не дошёл до конца строки.
+    #[serde(default)]
+    pub gaps: Vec<Gap>,
+    /// Левый вывод строки по грамматике языка: продукции в порядке применения.
+    /// Пусто, если строка не разобрана синтаксически.
+    #[serde(default)]
+    pub derivation: Vec<DerivationStep>,
+}
+
+impl AnalysisReport {
+    /// Читает отчёт, записанный ранее, например `taafl --format json`. Отчёт более
+    /// новой схемы или с вложенностью больше [`MAX_JSON_DEPTH`] не читается.
+    ///
+    /// ```
+    /// use taafl::model::AnalysisReport;
+    ///
+    /// let json = serde_json::to_string(&taafl::analyzer::analyze_report(
+    ///     "A := 1;",
+    ///     &Default::default(),
+    /// ))
+    /// .unwrap();
+    /// assert_eq!(AnalysisReport::from_json(&json).unwrap().input, "A := 1;");
+    ///
+    /// let deep = format!("{}{}", "[".repeat(100), "]".repeat(100));
+    /// assert!(AnalysisReport::from_json(&deep).unwrap_err().contains("вложенность"));
+    /// ```
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let depth = json_depth(json);
+        if depth > MAX_JSON_DEPTH {
+            return Err(format!(
+                "Отчёт не прочитан: вложенность {} больше допустимой {}",
+                depth, MAX_JSON_DEPTH
+            ));
+        }
+        let report: Self =
+            serde_json::from_str(json).map_err(|e| format!("Отчёт не прочитан: {}", e))?;
+        if report.schema_version > SCHEMA_VERSION {
+            return Err(format!(
+                "Отчёт не прочитан: схема версии {} новее поддерживаемой {}",
+                report.schema_version, SCHEMA_VERSION
+            ));
+        }
+        Ok(report)
+    }
+}
+
+/// Результат анализа последовательности операторов одного текста.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SequenceReport {
+    /// Версия схемы, по которой сформирован отчёт.
+    pub schema_version: u32,
+    /// Весь анализируемый текст.
+    pub input: String,
+    /// Файл, из которого прочитан текст, если он был.
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Приняты ли все операторы.
+    pub accepted: bool,
+    /// Отчёты операторов по порядку. Смещение оператора в тексте - в `offset`,
+    /// позиции в отчёте отсчитываются от начала оператора.
+    pub statements: Vec<AnalysisReport>,
+    /// Сводная таблица символов: вхождения символа в одной роли во всех операторах.
+    /// Позиции в ней отсчитываются от начала `input`.
+    pub identifiers: Vec<Identifier>,
+    pub constants: Vec<Constant>,
+    #[serde(default)]
+    pub reals: Vec<RealConstant>,
+    #[serde(default)]
+    pub literals: Vec<LiteralConstant>,
+}
+
+/// Наибольшая вложенность скобок `[` и `{` в тексте JSON вне строк.
+fn json_depth(json: &str) -> usize {
+    let (mut depth, mut deepest) = (0usize, 0);
+    let (mut string, mut escaped) = (false, false);
+    for c in json.chars() {
+        if string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => string = true,
+            '[' | '{' => {
+                depth += 1;
+                deepest = deepest.max(depth);
+            }
+            ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    deepest
+}
+
+/// Действующие настройки анализа.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigEntry {
+    /// Профиль настроек (`strict`, `extended`, `teaching`), если настройки не менялись вручную.
+    #[serde(default)]
+    pub profile: Option<String>,
+    pub recovery: bool,
+    #[serde(default)]
+    pub hints: bool,
+    /// Строгость каждого семантического правила по его имени
+    /// (`constant-range`, `identifier-length`, `array-in-expression`).
+    pub rules: BTreeMap<String, Severity>,
+    /// Действующий диапазон целых констант. Нет в отчётах, записанных до его
+    /// появления.
+    #[serde(default)]
+    pub constant_range: Option<RangeEntry>,
+    /// Остальные настройки, от которых зависит результат: по ним и диапазону
+    /// анализ можно повторить. Нет в отчётах, записанных до их появления.
+    #[serde(default)]
+    pub settings: Option<SettingsEntry>,
+}
+
+/// Настройки языка и вывода, с которыми выполнен анализ. Варианты настроек
+/// записываются своими именами из `taafl.toml`, знаки операций - лексемами.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SettingsEntry {
+    pub max_errors: usize,
+    pub max_input: usize,
+    /// `appearance` или `sorted`
+    pub order: String,
+    pub preserve_case: bool,
+    /// `descent`, `table` или `earley`
+    pub parser: String,
+    /// `direct` или `dfa`
+    pub scanner: String,
+    /// `semicolon`, `dot` или `none`
+    pub terminator: String,
+    /// `square` или `round`
+    pub brackets: String,
+    /// Лексемы присваивания: `colon-equals`, `equals`, `arrow`
+    pub assign: Vec<String>,
+    /// Знаки операций: `+`, `<=`, ...
+    pub operations: Vec<String>,
+    pub reals: bool,
+    pub underscores: bool,
+    pub unicode_identifiers: bool,
+    pub typography: bool,
+    pub compact_indices: bool,
+    pub spaced_assign: bool,
+    pub max_terms: usize,
+    pub max_indices: usize,
+    pub max_nesting: usize,
+}
+
+/// Диапазон значений; границы входят в него.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeEntry {
+    pub min: i32,
+    pub max: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+    Off,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Identifier {
+    /// Имя в верхнем регистре; по нему сравниваются вхождения. Поле записи
+    /// называется составным именем через точку: `REC.F`.
+    pub name: String,
+    /// Компоненты имени: `["REC", "F"]`, у простого имени - оно одно.
+    #[serde(default)]
+    pub path: Vec<String>,
+    /// Записи имени во вводе с исходным регистром, без повторов, в порядке
+    /// появления.
+    #[serde(default)]
+    pub lexemes: Vec<String>,
+    pub role: IdentifierRole,
+    /// Число вхождений в этой роли.
+    #[serde(default)]
+    pub occurrences: usize,
+    /// Смещения всех вхождений в этой роли в порядке появления.
+    #[serde(default)]
+    pub positions: Vec<usize>,
+    /// Продукция и функция разбора для каждого вхождения, в порядке `positions`.
+    #[serde(default)]
+    pub origins: Vec<Origin>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum IdentifierRole {
+    /// Идентификатор-массив
+    Array,
+    /// Идентификатор-индекс
+    Index,
+    /// Идентификатор-выражение
+    Expression,
+    /// Идентификатор-массив в правой части: имя массива, элемент которого стоит
+    /// в выражении
+    ExpressionArray,
+    /// Идентификатор-функция: имя функции, вызов которой стоит в правой части
+    Function,
+    /// Идентификатор-поле: поле элемента массива в левой части, `F` в `A[I].F`
+    Field,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Constant {
+    pub value: i32,
+    /// Различные записи константы во вводе в порядке появления, например `7` и `007`.
+    #[serde(default)]
+    pub lexemes: Vec<String>,
+    /// Значение вне допустимого диапазона варианта (при строгости правила ниже ошибки).
+    #[serde(default)]
+    pub out_of_range: bool,
+    pub role: ConstantRole,
+    /// Число вхождений в этой роли.
+    #[serde(default)]
+    pub occurrences: usize,
+    /// Смещения всех вхождений в этой роли в порядке появления.
+    #[serde(default)]
+    pub positions: Vec<usize>,
+    /// Продукция и функция разбора для каждого вхождения, в порядке `positions`.
+    #[serde(default)]
+    pub origins: Vec<Origin>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct RealConstant {
+    pub value: f64,
+    /// Различные записи константы во вводе в порядке появления, например `1.5` и `15.0E-1`.
+    #[serde(default)]
+    pub lexemes: Vec<String>,
+    /// [`ConstantRole::RealIndex`] или [`ConstantRole::RealExpression`].
+    pub role: ConstantRole,
+    /// Число вхождений в этой роли.
+    #[serde(default)]
+    pub occurrences: usize,
+    /// Смещения всех вхождений в этой роли в порядке появления.
+    #[serde(default)]
+    pub positions: Vec<usize>,
+    /// Продукция и функция разбора для каждого вхождения, в порядке `positions`.
+    #[serde(default)]
+    pub origins: Vec<Origin>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct LiteralConstant {
+    /// Текст между кавычками, с учётом регистра.
+    pub value: String,
+    /// Различные записи константы во вводе в порядке появления, например `'A'` и `"A"`.
+    #[serde(default)]
+    pub lexemes: Vec<String>,
+    /// [`ConstantRole::CharacterExpression`] или [`ConstantRole::StringExpression`].
+    pub role: ConstantRole,
+    /// Число вхождений в этой роли.
+    #[serde(default)]
+    pub occurrences: usize,
+    /// Смещения всех вхождений в этой роли в порядке появления.
+    #[serde(default)]
+    pub positions: Vec<usize>,
+    /// Продукция и функция разбора для каждого вхождения, в порядке `positions`.
+    #[serde(default)]
+    pub origins: Vec<Origin>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum ConstantRole {
+    /// Константа-индекс
+    Index,
+    /// Константа-выражение
+    Expression,
+    /// Вещественная константа-индекс: только при строгости правила `real-index`
+    /// ниже ошибки
+    RealIndex,
+    /// Вещественная константа-выражение
+    RealExpression,
+    /// Символьная константа-выражение: литерал из одного символа
+    CharacterExpression,
+    /// Строковая константа-выражение
+    StringExpression,
+}
+
+/// Откуда взялось вхождение символа: какая продукция грамматики его вывела и
+/// какая функция рекурсивного спуска его распознала.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Origin {
+    /// Смещение вхождения в байтах.
+    pub position: usize,
+    /// Продукция в записи грамматики, например `index = IDENT`.
+    pub production: String,
+    /// Функция анализатора, например `parse_term`.
+    pub parser: String,
+}
+
+/// Шаг левого вывода: продукция, применённая к самому левому нетерминалу.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DerivationStep {
+    /// Номер продукции в грамматике языка.
+    pub production: usize,
+    /// Нетерминал левой части: `left`.
+    pub lhs: String,
+    /// Символы правой части в записи грамматики: `IDENT`, `"["`, `index_list`.
+    /// Пусто у продукции `ε`.
+    pub rhs: Vec<String>,
+}
+
+/// Текст между соседними лексемами: пробелы, переводы строк, комментарии
+/// или пропущенные в режиме восстановления символы. Может быть пустым.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gap {
+    /// Смещение начала промежутка в байтах: конец предыдущей лексемы.
+    pub position: usize,
+    /// Смещение конца промежутка в байтах: начало следующей лексемы.
+    pub end: usize,
+    pub text: String,
+}
+
+impl Gap {
+    /// Есть ли в промежутке пробельный символ.
+    pub fn is_spaced(&self) -> bool {
+        self.text.chars().any(char::is_whitespace)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorEntry {
+    pub kind: ErrorKind,
+    /// Смещение начала ошибки в байтах.
+    pub position: usize,
+    /// Смещение конца ошибки в байтах, не включая его.
+    #[serde(default)]
+    pub end: usize,
+    pub message: String,
+    /// Терминалы грамматики, допустимые на месте синтаксической ошибки, по
+    /// таблице LL(1); у других ошибок список пуст.
+    #[serde(default)]
+    pub expected: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Lexical,
+    Syntax,
+    Semantic,
+}
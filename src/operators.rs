@@ -0,0 +1,331 @@
+//! Реестр операций выражений: знак, место относительно операнда, приоритет
+//! и ассоциативность. По реестру варианта ([`Variant::operators`](crate::variant::Variant::operators))
+//! рекурсивный спуск разбирает выражения методом Пратта: операции не записаны
+//! в коде анализатора, и набор операций варианта меняется без его правки.
+//!
+//! Знаки операций по-прежнему читает лексический анализатор ([`OperationSign`](crate::config::OperationSign)
+//! и ключевые слова [`KEYWORD_OPERATIONS`](crate::ast::KEYWORD_OPERATIONS)); реестр
+//! решает, какие из них и как связывают операнды. Дерево разбора хранит слагаемые
+//! в порядке записи, а их группировку по приоритетам даёт [`Registry::group`].
+//!
+//! ```
+//! use taafl::analyzer::Analyzer;
+//! use taafl::operators::{self, Operator};
+//!
+//! let analyzer = Analyzer::builder().collect_ast(true).build();
+//! let ast = analyzer.analyze("X := -A + B * C = NOT D;").ast.unwrap();
+//! let registry = operators::builtin();
+//! assert_eq!(registry.group(&ast.value).unwrap().to_string(), "(((-A) + (B * C)) = (NOT D))");
+//!
+//! let mut power = registry.clone();
+//! power.register(Operator::infix("*", 1).right());
+//! assert_eq!(power.group(&ast.value).unwrap().to_string(), "(((-A) + B) * (C = (NOT D)))");
+//! ```
+
+use crate::ast::{Expression, Operand, Operation, SIGNS};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// Место унарной или бинарной операции относительно операндов.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Fixity {
+    /// Унарная операция перед первым операндом выражения: знак в `-1 + A`
+    Leading,
+    /// Унарная операция перед любым операндом: `NOT`
+    Prefix,
+    /// Бинарная операция между операндами
+    Infix,
+}
+
+impl Fixity {
+    /// Число операндов.
+    pub fn arity(self) -> usize {
+        match self {
+            Fixity::Leading | Fixity::Prefix => 1,
+            Fixity::Infix => 2,
+        }
+    }
+}
+
+/// Порядок применения бинарных операций одного приоритета.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Associativity {
+    /// `A - B - C` = `(A - B) - C`
+    #[default]
+    Left,
+    /// `A ** B ** C` = `A ** (B ** C)`
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operator {
+    /// Знак или ключевое слово в верхнем регистре: `+`, `DIV`
+    pub symbol: String,
+    pub fixity: Fixity,
+    /// Чем больше, тем сильнее операция связывает операнды. Операнд унарной
+    /// операции включает бинарные операции с большим приоритетом: `-A * B`
+    /// при приоритете знака ниже умножения - это `-(A * B)`
+    pub precedence: u8,
+    pub associativity: Associativity,
+}
+
+impl Operator {
+    fn new(symbol: &str, fixity: Fixity, precedence: u8) -> Self {
+        Operator {
+            symbol: symbol.to_string(),
+            fixity,
+            precedence,
+            associativity: Associativity::Left,
+        }
+    }
+
+    /// Левоассоциативная бинарная операция.
+    pub fn infix(symbol: &str, precedence: u8) -> Self {
+        Self::new(symbol, Fixity::Infix, precedence)
+    }
+
+    pub fn prefix(symbol: &str, precedence: u8) -> Self {
+        Self::new(symbol, Fixity::Prefix, precedence)
+    }
+
+    pub fn leading(symbol: &str, precedence: u8) -> Self {
+        Self::new(symbol, Fixity::Leading, precedence)
+    }
+
+    /// Та же операция, правоассоциативная.
+    pub fn right(self) -> Self {
+        Operator {
+            associativity: Associativity::Right,
+            ..self
+        }
+    }
+
+    /// Наименьший приоритет операций, которые входят в правый операнд бинарной
+    /// или в операнд унарной операции.
+    fn operand_precedence(&self) -> u8 {
+        match (self.fixity, self.associativity) {
+            (Fixity::Infix, Associativity::Right) => self.precedence,
+            _ => self.precedence.saturating_add(1),
+        }
+    }
+}
+
+/// Операции по знаку и месту; у знака может быть и унарная, и бинарная операция.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Registry {
+    operators: Vec<Operator>,
+}
+
+impl Registry {
+    /// Пустой реестр: в выражениях нет операций.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Операции Modula-2 по убыванию приоритета: `NOT`; `* / % DIV MOD AND`;
+    /// `+ - OR` и знак перед первым слагаемым; сравнения.
+    pub fn modula2() -> Self {
+        let mut registry = Registry::new();
+        for symbol in ["=", "#", "<", ">", "<=", ">=", "<>"] {
+            registry.register(Operator::infix(symbol, 1));
+        }
+        for symbol in ["+", "-", "OR"] {
+            registry.register(Operator::infix(symbol, 2));
+        }
+        for symbol in SIGNS {
+            registry.register(Operator::leading(symbol, 2));
+        }
+        for symbol in ["*", "/", "%", "DIV", "MOD", "AND"] {
+            registry.register(Operator::infix(symbol, 3));
+        }
+        registry.register(Operator::prefix("NOT", 4));
+        registry
+    }
+
+    /// Добавляет операцию или заменяет операцию с тем же знаком и местом;
+    /// возвращает заменённую.
+    pub fn register(&mut self, operator: Operator) -> Option<Operator> {
+        let same = |o: &Operator| o.symbol == operator.symbol && o.fixity == operator.fixity;
+        match self.operators.iter_mut().find(|o| same(o)) {
+            Some(existing) => Some(std::mem::replace(existing, operator)),
+            None => {
+                self.operators.push(operator);
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, symbol: &str, fixity: Fixity) -> Option<Operator> {
+        let i = self
+            .operators
+            .iter()
+            .position(|o| o.symbol == symbol && o.fixity == fixity)?;
+        Some(self.operators.remove(i))
+    }
+
+    pub fn get(&self, symbol: &str, fixity: Fixity) -> Option<&Operator> {
+        self.operators
+            .iter()
+            .find(|o| o.symbol == symbol && o.fixity == fixity)
+    }
+
+    /// Унарная операция перед операндом: перед первым операндом выражения
+    /// (`leading`) годится и [`Fixity::Leading`], и [`Fixity::Prefix`].
+    pub fn unary(&self, symbol: &str, leading: bool) -> Option<&Operator> {
+        let prefix = self.get(symbol, Fixity::Prefix);
+        match leading {
+            true => prefix.or_else(|| self.get(symbol, Fixity::Leading)),
+            false => prefix,
+        }
+    }
+
+    /// Операции в порядке регистрации.
+    pub fn operators(&self) -> &[Operator] {
+        &self.operators
+    }
+
+    /// Группировка выражения по приоритетам операций реестра; `None`, если
+    /// в выражении есть операция, которой в реестре нет на её месте.
+    pub fn group<'a>(&self, expression: &'a Expression) -> Option<Group<'a>> {
+        let mut items = Vec::new();
+        for (i, term) in expression.terms().enumerate() {
+            if let Some((operation, _)) = i.checked_sub(1).and_then(|i| expression.rest.get(i)) {
+                items.push(Item::Infix(operation));
+            }
+            let leading = i == 0;
+            items.extend(term.unary.iter().map(|unary| Item::Unary(unary, leading)));
+            items.push(Item::Operand(&term.operand));
+        }
+        let mut grouping = Grouping {
+            registry: self,
+            items: items.into_iter().peekable(),
+        };
+        grouping.expression(0)
+    }
+}
+
+/// Реестр операций Modula-2 ([`Registry::modula2`]) - общий для вариантов,
+/// не задающих своего.
+pub fn builtin() -> &'static Registry {
+    static BUILTIN: OnceLock<Registry> = OnceLock::new();
+    BUILTIN.get_or_init(Registry::modula2)
+}
+
+/// Выражение, сгруппированное по приоритетам операций.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Group<'a> {
+    Operand(&'a Operand),
+    Unary(&'a Operation, Box<Group<'a>>),
+    Binary(&'a Operation, Box<Group<'a>>, Box<Group<'a>>),
+}
+
+impl fmt::Display for Group<'_> {
+    /// Каждая операция - в скобках: `((-A) + (B * C))`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Group::Operand(operand) => write!(f, "{}", operand),
+            Group::Unary(operation, operand) if SIGNS.contains(&operation.symbol) => {
+                write!(f, "({}{})", operation, operand)
+            }
+            Group::Unary(operation, operand) => write!(f, "({} {})", operation, operand),
+            Group::Binary(operation, left, right) => {
+                write!(f, "({} {} {})", left, operation, right)
+            }
+        }
+    }
+}
+
+/// Элемент выражения в порядке записи. У унарной операции отмечено, стоит ли
+/// она перед первым операндом.
+enum Item<'a> {
+    Unary(&'a Operation, bool),
+    Operand(&'a Operand),
+    Infix(&'a Operation),
+}
+
+struct Grouping<'r, 'a, I: Iterator<Item = Item<'a>>> {
+    registry: &'r Registry,
+    items: std::iter::Peekable<I>,
+}
+
+impl<'a, I: Iterator<Item = Item<'a>>> Grouping<'_, 'a, I> {
+    /// Операнд и следующие за ним бинарные операции с приоритетом не ниже `min`.
+    fn expression(&mut self, min: u8) -> Option<Group<'a>> {
+        let mut left = match self.items.next()? {
+            Item::Operand(operand) => Group::Operand(operand),
+            Item::Unary(operation, leading) => {
+                let operator = self.registry.unary(operation.symbol, leading)?;
+                let operand = self.expression(operator.operand_precedence())?;
+                Group::Unary(operation, Box::new(operand))
+            }
+            Item::Infix(_) => return None,
+        };
+        while let Some(Item::Infix(operation)) = self.items.peek() {
+            let operation = *operation;
+            let operator = self.registry.get(operation.symbol, Fixity::Infix)?;
+            if operator.precedence < min {
+                break;
+            }
+            self.items.next();
+            let right = self.expression(operator.operand_precedence())?;
+            left = Group::Binary(operation, Box::new(left), Box::new(right));
+        }
+        Some(left)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn operator_registry_drives_expression_parsing() {
+        use crate::analyzer::Analyzer;
+        use crate::operators::{self, Fixity, Operator, Registry};
+        use crate::variant::Variant;
+
+        /// Вариант со своим набором операций.
+        #[derive(Debug)]
+        struct Custom(Registry);
+
+        impl Variant for Custom {
+            fn name(&self) -> &str {
+                "Свои операции"
+            }
+
+            fn operators(&self) -> &Registry {
+                &self.0
+            }
+        }
+
+        let analyzer = |registry| Analyzer::builder().variant(Custom(registry)).build();
+        let default = Analyzer::builder().collect_ast(true).build();
+        assert!(default.analyze("X := A MOD B;").report.accepted);
+        assert!(!default.analyze("X := A * -B;").report.accepted);
+
+        let mut registry = Registry::modula2();
+        assert!(registry.remove("MOD", Fixity::Infix).is_some());
+        let analysis = analyzer(registry).analyze("X := A MOD B;");
+        assert!(!analysis.report.accepted);
+        assert_eq!(analysis.diagnostics[0].code, "syntax");
+        assert_eq!(analysis.diagnostics[0].span, 7..10);
+
+        let mut registry = Registry::modula2();
+        assert!(registry.register(Operator::prefix("-", 4)).is_none());
+        let analysis = analyzer(registry.clone()).analyze("X := A * -B - C;");
+        assert!(analysis.report.accepted, "{:?}", analysis.diagnostics);
+
+        // Дерево хранит слагаемые по порядку, группировку задаёт реестр
+        let ast = default.analyze("X := A - B - C * D OR E;").ast.unwrap();
+        let builtin = operators::builtin();
+        assert_eq!(
+            builtin.group(&ast.value).unwrap().to_string(),
+            "(((A - B) - (C * D)) OR E)"
+        );
+        registry.register(Operator::infix("-", 2).right());
+        assert_eq!(
+            registry.group(&ast.value).unwrap().to_string(),
+            "(A - (B - ((C * D) OR E)))"
+        );
+        registry.remove("OR", Fixity::Infix);
+        assert_eq!(registry.group(&ast.value), None);
+    }
+}
@@ -0,0 +1,274 @@
+//! Самопроверка по таблице символов: для оператора называются роли его символов
+//! и число вхождений в каждой роли, а ответ сверяется с результатом анализатора
+//! построчно.
+//!
+//! Ответ - по строке на символ в роли, как в таблице символов: `символ - роль: число`.
+//! Роль можно назвать полностью (`идентификатор-индекс`) или последним словом
+//! (`индекс`), имена идентификаторов - в любом регистре.
+//!
+//! ```
+//! use taafl::config::AnalyzerConfig;
+//! use taafl::quiz::{Mark, Quiz};
+//!
+//! let quiz = Quiz::new("X[I] := I + 7;", &AnalyzerConfig::default()).unwrap();
+//! let grade = quiz.grade("x - массив: 1\nI - индекс: 2\n7 - константа-выражение: 1");
+//! let marks: Vec<&Mark> = grade.rows.iter().map(|row| &row.mark).collect();
+//! assert_eq!(
+//!     marks,
+//!     [
+//!         &Mark::Correct,
+//!         &Mark::WrongCount { answered: 2 },
+//!         &Mark::Correct,
+//!         &Mark::Missing,
+//!     ]
+//! );
+//! assert_eq!(grade.score(), (2, 4));
+//! assert_eq!(grade.rows[3].to_string(), "I - идентификатор-выражение: 1 - пропущено");
+//! ```
+
+use crate::analyzer::analyze_report;
+use crate::ast;
+use crate::config::AnalyzerConfig;
+use crate::generator::Generator;
+use crate::variant::{Variant, Variant20};
+use std::fmt;
+
+/// Сколько сгенерированных операторов перебирается в поисках принятого
+/// с заданными настройками.
+const ATTEMPTS: usize = 100;
+
+/// Символ в одной роли: строка таблицы символов без позиций.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    /// Имя идентификатора в верхнем регистре или значение константы, как
+    /// в таблице символов
+    pub symbol: String,
+    pub role: String,
+    pub occurrences: usize,
+    /// Символ - идентификатор: в ответе его имя сравнивается без учёта регистра
+    pub identifier: bool,
+}
+
+impl fmt::Display for Row {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}: {}", self.symbol, self.role, self.occurrences)
+    }
+}
+
+/// Задание: оператор и ожидаемые строки ответа.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quiz {
+    pub statement: String,
+    /// Строки в порядке таблицы символов: идентификаторы, затем константы
+    pub expected: Vec<Row>,
+}
+
+/// Оценка строки ответа.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mark {
+    Correct,
+    /// Символ и роль верны, число вхождений - нет; `answered` - названное число
+    WrongCount {
+        answered: usize,
+    },
+    /// Ожидаемая строка в ответе не названа
+    Missing,
+    /// Такого символа в такой роли в операторе нет
+    Extra,
+    /// Строка не в виде `символ - роль: число`
+    Unreadable,
+}
+
+/// Строка проверенного ответа: ожидаемая строка или строка ответа и её оценка.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feedback {
+    pub row: String,
+    pub mark: Mark,
+}
+
+impl fmt::Display for Feedback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mark {
+            Mark::Correct => write!(f, "{} - верно", self.row),
+            Mark::WrongCount { answered } => {
+                write!(f, "{} - названо вхождений: {}", self.row, answered)
+            }
+            Mark::Missing => write!(f, "{} - пропущено", self.row),
+            Mark::Extra => write!(f, "{} - лишнее: такого символа в этой роли нет", self.row),
+            Mark::Unreadable => write!(
+                f,
+                "{} - не разобрать: ожидалось \"символ - роль: число\"",
+                self.row
+            ),
+        }
+    }
+}
+
+/// Проверенный ответ: сначала строки ответа в их порядке, затем пропущенные.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grade {
+    pub rows: Vec<Feedback>,
+}
+
+impl Grade {
+    /// Число верных строк и число ожидаемых.
+    pub fn score(&self) -> (usize, usize) {
+        let correct = self.count(|mark| *mark == Mark::Correct);
+        let expected = self.count(|mark| matches!(mark, Mark::WrongCount { .. } | Mark::Missing));
+        (correct, correct + expected)
+    }
+
+    /// Ответ верен целиком: все строки названы и лишних нет.
+    pub fn is_perfect(&self) -> bool {
+        self.rows.iter().all(|row| row.mark == Mark::Correct)
+    }
+
+    fn count(&self, matches: impl Fn(&Mark) -> bool) -> usize {
+        self.rows.iter().filter(|row| matches(&row.mark)).count()
+    }
+}
+
+impl Quiz {
+    /// Задание по оператору `statement`; `None`, если с настройками `config`
+    /// оператор не принадлежит языку.
+    pub fn new(statement: &str, config: &AnalyzerConfig) -> Option<Self> {
+        let report = analyze_report(statement, config);
+        if !report.accepted {
+            return None;
+        }
+        let variant = Variant20;
+        let row = |symbol: String, role: &str, occurrences, identifier| Row {
+            symbol,
+            role: role.to_string(),
+            occurrences,
+            identifier,
+        };
+        let identifiers = report.identifiers.iter().map(|id| {
+            let role = variant.identifier_role(id.role);
+            row(id.name.clone(), role, id.occurrences, true)
+        });
+        let constants = report.constants.iter().map(|c| {
+            let role = variant.constant_role(c.role);
+            row(c.value.to_string(), role, c.occurrences, false)
+        });
+        let reals = report.reals.iter().map(|r| {
+            let role = variant.constant_role(r.role);
+            row(ast::Real(r.value).to_string(), role, r.occurrences, false)
+        });
+        let literals = report.literals.iter().map(|l| {
+            let role = variant.constant_role(l.role);
+            row(ast::quoted(&l.value), role, l.occurrences, false)
+        });
+        Some(Quiz {
+            statement: statement.to_string(),
+            expected: identifiers
+                .chain(constants)
+                .chain(reals)
+                .chain(literals)
+                .collect(),
+        })
+    }
+
+    /// Задание по случайному оператору [`Generator`] с зерном `seed`, принятому
+    /// с настройками `config`.
+    pub fn generate(seed: u64, config: &AnalyzerConfig) -> Option<Self> {
+        Generator::new(seed)
+            .take(ATTEMPTS)
+            .find_map(|statement| Self::new(&statement, config))
+    }
+
+    /// Сверяет ответ `answer` с ожидаемыми строками. Пустые строки ответа пропускаются.
+    pub fn grade(&self, answer: &str) -> Grade {
+        let mut named = vec![false; self.expected.len()];
+        let mut rows = Vec::new();
+        for line in answer
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+        {
+            let mark = match parse_line(line) {
+                None => Mark::Unreadable,
+                Some((symbol, role, answered)) => {
+                    // Строка, названная повторно, - лишняя
+                    let found = self.expected.iter().zip(&named).position(|(row, &named)| {
+                        !named && same_symbol(row, symbol) && same_role(&row.role, role)
+                    });
+                    match found {
+                        Some(i) => {
+                            named[i] = true;
+                            if self.expected[i].occurrences == answered {
+                                Mark::Correct
+                            } else {
+                                Mark::WrongCount { answered }
+                            }
+                        }
+                        None => Mark::Extra,
+                    }
+                }
+            };
+            rows.push(Feedback {
+                row: line.to_string(),
+                mark,
+            });
+        }
+        for (row, named) in self.expected.iter().zip(named) {
+            if !named {
+                rows.push(Feedback {
+                    row: row.to_string(),
+                    mark: Mark::Missing,
+                });
+            }
+        }
+        Grade { rows }
+    }
+}
+
+/// Символ, роль и число из строки `символ - роль: число`. Символ отделяется
+/// последним ` - `: в названиях ролей пробелов вокруг дефиса нет.
+fn parse_line(line: &str) -> Option<(&str, &str, usize)> {
+    let (rest, count) = line.rsplit_once(':')?;
+    let (symbol, role) = rest.rsplit_once(" - ")?;
+    let count = count.trim().parse().ok()?;
+    Some((symbol.trim(), role.trim(), count))
+}
+
+fn same_symbol(row: &Row, symbol: &str) -> bool {
+    row.symbol == symbol || (row.identifier && row.symbol == symbol.to_uppercase())
+}
+
+/// Роль названа полностью или последним словом: `индекс` для `константа-индекс`.
+fn same_role(expected: &str, answered: &str) -> bool {
+    let answered = answered.to_lowercase();
+    let last = expected.rsplit(['-', ' ']).next().unwrap_or(expected);
+    expected == answered || last == answered
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn quiz_accepts_the_analyzer_table_as_an_answer() {
+        use crate::config::AnalyzerConfig;
+        use crate::quiz::{Mark, Quiz};
+
+        let config = AnalyzerConfig::default();
+        for seed in 0..50 {
+            let quiz = Quiz::generate(seed, &config).expect("генератор строит принятые операторы");
+            let rows: Vec<String> = quiz.expected.iter().map(ToString::to_string).collect();
+            let grade = quiz.grade(&rows.join("\n").to_lowercase());
+            assert!(grade.is_perfect(), "{}: {:?}", quiz.statement, grade);
+            assert_eq!(grade.score(), (rows.len(), rows.len()));
+
+            // Повтор строки и строка не по образцу не засчитываются
+            let answer = format!("{}\n{}\nчто-то", rows[0], rows[0]);
+            let marks: Vec<Mark> = quiz
+                .grade(&answer)
+                .rows
+                .into_iter()
+                .map(|r| r.mark)
+                .collect();
+            assert_eq!(marks[..3], [Mark::Correct, Mark::Extra, Mark::Unreadable]);
+            assert_eq!(marks.len(), 2 + rows.len());
+        }
+        assert_eq!(Quiz::new("X := ;", &config), None);
+    }
+}
@@ -0,0 +1,148 @@
+//! Очистка вставленного текста перед анализом.
+//!
+//! Одна и та же очистка применяется в графическом интерфейсе (при вводе и вставке)
+//! и в консольном режиме (к каждому источнику), поэтому результаты анализа совпадают.
+//! Замена типографских знаков [`normalize_typography`] включается настройкой
+//! `typography`; без неё лексер сообщает о таком знаке, чем его заменить.
+
+use std::fmt;
+
+/// Что было исправлено при очистке.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteKind {
+    /// Метка порядка байтов в начале текста
+    ByteOrderMark,
+    /// Невидимые символы нулевой ширины и мягкие переносы
+    Invisible,
+    /// Управляющие символы, кроме табуляции и перевода строки
+    Control,
+    /// Переводы строк `\r\n` и `\r`, приведённые к `\n`
+    LineEnding,
+    /// Типографские знаки, заменённые знаками ASCII
+    Typography,
+}
+
+/// Замечание об исправлении: вид и число исправленных мест.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    pub kind: NoteKind,
+    pub count: usize,
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            NoteKind::ByteOrderMark => f.write_str("Удалена метка порядка байтов (BOM)"),
+            NoteKind::Invisible => write!(f, "Удалено невидимых символов: {}", self.count),
+            NoteKind::Control => write!(f, "Удалено управляющих символов: {}", self.count),
+            NoteKind::LineEnding => write!(f, "Приведено переводов строк: {}", self.count),
+            NoteKind::Typography => write!(f, "Заменено типографских знаков: {}", self.count),
+        }
+    }
+}
+
+/// Очищает текст. Возвращает очищенный текст и замечания в порядке шагов очистки;
+/// если исправлять было нечего, замечаний нет и текст не меняется.
+///
+/// ```
+/// use taafl::sanitize::{sanitize, NoteKind};
+///
+/// let (text, notes) = sanitize("\u{FEFF}A\u{200B} := 1;\r\n");
+/// assert_eq!(text, "A := 1;\n");
+/// assert_eq!(notes.len(), 3);
+/// assert_eq!(notes[0].kind, NoteKind::ByteOrderMark);
+/// ```
+pub fn sanitize(input: &str) -> (String, Vec<Note>) {
+    let mut notes = Vec::new();
+    let mut note = |kind, count| {
+        if count > 0 {
+            notes.push(Note { kind, count });
+        }
+    };
+
+    let text = match input.strip_prefix('\u{FEFF}') {
+        Some(rest) => {
+            note(NoteKind::ByteOrderMark, 1);
+            rest
+        }
+        None => input,
+    };
+
+    let mut result = String::with_capacity(text.len());
+    let (mut invisible, mut control, mut line_endings) = (0, 0, 0);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                result.push('\n');
+                line_endings += 1;
+            }
+            '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}' => {
+                invisible += 1
+            }
+            '\n' | '\t' => result.push(c),
+            _ if c.is_control() => control += 1,
+            _ => result.push(c),
+        }
+    }
+    note(NoteKind::Invisible, invisible);
+    note(NoteKind::Control, control);
+    note(NoteKind::LineEnding, line_endings);
+
+    (result, notes)
+}
+
+/// Знак ASCII, вместо которого редакторы и мессенджеры подставляют типографский
+/// знак `c`: знаки умножения и деления, тире, кавычки-«ёлочки» и «лапки»,
+/// неразрывные пробелы, знаки сравнения.
+pub fn ascii_lookalike(c: char) -> Option<&'static str> {
+    Some(match c {
+        '×' | '·' | '∙' | '⋅' | '∗' => "*",
+        '÷' | '∕' => "/",
+        '–' | '—' | '−' | '‐' | '‑' | '‒' => "-",
+        '“' | '”' | '„' | '«' | '»' | '″' => "\"",
+        '‘' | '’' | '‚' | '′' => "'",
+        '≤' => "<=",
+        '≥' => ">=",
+        '≠' => "<>",
+        '\u{00A0}' | '\u{2007}' | '\u{2009}' | '\u{202F}' => " ",
+        _ => return None,
+    })
+}
+
+/// Заменяет типографские знаки их эквивалентами ASCII ([`ascii_lookalike`]).
+/// Как и [`sanitize`], возвращает текст и замечания; если заменять нечего,
+/// замечаний нет.
+///
+/// ```
+/// use taafl::sanitize::{normalize_typography, NoteKind};
+///
+/// let (text, notes) = normalize_typography("X := A × B – «C»;");
+/// assert_eq!(text, "X := A * B - \"C\";");
+/// assert_eq!((notes[0].kind, notes[0].count), (NoteKind::Typography, 4));
+/// ```
+pub fn normalize_typography(input: &str) -> (String, Vec<Note>) {
+    let mut result = String::with_capacity(input.len());
+    let mut count = 0;
+    for c in input.chars() {
+        match ascii_lookalike(c) {
+            Some(ascii) => {
+                result.push_str(ascii);
+                count += 1;
+            }
+            None => result.push(c),
+        }
+    }
+    let notes = if count > 0 {
+        vec![Note {
+            kind: NoteKind::Typography,
+            count,
+        }]
+    } else {
+        Vec::new()
+    };
+    (result, notes)
+}
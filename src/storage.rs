@@ -0,0 +1,129 @@
+//! Хранилище данных оболочек: настроек, экспортированных архивов и прочих
+//! файлов, которые сохраняются между запусками.
+//!
+//! Оболочка работает с хранилищем через [`Storage`] и не знает, где лежат данные.
+//! На настольной системе это каталог с файлами ([`FileStorage`]), в тестах - память
+//! ([`MemoryStorage`]); в браузере хранилищем может быть `localStorage`, а для
+//! больших объёмов - база данных.
+//!
+//! ```
+//! use taafl::storage::{MemoryStorage, Storage};
+//!
+//! let mut storage = MemoryStorage::default();
+//! assert_eq!(storage.read("taafl.toml"), Ok(None));
+//! storage.write("taafl.toml", b"recovery = true\n").unwrap();
+//! assert_eq!(storage.read("taafl.toml").unwrap().as_deref(), Some(&b"recovery = true\n"[..]));
+//! assert_eq!(storage.names(), Ok(vec!["taafl.toml".to_string()]));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Именованные записи с байтовым содержимым. Ошибки - готовые сообщения
+/// для пользователя с именем записи.
+pub trait Storage: fmt::Debug {
+    /// Содержимое записи `name`; `None`, если её нет.
+    fn read(&self, name: &str) -> Result<Option<Vec<u8>>, String>;
+
+    /// Записывает `data` в `name`, заменяя прежнее содержимое.
+    fn write(&mut self, name: &str, data: &[u8]) -> Result<(), String>;
+
+    /// Имена всех записей по алфавиту.
+    fn names(&self) -> Result<Vec<String>, String>;
+
+    /// Файл записи `name`, если хранилище держит записи в файлах: так к ним
+    /// обращаются библиотеки, которым нужен путь, например база SQLite.
+    fn file(&self, _name: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Хранилище по умолчанию - в памяти: ничего не пишет на диск.
+impl Default for Box<dyn Storage> {
+    fn default() -> Self {
+        Box::<MemoryStorage>::default()
+    }
+}
+
+/// Файлы в каталоге: имя записи - имя файла.
+#[derive(Debug, Clone)]
+pub struct FileStorage {
+    dir: PathBuf,
+}
+
+impl FileStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Путь к файлу записи `name`.
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+impl Storage for FileStorage {
+    fn read(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        let path = self.path(name);
+        match std::fs::read(&path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Не удалось прочитать {}: {}", path.display(), e)),
+        }
+    }
+
+    fn write(&mut self, name: &str, data: &[u8]) -> Result<(), String> {
+        let path = self.path(name);
+        std::fs::write(&path, data)
+            .map_err(|e| format!("Не удалось записать {}: {}", path.display(), e))
+    }
+
+    fn names(&self) -> Result<Vec<String>, String> {
+        let failed = |e: std::io::Error| {
+            format!("Не удалось прочитать каталог {}: {}", self.dir.display(), e)
+        };
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.dir).map_err(failed)? {
+            let entry = entry.map_err(failed)?;
+            if entry.file_type().map_err(failed)?.is_file() {
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn file(&self, name: &str) -> Option<PathBuf> {
+        Some(self.path(name))
+    }
+}
+
+impl Default for FileStorage {
+    /// Текущий каталог.
+    fn default() -> Self {
+        Self::new(Path::new("."))
+    }
+}
+
+/// Записи в памяти; пропадают вместе с хранилищем.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    records: BTreeMap<String, Vec<u8>>,
+}
+
+impl Storage for MemoryStorage {
+    fn read(&self, name: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.records.get(name).cloned())
+    }
+
+    fn write(&mut self, name: &str, data: &[u8]) -> Result<(), String> {
+        self.records.insert(name.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    fn names(&self) -> Result<Vec<String>, String> {
+        Ok(self.records.keys().cloned().collect())
+    }
+}
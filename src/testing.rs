@@ -0,0 +1,402 @@
+//! Генераторы входных данных для тестирования свойств анализатора: лексемы,
+//! правильные операторы и их мутации ("почти правильные" операторы).
+//!
+//! Для proptest это стратегии [`Tokens`], [`Statements`] и [`Mutants`]
+//! (`any::<Assignment>()` - те же [`Statements`]); контрпример упрощается
+//! удалением слагаемых, индексов или символов. Без proptest те же случаи даёт
+//! [`Cases`] из зерна, а свойство на их серии проверяет [`forall`].
+//!
+//! Модуль доступен с возможностью `testing`:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! taafl = { version = "0.1", features = ["testing"] }
+//! ```
+//!
+//! ```
+//! use proptest::prelude::*;
+//! use taafl::analyzer::Analyzer;
+//! use taafl::ast::Assignment;
+//! use taafl::testing::Mutants;
+//!
+//! proptest! {
+//!     // parse(print(ast)) == ast
+//!     fn reparse(ast in any::<Assignment>()) {
+//!         let analyzer = Analyzer::builder().collect_ast(true).build();
+//!         let parsed = analyzer.analyze(&ast.to_string()).ast;
+//!         prop_assert_eq!(parsed.map(|parsed| parsed.without_positions()), Some(ast));
+//!     }
+//! }
+//! reparse();
+//!
+//! // Контрпример свойства "нет двоеточия" упрощается до одного символа
+//! let mut runner = proptest::test_runner::TestRunner::deterministic();
+//! let failure = runner.run(&Mutants, |text| {
+//!     prop_assert!(!text.contains(':'));
+//!     Ok(())
+//! });
+//! assert!(matches!(failure, Err(proptest::test_runner::TestError::Fail(_, text)) if text == ":"));
+//! ```
+
+use crate::ast::Assignment;
+use crate::generator::{Generator, Rng};
+use proptest::arbitrary::Arbitrary;
+use proptest::prelude::Rng as _;
+use proptest::strategy::{NewTree, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+use std::fmt;
+
+/// Лексемы-разделители, из которых выбираются вставки.
+const SYMBOLS: &[&str] = &[
+    ":=", ";", "[", "]", ",", "+", "-", "*", "/", ">", "<", "=", "#", "<=", ">=", "<>",
+];
+
+/// Символы, недопустимые в операторе, для вставки лексических ошибок.
+const INVALID: &[&str] = &["$", "_", "?", ":", "!", "@", "Б", "é", "\u{a0}"];
+
+/// Источник случайных случаев. Одно и то же зерно даёт одни и те же случаи.
+#[derive(Debug, Clone)]
+pub struct Cases {
+    generator: Generator,
+    rng: Rng,
+}
+
+/// Изменение правильного оператора: позиции - в символах текста.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    /// Удалить символ
+    Delete(usize),
+    /// Вставить текст перед символом
+    Insert(usize, String),
+    /// Поменять местами символ и следующий за ним
+    Swap(usize),
+    /// Повторить символ
+    Duplicate(usize),
+}
+
+impl Mutation {
+    pub fn apply(&self, text: &str) -> String {
+        let mut chars: Vec<char> = text.chars().collect();
+        match self {
+            Mutation::Delete(i) if *i < chars.len() => {
+                chars.remove(*i);
+            }
+            Mutation::Insert(i, insert) => {
+                let i = (*i).min(chars.len());
+                chars.splice(i..i, insert.chars());
+            }
+            Mutation::Swap(i) if i + 1 < chars.len() => chars.swap(*i, i + 1),
+            Mutation::Duplicate(i) if *i < chars.len() => chars.insert(*i, chars[*i]),
+            _ => {}
+        }
+        chars.into_iter().collect()
+    }
+}
+
+impl fmt::Display for Mutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mutation::Delete(i) => write!(f, "удалён символ {}", i),
+            Mutation::Insert(i, text) => write!(f, "вставлено '{}' перед символом {}", text, i),
+            Mutation::Swap(i) => write!(f, "переставлены символы {} и {}", i, i + 1),
+            Mutation::Duplicate(i) => write!(f, "повторён символ {}", i),
+        }
+    }
+}
+
+impl Cases {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            generator: Generator::new(seed),
+            rng: Rng::new(seed ^ 0x5EED),
+        }
+    }
+
+    /// Текст одной лексемы: идентификатор, константа или разделитель.
+    pub fn token(&mut self) -> String {
+        match self.rng.range(0, 2) {
+            0 => self.generator.identifier(),
+            1 => self.rng.range(0, 40000).to_string(),
+            _ => self.rng.pick(SYMBOLS).to_string(),
+        }
+    }
+
+    /// Правильный оператор и его дерево разбора (без позиций).
+    pub fn statement(&mut self) -> (String, Assignment) {
+        let assignment = self.generator.assignment();
+        (self.generator.render(&assignment), assignment)
+    }
+
+    /// Правильный оператор с одной мутацией. Результат может остаться правильным.
+    pub fn mutant(&mut self) -> (String, Mutation) {
+        let (text, _) = self.statement();
+        let len = text.chars().count();
+        let i = self.rng.range(0, len - 1);
+        let mutation = match self.rng.range(0, 3) {
+            0 => Mutation::Delete(i),
+            1 => {
+                let insert = if self.rng.chance(30) {
+                    self.rng.pick(INVALID).to_string()
+                } else {
+                    self.token()
+                };
+                Mutation::Insert(i, insert)
+            }
+            2 => Mutation::Swap(i),
+            _ => Mutation::Duplicate(i),
+        };
+        (mutation.apply(&text), mutation)
+    }
+}
+
+/// Случай, на котором свойство не выполнилось.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure<T> {
+    /// Номер случая в серии, начиная с нуля
+    pub case: usize,
+    pub input: T,
+}
+
+/// Проверяет свойство на `count` случаях, порождённых из зерна `seed`.
+///
+/// ```
+/// use taafl::analyzer::Analyzer;
+/// use taafl::testing::forall;
+///
+/// let analyzer = Analyzer::default();
+/// let result = forall(500, 1, |cases| cases.statement().0, |text| {
+///     analyzer.analyze(text).report.accepted
+/// });
+/// assert!(result.is_ok());
+/// ```
+pub fn forall<T>(
+    count: usize,
+    seed: u64,
+    mut generate: impl FnMut(&mut Cases) -> T,
+    mut property: impl FnMut(&T) -> bool,
+) -> Result<(), Failure<T>> {
+    let mut cases = Cases::new(seed);
+    for case in 0..count {
+        let input = generate(&mut cases);
+        if !property(&input) {
+            return Err(Failure { case, input });
+        }
+    }
+    Ok(())
+}
+
+/// Упрощает строку-контрпример: удаляет символы, пока свойство продолжает нарушаться.
+pub fn shrink(input: &str, mut fails: impl FnMut(&str) -> bool) -> String {
+    let mut chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let mut shorter = chars.clone();
+        shorter.remove(i);
+        let text: String = shorter.iter().collect();
+        if fails(&text) {
+            chars = shorter;
+        } else {
+            i += 1;
+        }
+    }
+    chars.into_iter().collect()
+}
+
+/// Дерево значений стратегий модуля: текущее значение и его упрощения. Упрощение
+/// пробует варианты `simpler` по очереди; вариант, на котором свойство снова
+/// нарушено, становится новой отправной точкой, выполненное свойство - переход
+/// к следующему варианту.
+#[derive(Debug, Clone)]
+pub struct Shrinking<T> {
+    current: T,
+    /// Последнее значение, нарушившее свойство
+    failing: T,
+    candidates: Vec<T>,
+    next: usize,
+    simpler: fn(&T) -> Vec<T>,
+}
+
+impl<T: Clone> Shrinking<T> {
+    fn new(value: T, simpler: fn(&T) -> Vec<T>) -> Self {
+        Self {
+            current: value.clone(),
+            failing: value,
+            candidates: Vec::new(),
+            next: 0,
+            simpler,
+        }
+    }
+}
+
+impl<T: Clone + fmt::Debug> ValueTree for Shrinking<T> {
+    type Value = T;
+
+    fn current(&self) -> T {
+        self.current.clone()
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.failing = self.current.clone();
+        self.candidates = (self.simpler)(&self.failing);
+        self.next = 0;
+        self.complicate()
+    }
+
+    fn complicate(&mut self) -> bool {
+        match self.candidates.get(self.next) {
+            Some(candidate) => {
+                self.current = candidate.clone();
+                self.next += 1;
+                true
+            }
+            None => {
+                self.current = self.failing.clone();
+                false
+            }
+        }
+    }
+}
+
+/// Источник случаев для одного значения стратегии из генератора proptest.
+fn cases(runner: &mut TestRunner) -> Cases {
+    Cases::new(runner.rng().next_u64())
+}
+
+/// Текст одной лексемы ([`Cases::token`]); не упрощается.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tokens;
+
+impl Strategy for Tokens {
+    type Tree = Shrinking<String>;
+    type Value = String;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        Ok(Shrinking::new(cases(runner).token(), |_| Vec::new()))
+    }
+}
+
+/// Правильные операторы ([`Cases::statement`]): упрощаются удалением слагаемых
+/// правой части и лишних индексов, текст даёт [`Display`](fmt::Display) дерева.
+/// Стратегия по умолчанию для [`Assignment`]: `any::<Assignment>()`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Statements;
+
+impl Statements {
+    fn simpler(value: &Assignment) -> Vec<Assignment> {
+        let mut simpler = Vec::new();
+        for i in 0..value.value.rest.len() {
+            let mut shorter = value.clone();
+            shorter.value.rest.remove(i);
+            simpler.push(shorter);
+        }
+        let indices = value.target.indices.as_ref().map_or(0, Vec::len);
+        for i in (0..indices).skip(1) {
+            let mut shorter = value.clone();
+            if let Some(indices) = &mut shorter.target.indices {
+                indices.remove(i);
+            }
+            simpler.push(shorter);
+        }
+        simpler
+    }
+}
+
+impl Strategy for Statements {
+    type Tree = Shrinking<Assignment>;
+    type Value = Assignment;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        Ok(Shrinking::new(cases(runner).statement().1, Self::simpler))
+    }
+}
+
+impl Arbitrary for Assignment {
+    type Parameters = ();
+    type Strategy = Statements;
+
+    fn arbitrary_with(_: ()) -> Statements {
+        Statements
+    }
+}
+
+/// Правильные операторы с одной мутацией ([`Cases::mutant`]): упрощаются
+/// удалением символа, как в [`shrink`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Mutants;
+
+impl Mutants {
+    fn simpler(value: &str) -> Vec<String> {
+        let chars: Vec<char> = value.chars().collect();
+        (0..chars.len())
+            .map(|i| {
+                let mut shorter = chars.clone();
+                shorter.remove(i);
+                shorter.into_iter().collect()
+            })
+            .collect()
+    }
+}
+
+impl Strategy for Mutants {
+    type Tree = Shrinking<String>;
+    type Value = String;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        Ok(Shrinking::new(cases(runner).mutant().0, |text| Self::simpler(text)))
+    }
+}
+
+/// Число случаев в прогонах свойств тестов крейта.
+#[cfg(test)]
+pub(crate) const CASES: usize = 1000;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::Analyzer;
+
+    #[test]
+    fn generated_statements_are_accepted() {
+        let analyzer = Analyzer::default();
+        let result = forall(
+            CASES,
+            1,
+            |cases| cases.statement().0,
+            |text| analyzer.analyze(text).report.accepted,
+        );
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn parse_of_rendered_ast_is_the_same_ast() {
+        let analyzer = Analyzer::builder().collect_ast(true).build();
+        let result = forall(CASES, 2, Cases::statement, |(text, ast)| {
+            let parsed = analyzer.analyze(text).ast;
+            parsed.map(|parsed| parsed.without_positions()).as_ref() == Some(ast)
+        });
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn statement_strategy_shrinks_to_the_smallest_failure() {
+        use proptest::prelude::{any, prop_assert};
+        use proptest::test_runner::{TestError, TestRunner};
+
+        let mut runner = TestRunner::deterministic();
+        let failure = runner.run(&any::<Assignment>(), |ast| {
+            prop_assert!(ast.value.rest.is_empty() && ast.target.indices.is_none());
+            Ok(())
+        });
+        let Err(TestError::Fail(_, ast)) = failure else {
+            panic!("{:?}", failure);
+        };
+        let indices = ast.target.indices.as_ref().map_or(0, Vec::len);
+        assert_eq!(ast.value.rest.len() + indices, 1, "{}", ast);
+    }
+
+    #[test]
+    fn shrink_keeps_the_failure() {
+        let fails = |text: &str| text.contains('$');
+        assert_eq!(shrink("A[1] := B $ 2;", fails), "$");
+    }
+}
@@ -0,0 +1,317 @@
+//! Дерево разбора для рисования: в DOT, в SVG и текстом моноширинным шрифтом -
+//! для консоли и отчётов, где картинку не вставить.
+//!
+//! SVG и текст используют общую раскладку [`Tree::layout`]: поддерево занимает
+//! по ширине не меньше своей подписи и суммы ширин детей, а родитель стоит
+//! посередине между крайними детьми.
+//!
+//! ```
+//! use taafl::analyzer::Analyzer;
+//! use taafl::tree::Tree;
+//!
+//! let analysis = Analyzer::builder().collect_ast(true).build().analyze("X[I] := A + 1;");
+//! let tree = Tree::of(analysis.ast.as_ref().unwrap());
+//! let text = tree.to_text();
+//! let lines: Vec<&str> = text.lines().collect();
+//! assert_eq!(
+//!     lines,
+//!     [
+//!         "    :=",
+//!         " ┌───┴──┐",
+//!         " X  выражение",
+//!         " │   ┌──┼──┐",
+//!         " I   A  +  1",
+//!     ]
+//! );
+//! ```
+
+use crate::{ast, cst};
+
+/// Узел дерева разбора: подпись и дети слева направо.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tree {
+    pub label: String,
+    pub children: Vec<Tree>,
+}
+
+/// Шаг листьев по горизонтали и уровней по вертикали в SVG, в пикселях.
+const SVG_STEP: (f64, f64) = (90.0, 60.0);
+
+/// Промежуток между соседними подписями в тексте, в символах.
+const TEXT_GAP: usize = 2;
+
+impl Tree {
+    fn leaf(label: String) -> Self {
+        Tree {
+            label,
+            children: Vec::new(),
+        }
+    }
+
+    /// Присваивание - корень, левая часть с индексами и правая часть - его дети.
+    /// Поля цепочки левой части - листья `.F` между индексами.
+    pub fn of(assignment: &ast::Assignment) -> Self {
+        let target = &assignment.target;
+        let chain = target.chain.iter().flat_map(|access| match access {
+            ast::Access::Field { name, .. } => vec![Tree::leaf(format!(".{}", name))],
+            ast::Access::Indices(indices) => indices.iter().map(Tree::expression).collect(),
+        });
+        let target = Tree {
+            label: target.name.clone(),
+            children: target
+                .indices
+                .iter()
+                .flatten()
+                .map(Tree::expression)
+                .chain(chain)
+                .collect(),
+        };
+        Tree {
+            label: ":=".to_string(),
+            children: vec![target, Tree::expression(&assignment.value)],
+        }
+    }
+
+    /// Конкретное дерево разбора: узлы - нетерминалы грамматики, листья - лексемы.
+    /// Нетерминал, выведенный в пустую цепочку, получает лист `ε`.
+    pub fn concrete(node: &cst::Node) -> Self {
+        if node.is_leaf() {
+            return Tree::leaf(node.lexeme.clone());
+        }
+        let children = match node.children.as_slice() {
+            [] => vec![Tree::leaf("ε".to_string())],
+            children => children.iter().map(Tree::concrete).collect(),
+        };
+        Tree {
+            label: node.name.clone(),
+            children,
+        }
+    }
+
+    /// Выражение правой части или индекса; одно слагаемое - без узла выражения.
+    fn expression(expression: &ast::Expression) -> Self {
+        if expression.rest.is_empty() {
+            return Tree::term(&expression.first);
+        }
+        let mut children = vec![Tree::term(&expression.first)];
+        for (operation, term) in &expression.rest {
+            children.push(Tree::leaf(operation.to_string()));
+            children.push(Tree::term(term));
+        }
+        Tree {
+            label: "выражение".to_string(),
+            children,
+        }
+    }
+
+    /// Слагаемое: унарные операции - цепочка узлов над операндом.
+    fn term(term: &ast::Term) -> Self {
+        term.unary
+            .iter()
+            .rev()
+            .fold(Tree::operand(&term.operand), |node, operation| Tree {
+                label: operation.to_string(),
+                children: vec![node],
+            })
+    }
+
+    /// Операнд; у элемента массива индексы - дети имени, как в левой части,
+    /// у вызова аргументы - дети `F()`.
+    fn operand(operand: &ast::Operand) -> Self {
+        match operand {
+            ast::Operand::Element { name, indices, .. } => Tree {
+                label: name.clone(),
+                children: indices.iter().map(Tree::expression).collect(),
+            },
+            ast::Operand::Call {
+                name, arguments, ..
+            } => Tree {
+                label: format!("{}()", name),
+                children: arguments.iter().map(Tree::expression).collect(),
+            },
+            operand => Tree::leaf(operand.to_string()),
+        }
+    }
+
+    /// Узлы в порядке обхода в глубину: (номер родителя, подпись).
+    pub fn flatten(&self) -> Vec<(Option<usize>, &str)> {
+        fn walk<'a>(
+            node: &'a Tree,
+            parent: Option<usize>,
+            out: &mut Vec<(Option<usize>, &'a str)>,
+        ) {
+            let index = out.len();
+            out.push((parent, &node.label));
+            for child in &node.children {
+                walk(child, Some(index), out);
+            }
+        }
+        let mut out = Vec::new();
+        walk(self, None, &mut out);
+        out
+    }
+
+    /// Середины узлов по горизонтали и их глубины в порядке [`flatten`](Self::flatten)
+    /// и ширина всего дерева. `width` - ширина места под подпись узла.
+    pub fn layout(&self, width: &dyn Fn(&str) -> f64) -> (Vec<(f64, usize)>, f64) {
+        let mut out = Vec::new();
+        let span = self.place(0.0, 0, width, &mut out);
+        (out, span)
+    }
+
+    fn span(&self, width: &dyn Fn(&str) -> f64) -> f64 {
+        let children: f64 = self.children.iter().map(|child| child.span(width)).sum();
+        children.max(width(&self.label))
+    }
+
+    /// Раскладывает поддерево, начиная с `left`. Возвращает его ширину.
+    fn place(
+        &self,
+        left: f64,
+        depth: usize,
+        width: &dyn Fn(&str) -> f64,
+        out: &mut Vec<(f64, usize)>,
+    ) -> f64 {
+        let index = out.len();
+        out.push((0.0, depth));
+        let span = self.span(width);
+        let x = if self.children.is_empty() {
+            left + span / 2.0
+        } else {
+            let children: f64 = self.children.iter().map(|child| child.span(width)).sum();
+            let mut left = left + (span - children) / 2.0;
+            let mut xs = Vec::new();
+            for child in &self.children {
+                xs.push(out.len());
+                left += child.place(left, depth + 1, width, out);
+            }
+            (out[xs[0]].0 + out[xs[xs.len() - 1]].0) / 2.0
+        };
+        out[index].0 = x;
+        span
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut text =
+            String::from("digraph tree {\n    node [shape=box, fontname=\"monospace\"];\n");
+        let nodes = self.flatten();
+        for (i, (_, label)) in nodes.iter().enumerate() {
+            let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+            text.push_str(&format!("    n{} [label=\"{}\"];\n", i, label));
+        }
+        for (i, (parent, _)) in nodes.iter().enumerate() {
+            if let Some(parent) = parent {
+                text.push_str(&format!("    n{} -> n{};\n", parent, i));
+            }
+        }
+        text.push_str("}\n");
+        text
+    }
+
+    /// Каждому листу - одинаковый шаг [`SVG_STEP`], поэтому длинные подписи
+    /// соседей могут сближаться.
+    pub fn to_svg(&self) -> String {
+        let (layout, width) = self.layout(&|_| SVG_STEP.0);
+        let points: Vec<(f64, f64)> = layout
+            .iter()
+            .map(|&(x, depth)| (x, (depth as f64 + 0.5) * SVG_STEP.1))
+            .collect();
+        let nodes = self.flatten();
+        let depth = points.iter().map(|&(_, y)| y).fold(0.0, f64::max) + SVG_STEP.1 / 2.0;
+
+        let mut text = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             font-family=\"monospace\" font-size=\"14\">\n",
+            width, depth
+        );
+        for (i, (parent, _)) in nodes.iter().enumerate() {
+            if let Some(parent) = parent {
+                let ((x1, y1), (x2, y2)) = (points[*parent], points[i]);
+                text.push_str(&format!(
+                    "  <line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"gray\"/>\n",
+                    x1,
+                    y1 + 12.0,
+                    x2,
+                    y2 - 12.0
+                ));
+            }
+        }
+        for (i, (_, label)) in nodes.iter().enumerate() {
+            let (x, y) = points[i];
+            let width = label.chars().count() as f64 * 8.5 + 12.0;
+            let label = label
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;");
+            text.push_str(&format!(
+                "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"24\" rx=\"4\" \
+                 fill=\"white\" stroke=\"black\"/>\n",
+                x - width / 2.0,
+                y - 12.0,
+                width
+            ));
+            text.push_str(&format!(
+                "  <text x=\"{}\" y=\"{}\" text-anchor=\"middle\">{}</text>\n",
+                x,
+                y + 5.0,
+                label
+            ));
+        }
+        text.push_str("</svg>\n");
+        text
+    }
+
+    /// Дерево символами псевдографики: строка подписей каждого уровня, под ней -
+    /// линии к детям. Ширина подписи - число символов, поэтому дерево ровное
+    /// только в моноширинном шрифте.
+    pub fn to_text(&self) -> String {
+        let (layout, width) = self.layout(&|label| (label.chars().count() + TEXT_GAP) as f64);
+        let nodes = self.flatten();
+        let depth = layout.iter().map(|&(_, depth)| depth).max().unwrap_or(0);
+        let mut grid = vec![vec![' '; width as usize]; 2 * depth + 1];
+        let column = |x: f64| x.floor() as usize;
+
+        for (i, (parent, label)) in nodes.iter().enumerate() {
+            let (x, depth) = layout[i];
+            let start = column(x - label.chars().count() as f64 / 2.0 + 0.5);
+            for (offset, c) in label.chars().enumerate() {
+                grid[2 * depth][start + offset] = c;
+            }
+            let Some(parent) = *parent else { continue };
+            // Линия от родителя: угол над крайним ребёнком, развилка над средним
+            let row = &mut grid[2 * depth - 1];
+            let children: Vec<usize> = (0..nodes.len())
+                .filter(|&j| nodes[j].0 == Some(parent))
+                .map(|j| column(layout[j].0))
+                .collect();
+            let (first, last, here) = (children[0], children[children.len() - 1], column(x));
+            row[here] = match (here == first, here == last, row[here]) {
+                (true, true, _) => '│',
+                (true, false, _) => '┌',
+                (false, true, _) => '┐',
+                (false, false, '┴') => '┼',
+                (false, false, _) => '┬',
+            };
+            if here == first && first < last {
+                for cell in &mut row[first + 1..last] {
+                    if *cell == ' ' {
+                        *cell = '─';
+                    }
+                }
+                let above = column(layout[parent].0);
+                if row[above] == '─' {
+                    row[above] = '┴';
+                }
+            }
+        }
+
+        let mut text = String::new();
+        for row in grid {
+            let line: String = row.into_iter().collect();
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        text
+    }
+}
@@ -1,16 +1,50 @@
-use crate::analyzer::analyze_line;
 use iced::{
     self,
-    widget::{button, column, container, row, scrollable, text, text_input, Column},
-    Length::Fill,
-    Task, Theme,
+    widget::{button, checkbox, column, pick_list, row, text, text_editor, Column},
+    Element, Subscription, Task, Theme,
 };
+use std::collections::BTreeSet;
+use taafl::analyzer::TokenDump;
+use taafl::config::{AnalyzerConfig, Profile, SymbolOrder, CONFIG_FILE};
+use taafl::model::{AnalysisReport, Origin};
+use taafl::storage::{FileStorage, Storage};
+use taafl::variant::{Variant, Variant20};
+
+mod analysis;
+mod animation;
+#[cfg(feature = "corpus")]
+mod corpus;
+mod editor;
+mod export;
+#[cfg(test)]
+mod harness;
+mod quiz;
+mod settings;
+mod table;
+#[cfg(test)]
+mod tests;
 
 pub static WINDOW_WIDTH: f32 = 750.0;
 pub static WINDOW_HEIGHT: f32 = 550.0;
 pub static COLUMN_SPACING: u16 = 10;
 // pub static OUTPUT_WIDTH: f32 = ...;
-pub static OUTPUT_HEIGHT: f32 = 200.0;
+pub static OUTPUT_HEIGHT: f32 = 180.0;
+/// Ширина колонки при сравнении профилей, в символах
+pub static COMPARE_COLUMN_WIDTH: usize = 32;
+
+/// Отрисовщик представлений: в окне - iced, в тестах - запись дерева виджетов,
+/// которой окно не нужно.
+pub trait Renderer: iced::advanced::text::Renderer<Font = iced::Font> + Sized + 'static {
+    /// Поле ответа на задание. Содержимое поля iced хранит для отрисовщика окна,
+    /// поэтому другой отрисовщик показывает его по-своему.
+    fn answer_editor(answer: &text_editor::Content) -> Element<'_, Message, Theme, Self>;
+}
+
+impl Renderer for iced::Renderer {
+    fn answer_editor(answer: &text_editor::Content) -> Element<'_, Message, Theme, Self> {
+        quiz::answer_editor(answer)
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct TaaflUIState {
@@ -18,116 +52,256 @@ pub struct TaaflUIState {
     syntax_output: String,
     _syntax_success: bool,
     _semantics_output: String,
+    /// Строки `_semantics_output` и происхождение вхождений каждого символа
+    symbol_rows: Vec<(String, Vec<Origin>)>,
+    /// Номера строк таблицы символов, раскрытых до продукций и функций разбора
+    expanded_symbols: BTreeSet<usize>,
+    /// Построчное появление таблицы символов
+    symbols_reveal: animation::Animation,
+    /// Скорость, пауза и режим без движения для всех анимаций
+    animation: animation::Settings,
+    /// Настройки анализа, загружаемые из `taafl.toml`
+    config: AnalyzerConfig,
+    /// Запуск с `--deterministic`: символы по алфавиту, в том числе в экспорте
+    deterministic: bool,
+    /// Где хранятся настройки и экспортированные архивы: текущий каталог,
+    /// в тестах - память
+    storage: Box<dyn Storage>,
+    /// Сколько ошибок скрыто из-за ограничения на их число
+    hidden_errors: usize,
+    show_all_errors: bool,
+    show_settings: bool,
+    /// Результат последней загрузки или сохранения настроек
+    settings_status: String,
+    /// Результат последнего экспорта материалов анализа или открытия отчёта
+    export_status: String,
+    /// Профиль, с которым сравниваются текущие настройки
+    compare_profile: Option<Profile>,
+    /// Последний открытый отчёт; перепроверить его можно, пока ввод не изменён
+    imported: Option<AnalysisReport>,
+    /// Поколение анализа: увеличивается при каждом запуске и при изменении ввода
+    /// или настроек, чтобы результаты устаревших запусков отбрасывались
+    generation: u64,
+    /// Лексемы ввода, повторённого в начале `syntax_output`
+    tokens: TokenDump,
+    /// Длина набираемого идентификатора для счётчика в строке состояния
+    typed_identifier: Option<usize>,
+    /// Задание самопроверки; пока оно есть, таблица символов скрыта
+    quiz: Option<quiz::Session>,
+    /// Таблица LL(1) с трассой разбора ввода, если она показана вместо результатов
+    table: Option<table::Session>,
+    /// Панель базы корпусов, если она показана вместо результатов
+    #[cfg(feature = "corpus")]
+    corpus: Option<corpus::Panel>,
 }
 
+/// Сообщения интерфейса, сгруппированные по разделам. Каждый раздел обрабатывает
+/// свои сообщения в собственном модуле.
 #[derive(Debug, Clone)]
 pub enum Message {
-    TextInputClear,
-    TextInputChanged(String),
-    TextInputSubmit,
-    Analyze,
-    Semantics,
+    Editor(editor::Message),
+    Analysis(analysis::Message),
+    Export(export::Message),
+    Settings(settings::Message),
+    Animation(animation::Message),
+    Quiz(quiz::Message),
+    Table(table::Message),
+    #[cfg(feature = "corpus")]
+    Corpus(corpus::Message),
 }
 
 impl TaaflUIState {
+    pub fn new(deterministic: bool) -> (Self, Task<Message>) {
+        let mut state = Self {
+            storage: Box::<FileStorage>::default(),
+            deterministic,
+            ..Self::default()
+        };
+        match AnalyzerConfig::load_from(state.storage.as_ref(), CONFIG_FILE) {
+            Ok(config) => state.config = config,
+            Err(e) => state.settings_status = e,
+        }
+
+        (state, Task::none())
+    }
+
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
-            Message::TextInputChanged(content) => {
-                self.content = content;
-                self.syntax_output.clear();
-                self._semantics_output.clear();
-                self._syntax_success = false;
+            Message::Editor(message) => editor::update(self, message),
+            Message::Analysis(message) => analysis::update(self, message),
+            Message::Export(message) => export::update(self, message),
+            Message::Settings(message) => settings::update(self, message),
+            Message::Animation(message) => animation::update(self, message),
+            Message::Quiz(message) => quiz::update(self, message),
+            Message::Table(message) => table::update(self, message),
+            #[cfg(feature = "corpus")]
+            Message::Corpus(message) => corpus::update(self, message),
+        }
+    }
 
-                Task::none()
-            }
-            Message::TextInputClear => {
-                self.content = String::new();
-                self.syntax_output = String::new();
-                self._semantics_output = String::new();
-                self._syntax_success = false;
+    pub fn subscription(&self) -> Subscription<Message> {
+        animation::subscription(self)
+    }
 
-                Task::none()
-            }
-            Message::TextInputSubmit => {
-                self.syntax_output = self.content.clone();
+    /// Сбрасывает результаты анализа, ставшие неактуальными.
+    fn reset_output(&mut self) {
+        self.generation += 1;
+        self.syntax_output.clear();
+        self._semantics_output.clear();
+        self.symbol_rows.clear();
+        self.expanded_symbols.clear();
+        self.symbols_reveal = animation::Animation::default();
+        self._syntax_success = false;
+        self.hidden_errors = 0;
+        self.show_all_errors = false;
+        self.tokens = TokenDump::default();
+        table::refresh(self);
+    }
 
-                Task::none()
-            }
-            Message::Analyze => {
-                if !self.content.is_empty() {
-                    match analyze_line(&self.content) {
-                        Ok((ids, consts)) => {
-                            if ids.is_some() && consts.is_some() {
-                                self._syntax_success = true;
-                                self.syntax_output =
-                                    self.content.clone() + "\n" + "Строка принадлежит языку.";
-                            }
-                        }
-                        Err(e) => {
-                            self.syntax_output = e;
-                        }
-                    }
-                } else {
-                    self.syntax_output = "Введите хоть что-нибудь (o_O)".to_string();
-                }
+    /// Открытый отчёт, если ввод с тех пор не изменился.
+    fn imported_report(&self) -> Option<&AnalysisReport> {
+        self.imported
+            .as_ref()
+            .filter(|report| report.input == self.content)
+    }
 
-                Task::none()
-            }
-            Message::Semantics => {
-                if let Ok((Some(ids), Some(consts))) = analyze_line(&self.content) {
-                    self._semantics_output = ids + "\n" + consts.as_ref();
-                }
-                Task::none()
-            }
+    fn analyzer_config(&self) -> AnalyzerConfig {
+        let mut config = self.config.clone();
+        if self.show_all_errors {
+            config.max_errors = 0;
         }
+        if self.deterministic {
+            config.order = SymbolOrder::Sorted;
+        }
+        config
     }
 
-    pub fn view(&self) -> Column<Message> {
-        let (text_input_widget, button_clear) = (
-            scrollable(
-                text_input("Напишите здесь что-нибудь... 🤓", self.content.as_ref())
-                    .on_input(Message::TextInputChanged)
-                    .on_submit(Message::TextInputSubmit),
-            ),
-            button("Очистить").on_press(Message::TextInputClear),
-        );
-
+    pub fn view<R: Renderer>(&self) -> Column<'_, Message, Theme, R> {
         let (button_input, button_analyze, button_semantics) = (
-            button("Ввод").on_press(Message::TextInputSubmit),
-            button("Анализ").on_press(Message::Analyze),
-            button("Семантика").on_press_maybe(if self._syntax_success {
-                Some(Message::Semantics)
+            button("Ввод").on_press(Message::Editor(editor::Message::Submit)),
+            button("Анализ").on_press(Message::Analysis(analysis::Message::Analyze)),
+            button("Семантика").on_press_maybe(if self._syntax_success && self.quiz.is_none() {
+                Some(Message::Analysis(analysis::Message::Semantics))
             } else {
                 None
             }),
         );
+        let checkbox_recovery = checkbox("Восстановление", self.config.recovery)
+            .on_toggle(|on| Message::Settings(settings::Message::RecoveryToggled(on)));
+        let checkbox_sorted = checkbox("По алфавиту", self.config.order == SymbolOrder::Sorted)
+            .on_toggle(|on| Message::Settings(settings::Message::SortedToggled(on)));
+        let checkbox_case = checkbox("Регистр имён", self.config.preserve_case)
+            .on_toggle(|on| Message::Settings(settings::Message::PreserveCaseToggled(on)));
+        let button_quiz = button("Задание").on_press(Message::Quiz(quiz::Message::Start));
+        let button_table = button(if self.table.is_some() {
+            "Анализ"
+        } else {
+            "Таблица"
+        })
+        .on_press(Message::Table(table::Message::Toggled));
+        let button_settings = button(if self.show_settings {
+            "Результаты"
+        } else {
+            "Настройки"
+        })
+        .on_press(Message::Settings(settings::Message::Toggled));
+
+        let outputs = match &self.table {
+            _ if self.show_settings => settings::view(self),
+            #[cfg(feature = "corpus")]
+            _ if self.corpus.is_some() => corpus::view(self),
+            Some(session) => table::view(self, session),
+            None => analysis::view(self),
+        };
 
-        let framed_syntax_output = container(scrollable(text(self.syntax_output.clone())))
-            .style(container::rounded_box)
-            .width(Fill)
-            .height(OUTPUT_HEIGHT);
-        let framed_semantics_output = container(scrollable(text(self._semantics_output.clone())))
-            .style(container::rounded_box)
-            .width(Fill)
-            .height(OUTPUT_HEIGHT);
+        let buttons = row![
+            button_input,
+            button_analyze,
+            button_semantics,
+            checkbox_recovery,
+            checkbox_sorted,
+            checkbox_case,
+            button_quiz,
+            button_table,
+        ];
+        #[cfg(feature = "corpus")]
+        let buttons = buttons.push(
+            button(if self.corpus.is_some() {
+                "Анализ"
+            } else {
+                "База"
+            })
+            .on_press(Message::Corpus(corpus::Message::Toggled)),
+        );
 
         Self::base_column("Оператор присваивания языка Modula-2")
-            .push(row![button_input, button_analyze, button_semantics].spacing(COLUMN_SPACING / 3))
-            .push(row![].push(text_input_widget).push(button_clear))
             .push(
-                column![]
-                    .push(framed_syntax_output)
-                    .push(framed_semantics_output)
-                    .spacing(COLUMN_SPACING)
-                    .align_x(iced::Alignment::Center),
+                buttons
+                    .push(button_settings)
+                    .spacing(COLUMN_SPACING / 3)
+                    .align_y(iced::Alignment::Center),
             )
+            .push(editor::view(self))
+            .push(outputs)
+            .push(self.status_bar())
+    }
+
+    fn status_bar<R: Renderer>(&self) -> iced::widget::Row<'_, Message, Theme, R> {
+        let bar = row![
+            text("Профиль:"),
+            pick_list(Profile::ALL, self.config.profile, |profile| {
+                Message::Settings(settings::Message::ProfileSelected(profile))
+            })
+            .placeholder("Пользовательский")
+            .text_size(12),
+            text("Сравнить с:"),
+            pick_list(Profile::ALL, self.compare_profile, |profile| {
+                Message::Analysis(analysis::Message::CompareProfileSelected(profile))
+            })
+            .placeholder("Профиль")
+            .text_size(12),
+            button(text("Сравнить").size(12)).on_press_maybe(
+                (self.compare_profile.is_some() && !self.content.is_empty())
+                    .then_some(Message::Analysis(analysis::Message::Compare))
+            ),
+            button(text("Экспортировать всё").size(12)).on_press_maybe(
+                (!self.content.is_empty()).then_some(Message::Export(export::Message::ExportAll))
+            ),
+            button(text("Открыть отчёт").size(12))
+                .on_press(Message::Export(export::Message::ImportReport)),
+            button(text("Перепроверить").size(12)).on_press_maybe(
+                self.imported_report()
+                    .map(|_| Message::Export(export::Message::RecheckReport))
+            ),
+            text(self.export_status.as_str()).size(12),
+        ]
+        .spacing(COLUMN_SPACING)
+        .align_y(iced::Alignment::Center);
+
+        // Счётчик длины набираемого идентификатора, красный при превышении
+        match self.typed_identifier {
+            Some(len) => {
+                let max = Variant20.max_identifier_length();
+                let counter = text(format!("Имя: {}/{}", len, max))
+                    .size(12)
+                    .style(move |theme| {
+                        if len > max {
+                            text::danger(theme)
+                        } else {
+                            text::default(theme)
+                        }
+                    });
+                bar.push(counter)
+            }
+            None => bar,
+        }
     }
 
     pub fn theme(&self) -> Theme {
         Theme::Ferra
     }
 
-    fn base_column(title: &str) -> Column<Message> {
+    fn base_column<R: Renderer>(title: &str) -> Column<'_, Message, Theme, R> {
         column![text(title).size(20)]
             .spacing(COLUMN_SPACING)
             .padding(10)
@@ -0,0 +1,301 @@
+//! Синтаксический и семантический анализ, сравнение с профилем.
+
+use super::animation::{self, Animation};
+use super::{TaaflUIState, COLUMN_SPACING, COMPARE_COLUMN_WIDTH, OUTPUT_HEIGHT};
+use iced::{
+    widget::{button, column, container, rich_text, scrollable, span, text, Column},
+    Color,
+    Length::Fill,
+    Task, Theme,
+};
+use taafl::analyzer::{
+    analyze_line_with, catch_internal, token_dump, ErrorReport, InternalError, Success, TokenDump,
+};
+use taafl::compare::{compare, Comparison};
+use taafl::config::{AnalyzerConfig, Profile};
+use taafl::model::Origin;
+
+/// Фон чётных и нечётных лексем в повторённом вводе.
+const TOKEN_SHADES: [Color; 2] = [
+    Color::from_rgba(1.0, 1.0, 1.0, 0.10),
+    Color::from_rgba(1.0, 1.0, 1.0, 0.22),
+];
+
+/// Результат анализа, выполненного в фоне.
+pub type Outcome = Result<Success, ErrorReport>;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Analyze,
+    /// Анализ завершён; число - поколение, для которого он запускался
+    Analyzed(u64, Outcome),
+    Semantics,
+    SemanticsReady(u64, Outcome),
+    /// Раскрыть или свернуть происхождение строки таблицы символов
+    ToggleOrigin(usize),
+    ShowAllErrors,
+    CompareProfileSelected(Profile),
+    Compare,
+}
+
+pub(super) fn update(state: &mut TaaflUIState, message: Message) -> Task<super::Message> {
+    match message {
+        Message::Analyze => {
+            state.generation += 1;
+            state.hidden_errors = 0;
+            if !state.content.is_empty() {
+                return perform(state, Message::Analyzed);
+            } else {
+                state.syntax_output = "Введите хоть что-нибудь (o_O)".to_string();
+            }
+        }
+        Message::Analyzed(generation, outcome) => {
+            if generation == state.generation {
+                state.tokens = tokens(&state.content, &state.analyzer_config());
+                show_syntax(state, outcome);
+            }
+        }
+        Message::Semantics => {
+            return perform(state, Message::SemanticsReady);
+        }
+        Message::SemanticsReady(generation, outcome) => {
+            // Во время задания таблица символов не строится, чтобы не подсказать ответ
+            let current = generation == state.generation && state.quiz.is_none();
+            if let (true, Ok(success)) = (current, outcome) {
+                show_symbols(state, success);
+            }
+        }
+        Message::ToggleOrigin(row) => {
+            if !state.expanded_symbols.remove(&row) {
+                state.expanded_symbols.insert(row);
+            }
+        }
+        Message::ShowAllErrors => {
+            state.show_all_errors = true;
+            return update(state, Message::Analyze);
+        }
+        Message::CompareProfileSelected(profile) => {
+            state.compare_profile = Some(profile);
+        }
+        Message::Compare => {
+            if let Some(profile) = state.compare_profile {
+                let comparison = catch_internal(&state.content, || {
+                    compare(
+                        &state.content,
+                        (current_title(state), &state.analyzer_config()),
+                        (profile.title(), &AnalyzerConfig::from_profile(profile)),
+                    )
+                });
+                show_comparison(state, comparison);
+            }
+        }
+    }
+
+    Task::none()
+}
+
+/// Название текущих настроек в таблице сравнения.
+pub(super) fn current_title(state: &TaaflUIState) -> &'static str {
+    state.config.profile.map_or("Текущие настройки", |profile| {
+        profile.title()
+    })
+}
+
+/// Таблица сравнения вместо вывода анализа; сбой анализатора - вместо таблицы.
+pub(super) fn show_comparison(
+    state: &mut TaaflUIState,
+    comparison: Result<Comparison, InternalError>,
+) {
+    state.reset_output();
+    let comparison = match comparison {
+        Ok(comparison) => comparison,
+        Err(e) => {
+            state.syntax_output = format!("{}\n{}", state.content, e);
+            return;
+        }
+    };
+    state.syntax_output = format!(
+        "{}\n\n{}",
+        state.content,
+        comparison.render(COMPARE_COLUMN_WIDTH)
+    );
+    if comparison.is_same() {
+        state.syntax_output += "\n\nРезультаты совпадают.";
+    }
+}
+
+/// Итог синтаксического анализа: строка принята или отчёт об ошибках.
+pub(super) fn show_syntax(state: &mut TaaflUIState, outcome: Outcome) {
+    match outcome {
+        Ok(success) => {
+            if success.ids.is_some() && success.consts.is_some() {
+                state._syntax_success = true;
+                state.syntax_output = state.content.clone() + "\n" + "Строка принадлежит языку.";
+                if let Some(warnings) = success.warnings {
+                    state.syntax_output += &("\n\n".to_string() + &warnings);
+                }
+            }
+        }
+        Err(report) => {
+            state.syntax_output = report.text;
+            state.hidden_errors = report.hidden;
+        }
+    }
+}
+
+/// Таблица символов успешного анализа.
+pub(super) fn show_symbols(state: &mut TaaflUIState, success: Success) {
+    if let (Some(ids), Some(consts)) = (success.ids, success.consts) {
+        let identifiers = ids.lines().zip(success.identifiers);
+        // Вещественные константы перечислены после целых, литералы - последними
+        let origins = success.constants.into_iter().map(|c| c.origins);
+        let reals = success.reals.into_iter().map(|r| r.origins);
+        let literals = success.literals.into_iter().map(|l| l.origins);
+        let constants = consts.lines().zip(origins.chain(reals).chain(literals));
+        state.symbol_rows = identifiers
+            .map(|(line, id)| (line.to_string(), id.origins))
+            .chain([(String::new(), Vec::new())])
+            .chain(constants.map(|(line, origins)| (line.to_string(), origins)))
+            .collect();
+        state.expanded_symbols.clear();
+        state.symbols_reveal = Animation::new(state.symbol_rows.len());
+        state._semantics_output = ids + "\n" + consts.as_ref();
+    }
+}
+
+/// Запускает анализ текущего ввода в фоне. Результат приходит сообщением `done`
+/// с поколением на момент запуска; если ввод или настройки к тому времени
+/// изменились, он отбрасывается. Сбой самого анализатора показывается как
+/// отчёт об ошибке, а не роняет окно.
+fn perform(state: &TaaflUIState, done: fn(u64, Outcome) -> Message) -> Task<super::Message> {
+    let input = state.content.clone();
+    let config = state.analyzer_config();
+    let generation = state.generation;
+    Task::perform(
+        async move {
+            catch_internal(&input, || analyze_line_with(&input, &config)).unwrap_or_else(|e| {
+                Err(ErrorReport {
+                    text: format!("{}\n{}", input, e),
+                    hidden: 0,
+                })
+            })
+        },
+        move |outcome| super::Message::Analysis(done(generation, outcome)),
+    )
+}
+
+/// Лексемы строки. При лексической ошибке лексем нет.
+fn tokens(input: &str, config: &AnalyzerConfig) -> TokenDump {
+    token_dump(input, config).unwrap_or_default()
+}
+
+/// Вывод синтаксического анализа. Повторённый в его начале ввод разбит на лексемы
+/// чередующимся фоном, чтобы было видно, как лексический анализ разделил текст.
+fn syntax_text<R: super::Renderer>(state: &TaaflUIState) -> Column<'_, super::Message, Theme, R> {
+    let output = state.syntax_output.as_str();
+    let Some(rest) = output.strip_prefix(state.content.as_str()) else {
+        return column![text(output)];
+    };
+    if state.tokens.entries.is_empty() {
+        return column![text(output)];
+    }
+
+    let input = state.content.as_str();
+    let mut spans = Vec::new();
+    let mut end = 0;
+    for (i, token) in state.tokens.entries.iter().enumerate() {
+        if token.span.start > end {
+            // Отрезок не на границе символов: без подсветки, как без лексем
+            let Some(gap) = input.get(end..token.span.start) else {
+                return column![text(output)];
+            };
+            spans.push(span(gap));
+        }
+        spans.push(span(token.text.as_str()).background(TOKEN_SHADES[i % 2]));
+        end = token.span.end;
+    }
+    if end < input.len() {
+        let Some(tail) = input.get(end..) else {
+            return column![text(output)];
+        };
+        spans.push(span(tail));
+    }
+
+    let echo = column![rich_text(spans)];
+    match rest.strip_prefix('\n') {
+        Some(rest) => echo.push(text(rest)),
+        None => echo,
+    }
+}
+
+/// Происхождение вхождения: столбец, продукция и функция разбора.
+pub(super) fn origin_line(origin: &Origin) -> String {
+    format!(
+        "    столбец {}: {} ({})",
+        origin.position + 1,
+        origin.production,
+        origin.parser
+    )
+}
+
+/// Таблица символов: строка символа раскрывается нажатием и показывает, какая
+/// продукция и какая функция разбора записали каждое его вхождение. Строки
+/// появляются по одной.
+fn symbols_table<R: super::Renderer>(state: &TaaflUIState) -> Column<'_, super::Message, Theme, R> {
+    if state.symbol_rows.is_empty() {
+        return column![text(state._semantics_output.as_str())];
+    }
+
+    let shown = state.symbols_reveal.shown(&state.animation);
+    let mut table = column![animation::controls(state, &state.symbols_reveal)];
+    for (i, (line, origins)) in state.symbol_rows.iter().enumerate().take(shown) {
+        if origins.is_empty() {
+            table = table.push(text(line.as_str()));
+            continue;
+        }
+        let expanded = state.expanded_symbols.contains(&i);
+        let marker = if expanded { "▾" } else { "▸" };
+        table = table.push(
+            button(text(format!("{} {}", marker, line)))
+                .style(button::text)
+                .padding(0)
+                .on_press(super::Message::Analysis(Message::ToggleOrigin(i))),
+        );
+        if expanded {
+            for origin in origins {
+                table = table.push(text(origin_line(origin)));
+            }
+        }
+    }
+    table
+}
+
+pub(super) fn view<R: super::Renderer>(
+    state: &TaaflUIState,
+) -> Column<'_, super::Message, Theme, R> {
+    let mut syntax_output = syntax_text(state);
+    if state.hidden_errors > 0 {
+        syntax_output = syntax_output.push(
+            button("Показать все").on_press(super::Message::Analysis(Message::ShowAllErrors)),
+        );
+    }
+
+    let framed_syntax_output = container(scrollable(syntax_output))
+        .style(container::rounded_box)
+        .width(Fill)
+        .height(OUTPUT_HEIGHT);
+    let semantics_output = match &state.quiz {
+        Some(session) => super::quiz::view(session),
+        None => symbols_table(state),
+    };
+    let framed_semantics_output = container(scrollable(semantics_output))
+        .style(container::rounded_box)
+        .width(Fill)
+        .height(OUTPUT_HEIGHT);
+
+    column![]
+        .push(framed_syntax_output)
+        .push(framed_semantics_output)
+        .spacing(COLUMN_SPACING)
+        .align_x(iced::Alignment::Center)
+}
@@ -0,0 +1,177 @@
+//! Анимации представлений: пошаговое появление содержимого во времени.
+//!
+//! Шаги отсчитываются по прошедшему времени, а не по кадрам, поэтому скорость
+//! не зависит от частоты кадров. Скорость, пауза и режим без движения общие
+//! для всех анимаций; в режиме без движения содержимое показывается сразу.
+
+use super::{TaaflUIState, COLUMN_SPACING};
+use iced::{
+    widget::{button, row, text, Row},
+    window, Subscription, Task, Theme,
+};
+use std::fmt;
+use std::time::Instant;
+
+/// Скорость анимаций.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Speed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+}
+
+impl Speed {
+    pub const ALL: [Speed; 3] = [Speed::Slow, Speed::Normal, Speed::Fast];
+
+    fn steps_per_second(&self) -> f32 {
+        match self {
+            Speed::Slow => 2.0,
+            Speed::Normal => 6.0,
+            Speed::Fast => 20.0,
+        }
+    }
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Speed::Slow => "медленно",
+            Speed::Normal => "обычно",
+            Speed::Fast => "быстро",
+        })
+    }
+}
+
+/// Настройки, общие для всех анимаций.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Settings {
+    pub speed: Speed,
+    pub paused: bool,
+    /// Без движения: анимации сразу показывают последний шаг
+    pub reduced_motion: bool,
+}
+
+/// Ход одной анимации из `steps` шагов.
+#[derive(Debug, Clone, Default)]
+pub struct Animation {
+    steps: usize,
+    /// Пройденные шаги, с дробной частью между кадрами
+    position: f32,
+    /// Время предыдущего кадра; `None` до первого кадра и после паузы
+    last: Option<Instant>,
+}
+
+impl Animation {
+    /// Анимация с начала.
+    pub fn new(steps: usize) -> Self {
+        Self {
+            steps,
+            position: 0.0,
+            last: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.position < self.steps as f32
+    }
+
+    /// Сколько шагов показать сейчас.
+    pub fn shown(&self, settings: &Settings) -> usize {
+        if settings.reduced_motion {
+            self.steps
+        } else {
+            (self.position as usize).min(self.steps)
+        }
+    }
+
+    fn advance(&mut self, now: Instant, speed: Speed) {
+        if let Some(last) = self.last {
+            let elapsed = now.saturating_duration_since(last).as_secs_f32();
+            self.position =
+                (self.position + elapsed * speed.steps_per_second()).min(self.steps as f32);
+        }
+        self.last = Some(now);
+    }
+
+    fn finish(&mut self) {
+        self.position = self.steps as f32;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Кадр: время его отрисовки
+    Tick(Instant),
+    PauseToggled,
+    SpeedSelected(Speed),
+    ReducedMotionToggled(bool),
+}
+
+/// Все анимации представлений.
+fn animations(state: &mut TaaflUIState) -> [&mut Animation; 1] {
+    [&mut state.symbols_reveal]
+}
+
+pub(super) fn update(state: &mut TaaflUIState, message: Message) -> Task<super::Message> {
+    match message {
+        // Кадр, запрошенный до паузы, может прийти уже после неё
+        Message::Tick(_) if state.animation.paused => {}
+        Message::Tick(now) => {
+            let speed = state.animation.speed;
+            for animation in animations(state) {
+                animation.advance(now, speed);
+            }
+        }
+        Message::PauseToggled => {
+            state.animation.paused = !state.animation.paused;
+            // Время паузы не засчитывается: отсчёт начнётся со следующего кадра
+            for animation in animations(state) {
+                animation.last = None;
+            }
+        }
+        Message::SpeedSelected(speed) => {
+            state.animation.speed = speed;
+        }
+        Message::ReducedMotionToggled(reduced) => {
+            state.animation.reduced_motion = reduced;
+            if reduced {
+                for animation in animations(state) {
+                    animation.finish();
+                }
+            }
+        }
+    }
+
+    Task::none()
+}
+
+/// Кадры нужны, только пока хотя бы одна анимация идёт.
+pub(super) fn subscription(state: &TaaflUIState) -> Subscription<super::Message> {
+    let settings = &state.animation;
+    if settings.paused || settings.reduced_motion || !state.symbols_reveal.is_running() {
+        return Subscription::none();
+    }
+    window::frames().map(|now| super::Message::Animation(Message::Tick(now)))
+}
+
+/// Кнопка паузы для идущей анимации или остановленной на паузе.
+pub(super) fn controls<'a, R: super::Renderer>(
+    state: &TaaflUIState,
+    animation: &Animation,
+) -> Row<'a, super::Message, Theme, R> {
+    if state.animation.reduced_motion || !animation.is_running() {
+        return row![];
+    }
+    let label = if state.animation.paused {
+        "Продолжить"
+    } else {
+        "Пауза"
+    };
+    row![
+        button(text(label).size(12)).on_press(super::Message::Animation(Message::PauseToggled)),
+        text(format!("Скорость: {}", state.animation.speed)).size(12),
+    ]
+    .spacing(COLUMN_SPACING)
+    .align_y(iced::Alignment::Center)
+}
@@ -0,0 +1,132 @@
+//! База корпусов: добавление введённых операторов с меткой и выборка записей по
+//! коду диагностики, метке и датам.
+
+use super::{TaaflUIState, COLUMN_SPACING, OUTPUT_HEIGHT};
+use iced::{
+    widget::{button, column, container, row, scrollable, text, text_input, Column},
+    Length::Fill,
+    Task, Theme,
+};
+use taafl::analyzer::catch_internal;
+use taafl::corpus::{records, today, Query, Record};
+
+/// Условия выборки в полях панели и её последний результат.
+#[derive(Debug, Default)]
+pub(super) struct Panel {
+    pub(super) code: String,
+    pub(super) tag: String,
+    pub(super) from: String,
+    pub(super) to: String,
+    pub(super) found: Vec<Record>,
+    /// Итог последнего добавления или выборки
+    pub(super) status: String,
+}
+
+impl Panel {
+    /// Условия из полей; пустое поле подходит для любой записи.
+    fn query(&self) -> Query {
+        let field = |value: &str| Some(value.trim().to_string()).filter(|v| !v.is_empty());
+        Query {
+            code: field(&self.code),
+            tag: field(&self.tag),
+            from: field(&self.from),
+            to: field(&self.to),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Показать панель вместо результатов анализа или вернуться к ним
+    Toggled,
+    CodeEdited(String),
+    TagEdited(String),
+    FromEdited(String),
+    ToEdited(String),
+    /// Добавить операторы ввода с меткой из поля и сегодняшней датой
+    Add,
+    Search,
+}
+
+pub(super) fn update(state: &mut TaaflUIState, message: Message) -> Task<super::Message> {
+    if let Message::Toggled = message {
+        state.corpus = match state.corpus.take() {
+            Some(_) => None,
+            None => Some(Panel::default()),
+        };
+        return Task::none();
+    }
+    let config = state.analyzer_config();
+    let Some(panel) = &mut state.corpus else {
+        return Task::none();
+    };
+    let storage = state.storage.as_mut();
+    match message {
+        Message::Toggled => {}
+        Message::CodeEdited(code) => panel.code = code,
+        Message::TagEdited(tag) => panel.tag = tag,
+        Message::FromEdited(from) => panel.from = from,
+        Message::ToEdited(to) => panel.to = to,
+        Message::Add => {
+            let (input, tag) = (&state.content, panel.tag.trim());
+            let added = catch_internal(input, || records(input, tag, &today(), &config));
+            let added = added.map_err(|e| e.to_string()).and_then(|records| {
+                let count = records.len();
+                taafl::corpus::open(storage)?.append(records)?;
+                Ok(count)
+            });
+            panel.status = match added {
+                Ok(count) => format!("Добавлено операторов: {}", count),
+                Err(e) => e,
+            };
+        }
+        Message::Search => {
+            let found = taafl::corpus::open(storage)
+                .and_then(|backend| Ok((backend.query(&panel.query())?, backend.count()?)));
+            panel.status = match found {
+                Ok((found, total)) => {
+                    let status = format!("Найдено: {} из {}", found.len(), total);
+                    panel.found = found;
+                    status
+                }
+                Err(e) => e,
+            };
+        }
+    }
+
+    Task::none()
+}
+
+pub(super) fn view<R: super::Renderer>(
+    state: &TaaflUIState,
+) -> Column<'_, super::Message, Theme, R> {
+    let Some(panel) = &state.corpus else {
+        return column![];
+    };
+    let field = |placeholder, value, edited: fn(String) -> Message| {
+        text_input(placeholder, value)
+            .on_input(move |value| super::Message::Corpus(edited(value)))
+            .size(12)
+    };
+    let conditions = row![
+        field("Код: syntax", &panel.code, Message::CodeEdited),
+        field("Метка", &panel.tag, Message::TagEdited),
+        field("С ГГГГ-ММ-ДД", &panel.from, Message::FromEdited),
+        field("По ГГГГ-ММ-ДД", &panel.to, Message::ToEdited),
+        button(text("Найти").size(12)).on_press(super::Message::Corpus(Message::Search)),
+        button(text("Добавить ввод").size(12)).on_press_maybe(
+            (!state.content.is_empty()).then_some(super::Message::Corpus(Message::Add))
+        ),
+    ]
+    .spacing(COLUMN_SPACING / 2);
+
+    let found = panel.found.iter().fold(column![], |found, record| {
+        found.push(text(record.to_string()).size(12))
+    });
+    let framed_found = container(scrollable(found))
+        .style(container::rounded_box)
+        .width(Fill)
+        .height(OUTPUT_HEIGHT);
+
+    column![conditions, text(panel.status.as_str()), framed_found].spacing(COLUMN_SPACING)
+}
@@ -0,0 +1,72 @@
+//! Поле ввода оператора.
+
+use super::TaaflUIState;
+use iced::{
+    widget::{button, row, scrollable, text_input, Row},
+    Task, Theme,
+};
+use taafl::config::AnalyzerConfig;
+use taafl::sanitize::{normalize_typography, sanitize};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Clear,
+    Changed(String),
+    Submit,
+}
+
+pub(super) fn update(state: &mut TaaflUIState, message: Message) -> Task<super::Message> {
+    match message {
+        Message::Changed(content) => {
+            let (mut content, mut notes) = sanitize(&content);
+            if state.config.typography {
+                let (normalized, typography) = normalize_typography(&content);
+                content = normalized;
+                notes.extend(typography);
+            }
+            state.typed_identifier = typed_identifier(&content, &state.config);
+            state.content = content;
+            state.reset_output();
+            // Сообщаем, что вставленный текст был исправлен
+            let notes: Vec<String> = notes.iter().map(ToString::to_string).collect();
+            state.syntax_output = notes.join("\n");
+        }
+        Message::Clear => {
+            state.content = String::new();
+            state.typed_identifier = None;
+            state.reset_output();
+        }
+        Message::Submit => {
+            state.syntax_output = state.content.clone();
+        }
+    }
+
+    Task::none()
+}
+
+/// Длина в символах идентификатора, который набирается в конце строки, без
+/// полного анализа. Допустимые символы - по настройкам: подчёркивание и буквы
+/// других алфавитов. Цепочка, начинающаяся не с буквы, идентификатором не считается.
+pub(super) fn typed_identifier(content: &str, config: &AnalyzerConfig) -> Option<usize> {
+    let letter =
+        |c: char| c.is_ascii_alphabetic() || (config.unicode_identifiers && c.is_alphabetic());
+    let tail: Vec<char> = content
+        .chars()
+        .rev()
+        .take_while(|&c| letter(c) || c.is_ascii_digit() || (config.underscores && c == '_'))
+        .collect();
+    tail.last()
+        .is_some_and(|&c| letter(c))
+        .then_some(tail.len())
+}
+
+pub(super) fn view<R: super::Renderer>(state: &TaaflUIState) -> Row<'_, super::Message, Theme, R> {
+    let text_input_widget = scrollable(
+        text_input("Напишите здесь что-нибудь... 🤓", state.content.as_ref())
+            .on_input(|content| super::Message::Editor(Message::Changed(content)))
+            .on_submit(super::Message::Editor(Message::Submit)),
+    );
+    let button_clear = button("Очистить").on_press(super::Message::Editor(Message::Clear));
+
+    row![].push(text_input_widget).push(button_clear)
+}
@@ -0,0 +1,74 @@
+//! Экспорт материалов анализа в архив и отчёт, записанный ранее: открытие
+//! без повторного анализа и перепроверка текущими настройками.
+
+use super::analysis::{current_title, show_comparison, show_symbols, show_syntax};
+use super::TaaflUIState;
+use iced::Task;
+use taafl::analyzer::{catch_internal, render_report};
+use taafl::bundle::{self, BUNDLE_FILE};
+use taafl::compare::recheck;
+use taafl::model::{AnalysisReport, REPORT_FILE};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Записать все материалы анализа ввода в архив [`BUNDLE_FILE`]
+    ExportAll,
+    /// Показать отчёт [`REPORT_FILE`], записанный ранее, без повторного анализа
+    ImportReport,
+    /// Проанализировать строку открытого отчёта заново и показать отличия от него
+    RecheckReport,
+}
+
+pub(super) fn update(state: &mut TaaflUIState, message: Message) -> Task<super::Message> {
+    match message {
+        Message::ExportAll => {
+            let config = state.analyzer_config();
+            let archive =
+                catch_internal(&state.content, || bundle::export(&state.content, &config));
+            state.export_status = match archive {
+                Ok(archive) => match state.storage.write(BUNDLE_FILE, &archive) {
+                    Ok(()) => format!("Записано в {}", BUNDLE_FILE),
+                    Err(e) => e,
+                },
+                Err(e) => e.to_string(),
+            };
+        }
+        Message::ImportReport => match read_report(state) {
+            Ok(report) => {
+                state.content = report.input.clone();
+                state.typed_identifier = None;
+                state.reset_output();
+                let outcome = render_report(&report, &state.analyzer_config());
+                show_syntax(state, outcome.clone());
+                if let Ok(success) = outcome {
+                    show_symbols(state, success);
+                }
+                state.imported = Some(report);
+                state.export_status = format!("Открыт {}", REPORT_FILE);
+            }
+            Err(e) => state.export_status = e,
+        },
+        Message::RecheckReport => {
+            if let Some(report) = state.imported_report() {
+                let config = state.analyzer_config();
+                let comparison = catch_internal(&report.input, || {
+                    recheck(report, (current_title(state), &config))
+                });
+                show_comparison(state, comparison);
+            }
+        }
+    }
+
+    Task::none()
+}
+
+/// Отчёт [`REPORT_FILE`] из хранилища.
+fn read_report(state: &TaaflUIState) -> Result<AnalysisReport, String> {
+    let data = state
+        .storage
+        .read(REPORT_FILE)?
+        .ok_or_else(|| format!("Нет файла {}", REPORT_FILE))?;
+    let json =
+        std::str::from_utf8(&data).map_err(|_| format!("{} - не текст UTF-8", REPORT_FILE))?;
+    AnalysisReport::from_json(json)
+}
@@ -0,0 +1,204 @@
+//! Отрисовщик для тестов: раскладывает представление без окна и снимает с него
+//! дерево виджетов - виды виджетов и тексты надписей, по строке на виджет.
+//!
+//! Размеры текста приблизительные: знак шириной в 0.6 кегля, строка высотой
+//! в 1.3 кегля. Их хватает, чтобы раскладка прошла; снимок от них не зависит.
+
+use super::{settings, Message, TaaflUIState, WINDOW_HEIGHT, WINDOW_WIDTH};
+use iced::advanced::layout::Limits;
+use iced::advanced::renderer::{self, Quad};
+use iced::advanced::text::{self, Difference, Hit, Paragraph, Span, Text};
+use iced::advanced::widget::{
+    text as text_widget,
+    tree::{Tag, Tree},
+    Widget,
+};
+use iced::widget::{button, column, pick_list, rich_text, scrollable, text_editor, text_input};
+use iced::{
+    alignment, Background, Color, Element, Font, Pixels, Point, Rectangle, Size, Theme,
+    Transformation,
+};
+use taafl::config::Profile;
+
+/// Кегль по умолчанию, как у iced.
+const DEFAULT_SIZE: f32 = 16.0;
+
+/// Отрисовщик, который ничего не рисует: текст только измеряется и запоминается.
+pub(super) struct Recorder;
+
+/// Абзац текста: содержимое и то, что нужно для его размеров.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Label {
+    content: String,
+    size: f32,
+    bounds: Size,
+}
+
+impl Label {
+    fn new(content: String, size: Pixels, bounds: Size) -> Self {
+        Label {
+            content,
+            size: size.0,
+            bounds,
+        }
+    }
+}
+
+impl Paragraph for Label {
+    type Font = Font;
+
+    fn with_text(text: Text<&str>) -> Self {
+        Label::new(text.content.to_string(), text.size, text.bounds)
+    }
+
+    fn with_spans<Link>(text: Text<&[Span<'_, Link, Self::Font>], Self::Font>) -> Self {
+        let content = text.content.iter().map(|span| span.text.as_ref()).collect();
+        Label::new(content, text.size, text.bounds)
+    }
+
+    fn resize(&mut self, new_bounds: Size) {
+        self.bounds = new_bounds;
+    }
+
+    fn compare(&self, text: Text<()>) -> Difference {
+        if text.size.0 != self.size {
+            Difference::Shape
+        } else if text.bounds != self.bounds {
+            Difference::Bounds
+        } else {
+            Difference::None
+        }
+    }
+
+    fn horizontal_alignment(&self) -> alignment::Horizontal {
+        alignment::Horizontal::Left
+    }
+
+    fn vertical_alignment(&self) -> alignment::Vertical {
+        alignment::Vertical::Top
+    }
+
+    fn min_bounds(&self) -> Size {
+        let lines = self.content.lines();
+        let width = lines.clone().map(|line| line.chars().count()).max();
+        Size::new(
+            width.unwrap_or(0) as f32 * self.size * 0.6,
+            lines.count().max(1) as f32 * self.size * 1.3,
+        )
+    }
+
+    fn hit_test(&self, _point: Point) -> Option<Hit> {
+        None
+    }
+
+    fn hit_span(&self, _point: Point) -> Option<usize> {
+        None
+    }
+
+    fn span_bounds(&self, _index: usize) -> Vec<Rectangle> {
+        Vec::new()
+    }
+
+    fn grapheme_position(&self, _line: usize, _index: usize) -> Option<Point> {
+        None
+    }
+}
+
+impl renderer::Renderer for Recorder {
+    fn start_layer(&mut self, _bounds: Rectangle) {}
+
+    fn end_layer(&mut self) {}
+
+    fn start_transformation(&mut self, _transformation: Transformation) {}
+
+    fn end_transformation(&mut self) {}
+
+    fn fill_quad(&mut self, _quad: Quad, _background: impl Into<Background>) {}
+
+    fn clear(&mut self) {}
+}
+
+impl text::Renderer for Recorder {
+    type Font = Font;
+    type Paragraph = Label;
+    type Editor = ();
+
+    const ICON_FONT: Font = Font::DEFAULT;
+    const CHECKMARK_ICON: char = '✓';
+    const ARROW_DOWN_ICON: char = '▾';
+
+    fn default_font(&self) -> Font {
+        Font::MONOSPACE
+    }
+
+    fn default_size(&self) -> Pixels {
+        Pixels(DEFAULT_SIZE)
+    }
+
+    fn fill_paragraph(&mut self, _: &Label, _: Point, _: Color, _: Rectangle) {}
+
+    fn fill_editor(&mut self, _: &(), _: Point, _: Color, _: Rectangle) {}
+
+    fn fill_text(&mut self, _: Text<String>, _: Point, _: Color, _: Rectangle) {}
+}
+
+impl super::Renderer for Recorder {
+    /// Поле ответа снимается надписью с его текстом.
+    fn answer_editor(answer: &text_editor::Content) -> Element<'_, Message, Theme, Self> {
+        iced::widget::text(answer.text()).into()
+    }
+}
+
+/// Виды виджетов с состоянием, которые попадают в снимок, кроме надписей.
+fn kinds() -> [(Tag, &'static str); 5] {
+    fn tag(widget: impl Widget<Message, Theme, Recorder>) -> Tag {
+        widget.tag()
+    }
+    let profile = |profile| Message::Settings(settings::Message::ProfileSelected(profile));
+    [
+        (tag(button("")), "button"),
+        (tag(scrollable(column![])), "scrollable"),
+        (tag(text_input("", "")), "text_input"),
+        (
+            tag(pick_list(Profile::ALL, None::<Profile>, profile)),
+            "pick_list",
+        ),
+        (
+            tag(rich_text(Vec::<Span<'_, Message, Font>>::new())),
+            "rich_text",
+        ),
+    ]
+}
+
+/// Снимок представления `state` после раскладки в окне приложения: по строке на
+/// виджет, вложенные - с отступом. Столбцы, строки и рамки в снимок не попадают;
+/// надпись и флажок записываются как `text` со своим текстом.
+pub(super) fn snapshot(state: &TaaflUIState) -> Vec<String> {
+    let element: Element<'_, Message, Theme, Recorder> = state.view().into();
+    let mut tree = Tree::new(&element);
+    let limits = Limits::new(Size::ZERO, Size::new(WINDOW_WIDTH, WINDOW_HEIGHT));
+    let _ = element.as_widget().layout(&mut tree, &Recorder, &limits);
+
+    let mut lines = Vec::new();
+    record(&tree, &kinds(), 0, &mut lines);
+    lines
+}
+
+fn record(tree: &Tree, kinds: &[(Tag, &str)], depth: usize, lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    if tree.tag == Tag::of::<text_widget::State<Label>>() {
+        let label = tree.state.downcast_ref::<text_widget::State<Label>>();
+        lines.push(format!("{}text {:?}", indent, label.0.raw().content));
+        return;
+    }
+    let depth = match kinds.iter().find(|(tag, _)| *tag == tree.tag) {
+        Some((_, kind)) => {
+            lines.push(format!("{}{}", indent, kind));
+            depth + 1
+        }
+        None => depth,
+    };
+    for child in &tree.children {
+        record(child, kinds, depth, lines);
+    }
+}
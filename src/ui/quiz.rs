@@ -0,0 +1,121 @@
+//! Самопроверка: таблица символов скрыта, роли и число вхождений символов
+//! случайного оператора называет пользователь, а ответ проверяется построчно.
+
+use super::{TaaflUIState, COLUMN_SPACING};
+use iced::{
+    widget::{button, column, row, text, text_editor, Column},
+    Element, Task, Theme,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+use taafl::analyzer::catch_internal;
+use taafl::quiz::{Grade, Mark, Quiz};
+
+/// Высота поля ответа.
+const ANSWER_HEIGHT: f32 = 80.0;
+
+/// Текущее задание и ответ на него.
+#[derive(Debug)]
+pub(super) struct Session {
+    pub(super) quiz: Quiz,
+    pub(super) answer: text_editor::Content,
+    /// Оценка последней проверки ответа
+    pub(super) grade: Option<Grade>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Новое задание по случайному оператору
+    Start,
+    AnswerEdited(text_editor::Action),
+    Check,
+    Finish,
+}
+
+pub(super) fn update(state: &mut TaaflUIState, message: Message) -> Task<super::Message> {
+    match message {
+        Message::Start => {
+            let seed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+            let config = state.analyzer_config();
+            // Сбой анализатора на случайном операторе - задание не подобрано
+            let quiz = catch_internal("", || Quiz::generate(seed, &config));
+            start(state, quiz.ok().flatten());
+        }
+        Message::AnswerEdited(action) => {
+            if let Some(session) = &mut state.quiz {
+                session.answer.perform(action);
+            }
+        }
+        Message::Check => {
+            if let Some(session) = &mut state.quiz {
+                session.grade = Some(session.quiz.grade(&session.answer.text()));
+            }
+        }
+        Message::Finish => state.quiz = None,
+    }
+
+    Task::none()
+}
+
+/// Ставит оператор задания в поле ввода; прежние результаты анализа сбрасываются.
+pub(super) fn start(state: &mut TaaflUIState, quiz: Option<Quiz>) {
+    state.reset_output();
+    let Some(quiz) = quiz else {
+        state.syntax_output = "Не удалось подобрать оператор, который принимают \
+                               текущие настройки"
+            .to_string();
+        return;
+    };
+    state.content = quiz.statement.clone();
+    state.typed_identifier = None;
+    super::table::refresh(state);
+    state.quiz = Some(Session {
+        quiz,
+        answer: text_editor::Content::new(),
+        grade: None,
+    });
+}
+
+/// Панель задания на месте таблицы символов.
+/// Поле ответа в окне.
+pub(super) fn answer_editor(answer: &text_editor::Content) -> Element<'_, super::Message> {
+    text_editor(answer)
+        .placeholder("X - идентификатор-массив: 1")
+        .on_action(|action| super::Message::Quiz(Message::AnswerEdited(action)))
+        .height(ANSWER_HEIGHT)
+        .into()
+}
+
+pub(super) fn view<R: super::Renderer>(session: &Session) -> Column<'_, super::Message, Theme, R> {
+    let mut panel = column![
+        text(format!(
+            "Назовите роли символов оператора {}",
+            session.quiz.statement
+        )),
+        text("По строке на символ в роли: символ - роль: число").size(12),
+        R::answer_editor(&session.answer),
+        row![
+            button("Проверить").on_press(super::Message::Quiz(Message::Check)),
+            button("Завершить").on_press(super::Message::Quiz(Message::Finish)),
+        ]
+        .spacing(COLUMN_SPACING),
+    ]
+    .spacing(COLUMN_SPACING / 2);
+
+    if let Some(grade) = &session.grade {
+        let (correct, total) = grade.score();
+        panel = panel.push(text(format!("Верно {} из {}", correct, total)));
+        for feedback in &grade.rows {
+            let correct = feedback.mark == Mark::Correct;
+            panel = panel.push(text(feedback.to_string()).style(move |theme| {
+                if correct {
+                    text::success(theme)
+                } else {
+                    text::danger(theme)
+                }
+            }));
+        }
+    }
+    panel
+}
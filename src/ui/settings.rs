@@ -0,0 +1,226 @@
+//! Настройки анализа: режим восстановления, порядок и регистр символов, конец
+//! оператора, скобки индексов, вещественные константы, подчёркивание и алфавит
+//! идентификаторов, диапазон констант, замена типографских знаков, строгость правил,
+//! профили. Здесь же - скорость анимаций.
+
+use super::animation::{self, Speed};
+use super::{analysis, editor, TaaflUIState, COLUMN_SPACING, OUTPUT_HEIGHT};
+use iced::{
+    widget::{button, checkbox, column, container, pick_list, row, scrollable, text, Column},
+    Length::Fill,
+    Task, Theme,
+};
+use taafl::config::{
+    Brackets, ConstantRange, Profile, Rule, Severity, SymbolOrder, Terminator, CONFIG_FILE,
+};
+use taafl::sanitize::normalize_typography;
+use taafl::variant::{Variant, Variant20};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    RecoveryToggled(bool),
+    SortedToggled(bool),
+    PreserveCaseToggled(bool),
+    RealsToggled(bool),
+    UnderscoresToggled(bool),
+    UnicodeToggled(bool),
+    ConstantsSelected(ConstantRange),
+    ZeroToggled(bool),
+    TypographyToggled(bool),
+    Toggled,
+    SeveritySelected(Rule, Severity),
+    TerminatorSelected(Terminator),
+    BracketsSelected(Brackets),
+    ProfileSelected(Profile),
+    Save,
+}
+
+pub(super) fn update(state: &mut TaaflUIState, message: Message) -> Task<super::Message> {
+    match message {
+        Message::RecoveryToggled(recovery) => {
+            state.config.recovery = recovery;
+            state.config.profile = None;
+            state.reset_output();
+        }
+        Message::SortedToggled(sorted) => {
+            state.config.order = if sorted {
+                SymbolOrder::Sorted
+            } else {
+                SymbolOrder::Appearance
+            };
+            state.generation += 1;
+            if !state._semantics_output.is_empty() {
+                return analysis::update(state, analysis::Message::Semantics);
+            }
+        }
+        Message::PreserveCaseToggled(preserve) => {
+            state.config.preserve_case = preserve;
+            state.generation += 1;
+            if !state._semantics_output.is_empty() {
+                return analysis::update(state, analysis::Message::Semantics);
+            }
+        }
+        Message::RealsToggled(reals) => {
+            state.config.reals = reals;
+            state.reset_output();
+        }
+        Message::UnderscoresToggled(underscores) => {
+            state.config.underscores = underscores;
+            state.typed_identifier = editor::typed_identifier(&state.content, &state.config);
+            state.reset_output();
+        }
+        Message::UnicodeToggled(unicode) => {
+            state.config.unicode_identifiers = unicode;
+            state.typed_identifier = editor::typed_identifier(&state.content, &state.config);
+            state.reset_output();
+        }
+        Message::ConstantsSelected(constants) => {
+            state.config.constants = constants;
+            state.reset_output();
+        }
+        Message::ZeroToggled(zero) => {
+            state.config.zero = zero;
+            state.config.profile = None;
+            state.reset_output();
+        }
+        Message::TypographyToggled(typography) => {
+            state.config.typography = typography;
+            if typography {
+                state.content = normalize_typography(&state.content).0;
+            }
+            state.reset_output();
+        }
+        Message::Toggled => {
+            state.show_settings = !state.show_settings;
+        }
+        Message::SeveritySelected(rule, severity) => {
+            state.config.set_severity(rule, severity);
+            state.config.profile = None;
+            state.reset_output();
+        }
+        Message::TerminatorSelected(terminator) => {
+            state.config.terminator = terminator;
+            state.reset_output();
+        }
+        Message::BracketsSelected(brackets) => {
+            state.config.brackets = brackets;
+            state.reset_output();
+        }
+        Message::ProfileSelected(profile) => {
+            state.config.apply_profile(profile);
+            state.reset_output();
+        }
+        Message::Save => {
+            state.settings_status = match state.config.save_to(state.storage.as_mut(), CONFIG_FILE)
+            {
+                Ok(()) => format!("Настройки сохранены в {}", CONFIG_FILE),
+                Err(e) => e,
+            };
+        }
+    }
+
+    Task::none()
+}
+
+pub(super) fn view<R: super::Renderer>(
+    state: &TaaflUIState,
+) -> Column<'_, super::Message, Theme, R> {
+    let mut rules =
+        column![text("Строгость семантических правил").size(16)].spacing(COLUMN_SPACING);
+    let constants = state.config.constant_range(Variant20.constant_range());
+    for rule in Rule::ALL {
+        let doc = column![
+            text(rule.title()),
+            text(rule.description(&constants)).size(12),
+            text(format!("Пример нарушения: {}", rule.example())).size(12),
+        ];
+        rules = rules.push(
+            row![
+                doc.width(Fill),
+                pick_list(Severity::ALL, Some(state.config.severity(rule)), move |s| {
+                    super::Message::Settings(Message::SeveritySelected(rule, s))
+                })
+            ]
+            .spacing(COLUMN_SPACING)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    let terminator = row![
+        text("Конец оператора").width(Fill),
+        pick_list(Terminator::ALL, Some(state.config.terminator), |t| {
+            super::Message::Settings(Message::TerminatorSelected(t))
+        })
+    ]
+    .spacing(COLUMN_SPACING)
+    .align_y(iced::Alignment::Center);
+    let brackets = row![
+        text("Скобки индексов").width(Fill),
+        pick_list(Brackets::ALL, Some(state.config.brackets), |b| {
+            super::Message::Settings(Message::BracketsSelected(b))
+        })
+    ]
+    .spacing(COLUMN_SPACING)
+    .align_y(iced::Alignment::Center);
+    let reals = checkbox("Вещественные константы (3.14, 1.0E5)", state.config.reals)
+        .on_toggle(|on| super::Message::Settings(Message::RealsToggled(on)));
+    let underscores = checkbox(
+        "Подчёркивание в идентификаторах (MAX_LEN)",
+        state.config.underscores,
+    )
+    .on_toggle(|on| super::Message::Settings(Message::UnderscoresToggled(on)));
+    let unicode = checkbox(
+        "Буквы любых алфавитов в идентификаторах (МАСС[И])",
+        state.config.unicode_identifiers,
+    )
+    .on_toggle(|on| super::Message::Settings(Message::UnicodeToggled(on)));
+    let typography = checkbox(
+        "Заменять типографские знаки (×, –, ”) при вводе",
+        state.config.typography,
+    )
+    .on_toggle(|on| super::Message::Settings(Message::TypographyToggled(on)));
+    let constants = row![
+        text("Диапазон констант").width(Fill),
+        checkbox("Допускать 0", state.config.zero)
+            .on_toggle(|on| super::Message::Settings(Message::ZeroToggled(on))),
+        pick_list(ConstantRange::PRESETS, Some(state.config.constants), |c| {
+            super::Message::Settings(Message::ConstantsSelected(c))
+        })
+    ]
+    .spacing(COLUMN_SPACING)
+    .align_y(iced::Alignment::Center);
+    let motion = row![
+        text("Скорость анимации").width(Fill),
+        checkbox("Без анимации", state.animation.reduced_motion).on_toggle(|on| {
+            super::Message::Animation(animation::Message::ReducedMotionToggled(on))
+        }),
+        pick_list(Speed::ALL, Some(state.animation.speed), |speed| {
+            super::Message::Animation(animation::Message::SpeedSelected(speed))
+        })
+    ]
+    .spacing(COLUMN_SPACING)
+    .align_y(iced::Alignment::Center);
+
+    column![
+        terminator,
+        brackets,
+        reals,
+        underscores,
+        unicode,
+        typography,
+        constants,
+        motion,
+        container(scrollable(rules))
+            .style(container::rounded_box)
+            .padding(10)
+            .width(Fill)
+            .max_height(2.0 * OUTPUT_HEIGHT),
+        row![
+            button("Сохранить").on_press(super::Message::Settings(Message::Save)),
+            text(state.settings_status.clone())
+        ]
+        .spacing(COLUMN_SPACING)
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(COLUMN_SPACING)
+}
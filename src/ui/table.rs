@@ -0,0 +1,201 @@
+//! Таблица LL(1) и трасса разбора по ней: шаг трассы выбирается нажатием,
+//! и клетка, к которой анализатор обратился на этом шаге, выделяется в таблице.
+
+use super::{TaaflUIState, COLUMN_SPACING, OUTPUT_HEIGHT};
+use iced::{
+    widget::{button, column, container, row, scrollable, text, Column, Row},
+    Length::Fill,
+    Task, Theme,
+};
+use std::collections::BTreeSet;
+use taafl::analyzer::{analyze_with_events, catch_internal, events::Event, ll1_table};
+use taafl::config::{AnalyzerConfig, ParserKind};
+use taafl::diagnostic::Severity;
+use taafl::grammar::{Ll1Table, TableStep};
+
+/// Ширина столбца таблицы.
+const CELL_WIDTH: f32 = 80.0;
+
+/// Трасса разбора текущего ввода и выбранный шаг.
+#[derive(Debug, Default)]
+pub(super) struct Session {
+    /// Обращения к таблице в порядке разбора
+    pub(super) trace: Vec<TableStep>,
+    /// Итог анализа: строка принята или первая ошибка
+    pub(super) verdict: String,
+    pub(super) selected: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Показать таблицу вместо результатов анализа или вернуться к ним
+    Toggled,
+    /// Выделить клетку, к которой обратился шаг трассы с этим номером
+    StepSelected(usize),
+}
+
+pub(super) fn update(state: &mut TaaflUIState, message: Message) -> Task<super::Message> {
+    match message {
+        Message::Toggled => {
+            state.table = match state.table.take() {
+                Some(_) => None,
+                None => Some(trace(&state.content, &state.analyzer_config())),
+            };
+        }
+        Message::StepSelected(step) => {
+            if let Some(session) = &mut state.table {
+                session.selected = Some(step);
+            }
+        }
+    }
+
+    Task::none()
+}
+
+/// Строит трассу заново, если таблица показана: ввод или настройки изменились.
+pub(super) fn refresh(state: &mut TaaflUIState) {
+    if state.table.is_some() {
+        state.table = Some(trace(&state.content, &state.analyzer_config()));
+    }
+}
+
+/// Трасса разбора `input` таблицей LL(1), какой бы анализатор ни был выбран
+/// в настройках `config`.
+pub(super) fn trace(input: &str, config: &AnalyzerConfig) -> Session {
+    let config = AnalyzerConfig {
+        parser: ParserKind::Table,
+        ..config.clone()
+    };
+    let mut trace = Vec::new();
+    let mut error = None;
+    let report = catch_internal(input, || {
+        analyze_with_events(input, &config, &mut |event| match event {
+            Event::TableConsulted(step) => trace.push(step),
+            Event::DiagnosticEmitted(diagnostic)
+                if diagnostic.severity == Severity::Error && error.is_none() =>
+            {
+                error = Some(diagnostic.message)
+            }
+            _ => {}
+        })
+    });
+    let verdict = match (report, error) {
+        (Err(e), _) => e.to_string(),
+        (Ok(report), _) if report.accepted => "Строка принадлежит языку.".to_string(),
+        (_, Some(message)) => message,
+        (_, None) => "Строка не принадлежит языку.".to_string(),
+    };
+    Session {
+        trace,
+        verdict,
+        selected: None,
+    }
+}
+
+/// Шаг трассы в записи M[нетерминал, терминал] с продукцией из клетки.
+fn step_line(table: &Ll1Table, number: usize, step: &TableStep) -> String {
+    let production = step
+        .production
+        .and_then(|index| Some((index, table.grammar().productions.get(index)?)));
+    let cell = match production {
+        Some((index, production)) => format!("{}: {}", index + 1, production),
+        None => "пусто - разбор остановлен".to_string(),
+    };
+    format!(
+        "{}. M[{}, {}] = {}",
+        number + 1,
+        step.nonterminal,
+        step.terminal,
+        cell
+    )
+}
+
+/// Клетка таблицы фиксированной ширины; выбранная - в рамке.
+fn cell<R: super::Renderer>(
+    content: String,
+    selected: bool,
+) -> container::Container<'static, super::Message, Theme, R> {
+    let cell = container(text(content).size(12)).width(CELL_WIDTH);
+    if selected {
+        cell.style(container::bordered_box)
+    } else {
+        cell
+    }
+}
+
+/// Таблица: строки - нетерминалы, столбцы - терминалы, в клетках - номера продукций.
+fn grid<'a, R: super::Renderer>(
+    table: &Ll1Table,
+    session: &Session,
+) -> Column<'a, super::Message, Theme, R> {
+    let selected = session.selected.and_then(|step| session.trace.get(step));
+    let is_selected = |nonterminal: &str, terminal: &str| {
+        selected.is_some_and(|step| step.nonterminal == nonterminal && step.terminal == terminal)
+    };
+    // Терминал пустой клетки, на которой остановился разбор, тоже получает столбец
+    let terminals: BTreeSet<String> = table
+        .entries()
+        .map(|(_, terminal, _)| terminal.to_string())
+        .chain(session.trace.iter().map(|step| step.terminal.clone()))
+        .collect();
+
+    let header = terminals.iter().fold(
+        Row::new().push(cell(String::new(), false)),
+        |header, terminal| header.push(cell(terminal.clone(), false)),
+    );
+    let mut grid = column![header];
+    for nonterminal in table.grammar().nonterminals() {
+        let mut line = row![cell(nonterminal.clone(), false)];
+        for terminal in &terminals {
+            let entry = table
+                .entry(&nonterminal, terminal)
+                .map_or(String::new(), |index| (index + 1).to_string());
+            line = line.push(cell(entry, is_selected(&nonterminal, terminal)));
+        }
+        grid = grid.push(line);
+    }
+    grid
+}
+
+pub(super) fn view<'a, R: super::Renderer>(
+    state: &'a TaaflUIState,
+    session: &'a Session,
+) -> Column<'a, super::Message, Theme, R> {
+    let Some(table) = ll1_table(&state.analyzer_config()) else {
+        return column![text(
+            "Грамматика языка с текущими настройками не является LL(1)"
+        )];
+    };
+
+    let mut steps = column![text(session.verdict.as_str())];
+    for (i, step) in session.trace.iter().enumerate() {
+        let line = text(step_line(table, i, step)).size(12);
+        let line = if session.selected == Some(i) {
+            line.style(text::success)
+        } else {
+            line
+        };
+        steps = steps.push(
+            button(line)
+                .style(button::text)
+                .padding(0)
+                .on_press(super::Message::Table(Message::StepSelected(i))),
+        );
+    }
+
+    let framed_steps = container(scrollable(steps))
+        .style(container::rounded_box)
+        .width(Fill)
+        .height(OUTPUT_HEIGHT);
+    let framed_grid = container(scrollable(grid(table, session)).direction(
+        scrollable::Direction::Both {
+            vertical: scrollable::Scrollbar::default(),
+            horizontal: scrollable::Scrollbar::default(),
+        },
+    ))
+    .style(container::rounded_box)
+    .width(Fill)
+    .height(OUTPUT_HEIGHT);
+
+    column![framed_steps, framed_grid].spacing(COLUMN_SPACING)
+}
@@ -0,0 +1,871 @@
+//! Проверки логики `update`: последовательности сообщений и состояние после них.
+//! Интерфейс не отрисовывается, поэтому окно для тестов не нужно: представление
+//! раскладывается отрисовщиком [`harness`] и сверяется по снимку дерева виджетов.
+//!
+//! Фоновые задачи здесь не выполняются: результат анализа доставляется тестом
+//! сообщением `Analyzed` или `SemanticsReady`, как его доставил бы `Task::perform`.
+
+use super::{analysis, editor, export, harness, quiz, settings, table, Message, TaaflUIState};
+use taafl::analyzer::analyze_line_with;
+use taafl::bundle::BUNDLE_FILE;
+use taafl::config::{
+    AnalyzerConfig, Brackets, ConstantRange, Profile, Rule, Severity, SymbolOrder, Terminator,
+    CONFIG_FILE,
+};
+use taafl::model::REPORT_FILE;
+
+fn send(state: &mut TaaflUIState, messages: impl IntoIterator<Item = Message>) {
+    for message in messages {
+        let _ = state.update(message);
+    }
+}
+
+fn input(text: &str) -> Message {
+    Message::Editor(editor::Message::Changed(text.to_string()))
+}
+
+/// Результат фонового анализа текущего ввода для текущего поколения.
+fn finished(
+    state: &TaaflUIState,
+    done: fn(u64, analysis::Outcome) -> analysis::Message,
+) -> Message {
+    let outcome = analyze_line_with(&state.content, &state.analyzer_config());
+    Message::Analysis(done(state.generation, outcome))
+}
+
+fn analyze(state: &mut TaaflUIState) {
+    send(state, [Message::Analysis(analysis::Message::Analyze)]);
+    let done = finished(state, analysis::Message::Analyzed);
+    send(state, [done]);
+}
+
+fn semantics(state: &mut TaaflUIState) {
+    send(state, [Message::Analysis(analysis::Message::Semantics)]);
+    let done = finished(state, analysis::Message::SemanticsReady);
+    send(state, [done]);
+}
+
+#[test]
+fn accepted_line_enables_semantics() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X[I] := I + 1;")]);
+    analyze(&mut state);
+
+    assert!(state._syntax_success);
+    assert!(state.syntax_output.ends_with("Строка принадлежит языку."));
+
+    semantics(&mut state);
+    assert!(state._semantics_output.contains("X - идентификатор-массив"));
+    assert!(state._semantics_output.contains("1 - константа-выражение"));
+}
+
+#[test]
+fn rejected_line_shows_error() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X := ;")]);
+    analyze(&mut state);
+    semantics(&mut state);
+
+    assert!(!state._syntax_success);
+    assert!(state.syntax_output.contains("Синтаксическая ошибка"));
+    assert!(state._semantics_output.is_empty());
+}
+
+#[test]
+fn error_cursor_underlines_whole_lexeme() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X := ABCDEFGHIJ;")]);
+    analyze(&mut state);
+
+    assert!(state.syntax_output.contains("\n     ^^^^^^^^^^\n"));
+}
+
+#[test]
+fn empty_input_asks_for_text() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [Message::Analysis(analysis::Message::Analyze)]);
+
+    assert_eq!(state.syntax_output, "Введите хоть что-нибудь (o_O)");
+}
+
+#[test]
+fn editing_resets_results() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("A := 1;")]);
+    analyze(&mut state);
+    semantics(&mut state);
+    send(&mut state, [input("A := 2;")]);
+
+    assert_eq!(state.content, "A := 2;");
+    assert!(state.syntax_output.is_empty());
+    assert!(state._semantics_output.is_empty());
+    assert!(!state._syntax_success);
+
+    analyze(&mut state);
+    send(&mut state, [Message::Editor(editor::Message::Clear)]);
+    assert!(state.content.is_empty());
+    assert!(state.syntax_output.is_empty());
+}
+
+#[test]
+fn stale_result_is_discarded() {
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [
+            input("A := 1;"),
+            Message::Analysis(analysis::Message::Analyze),
+        ],
+    );
+    let stale = finished(&state, analysis::Message::Analyzed);
+
+    send(&mut state, [input("A := ;"), stale]);
+    assert!(state.syntax_output.is_empty());
+    assert!(!state._syntax_success);
+
+    // Повторный запуск тоже делает предыдущий результат устаревшим
+    send(&mut state, [Message::Analysis(analysis::Message::Analyze)]);
+    let stale = finished(&state, analysis::Message::Analyzed);
+    analyze(&mut state);
+    let current = state.syntax_output.clone();
+    send(&mut state, [stale]);
+    assert_eq!(state.syntax_output, current);
+}
+
+#[test]
+fn pasted_text_is_sanitized() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("A\u{200B} := 1;")]);
+
+    assert_eq!(state.content, "A := 1;");
+    assert_eq!(state.syntax_output, "Удалено невидимых символов: 1");
+}
+
+#[test]
+fn submit_echoes_input() {
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [input("A := 1;"), Message::Editor(editor::Message::Submit)],
+    );
+
+    assert_eq!(state.syntax_output, "A := 1;");
+}
+
+#[test]
+fn hidden_errors_can_be_shown() {
+    let mut state = TaaflUIState::default();
+    state.config.recovery = true;
+    state.config.max_errors = 1;
+    send(&mut state, [input("A[1] := A + A + A;")]);
+    analyze(&mut state);
+
+    assert_eq!(state.hidden_errors, 2);
+    assert!(state.syntax_output.contains("... и ещё 2 ошибки"));
+
+    send(
+        &mut state,
+        [Message::Analysis(analysis::Message::ShowAllErrors)],
+    );
+    let done = finished(&state, analysis::Message::Analyzed);
+    send(&mut state, [done]);
+    assert_eq!(state.hidden_errors, 0);
+    assert_eq!(
+        state.syntax_output.matches("Семантическая ошибка").count(),
+        3
+    );
+}
+
+#[test]
+fn manual_setting_clears_profile() {
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [Message::Settings(settings::Message::ProfileSelected(
+            Profile::Extended,
+        ))],
+    );
+    assert_eq!(state.config.profile, Some(Profile::Extended));
+    assert!(state.config.recovery);
+
+    send(
+        &mut state,
+        [Message::Settings(settings::Message::SeveritySelected(
+            Rule::ConstantRange,
+            Severity::Off,
+        ))],
+    );
+    assert_eq!(state.config.profile, None);
+    assert_eq!(state.config.severity(Rule::ConstantRange), Severity::Off);
+}
+
+#[test]
+fn sorting_refreshes_semantics() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("Z := B + A;")]);
+    analyze(&mut state);
+    semantics(&mut state);
+    assert!(state._semantics_output.starts_with("Z"));
+
+    // Результат, запущенный до переключения, устарел
+    let stale = finished(&state, analysis::Message::SemanticsReady);
+    send(
+        &mut state,
+        [
+            Message::Settings(settings::Message::SortedToggled(true)),
+            stale,
+        ],
+    );
+    assert!(state._semantics_output.starts_with("Z"));
+
+    let done = finished(&state, analysis::Message::SemanticsReady);
+    send(&mut state, [done]);
+    assert!(state._semantics_output.starts_with("A"));
+}
+
+#[test]
+fn compare_with_same_profile_matches() {
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [
+            Message::Settings(settings::Message::ProfileSelected(Profile::Strict)),
+            input("A := 1;"),
+            Message::Analysis(analysis::Message::CompareProfileSelected(Profile::Strict)),
+            Message::Analysis(analysis::Message::Compare),
+        ],
+    );
+
+    assert!(state.syntax_output.ends_with("Результаты совпадают."));
+}
+
+#[test]
+fn analysis_marks_token_boundaries() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("AB[1] :=C+ 20;")]);
+    analyze(&mut state);
+
+    let tokens: Vec<&str> = state
+        .tokens
+        .entries
+        .iter()
+        .map(|token| token.text.as_str())
+        .collect();
+    assert_eq!(tokens, ["AB", "[", "1", "]", ":=", "C", "+", "20", ";"]);
+
+    send(&mut state, [input("A := $;")]);
+    assert!(state.tokens.entries.is_empty());
+    analyze(&mut state);
+    assert!(state.tokens.entries.is_empty());
+}
+
+#[test]
+fn constants_list_shows_lexemes_and_range() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X[007] := 7 + 5;")]);
+    analyze(&mut state);
+    semantics(&mut state);
+
+    assert!(state._semantics_output.contains(
+        "7 - константа-индекс (столбец 3): запись 007 (ведущие нули), десятичная, в диапазоне [1..32767]"
+    ));
+    assert!(state
+        ._semantics_output
+        .contains("5 - константа-выражение (столбец 15): запись 5, десятичная"));
+}
+
+#[test]
+fn constants_list_shows_radix_of_each_lexeme() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X[10H] := 0FFH + 255 + 377C;")]);
+    analyze(&mut state);
+    semantics(&mut state);
+
+    assert!(state._semantics_output.contains(
+        "16 - константа-индекс (столбец 3): запись 10H (шестнадцатеричная), в диапазоне"
+    ));
+    assert!(state._semantics_output.contains(
+        "запись 0FFH (шестнадцатеричная), 255 (десятичная), 377C (восьмеричная), в диапазоне"
+    ));
+}
+
+#[test]
+fn typing_identifier_updates_length_counter() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X := ALPHA")]);
+    assert_eq!(state.typed_identifier, Some(5));
+
+    send(&mut state, [input("X := ALPHABET1")]);
+    assert_eq!(state.typed_identifier, Some(9));
+
+    for text in ["X := ALPHA ", "X := 12", "X := 1AB", ""] {
+        send(&mut state, [input(text)]);
+        assert_eq!(state.typed_identifier, None, "{:?}", text);
+    }
+
+    send(&mut state, [input("Б1")]);
+    assert_eq!(state.typed_identifier, None);
+
+    send(&mut state, [input("X := MAX_LEN")]);
+    assert_eq!(state.typed_identifier, Some(3));
+    send(
+        &mut state,
+        [Message::Settings(settings::Message::UnderscoresToggled(
+            true,
+        ))],
+    );
+    assert_eq!(state.typed_identifier, Some(7));
+
+    send(&mut state, [input("МАСС[И] := СЧЁТЧИК1")]);
+    assert_eq!(state.typed_identifier, None);
+    send(
+        &mut state,
+        [Message::Settings(settings::Message::UnicodeToggled(true))],
+    );
+    assert_eq!(state.typed_identifier, Some(8));
+}
+
+#[test]
+fn symbol_rows_expand_to_productions() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X[I] := I + 1;")]);
+    analyze(&mut state);
+    semantics(&mut state);
+
+    let row = state
+        .symbol_rows
+        .iter()
+        .position(|(line, _)| line.starts_with("I - идентификатор-выражение"))
+        .unwrap();
+    let origins: Vec<String> = state.symbol_rows[row]
+        .1
+        .iter()
+        .map(analysis::origin_line)
+        .collect();
+    assert_eq!(
+        origins,
+        ["    столбец 9: term = IDENT field term_tail (parse_term)"]
+    );
+    assert_eq!(
+        analysis::origin_line(&state.symbol_rows[0].1[0]),
+        "    столбец 1: left = IDENT field left_tail (parse_left_part)"
+    );
+
+    let toggle = Message::Analysis(analysis::Message::ToggleOrigin(row));
+    send(&mut state, [toggle.clone()]);
+    assert!(state.expanded_symbols.contains(&row));
+    send(&mut state, [toggle]);
+    assert!(state.expanded_symbols.is_empty());
+
+    send(
+        &mut state,
+        [Message::Analysis(analysis::Message::ToggleOrigin(0))],
+    );
+    send(&mut state, [input("X := 1;")]);
+    assert!(state.symbol_rows.is_empty() && state.expanded_symbols.is_empty());
+}
+
+#[test]
+fn selected_terminator_ends_statements() {
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [
+            input("X := 1."),
+            Message::Settings(settings::Message::TerminatorSelected(Terminator::Dot)),
+        ],
+    );
+    analyze(&mut state);
+    assert!(state._syntax_success);
+
+    // Профиль меняет строгость правил, но не язык
+    send(
+        &mut state,
+        [Message::Settings(settings::Message::ProfileSelected(
+            Profile::Teaching,
+        ))],
+    );
+    assert_eq!(state.config.terminator, Terminator::Dot);
+
+    send(&mut state, [input("X := 1;")]);
+    analyze(&mut state);
+    assert!(!state._syntax_success);
+    assert!(state.syntax_output.contains("оператор заканчивается '.'"));
+}
+
+#[test]
+fn selected_brackets_delimit_indices() {
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [
+            input("A(I) := 1;"),
+            Message::Settings(settings::Message::BracketsSelected(Brackets::Round)),
+        ],
+    );
+    analyze(&mut state);
+    assert!(state._syntax_success);
+
+    semantics(&mut state);
+    assert!(state._semantics_output.contains("A - идентификатор-массив"));
+}
+
+#[test]
+fn selected_constant_range_admits_cardinal_values() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("A := 40000;")]);
+    analyze(&mut state);
+    assert!(state
+        .syntax_output
+        .contains("Константа вне диапазона [1..32767]"));
+
+    send(
+        &mut state,
+        [Message::Settings(settings::Message::ConstantsSelected(
+            ConstantRange::Cardinal,
+        ))],
+    );
+    analyze(&mut state);
+    assert!(state._syntax_success);
+    semantics(&mut state);
+    assert!(state._semantics_output.contains("в диапазоне [0..65535]"));
+}
+
+#[test]
+fn typography_setting_replaces_pasted_characters() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X := A × 2;")]);
+    analyze(&mut state);
+    assert!(state.syntax_output.contains("похоже на типографский знак"));
+
+    send(
+        &mut state,
+        [Message::Settings(settings::Message::TypographyToggled(
+            true,
+        ))],
+    );
+    assert_eq!(state.content, "X := A * 2;");
+    send(&mut state, [input("Y := “AB” — 1;")]);
+    assert_eq!(state.content, "Y := \"AB\" - 1;");
+    assert_eq!(state.syntax_output, "Заменено типографских знаков: 3");
+    analyze(&mut state);
+    assert!(state._syntax_success);
+}
+
+#[test]
+fn symbol_rows_appear_by_elapsed_time_not_frames() {
+    use super::animation::Message::{PauseToggled, Tick};
+    use std::time::{Duration, Instant};
+
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X[I, J, K] := A + B + C + 1 + 2;")]);
+    analyze(&mut state);
+    semantics(&mut state);
+    assert_eq!(state.symbol_rows.len(), 10);
+    assert_eq!(state.symbols_reveal.shown(&state.animation), 0);
+
+    // Шесть строк в секунду на обычной скорости, сколько бы кадров ни было
+    let start = Instant::now();
+    let frames = (0..=60).map(|i| Tick(start + Duration::from_millis(i * 1100 / 60)));
+    send(&mut state, frames.map(Message::Animation));
+    assert_eq!(state.symbols_reveal.shown(&state.animation), 6);
+
+    // На паузе время не идёт
+    let paused = start + Duration::from_millis(1100);
+    send(
+        &mut state,
+        [
+            Message::Animation(PauseToggled),
+            Message::Animation(PauseToggled),
+            Message::Animation(Tick(paused + Duration::from_secs(60))),
+        ],
+    );
+    assert_eq!(state.symbols_reveal.shown(&state.animation), 6);
+}
+
+#[test]
+fn reduced_motion_shows_everything_at_once() {
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [Message::Animation(
+            super::animation::Message::ReducedMotionToggled(true),
+        )],
+    );
+    send(&mut state, [input("X[I] := A + 1;")]);
+    analyze(&mut state);
+    semantics(&mut state);
+
+    let rows = state.symbol_rows.len();
+    assert_eq!(state.symbols_reveal.shown(&state.animation), rows);
+}
+
+#[test]
+fn saved_settings_and_export_go_to_storage() {
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [
+            Message::Settings(settings::Message::RecoveryToggled(true)),
+            Message::Settings(settings::Message::Save),
+            input("X := 1;"),
+            Message::Export(export::Message::ExportAll),
+        ],
+    );
+
+    let saved = AnalyzerConfig::load_from(state.storage.as_ref(), CONFIG_FILE).unwrap();
+    assert!(saved.recovery);
+    let archive = state.storage.read(BUNDLE_FILE).unwrap().unwrap();
+    assert_eq!(taafl::bundle::verify(&archive), Ok(()));
+    assert_eq!(state.export_status, format!("Записано в {}", BUNDLE_FILE));
+}
+
+#[test]
+fn deterministic_export_sorts_symbols() {
+    let mut state = TaaflUIState {
+        deterministic: true,
+        ..TaaflUIState::default()
+    };
+    send(
+        &mut state,
+        [
+            input("Z := B + A;"),
+            Message::Export(export::Message::ExportAll),
+        ],
+    );
+
+    let sorted = AnalyzerConfig {
+        order: SymbolOrder::Sorted,
+        ..AnalyzerConfig::default()
+    };
+    let archive = state.storage.read(BUNDLE_FILE).unwrap().unwrap();
+    assert_eq!(archive, taafl::bundle::export("Z := B + A;", &sorted));
+}
+
+#[test]
+fn imported_report_fills_panels_without_analysis() {
+    let mut state = TaaflUIState::default();
+    let report = taafl::analyzer::analyze_report("X[I] := 0FFH;", &AnalyzerConfig::default());
+    let json = serde_json::to_string(&report).unwrap();
+    state.storage.write(REPORT_FILE, json.as_bytes()).unwrap();
+    send(&mut state, [Message::Export(export::Message::ImportReport)]);
+
+    assert_eq!(state.content, "X[I] := 0FFH;");
+    assert!(state.syntax_output.ends_with("Строка принадлежит языку."));
+    assert!(state
+        ._semantics_output
+        .contains("255 - константа-выражение"));
+    assert_eq!(state.symbol_rows.len(), 4);
+    assert_eq!(state.export_status, format!("Открыт {}", REPORT_FILE));
+
+    state
+        .storage
+        .write(REPORT_FILE, b"[[[[[[[[[[[[[[[[[[]]]]]]]]]]]]]]]]]]")
+        .unwrap();
+    send(&mut state, [Message::Export(export::Message::ImportReport)]);
+    assert!(state.export_status.contains("вложенность"));
+    assert_eq!(state.content, "X[I] := 0FFH;");
+}
+
+#[test]
+fn recheck_marks_drift_from_imported_report() {
+    let mut state = TaaflUIState::default();
+    let mut report = taafl::analyzer::analyze_report("A := B;", &AnalyzerConfig::default());
+    let json = serde_json::to_string(&report).unwrap();
+    state.storage.write(REPORT_FILE, json.as_bytes()).unwrap();
+    let recheck = Message::Export(export::Message::RecheckReport);
+    send(
+        &mut state,
+        [
+            Message::Export(export::Message::ImportReport),
+            recheck.clone(),
+        ],
+    );
+    assert!(state.syntax_output.ends_with("Результаты совпадают."));
+
+    // Отчёт записан версией, которая ещё не принимала эту строку
+    report.accepted = false;
+    let json = serde_json::to_string(&report).unwrap();
+    state.storage.write(REPORT_FILE, json.as_bytes()).unwrap();
+    send(
+        &mut state,
+        [
+            Message::Export(export::Message::ImportReport),
+            recheck.clone(),
+        ],
+    );
+    assert!(state
+        .syntax_output
+        .starts_with("A := B;\n\n  Сохранённый отчёт"));
+    assert!(state
+        .syntax_output
+        .contains("* Строка не принадлежит языку"));
+    assert!(!state.syntax_output.contains("Результаты совпадают."));
+
+    send(&mut state, [input("A := C;"), recheck]);
+    assert!(state.imported_report().is_none());
+    assert!(state.syntax_output.is_empty());
+}
+
+#[test]
+fn quiz_hides_symbols_and_grades_the_answer() {
+    let mut state = TaaflUIState::default();
+    let task = taafl::quiz::Quiz::new("X[I] := I + 7;", &AnalyzerConfig::default());
+    quiz::start(&mut state, task);
+    assert_eq!(state.content, "X[I] := I + 7;");
+
+    analyze(&mut state);
+    assert!(state._syntax_success);
+    semantics(&mut state);
+    assert!(state._semantics_output.is_empty());
+
+    let answer = "X - массив: 1\nI - индекс: 1\nI - выражение: 2";
+    let paste = iced::widget::text_editor::Edit::Paste(std::sync::Arc::new(answer.to_string()));
+    send(
+        &mut state,
+        [
+            Message::Quiz(quiz::Message::AnswerEdited(
+                iced::widget::text_editor::Action::Edit(paste),
+            )),
+            Message::Quiz(quiz::Message::Check),
+        ],
+    );
+    let grade = state.quiz.as_ref().unwrap().grade.as_ref().unwrap();
+    assert_eq!(grade.score(), (2, 4));
+    assert_eq!(
+        grade.rows[2].to_string(),
+        "I - выражение: 2 - названо вхождений: 2"
+    );
+
+    send(&mut state, [Message::Quiz(quiz::Message::Finish)]);
+    assert!(state.quiz.is_none());
+}
+
+#[test]
+fn table_step_selects_consulted_cell() {
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [
+            input("A[I] := 1;"),
+            Message::Table(table::Message::Toggled),
+            Message::Table(table::Message::StepSelected(3)),
+        ],
+    );
+    let session = state.table.as_ref().unwrap();
+    assert_eq!(session.verdict, "Строка принадлежит языку.");
+    let step = &session.trace[session.selected.unwrap()];
+    assert_eq!(
+        (step.nonterminal.as_str(), step.terminal.as_str()),
+        ("left_tail", "[")
+    );
+    assert!(step.production.is_some());
+
+    // Изменённый ввод разбирается заново, выбор шага сбрасывается
+    send(&mut state, [input("A := ;")]);
+    let session = state.table.as_ref().unwrap();
+    assert_eq!(session.selected, None);
+    let last = session.trace.last().unwrap();
+    assert_eq!(
+        (last.nonterminal.as_str(), last.production),
+        ("right", None)
+    );
+    assert!(
+        session.verdict.starts_with("Ожидалось"),
+        "{}",
+        session.verdict
+    );
+
+    send(&mut state, [Message::Table(table::Message::Toggled)]);
+    assert!(state.table.is_none());
+}
+
+/// Снимок главного окна после ввода и анализа строки `X[I] := I + 1;`.
+const ANALYZED_VIEW: &[&str] = &[
+    "text \"Оператор присваивания языка Modula-2\"",
+    "button",
+    "  text \"Ввод\"",
+    "button",
+    "  text \"Анализ\"",
+    "button",
+    "  text \"Семантика\"",
+    "text \"Восстановление\"",
+    "text \"По алфавиту\"",
+    "text \"Регистр имён\"",
+    "button",
+    "  text \"Задание\"",
+    "button",
+    "  text \"Таблица\"",
+    "button",
+    "  text \"База\"",
+    "button",
+    "  text \"Настройки\"",
+    "scrollable",
+    "  text_input",
+    "button",
+    "  text \"Очистить\"",
+    "scrollable",
+    "  rich_text",
+    "  text \"Строка принадлежит языку.\"",
+    "scrollable",
+    "  text \"\"",
+    "text \"Профиль:\"",
+    "pick_list",
+    "text \"Сравнить с:\"",
+    "pick_list",
+    "button",
+    "  text \"Сравнить\"",
+    "button",
+    "  text \"Экспортировать всё\"",
+    "button",
+    "  text \"Открыть отчёт\"",
+    "button",
+    "  text \"Перепроверить\"",
+    "text \"\"",
+];
+
+/// [`ANALYZED_VIEW`] в этой сборке: без базы корпусов нет кнопки "База".
+fn analyzed_view() -> Vec<&'static str> {
+    let mut view = ANALYZED_VIEW.to_vec();
+    if cfg!(not(feature = "corpus")) {
+        let i = view
+            .iter()
+            .position(|line| *line == "  text \"База\"")
+            .unwrap();
+        view.drain(i - 1..=i);
+    }
+    view
+}
+
+#[test]
+fn view_follows_type_analyze_export_clear() {
+    let expected = analyzed_view();
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X[I] := I + 1;")]);
+    analyze(&mut state);
+    assert_eq!(harness::snapshot(&state), expected);
+
+    send(&mut state, [Message::Export(export::Message::ExportAll)]);
+    let exported = harness::snapshot(&state);
+    assert_eq!(
+        exported[..expected.len() - 1],
+        expected[..expected.len() - 1]
+    );
+    assert_eq!(
+        exported.last().unwrap(),
+        &format!("text {:?}", format!("Записано в {}", BUNDLE_FILE))
+    );
+
+    send(
+        &mut state,
+        [Message::Animation(
+            super::animation::Message::ReducedMotionToggled(true),
+        )],
+    );
+    semantics(&mut state);
+    let symbols = harness::snapshot(&state);
+    assert!(symbols
+        .iter()
+        .any(|line| line.starts_with("    text \"▸ X - идентификатор-массив")));
+
+    send(&mut state, [Message::Editor(editor::Message::Clear)]);
+    let cleared = harness::snapshot(&state);
+    let outputs = cleared
+        .iter()
+        .position(|line| line == "  text \"Очистить\"")
+        .unwrap();
+    assert_eq!(
+        cleared[outputs + 1..outputs + 5],
+        ["scrollable", "  text \"\"", "scrollable", "  text \"\""]
+    );
+}
+
+#[test]
+fn settings_table_and_quiz_views_render_without_a_window() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [Message::Settings(settings::Message::Toggled)]);
+    let view = harness::snapshot(&state);
+    let rules = view.iter().filter(|line| *line == "  pick_list").count();
+    assert_eq!(rules, Rule::ALL.len());
+    assert!(view.contains(&"text \"Конец оператора\"".to_string()));
+
+    send(
+        &mut state,
+        [
+            Message::Settings(settings::Message::Toggled),
+            input("X := 1;"),
+            Message::Table(table::Message::Toggled),
+            Message::Table(table::Message::StepSelected(0)),
+        ],
+    );
+    let view = harness::snapshot(&state);
+    assert!(view.contains(&"  text \"Строка принадлежит языку.\"".to_string()));
+    assert!(view.iter().any(|line| line.starts_with("    text \"1. M[")));
+
+    send(
+        &mut state,
+        [
+            Message::Table(table::Message::Toggled),
+            Message::Quiz(quiz::Message::Start),
+        ],
+    );
+    let view = harness::snapshot(&state);
+    assert!(view
+        .iter()
+        .any(|line| line.starts_with("  text \"Назовите роли")));
+    assert!(view.contains(&"    text \"Проверить\"".to_string()));
+}
+
+#[cfg(feature = "corpus")]
+#[test]
+fn corpus_panel_adds_input_and_filters_by_code() {
+    use super::corpus;
+
+    let mut state = TaaflUIState::default();
+    send(
+        &mut state,
+        [
+            Message::Corpus(corpus::Message::Toggled),
+            Message::Corpus(corpus::Message::TagEdited("лаб-1".to_string())),
+            input("A := 1; B := 0; C := ;"),
+            Message::Corpus(corpus::Message::Add),
+            Message::Corpus(corpus::Message::CodeEdited("constant-range".to_string())),
+            Message::Corpus(corpus::Message::Search),
+        ],
+    );
+
+    let panel = state.corpus.as_ref().unwrap();
+    let found: Vec<_> = panel
+        .found
+        .iter()
+        .map(|record| record.input.as_str())
+        .collect();
+    assert_eq!(found, ["B := 0;"]);
+    assert_eq!(panel.found[0].tag, "лаб-1");
+    assert_eq!(panel.status, "Найдено: 1 из 3");
+    let view = harness::snapshot(&state);
+    assert!(view
+        .iter()
+        .any(|line| line.contains("B := 0;  [constant-range]")));
+}
+
+/// Отрезок лексемы не на границе символа не роняет представление: вывод
+/// показывается без подсветки лексем.
+#[test]
+fn token_span_inside_a_character_falls_back_to_plain_output() {
+    let mut state = TaaflUIState::default();
+    send(&mut state, [input("X := 'é';")]);
+    analyze(&mut state);
+    let literal = state
+        .tokens
+        .entries
+        .iter_mut()
+        .find(|token| token.text == "'é'")
+        .unwrap();
+    // Конец внутри 'é': следующий промежуток начинается посреди символа
+    literal.span.end = 7;
+
+    let view = harness::snapshot(&state);
+    let plain = format!("text {:?}", state.syntax_output);
+    assert!(view.iter().any(|line| line.trim_start() == plain), "{:?}", view);
+}
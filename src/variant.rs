@@ -0,0 +1,108 @@
+//! Варианты лабораторной работы: грамматика, операции выражений, лексические
+//! ограничения, набор семантических правил и названия ролей символов.
+//!
+//! Анализатор реализует [`Variant20`]; другой вариант подключается реализацией
+//! [`Variant`] и передаётся в [`AnalyzerBuilder::variant`](crate::analyzer::AnalyzerBuilder::variant).
+
+use crate::ast::RESERVED_WORDS;
+use crate::config::Rule;
+use crate::grammar::GRAMMAR;
+use crate::model::{ConstantRole, IdentifierRole};
+use crate::operators::{self, Registry};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// ```
+/// use taafl::analyzer::Analyzer;
+/// use taafl::variant::Variant;
+///
+/// /// Вариант с короткими идентификаторами.
+/// #[derive(Debug)]
+/// struct Short;
+///
+/// impl Variant for Short {
+///     fn name(&self) -> &str {
+///         "Короткие имена"
+///     }
+///
+///     fn max_identifier_length(&self) -> usize {
+///         4
+///     }
+/// }
+///
+/// let analyzer = Analyzer::builder().variant(Short).build();
+/// assert!(!analyzer.analyze("COUNT := 1;").report.accepted);
+/// assert!(Analyzer::default().analyze("COUNT := 1;").report.accepted);
+/// ```
+pub trait Variant: fmt::Debug {
+    /// Название варианта для заголовков и отчётов.
+    fn name(&self) -> &str;
+
+    /// Грамматика в формате модуля [`grammar`](crate::grammar). Используется
+    /// табличным анализатором; рекурсивный спуск реализует грамматику варианта 20.
+    fn grammar(&self) -> &str {
+        GRAMMAR
+    }
+
+    /// Операции выражений с приоритетами, по которым рекурсивный спуск разбирает
+    /// правую часть и индексы. Знак операции, которого нет в реестре, завершает
+    /// выражение.
+    fn operators(&self) -> &Registry {
+        operators::builtin()
+    }
+
+    /// Наибольшая длина идентификатора (правило [`Rule::IdentifierLength`]).
+    fn max_identifier_length(&self) -> usize {
+        8
+    }
+
+    /// Допустимые значения констант (правило [`Rule::ConstantRange`]).
+    fn constant_range(&self) -> RangeInclusive<i32> {
+        1..=32767
+    }
+
+    /// Слова, которые не могут быть идентификаторами (правило [`Rule::ReservedWord`]),
+    /// в верхнем регистре.
+    fn reserved_words(&self) -> &[&str] {
+        RESERVED_WORDS
+    }
+
+    /// Семантические правила варианта. Правила не из этого списка не проверяются
+    /// при любой настроенной строгости.
+    fn rules(&self) -> &[Rule] {
+        &Rule::ALL
+    }
+
+    fn identifier_role(&self, role: IdentifierRole) -> &str {
+        match role {
+            IdentifierRole::Array => "идентификатор-массив",
+            IdentifierRole::Index => "идентификатор-индекс",
+            IdentifierRole::Expression => "идентификатор-выражение",
+            IdentifierRole::ExpressionArray => "идентификатор-массив (правая часть)",
+            IdentifierRole::Function => "идентификатор-функция",
+            IdentifierRole::Field => "идентификатор-поле",
+        }
+    }
+
+    fn constant_role(&self, role: ConstantRole) -> &str {
+        match role {
+            ConstantRole::Index => "константа-индекс",
+            ConstantRole::Expression => "константа-выражение",
+            ConstantRole::RealIndex => "вещественная константа-индекс",
+            ConstantRole::RealExpression => "вещественная константа-выражение",
+            ConstantRole::CharacterExpression => "символьная константа-выражение",
+            ConstantRole::StringExpression => "строковая константа-выражение",
+        }
+    }
+}
+
+/// Вариант 20: оператор присваивания с индексами, идентификаторы до 8 символов,
+/// константы [1..32767].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Variant20;
+
+impl Variant for Variant20 {
+    fn name(&self) -> &str {
+        "Вариант 20"
+    }
+}
@@ -0,0 +1,113 @@
+//! Запуски собранной программы: с `--deterministic` два запуска на одном вводе
+//! дают побайтно одинаковые результаты.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Пустой временной каталог проверки `name`.
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("taafl-cli-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Запуск `taafl` в каталоге `dir`; стандартный вывод успешного запуска.
+fn taafl(dir: &Path, args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_taafl"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}: {:?}", args, output);
+    output.stdout
+}
+
+#[test]
+fn deterministic_runs_are_byte_identical() {
+    let dir = scratch("deterministic");
+    let statement = "ABC[I, 2] := X + 10 * Y;";
+    let runs: Vec<_> = ["first", "second"]
+        .iter()
+        .map(|run| {
+            let generated = taafl(&dir, &["--deterministic", "--generate", "5"]);
+
+            let archive = format!("{}.zip", run);
+            taafl(&dir, &["--export", &archive, "--deterministic", statement]);
+            let exported = std::fs::read(dir.join(&archive)).unwrap();
+
+            #[cfg(all(feature = "corpus", not(feature = "sqlite")))]
+            let added = {
+                let corpus = dir.join(taafl::corpus::CORPUS_FILE);
+                let _ = std::fs::remove_file(&corpus);
+                std::fs::write(dir.join("input.txt"), statement).unwrap();
+                taafl(
+                    &dir,
+                    &["corpus", "add", "lab", "input.txt", "--deterministic"],
+                );
+                std::fs::read(&corpus).unwrap()
+            };
+            // Файл SQLite не обязан совпадать побайтно: сравниваются записи базы
+            #[cfg(feature = "sqlite")]
+            let added = {
+                let corpus = dir.join(taafl::corpus::sqlite::CORPUS_DATABASE);
+                let _ = std::fs::remove_file(&corpus);
+                std::fs::write(dir.join("input.txt"), statement).unwrap();
+                taafl(
+                    &dir,
+                    &["corpus", "add", "lab", "input.txt", "--deterministic"],
+                );
+                taafl(&dir, &["corpus", "query"])
+            };
+            #[cfg(not(feature = "corpus"))]
+            let added = Vec::<u8>::new();
+
+            (generated, exported, added)
+        })
+        .collect();
+    assert!(!runs[0].0.is_empty());
+    assert_eq!(runs[0], runs[1]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn closed_output_is_a_normal_exit() {
+    use std::process::Stdio;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_taafl"))
+        .args(["--generate", "100000"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Читатель уходит, не прочитав ничего, как `head` после первых строк
+    drop(child.stdout.take());
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+}
+
+#[test]
+fn export_is_written_in_every_output_mode() {
+    let dir = scratch("export");
+    taafl(
+        &dir,
+        &["--format", "json", "--export", "json.zip", "A := B + 1;"],
+    );
+    assert!(dir.join("json.zip").is_file());
+
+    std::fs::write(dir.join("grammar.txt"), taafl::grammar::GRAMMAR).unwrap();
+    let args = ["--ambiguity", "grammar.txt", "--export", "ambiguity.zip", "A := B + 1;"];
+    taafl(&dir, &args);
+    assert!(dir.join("ambiguity.zip").is_file());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn dot_after_digits_is_a_fraction_in_many() {
+    let dir = scratch("many");
+    let args = ["--terminator", "dot", "--reals", "--many", "A := 1.5. B := 2."];
+    let output = String::from_utf8(taafl(&dir, &args)).unwrap();
+    assert_eq!(output.matches("=== Оператор").count(), 2, "{}", output);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
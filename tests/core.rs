@@ -0,0 +1,72 @@
+//! Внешняя оболочка, которой доступен только стабильный интерфейс [`taafl::core`]:
+//! тест собирается отдельно от библиотеки, поэтому `#[non_exhaustive]` действует
+//! на него, как на любого пользователя.
+
+use taafl::core::{
+    analyze, tokenize, AnalyzerConfig, ConstantRole, IdentifierRole, Radix, Rule, RuleSeverity,
+    Severity, Token,
+};
+
+fn identifier_role(role: IdentifierRole) -> &'static str {
+    match role {
+        IdentifierRole::Array => "массив",
+        IdentifierRole::Index => "индекс",
+        IdentifierRole::Expression => "выражение",
+        _ => "другое",
+    }
+}
+
+fn constant_role(role: ConstantRole) -> &'static str {
+    match role {
+        ConstantRole::Index => "индекс",
+        ConstantRole::Expression => "выражение",
+        _ => "другое",
+    }
+}
+
+#[test]
+fn facade_reports_symbols_and_diagnostics() {
+    let mut config = AnalyzerConfig::default();
+    let result = analyze("A[I] := B(2) + 0FFH;", &config);
+    assert!(result.accepted, "{:?}", result.diagnostics);
+    let identifiers: Vec<_> = result
+        .symbols
+        .identifiers
+        .iter()
+        .map(|id| (id.name.as_str(), identifier_role(id.role)))
+        .collect();
+    assert_eq!(
+        identifiers,
+        [("A", "массив"), ("B", "другое"), ("I", "индекс")]
+    );
+    let constants: Vec<_> = result
+        .symbols
+        .constants
+        .iter()
+        .map(|c| (c.value, constant_role(c.role)))
+        .collect();
+    assert_eq!(constants, [(2, "выражение"), (255, "выражение")]);
+
+    let result = analyze("A := 0;", &config);
+    assert!(!result.accepted);
+    assert_eq!(result.diagnostics[0].code, "constant-range");
+
+    // Предупреждение не мешает принять строку
+    config.set_severity(Rule::ConstantRange, RuleSeverity::Warning);
+    let result = analyze("A := 0;", &config);
+    assert!(result.accepted);
+    let diagnostic = &result.diagnostics[0];
+    assert!(matches!(diagnostic.severity, Severity::Warning));
+    assert_eq!(diagnostic.span, 5..6);
+}
+
+#[test]
+fn facade_tokenizes_with_radix() {
+    let tokens = tokenize("X := 377B;").unwrap();
+    let radix = tokens.iter().find_map(|(span, token)| match token {
+        Token::Constant(value, radix) => Some((span.clone(), *value, *radix)),
+        _ => None,
+    });
+    assert_eq!(radix, Some((5..9, 255, Radix::Octal)));
+    assert_eq!(tokenize("X := $;").unwrap_err().span, 5..6);
+}